@@ -0,0 +1,125 @@
+//! Animation curves for object spawn effects and idle motion
+
+use crate::config::{SpawnAnim, CONFIG};
+
+/// Overshoot amount of the scale-up curve (standard "ease out back" constant)
+const OVERSHOOT: f32 = 1.70158;
+
+/// Bounces simulated by the drop-in curve before it settles
+const MAX_BOUNCES: i32 = 8;
+
+/// Vertical offset and scale multiplier of a spawning object at `progress`
+/// (0 = just added, 1 = settled)
+pub fn spawn_transform(anim: SpawnAnim, progress: f32) -> (f32, f32) {
+    let t = progress.clamp(0.0, 1.0);
+    match anim {
+        SpawnAnim::None => (0.0, 1.0),
+        SpawnAnim::ScaleUp => (0.0, ease_out_back(t)),
+        SpawnAnim::DropIn => {
+            let height = bounce_height(t, CONFIG.physics.bounce_factor);
+            (height * CONFIG.animation.spawn_drop_height, 1.0)
+        }
+    }
+}
+
+/// Vertical offset and yaw (radians) of an idle object at `time` seconds,
+/// for idle motion `amplitude` (0 = still, 1 = full).
+///
+/// Each object gets its own phase from its ID, so the desk doesn't move in
+/// lockstep, and the same object always moves the same way.
+pub fn idle_offset(id: u64, time: f32, amplitude: f32) -> (f32, f32) {
+    if amplitude <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let config = &CONFIG.animation;
+    // Golden-ratio steps spread consecutive IDs evenly around the cycle
+    let phase = (id as f64 * 0.618_033_988_75).fract() as f32 * std::f32::consts::TAU;
+    let angle = time / config.idle_period.max(0.1) * std::f32::consts::TAU + phase;
+    // Sway at a slightly different rate so the motion doesn't look mechanical
+    let lift = (angle.sin() * 0.5 + 0.5) * config.idle_bob_height * amplitude;
+    let yaw = (angle * 0.7).sin() * config.idle_sway_angle * amplitude;
+    (lift, yaw)
+}
+
+/// Scale multiplier and glow of an alerting object with `remaining` seconds
+/// of its alert left. Pulses start from rest and fade out as the alert ends.
+pub fn alert_pulse(remaining: f32) -> (f32, f32) {
+    let config = &CONFIG.animation;
+    let duration = config.alert_duration.max(f32::EPSILON);
+    let fade = (remaining / duration).clamp(0.0, 1.0);
+    let elapsed = duration - remaining;
+    let wave = 0.5 - 0.5 * (elapsed * config.alert_pulse_rate * std::f32::consts::TAU).cos();
+    let pulse = wave * fade;
+    (1.0 + pulse * config.alert_scale, pulse * config.alert_glow)
+}
+
+/// Grow from 0 to 1, briefly overshooting before settling
+fn ease_out_back(t: f32) -> f32 {
+    let u = t - 1.0;
+    1.0 + (OVERSHOOT + 1.0) * u * u * u + OVERSHOOT * u * u
+}
+
+/// Height (1 = drop height) of an object falling under constant gravity and
+/// bouncing with the given restitution, with the whole motion spread over t in 0..1
+fn bounce_height(t: f32, restitution: f32) -> f32 {
+    // In units where the initial fall takes 1 and starts at height 1, gravity is 2
+    // and each bounce k leaves the ground at speed 2e^k and lasts 2e^k.
+    if t >= 1.0 {
+        return 0.0;
+    }
+    let restitution = restitution.clamp(0.0, 0.9);
+    let total: f32 = 1.0 + (1..=MAX_BOUNCES).map(|k| 2.0 * restitution.powi(k)).sum::<f32>();
+    let mut time = t * total;
+
+    if time < 1.0 {
+        return 1.0 - time * time;
+    }
+    time -= 1.0;
+    for k in 1..=MAX_BOUNCES {
+        let speed = 2.0 * restitution.powi(k);
+        if time < speed {
+            return speed * time - time * time;
+        }
+        time -= speed;
+    }
+    0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_curves_start_and_end_at_rest() {
+        assert!(ease_out_back(0.0).abs() < 1e-6);
+        assert!((ease_out_back(1.0) - 1.0).abs() < 1e-6);
+        assert!((0..100).any(|i| ease_out_back(i as f32 / 100.0) > 1.0), "scale-up should overshoot");
+
+        assert_eq!(bounce_height(0.0, 0.4), 1.0);
+        assert_eq!(bounce_height(1.0, 0.4), 0.0);
+        assert_eq!(spawn_transform(SpawnAnim::None, 0.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn idle_motion_stays_small_and_differs_per_object() {
+        assert_eq!(idle_offset(3, 1.5, 0.0), (0.0, 0.0));
+        let config = &CONFIG.animation;
+        for time in [0.0, 0.7, 2.9, 10.0] {
+            let (lift, yaw) = idle_offset(3, time, 1.0);
+            assert!((0.0..=config.idle_bob_height).contains(&lift));
+            assert!(yaw.abs() <= config.idle_sway_angle);
+        }
+        assert_ne!(idle_offset(1, 0.0, 1.0), idle_offset(2, 0.0, 1.0));
+    }
+
+    #[test]
+    fn bounces_peak_at_restitution_squared() {
+        let restitution = 0.4;
+        let peak = (0..1000)
+            .map(|i| i as f32 / 1000.0)
+            .skip_while(|&t| bounce_height(t, restitution) > 0.01)
+            .map(|t| bounce_height(t, restitution))
+            .fold(0.0, f32::max);
+        assert!((peak - restitution * restitution).abs() < 0.01, "first bounce peaked at {}", peak);
+    }
+}
@@ -0,0 +1,2644 @@
+//! Application module
+//!
+//! The `App` ties a window's surface to the renderer, desk state, physics and
+//! egui UI. Hosts drive it from their own event loop by forwarding window
+//! events to [`App::handle_event`] and calling [`App::update`] and
+//! [`App::render`] on each redraw.
+
+use crate::arrange::arrange_grid;
+use crate::camera::{Camera, CameraFlight};
+use crate::clipboard::Clipboard;
+use crate::config::{KeyAction, KeyBinding, PresentMode, CONFIG, PIXELS_PER_SCROLL_LINE};
+use crate::console::{self, Command};
+use crate::desk_object::{DeskObject, ImageSource, ObjectType, MAX_SCALE, MIN_SCALE, ROTATION_SNAP_STEP};
+use crate::focus::{focus_streak, record_focus};
+use crate::gizmo::{self, GizmoDrag, GizmoHandle};
+use crate::history::History;
+use crate::i18n::Key;
+use crate::mesh::{add_collision_wireframe, grid_line_offsets, MeshData};
+use crate::physics::{self, FixedTimestep, PhysicsEngine};
+use crate::recording::{Player, Recorder};
+use crate::renderer::{request_adapter, FrameTargets, ModelUniform, Renderer};
+use crate::shuffle::shuffle_layout;
+use crate::state::AppState;
+use crate::texture::is_supported_image;
+use crate::ui::{
+    render_console, render_context_menu, render_debug_overlay, render_focus_widget, render_grid_rulers,
+    render_left_sidebar, render_lock_badges, render_right_sidebar, render_toasts, workspace_label, DebugStats, FocusSummary,
+    UiAction, UiState,
+};
+
+use egui_wgpu::ScreenDescriptor;
+use glam::{Quat, Vec3};
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, MouseButton, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+/// Extra margin around collision shapes so small or flat objects are easy to click
+const PICK_MARGIN: f32 = 0.05;
+
+/// Opacity of the placement preview ghost
+const GHOST_OPACITY: f32 = 0.4;
+
+/// Maximum gap between two clicks on the same object to count as a double-click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Radians a held object turns per scroll line, before the user's sensitivity
+const SCROLL_ROTATE_STEP: f32 = 0.2;
+
+/// Scale change of a held object per scroll line, before the user's sensitivity
+const SCROLL_SCALE_STEP: f32 = 0.1;
+
+/// Camera zoom per scroll line (about 10% closer), before the user's sensitivity
+const SCROLL_ZOOM_STEP: f32 = 0.1;
+
+/// Radius of the transform gizmo's ring as a fraction of the viewport height
+const GIZMO_SCREEN_SIZE: f32 = 0.08;
+
+/// Smallest gizmo ring radius relative to the object's collision radius, so
+/// the ring stays outside the object
+const GIZMO_OBJECT_MARGIN: f32 = 1.3;
+
+/// Color changes to the same object and channel this close together count as
+/// one choice for the recently used colors (e.g. dragging a color picker)
+const RECENT_COLOR_SETTLE_TIME: Duration = Duration::from_millis(1500);
+
+/// Main application state
+pub struct App {
+    window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    /// Present modes the surface supports
+    present_modes: Vec<wgpu::PresentMode>,
+    size: PhysicalSize<u32>,
+    renderer: Renderer,
+    /// Depth and bloom textures sized to the window
+    frame_targets: FrameTargets,
+    /// Object type and desk-surface position awaiting a click to be placed
+    pending_placement: Option<(ObjectType, Vec3)>,
+    /// Whether the pending placement is being dragged out of the palette,
+    /// to be placed when the mouse button is let go
+    palette_drag: bool,
+    camera: Camera,
+    /// Camera move under way to a framed view, cancelled by manual camera input
+    camera_flight: Option<CameraFlight>,
+    /// When the camera was last moved by hand, which pauses the turntable
+    last_camera_input: Option<Instant>,
+    state: AppState,
+    /// Undo/redo snapshots of the desk
+    history: History,
+    /// Objects copied with Ctrl+C, used when the system clipboard is unavailable
+    clipboard: Option<Clipboard>,
+    /// Session being recorded, if any
+    recorder: Option<Recorder>,
+    /// Recording being played back, if any
+    playback: Option<Player>,
+    /// The desk to restore when playback ends
+    desk_before_playback: Vec<DeskObject>,
+    physics: PhysicsEngine,
+    physics_timestep: FixedTimestep,
+    mouse_position: (f32, f32),
+    left_mouse_down: bool,
+    middle_mouse_down: bool,
+    dragging_object_id: Option<u64>,
+    /// Offset from the grab point to the dragged object's center (XZ)
+    drag_offset: Vec3,
+    last_frame_time: Instant,
+    /// When the state was last written to disk
+    last_save_time: Instant,
+    /// Time of the most recent unsaved object change
+    last_mutation_time: Option<Instant>,
+    /// Smoothed frames per second
+    fps: f32,
+    /// Whether the window has input focus (full-rate rendering)
+    focused: bool,
+    /// Whether the wireframe/collision debug view is on
+    debug_draw: bool,
+    shift_pressed: bool,
+    /// Held to rotate freely while rotation snapping is enabled
+    ctrl_pressed: bool,
+    /// Unsnapped yaw of the dragged object, accumulated from scroll input
+    drag_yaw: f32,
+    /// Gizmo handle the dragged object is being turned or moved with, if any
+    gizmo_drag: Option<GizmoDrag>,
+    /// Center and radius of the light ring shown for the selected lamp
+    light_ring: Option<(Vec3, f32)>,
+    /// Whether a click at the cursor would grab an object or gizmo handle
+    hovering_grabbable: bool,
+    /// Object and time of the last left click, for double-click detection
+    last_click: Option<(u64, Instant)>,
+    /// Object, channel (true = accent) and time of the last color change
+    last_color_change: Option<(u64, bool, Instant)>,
+    /// File being dragged over the window from the OS
+    hovered_file: Option<PathBuf>,
+    /// Object glowing as the drop target of the hovered file
+    drop_target: Option<u64>,
+    current_object_type_index: usize,
+    // Egui integration
+    egui_ctx: egui::Context,
+    egui_state: egui_winit::State,
+    egui_renderer: egui_wgpu::Renderer,
+    ui_state: UiState,
+}
+
+impl App {
+    /// Create the app for a window, creating the wgpu instance and surface
+    pub async fn new(
+        window: Arc<Window>,
+        app_state: AppState,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // Create wgpu instance
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        // Create surface
+        let surface = instance.create_surface(window.clone())?;
+
+        Self::from_surface(&instance, surface, window, app_state).await
+    }
+
+    /// Create the app for a surface the host already created for `window`
+    pub async fn from_surface(
+        instance: &wgpu::Instance,
+        surface: wgpu::Surface<'static>,
+        window: Arc<Window>,
+        app_state: AppState,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let size = window.inner_size();
+        let aspect = size.width as f32 / size.height as f32;
+
+        let adapter = request_adapter(instance, Some(&surface)).await?;
+
+        // Create device and queue
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Device"),
+                    // Optional: enables the F4 wireframe view where supported
+                    required_features: adapter.features() & wgpu::Features::POLYGON_MODE_LINE,
+                    // Low enough for the GL and software fallback adapters,
+                    // with textures and the surface as large as the adapter allows
+                    required_limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                None,
+            )
+            .await?;
+
+        // Configure surface
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .copied()
+            .unwrap_or(surface_caps.formats[0]);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: present_mode(app_state.present_mode, app_state.low_power, &surface_caps.present_modes),
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        let mut renderer = Renderer::new(device, queue, config.format);
+        renderer.set_low_power(app_state.low_power);
+        renderer.set_bloom_intensity(app_state.bloom_intensity);
+        renderer.set_resolution_divisor(app_state.resolution_divisor);
+        let frame_targets = renderer.create_frame_targets(config.width, config.height);
+
+        // Create camera
+        let camera = Camera::new(aspect);
+
+        let mut physics = PhysicsEngine::new();
+        physics.collision_radius_multiplier = app_state.collision_radius_multiplier;
+
+        // Initialize egui
+        let egui_ctx = egui::Context::default();
+
+        app_state.ui_theme.apply(&egui_ctx);
+        egui_ctx.set_zoom_factor(app_state.ui_scale);
+
+        let egui_state = egui_winit::State::new(
+            egui_ctx.clone(),
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            None,
+            None,
+        );
+
+        let egui_renderer =
+            egui_wgpu::Renderer::new(&renderer.device, config.format, None, 1, false);
+
+        let mut ui_state = UiState::new();
+        ui_state.orthographic = camera.is_orthographic();
+        ui_state.ui_scale = app_state.ui_scale;
+        ui_state.rotation_snap = app_state.rotation_snap;
+        ui_state.edge_snap = app_state.edge_snap;
+        ui_state.show_gizmo = app_state.show_gizmo;
+        ui_state.palette_drag = app_state.palette_drag;
+        ui_state.spawn_at_aim = app_state.spawn_at_aim;
+        ui_state.show_grid = app_state.show_grid;
+        ui_state.idle_motion = app_state.idle_motion;
+        ui_state.bloom_intensity = app_state.bloom_intensity;
+        ui_state.resolution_divisor = app_state.resolution_divisor;
+        ui_state.turntable = app_state.turntable;
+        ui_state.turntable_speed = app_state.turntable_speed;
+        ui_state.low_power = app_state.low_power;
+        ui_state.present_mode = app_state.present_mode;
+        ui_state.fps_cap = app_state.fps_cap;
+        ui_state.supported_present_modes = PresentMode::ALL
+            .into_iter()
+            .filter(|mode| mode.is_supported(&surface_caps.present_modes))
+            .collect();
+        ui_state.scroll = app_state.scroll;
+        ui_state.theme = app_state.ui_theme;
+        ui_state.lang = app_state.ui_lang;
+        ui_state.keybindings = app_state.keybindings.clone();
+        ui_state.saved_layouts = AppState::list_named_saves(ui_state.layout_sort);
+        ui_state.templates = app_state.templates.clone();
+        ui_state.recent_colors = app_state.recent_colors.clone();
+        ui_state.workspace_cameras = app_state.workspace_cameras;
+
+        let mut app = Self {
+            window,
+            surface,
+            config,
+            present_modes: surface_caps.present_modes.clone(),
+            size,
+            renderer,
+            frame_targets,
+            pending_placement: None,
+            palette_drag: false,
+            camera,
+            camera_flight: None,
+            last_camera_input: None,
+            history: History::new(&app_state.objects),
+            clipboard: None,
+            recorder: None,
+            playback: None,
+            desk_before_playback: Vec::new(),
+            state: app_state,
+            physics,
+            physics_timestep: FixedTimestep::default(),
+            mouse_position: (0.0, 0.0),
+            left_mouse_down: false,
+            middle_mouse_down: false,
+            dragging_object_id: None,
+            drag_offset: Vec3::ZERO,
+            last_frame_time: Instant::now(),
+            last_save_time: Instant::now(),
+            last_mutation_time: None,
+            fps: 0.0,
+            focused: true,
+            debug_draw: false,
+            shift_pressed: false,
+            ctrl_pressed: false,
+            drag_yaw: 0.0,
+            gizmo_drag: None,
+            light_ring: None,
+            hovering_grabbable: false,
+            last_click: None,
+            last_color_change: None,
+            hovered_file: None,
+            drop_target: None,
+            current_object_type_index: 0,
+            egui_ctx,
+            egui_state,
+            egui_renderer,
+            ui_state,
+        };
+
+        // Create meshes for existing objects
+        app.physics.settle(&mut app.state.objects);
+        app.rebuild_object_meshes();
+        app.renderer.set_grid_visible(app.state.show_grid);
+        app.sync_workspace_tabs();
+        if app.state.workspace_cameras {
+            if let Some(view) = app.state.workspaces[app.state.active_workspace].camera {
+                app.camera.set_view(&view);
+            }
+        }
+        if app.state.read_only {
+            app.ui_state.show_hint(app.state.ui_lang.tr(Key::StateReadOnly));
+        }
+
+        Ok(app)
+    }
+
+    fn rebuild_object_meshes(&mut self) {
+        self.renderer.rebuild_object_meshes(&self.state.objects);
+        self.exit_focus_if_missing();
+    }
+
+    fn create_object_mesh(&mut self, obj: &DeskObject) {
+        self.renderer.create_object_mesh(obj);
+    }
+
+    fn remove_object_mesh(&mut self, id: u64) {
+        self.renderer.remove_object_mesh(id);
+        self.exit_focus_if_missing();
+    }
+
+    /// Enter focus mode on an object, or leave it with `None`
+    fn set_focus(&mut self, focus: Option<u64>) {
+        if self.renderer.focused_object() != focus {
+            self.renderer.set_focus(&self.state.objects, focus);
+        }
+    }
+
+    /// Leave focus mode once the focused object has been removed
+    fn exit_focus_if_missing(&mut self) {
+        if let Some(id) = self.renderer.focused_object() {
+            if self.state.get_object(id).is_none() {
+                self.set_focus(None);
+            }
+        }
+    }
+
+    /// Check the object cap, showing a toast if no more objects can be added
+    fn can_add_object(&mut self) -> bool {
+        let max_objects = CONFIG.objects.max_objects;
+        if self.state.objects.len() >= max_objects {
+            self.ui_state
+                .show_toast(self.state.ui_lang.format(Key::DeskFull, &[&max_objects]));
+            return false;
+        }
+        true
+    }
+
+    /// Start a placement preview for the given object type at the cursor
+    fn begin_placement(&mut self, object_type: ObjectType) {
+        if !self.can_add_object() {
+            return;
+        }
+
+        let desk_y = self.physics.desk_surface_y();
+        let position = self
+            .cursor_surface_position()
+            .unwrap_or(Vec3::new(0.0, desk_y, 0.0));
+
+        let preview = DeskObject::new(0, object_type, position);
+        let model_uniform = Self::ghost_model_uniform(object_type, position);
+        self.renderer.set_ghost(&preview, model_uniform);
+        self.pending_placement = Some((object_type, position));
+    }
+
+    /// Move the placement preview to follow the cursor
+    fn update_placement(&mut self) {
+        let Some((object_type, _)) = self.pending_placement else { return };
+        let Some(position) = self.cursor_surface_position() else { return };
+
+        self.pending_placement = Some((object_type, position));
+        self.renderer.update_ghost(Self::ghost_model_uniform(object_type, position));
+    }
+
+    /// Place the pending object where the preview currently is
+    fn commit_placement(&mut self) {
+        self.palette_drag = false;
+        if let Some((object_type, position)) = self.pending_placement.take() {
+            self.renderer.clear_ghost();
+            if self.add_object_at(object_type, position).is_some() {
+                info!("Placed {}", object_type.display_name());
+            }
+        }
+    }
+
+    /// Discard the pending placement preview
+    fn cancel_placement(&mut self) {
+        self.pending_placement = None;
+        self.palette_drag = false;
+        self.renderer.clear_ghost();
+    }
+
+    fn ghost_model_uniform(object_type: ObjectType, position: Vec3) -> ModelUniform {
+        let scale = object_type.default_scale();
+        let offset = Vec3::new(0.0, object_type.rest_offset() * scale, 0.0);
+        ModelUniform::from_transform(position + offset, Quat::IDENTITY, scale)
+            .with_opacity(GHOST_OPACITY)
+    }
+
+    fn update_object_transform(&mut self, id: u64) {
+        if let Some(obj) = self.state.get_object(id) {
+            self.renderer.update_object_transform(obj);
+        }
+    }
+
+    /// Resize the surface to the window's new size. A zero size (minimized
+    /// window) keeps the old configuration; rendering is skipped until restored.
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.renderer.device, &self.config);
+            self.frame_targets = self
+                .renderer
+                .create_frame_targets(new_size.width, new_size.height);
+            self.camera
+                .set_aspect(new_size.width as f32 / new_size.height as f32);
+
+            // Remember the restored (non-maximized) size for the next launch
+            if !self.window.is_maximized() {
+                let logical = new_size.to_logical::<u32>(self.window.scale_factor());
+                self.state.window_size = Some((logical.width, logical.height));
+            }
+        }
+    }
+
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let dt = (now - self.last_frame_time).as_secs_f32();
+        self.last_frame_time = now;
+        if dt > 0.0 {
+            self.fps = self.fps * 0.9 + (1.0 / dt) * 0.1;
+        }
+
+        if self.playback.is_some() {
+            self.advance_playback(dt);
+        }
+
+        // Update physics for dropping objects at a fixed rate (playback
+        // positions come straight from the recording)
+        let mut updated_ids: Vec<u64> = Vec::new();
+        for _ in 0..self.physics_timestep.advance(dt) {
+            if self.playback.is_none() {
+                updated_ids.extend(self.physics.step(&mut self.state.objects));
+            }
+        }
+        // Spawn animations run on the clamped frame time, alert pulses on the clock
+        let anim_dt = dt.min(CONFIG.physics.max_frame_time);
+        let now = Instant::now();
+        for obj in &mut self.state.objects {
+            let spawning = obj.advance_spawn(anim_dt);
+            if obj.advance_alert(now) || spawning {
+                updated_ids.push(obj.id);
+            }
+            // Registered types run their own per-frame behavior
+            if let ObjectType::Custom(custom) = obj.object_type {
+                let update = &custom.descriptor().update;
+                if update.as_ref().is_some_and(|update| update(obj, anim_dt)) {
+                    updated_ids.push(obj.id);
+                }
+            }
+        }
+        // Idle motion moves every object each frame, and once more when it's
+        // turned off (or low power mode comes on) so they settle back
+        let idle_motion = if self.state.low_power { 0.0 } else { self.state.idle_motion };
+        if idle_motion > 0.0 || self.renderer.idle_amplitude() > 0.0 {
+            let exempt: Vec<u64> = self
+                .dragging_object_id
+                .into_iter()
+                .chain(self.ui_state.selected_object_id)
+                .collect();
+            self.renderer.set_idle_motion(idle_motion, anim_dt, &self.state.objects, &exempt);
+            updated_ids.extend(self.state.objects.iter().map(|obj| obj.id));
+        }
+        updated_ids.sort_unstable();
+        updated_ids.dedup();
+
+        if let Some(flight) = &mut self.camera_flight {
+            if !flight.advance(&mut self.camera, anim_dt) {
+                self.camera_flight = None;
+            }
+        }
+        // The turntable waits while the camera or an object is being moved by hand
+        let camera_idle = self
+            .last_camera_input
+            .is_none_or(|moved| now - moved >= CONFIG.camera.turntable_resume_delay);
+        let hands_off = camera_idle && self.camera_flight.is_none() && self.dragging_object_id.is_none();
+        if self.state.turntable && !self.state.low_power && hands_off {
+            self.camera.spin(self.state.turntable_speed.to_radians() * anim_dt);
+        }
+
+        self.track_focus(dt);
+
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.capture(&self.state.objects) {
+                log::error!("Recording failed: {}", e);
+                self.recorder = None;
+                self.ui_state.recording = false;
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::RecordingStopped, &[&e]));
+            }
+        }
+
+        for id in updated_ids {
+            self.update_object_transform(id);
+        }
+
+        // Save shortly after the last change, and periodically as a safety net
+        // (never mid-playback, which would save the replayed desk)
+        let persistence = &CONFIG.persistence;
+        let debounce_elapsed = self
+            .last_mutation_time
+            .is_some_and(|changed| now - changed >= persistence.save_debounce);
+        let save_due = debounce_elapsed || now - self.last_save_time >= persistence.autosave_interval;
+        if save_due && self.playback.is_none() {
+            self.save_state();
+        }
+
+        if self.debug_draw {
+            self.update_debug_lines();
+        }
+
+        self.update_gizmo();
+        self.update_hover();
+        self.update_light_ring();
+        self.renderer.update_shadows(&self.state.objects, &self.camera);
+        self.renderer.update_lamps(&self.state.objects);
+        self.renderer.update_camera(&self.camera);
+    }
+
+    /// Run hourglass timers and add this frame to today's focus time. Time
+    /// counts while the window is focused or a timer runs; without a timer, long
+    /// gaps between frames (e.g. a paused background window) are capped.
+    fn track_focus(&mut self, dt: f32) {
+        let lang = self.state.ui_lang;
+        let mut timer_running = false;
+        for obj in &mut self.state.objects {
+            timer_running |= obj.state.timer_remaining().is_some();
+            if obj.state.advance_timer(dt) {
+                obj.start_alert(Instant::now());
+                if CONFIG.animation.alert_toast {
+                    self.ui_state.show_toast(lang.format(Key::TimerFinished, &[&lang.object_label(obj)]));
+                }
+            }
+        }
+
+        if !self.focused && !timer_running {
+            return;
+        }
+        let mut time = Duration::from_secs_f32(dt.max(0.0));
+        if !timer_running {
+            time = time.min(CONFIG.focus.max_idle_gap);
+        }
+        let today = chrono::Local::now().date_naive();
+        record_focus(&mut self.state.focus_stats, today, time);
+    }
+
+    /// Today's focus time, streak and running timer for the focus widget
+    fn focus_summary(&self) -> FocusSummary {
+        let today = chrono::Local::now().date_naive();
+        FocusSummary {
+            today: self.state.focus_stats.get(&today).copied().unwrap_or_default(),
+            streak: focus_streak(&self.state.focus_stats, today, CONFIG.focus.streak_minimum),
+            timer_remaining: self
+                .state
+                .objects
+                .iter()
+                .filter_map(|obj| obj.state.timer_remaining())
+                .reduce(f32::min)
+                .map(Duration::from_secs_f32),
+        }
+    }
+
+    /// Rebuild the collision overlay: physics shapes in yellow, and the
+    /// slightly larger shapes used for mouse picking in cyan
+    fn update_debug_lines(&mut self) {
+        const PHYSICS_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+        const PICK_COLOR: [f32; 4] = [0.2, 0.9, 1.0, 1.0];
+
+        let mut lines = MeshData::new();
+        for obj in &self.state.objects {
+            let physics_shape = self.physics.world_shape(obj);
+            add_collision_wireframe(&mut lines, &physics_shape, obj.position, obj.rotation, PHYSICS_COLOR);
+            let pick_shape = obj.collision_shape().inflated(PICK_MARGIN);
+            add_collision_wireframe(&mut lines, &pick_shape, obj.position, obj.rotation, PICK_COLOR);
+        }
+        self.renderer.set_debug_lines(Some(&lines));
+    }
+
+    /// Draw a frame to the window.
+    ///
+    /// Nothing is drawn while the window is minimized (zero-sized). An outdated
+    /// or lost surface is reconfigured and acquired again once; any error
+    /// returned means this frame was dropped, and only `OutOfMemory` is fatal.
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let window_size = self.window.inner_size();
+        if window_size.width == 0 || window_size.height == 0 {
+            return Ok(());
+        }
+
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                // E.g. after a GPU switch or a resize the event loop hasn't delivered yet
+                log::info!("Surface outdated or lost; reconfiguring");
+                self.resize(window_size);
+                self.surface.get_current_texture()?
+            }
+            Err(e) => return Err(e),
+        };
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .renderer
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        self.renderer.render_scene(
+            &mut encoder,
+            &view,
+            &self.frame_targets,
+            &self.camera,
+            &self.state.objects,
+            true,
+        );
+
+        // Render egui UI
+        // Note: We need to prepare UI data before running egui to avoid borrow issues
+        // The inspector follows the selected object as it is dragged or turned
+        if let Some(obj) = self.ui_state.selected_object_id.and_then(|id| self.state.get_object(id)) {
+            self.ui_state.transform.sync(obj);
+        }
+        let object_name = if let Some(id) = self.ui_state.selected_object_id {
+            let lang = self.state.ui_lang;
+            self.state.get_object(id).map(|obj| lang.object_name(obj.object_type).to_string())
+        } else {
+            None
+        };
+
+        let egui_input = self.egui_state.take_egui_input(&self.window);
+        let egui_ctx = self.egui_ctx.clone();
+
+        let debug_stats = DebugStats {
+            fps: self.fps,
+            object_count: self.state.objects.len(),
+            max_objects: CONFIG.objects.max_objects,
+        };
+        let focus_summary = self.focus_summary();
+        let ruler_labels = self.grid_ruler_labels();
+
+        let lock_badges: Vec<egui::Pos2> = self
+            .state
+            .objects
+            .iter()
+            .filter(|obj| obj.locked && !obj.hidden)
+            .filter_map(|obj| {
+                self.world_to_screen(obj.position + Vec3::Y * obj.collision_height())
+            })
+            .collect();
+        let palette = self.state.ui_theme.palette();
+
+        let mut ui_actions = Vec::new();
+        let mut egui_output = egui_ctx.run(egui_input, |ctx| {
+            // Clean view leaves only the toasts on screen
+            if !self.ui_state.ui_hidden {
+                render_grid_rulers(ctx, &palette, &ruler_labels);
+                render_lock_badges(ctx, &palette, &lock_badges);
+
+                // Render left sidebar (palette)
+                let left_actions = render_left_sidebar(ctx, &mut self.ui_state, &self.state.objects);
+                ui_actions.extend(left_actions);
+
+                // Render right sidebar (customization)
+                let right_actions =
+                    render_right_sidebar(ctx, &mut self.ui_state, object_name.as_deref());
+                ui_actions.extend(right_actions);
+
+                // Render object context menu
+                let menu_actions = render_context_menu(ctx, &mut self.ui_state);
+                ui_actions.extend(menu_actions);
+
+                ui_actions.extend(render_console(ctx, &mut self.ui_state));
+
+                render_focus_widget(ctx, &self.ui_state, focus_summary);
+                render_debug_overlay(ctx, &self.ui_state, debug_stats);
+            }
+            render_toasts(ctx, &mut self.ui_state);
+        });
+
+        // Process UI actions after egui rendering
+        for action in ui_actions {
+            self.process_ui_action(action);
+        }
+
+        // Over the scene the cursor shows what can be dragged; egui keeps its
+        // own cursors while the pointer is over or in use by the UI
+        if CONFIG.window.cursor_feedback && !egui_ctx.wants_pointer_input() && !egui_ctx.is_pointer_over_area() {
+            egui_output.platform_output.cursor_icon = self.scene_cursor();
+        }
+
+        // Handle egui platform output
+        self.egui_state.handle_platform_output(&self.window, egui_output.platform_output);
+
+        // Render egui
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [self.size.width, self.size.height],
+            // Includes the user's UI scale on top of the monitor's scale factor
+            pixels_per_point: egui_output.pixels_per_point,
+        };
+
+        let tris = self.egui_ctx.tessellate(egui_output.shapes, egui_output.pixels_per_point);
+        for (id, image_delta) in &egui_output.textures_delta.set {
+            self.egui_renderer
+                .update_texture(&self.renderer.device, &self.renderer.queue, *id, image_delta);
+        }
+        self.egui_renderer.update_buffers(
+            &self.renderer.device,
+            &self.renderer.queue,
+            &mut encoder,
+            &tris,
+            &screen_descriptor,
+        );
+
+        {
+            let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Egui Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load, // Keep previous content
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            // Need to forget lifetime for egui-wgpu compatibility with wgpu 22
+            let mut render_pass = render_pass.forget_lifetime();
+            self.egui_renderer.render(&mut render_pass, &tris, &screen_descriptor);
+        }
+
+        // Free textures
+        for id in &egui_output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+
+        self.renderer.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    /// Process a UI action
+    fn process_ui_action(&mut self, action: UiAction) {
+        if self.playback.is_some() && !matches!(action, UiAction::StopPlayback) {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::StopPlaybackFirst));
+            return;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record_action(&action) {
+                log::warn!("Could not record action: {}", e);
+            }
+        }
+
+        match action {
+            UiAction::ToggleRecording => {
+                self.toggle_recording();
+                return;
+            }
+            UiAction::PlayLastRecording => {
+                self.play_last_recording();
+                return;
+            }
+            UiAction::StopPlayback => {
+                self.stop_playback();
+                return;
+            }
+            UiAction::AddObject(object_type) if self.state.palette_drag => {
+                // Dragging is how items are placed in this mode; a click drops one anywhere
+                if self.add_object(object_type).is_some() {
+                    info!("Added {} from UI", object_type.display_name());
+                }
+                return;
+            }
+            UiAction::AddObjectNow(object_type) => {
+                if self.add_object(object_type).is_some() {
+                    info!("Added {} from the palette search", object_type.display_name());
+                }
+                return;
+            }
+            UiAction::BeginPaletteDrag(object_type) => {
+                self.cancel_placement();
+                self.begin_placement(object_type);
+                self.palette_drag = self.pending_placement.is_some();
+                return;
+            }
+            UiAction::EndPaletteDrag { over_ui } => {
+                if !self.palette_drag {
+                    return;
+                }
+                let over_desk = self
+                    .cursor_plane_position(self.physics.desk_surface_y())
+                    .is_some_and(|hit| self.physics.is_on_desk(hit));
+                if over_ui || !over_desk {
+                    self.cancel_placement();
+                } else {
+                    self.commit_placement();
+                }
+                return;
+            }
+            UiAction::AddObject(object_type) => {
+                self.begin_placement(object_type);
+                if self.pending_placement.is_none() {
+                    return;
+                }
+                info!("Placing {} from UI (click to place, Esc to cancel)", object_type.display_name());
+                return;
+            }
+            UiAction::DeleteSelected => {
+                let ids = std::mem::take(&mut self.ui_state.selected_ids);
+                let (locked, unlocked): (Vec<u64>, Vec<u64>) =
+                    ids.into_iter().partition(|&id| self.state.get_object(id).is_some_and(|obj| obj.locked));
+                for &id in &unlocked {
+                    self.state.remove_object(id);
+                    self.remove_object_mesh(id);
+                }
+                self.ui_state.close_customization();
+                if !locked.is_empty() {
+                    let message = self.state.ui_lang.format(Key::KeptLockedObjects, &[&locked.len()]);
+                    self.ui_state.show_toast(message);
+                }
+                info!("Deleted {} selected objects from UI", unlocked.len());
+                if unlocked.is_empty() {
+                    return;
+                }
+            }
+            UiAction::DeleteObject(id) => {
+                if self.state.get_object(id).is_some_and(|obj| obj.locked) {
+                    self.ui_state.show_toast(self.state.ui_lang.tr(Key::UnlockBeforeDeleting));
+                    return;
+                }
+                self.state.remove_object(id);
+                self.remove_object_mesh(id);
+                self.ui_state.close_customization();
+                info!("Deleted object {} from UI", id);
+            }
+            UiAction::ChangeMainColor(id, color) => {
+                for id in self.color_targets(id) {
+                    if let Some(obj) = self.state.get_object_mut(id) {
+                        obj.color = color;
+                    }
+                    // Rebuild mesh with new color
+                    if let Some(obj) = self.state.get_object(id).cloned() {
+                        self.remove_object_mesh(id);
+                        self.create_object_mesh(&obj);
+                    }
+                }
+                self.remember_color(id, false, color);
+                self.ui_state.main_color_mixed = false;
+            }
+            UiAction::ChangeAccentColor(id, color) => {
+                for id in self.color_targets(id) {
+                    if let Some(obj) = self.state.get_object_mut(id) {
+                        obj.accent_color = color;
+                    }
+                    // Rebuild mesh with new color
+                    if let Some(obj) = self.state.get_object(id).cloned() {
+                        self.remove_object_mesh(id);
+                        self.create_object_mesh(&obj);
+                    }
+                }
+                self.remember_color(id, true, color);
+                self.ui_state.accent_color_mixed = false;
+            }
+            UiAction::ChangeBookCount(id, count) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.book_count = count;
+                }
+                // Rebuild mesh with the new stack
+                if let Some(obj) = self.state.get_object(id).cloned() {
+                    self.remove_object_mesh(id);
+                    self.create_object_mesh(&obj);
+                }
+            }
+            UiAction::SetLightRadius(id, radius) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.state.set_light_radius(radius);
+                }
+            }
+            UiAction::ChangeAnimSpeed(id, speed) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.anim_speed = speed;
+                }
+                self.update_object_transform(id);
+            }
+            UiAction::SetCoverImage(id, path) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.cover_image = path;
+                }
+                if let Some(obj) = self.state.get_object(id).cloned() {
+                    self.create_object_mesh(&obj);
+                }
+            }
+            UiAction::Customize(id) => {
+                if let Some(obj) = self.state.get_object(id) {
+                    self.ui_state.open_customization(obj);
+                }
+                return;
+            }
+            UiAction::ToggleSelected(id) => {
+                let mut ids = self.ui_state.selected_ids.clone();
+                match ids.iter().position(|&selected| selected == id) {
+                    Some(index) => {
+                        ids.remove(index);
+                    }
+                    None => ids.push(id),
+                }
+                let objects: Vec<&DeskObject> = ids.iter().filter_map(|&id| self.state.get_object(id)).collect();
+                self.ui_state.select_many(&objects);
+                return;
+            }
+            UiAction::SelectObject(id) => {
+                if let Some(obj) = self.state.get_object(id) {
+                    self.ui_state.open_customization(obj);
+                    let position = obj.position;
+                    self.take_camera_control();
+                    self.camera.center_on(position);
+                }
+                return;
+            }
+            UiAction::RenameObject(id, name) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.name = name;
+                }
+            }
+            UiAction::SetTags(id, tags) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.tags = tags;
+                }
+            }
+            UiAction::SetTransform {
+                id,
+                position,
+                rotation,
+                scale,
+                finished,
+            } => {
+                let objects_clone: Vec<DeskObject> = self.state.objects.clone();
+                let Some(obj) = self.state.get_object_mut(id).filter(|obj| !obj.locked) else {
+                    return;
+                };
+                let typed_y = position.y != obj.position.y;
+                obj.position = self.physics.clamp_to_floor(position, 0.0);
+                obj.rotation = rotation.normalize();
+                obj.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+                // Objects settle on whatever is under them, unless a height
+                // above that was typed in, where they stay
+                self.physics.end_drag(obj, &objects_clone);
+                if typed_y {
+                    obj.target_y = obj.position.y.max(obj.target_y);
+                    obj.original_y = obj.target_y;
+                }
+                self.update_object_transform(id);
+                if !finished {
+                    return;
+                }
+            }
+            UiAction::SetHidden(id, hidden) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.hidden = hidden;
+                }
+            }
+            UiAction::Duplicate(id) => {
+                self.duplicate_object(id);
+            }
+            UiAction::SaveTemplate(id) => {
+                let Some(obj) = self.state.get_object(id) else { return };
+                let lang = self.state.ui_lang;
+                self.ui_state.show_toast(lang.format(Key::SavedToFavorites, &[&lang.object_label(obj)]));
+                self.state.templates.push(obj.to_template());
+                self.ui_state.templates = self.state.templates.clone();
+            }
+            UiAction::AddTemplate(index) => {
+                if self.add_from_template(index).is_none() {
+                    return;
+                }
+            }
+            UiAction::RemoveTemplate(index) => {
+                if index >= self.state.templates.len() {
+                    return;
+                }
+                self.state.templates.remove(index);
+                self.ui_state.templates = self.state.templates.clone();
+            }
+            UiAction::Focus(id) => {
+                self.set_focus(Some(id));
+                return;
+            }
+            UiAction::BringToFront(id) => {
+                self.state.bring_to_front(id);
+            }
+            UiAction::SendToBack(id) => {
+                self.state.send_to_back(id);
+            }
+            UiAction::Mirror(id, axis) => {
+                let objects_clone = self.state.objects.clone();
+                let Some(obj) = self.state.get_object_mut(id).filter(|obj| !obj.locked) else { return };
+                obj.mirror(axis);
+                // Settle on (or off) whatever is on the other side
+                self.physics.end_drag(obj, &objects_clone);
+                self.update_object_transform(id);
+            }
+            UiAction::Shuffle(seed) => {
+                self.shuffle_desk(seed.unwrap_or_else(rand::random));
+            }
+            UiAction::Arrange => {
+                let unplaced = arrange_grid(&mut self.state.objects, &self.physics);
+                let ids: Vec<u64> = self.state.objects.iter().map(|obj| obj.id).collect();
+                for id in ids {
+                    self.update_object_transform(id);
+                }
+                if unplaced > 0 {
+                    self.ui_state.show_toast(self.state.ui_lang.format(Key::ArrangeLeftover, &[&unplaced]));
+                }
+            }
+            UiAction::ClearAll { keep_locked } => {
+                if keep_locked {
+                    self.state.objects.retain(|obj| obj.locked);
+                } else {
+                    self.state.objects.clear();
+                }
+                self.rebuild_object_meshes();
+                self.ui_state.close_customization();
+                info!("Cleared all objects from UI");
+            }
+            UiAction::SetLocked(id, locked) => {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    obj.locked = locked;
+                }
+                if self.ui_state.selected_object_id == Some(id) {
+                    self.ui_state.current_locked = locked;
+                }
+            }
+            UiAction::ToggleTimer(id) => {
+                let Some(obj) = self.state.get_object_mut(id) else { return };
+                if !obj.try_interact(Instant::now()) {
+                    return;
+                }
+                obj.state.toggle_timer();
+            }
+            UiAction::Interact(id) => {
+                let Some(obj) = self.state.get_object_mut(id) else { return };
+                let ObjectType::Custom(custom) = obj.object_type else { return };
+                let Some(interact) = &custom.descriptor().interact else { return };
+                if !obj.try_interact(Instant::now()) {
+                    return;
+                }
+                interact(obj);
+                // It may have been moved or recolored
+                let obj = obj.clone();
+                self.remove_object_mesh(id);
+                self.create_object_mesh(&obj);
+            }
+            UiAction::SetUiScale(scale) => {
+                self.state.ui_scale = scale;
+                self.egui_ctx.set_zoom_factor(scale);
+            }
+            UiAction::SetRotationSnap(enabled) => {
+                self.state.rotation_snap = enabled;
+            }
+            UiAction::SetEdgeSnap(enabled) => {
+                self.state.edge_snap = enabled;
+            }
+            UiAction::SetShowGizmo(enabled) => {
+                self.state.show_gizmo = enabled;
+            }
+            UiAction::SetPaletteDrag(enabled) => {
+                self.state.palette_drag = enabled;
+            }
+            UiAction::SetSpawnAtAim(enabled) => {
+                self.state.spawn_at_aim = enabled;
+            }
+            UiAction::SetIdleMotion(amplitude) => {
+                self.state.idle_motion = amplitude;
+            }
+            UiAction::SetBloomIntensity(intensity) => {
+                self.state.bloom_intensity = intensity;
+                self.renderer.set_bloom_intensity(intensity);
+            }
+            UiAction::SetResolutionDivisor(divisor) => {
+                self.state.resolution_divisor = divisor;
+                self.renderer.set_resolution_divisor(divisor);
+                self.frame_targets = self.renderer.create_frame_targets(self.config.width, self.config.height);
+            }
+            UiAction::SetTurntable(enabled) => {
+                self.state.turntable = enabled;
+            }
+            UiAction::SetTurntableSpeed(speed) => {
+                self.state.turntable_speed = speed;
+            }
+            UiAction::SetLowPower(enabled) => {
+                self.set_low_power(enabled);
+            }
+            UiAction::SetPresentMode(mode) => {
+                self.set_present_mode(mode);
+            }
+            UiAction::SetFpsCap(cap) => {
+                self.state.fps_cap = cap;
+                info!("Frame rate cap: {:?}", cap);
+            }
+            UiAction::SetScroll(scroll) => {
+                self.state.scroll = scroll;
+            }
+            UiAction::SetShowGrid(visible) => {
+                self.set_grid_visible(visible);
+            }
+            UiAction::SetOrthographic(orthographic) => {
+                self.take_camera_control();
+                self.camera.set_orthographic(orthographic);
+                return;
+            }
+            UiAction::SetTheme(theme) => {
+                self.state.ui_theme = theme;
+                theme.apply(&self.egui_ctx);
+            }
+            UiAction::SetLanguage(lang) => {
+                self.state.ui_lang = lang;
+                self.ui_state.lang = lang;
+            }
+            UiAction::SetKeybinding(action, binding) => {
+                self.state.keybindings.set(action, binding);
+                self.ui_state.keybindings = self.state.keybindings.clone();
+            }
+            UiAction::ResetKeybindings => {
+                self.state.keybindings = CONFIG.keybindings.clone();
+                self.ui_state.keybindings = self.state.keybindings.clone();
+            }
+            UiAction::SaveLayout(name) => {
+                self.save_layout(&name);
+                return;
+            }
+            UiAction::LoadLayout(name) => {
+                self.load_layout(&name);
+            }
+            UiAction::SwitchWorkspace(index) => {
+                self.switch_workspace(index);
+                return;
+            }
+            UiAction::AddWorkspace => {
+                let index = self.state.add_workspace("");
+                self.switch_workspace(index);
+                return;
+            }
+            UiAction::RenameWorkspace(index, name) => {
+                if let Some(workspace) = self.state.workspaces.get_mut(index) {
+                    workspace.name = name;
+                }
+                self.sync_workspace_tabs();
+                // Saved without adding an undo step
+                self.last_mutation_time = Some(Instant::now());
+                return;
+            }
+            UiAction::RemoveWorkspace(index) => {
+                if self.state.workspaces.len() > 1 && index == self.state.active_workspace {
+                    self.switch_workspace(if index == 0 { 1 } else { index - 1 });
+                }
+                if self.state.remove_workspace(index) {
+                    self.sync_workspace_tabs();
+                    self.save_state();
+                }
+                return;
+            }
+            UiAction::SetWorkspaceCameras(enabled) => {
+                self.state.workspace_cameras = enabled;
+            }
+            UiAction::RunCommand(line) => {
+                self.run_console_command(&line);
+                return;
+            }
+            UiAction::CloseCustomization => {
+                self.ui_state.close_customization();
+                return;
+            }
+            UiAction::None => return,
+        }
+        self.mark_dirty();
+    }
+
+    /// Handle a window event, returning whether egui consumed it
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        // The console's text field keeps keyboard focus, so its toggle key is
+        // caught before egui would type it into the field
+        if let WindowEvent::KeyboardInput { event, .. } = event {
+            if self.ui_state.console_open && self.ui_state.capturing_key.is_none() {
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    let ctrl = self.egui_state.egui_input().modifiers.ctrl;
+                    let toggle = self.state.keybindings.get(KeyAction::ToggleConsole);
+                    if event.state == ElementState::Pressed && toggle == (KeyBinding { key, ctrl }) {
+                        if !event.repeat {
+                            self.run_key_action(KeyAction::ToggleConsole);
+                        }
+                        return true;
+                    }
+                }
+            }
+        }
+
+        // First pass event to egui
+        let response = self.egui_state.on_window_event(&self.window, event);
+
+        // A key being rebound in the settings never reaches egui or the scene
+        if let WindowEvent::KeyboardInput { event, .. } = event {
+            if self.capture_keybinding(event) {
+                return true;
+            }
+        }
+
+        // egui holds the pointer during a drag out of the palette, but the
+        // ghost still follows the cursor onto the desk
+        if let WindowEvent::CursorMoved { position, .. } = event {
+            if self.palette_drag {
+                self.mouse_position = (position.x as f32, position.y as f32);
+                self.update_placement();
+            }
+        }
+
+        // If egui consumed the event, don't process it further
+        if response.consumed || self.egui_wants_input(event) {
+            return true;
+        }
+
+        // The replayed desk can't be edited; Escape stops playback
+        if self.playback.is_some() {
+            match event {
+                WindowEvent::KeyboardInput { event, .. } => {
+                    if event.state == ElementState::Pressed
+                        && event.physical_key == PhysicalKey::Code(KeyCode::Escape)
+                    {
+                        self.stop_playback();
+                    }
+                    return false;
+                }
+                WindowEvent::MouseInput { .. } | WindowEvent::MouseWheel { .. } => return false,
+                _ => {}
+            }
+        }
+
+        match event {
+            WindowEvent::MouseInput { button, state, .. } => {
+                if *button == MouseButton::Left {
+                    self.left_mouse_down = *state == ElementState::Pressed;
+                    if !self.left_mouse_down {
+                        // End drag
+                        self.gizmo_drag = None;
+                        if let Some(id) = self.dragging_object_id.take() {
+                            let objects_clone: Vec<DeskObject> = self.state.objects.clone();
+                            if let Some(obj) = self.state.get_object_mut(id) {
+                                self.physics.end_drag(obj, &objects_clone);
+                                self.update_object_transform(id);
+                            }
+                            self.mark_dirty();
+                        }
+                    } else if self.pending_placement.is_some() {
+                        self.commit_placement();
+                    } else if !self.try_pick_gizmo() {
+                        self.try_pick_object();
+                    }
+                } else if *button == MouseButton::Middle {
+                    self.middle_mouse_down = *state == ElementState::Pressed;
+                } else if *button == MouseButton::Right && *state == ElementState::Pressed {
+                    // Right-click on an object opens its context menu at the cursor
+                    let picked = self
+                        .find_object_at_cursor(true)
+                        .and_then(|id| self.state.get_object(id));
+                    if let Some(obj) = picked {
+                        self.ui_state.leave_clean_view();
+                        let scale = self.egui_ctx.pixels_per_point();
+                        let (mx, my) = self.mouse_position;
+                        self.ui_state
+                            .open_context_menu(obj, egui::pos2(mx / scale, my / scale));
+                    } else {
+                        // Right-click on empty space toggles the left sidebar
+                        if self.ui_state.leave_clean_view() {
+                            self.ui_state.left_sidebar_open = true;
+                        } else {
+                            self.ui_state.toggle_left_sidebar();
+                        }
+                    }
+                }
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // egui-winit picks up the new native scale; redraw so the
+                // sidebars are laid out for the new monitor right away
+                info!("Scale factor changed to {}", scale_factor);
+                self.window.request_redraw();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let (prev_x, prev_y) = self.mouse_position;
+                self.mouse_position = (position.x as f32, position.y as f32);
+                if self.middle_mouse_down {
+                    // Pan by the cursor movement relative to the viewport height
+                    let height = self.size.height.max(1) as f32;
+                    let dx = (self.mouse_position.0 - prev_x) / height;
+                    let dy = (self.mouse_position.1 - prev_y) / height;
+                    self.take_camera_control();
+                    self.camera.pan(dx, dy);
+                }
+                if self.pending_placement.is_some() {
+                    self.update_placement();
+                }
+                if self.hovered_file.is_some() {
+                    self.update_drop_target();
+                }
+                if self.left_mouse_down && self.dragging_object_id.is_some() {
+                    if self.gizmo_drag.is_some() {
+                        self.update_gizmo_drag();
+                    } else {
+                        self.update_drag();
+                    }
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let settings = self.state.scroll;
+                let (scroll_x, scroll) = settings.lines(*delta, self.window.scale_factor());
+                // Without an object in hand, two-finger trackpad scroll orbits
+                // the view and the mouse wheel zooms it
+                if self.dragging_object_id.is_none() && self.ui_state.selected_object_id.is_none() {
+                    self.take_camera_control();
+                    match delta {
+                        winit::event::MouseScrollDelta::PixelDelta(_) => {
+                            let sensitivity = CONFIG.camera.trackpad_orbit_sensitivity * PIXELS_PER_SCROLL_LINE;
+                            self.camera.orbit(scroll_x * sensitivity, -scroll * sensitivity);
+                        }
+                        winit::event::MouseScrollDelta::LineDelta(..) => {
+                            self.camera.zoom((scroll * SCROLL_ZOOM_STEP * settings.zoom_sensitivity).exp());
+                        }
+                    }
+                }
+                if let Some(id) = self.dragging_object_id {
+                    self.mark_dirty();
+                    if self.shift_pressed {
+                        if let Some(obj) = self.state.get_object_mut(id) {
+                            let step = SCROLL_SCALE_STEP * settings.scale_sensitivity;
+                            obj.scale = (obj.scale + scroll * step).clamp(MIN_SCALE, MAX_SCALE);
+                            self.update_object_transform(id);
+                        }
+                    } else {
+                        self.drag_yaw += scroll * SCROLL_ROTATE_STEP * settings.rotate_sensitivity;
+                        let yaw = self.snapped_drag_yaw();
+                        let Some(obj) = self.state.get_object_mut(id) else { return false };
+                        obj.rotation = Quat::from_rotation_y(yaw - obj.yaw()) * obj.rotation;
+                        self.update_object_transform(id);
+                    }
+                }
+            }
+            WindowEvent::HoveredFile(path) => {
+                self.hovered_file = Some(path.clone());
+                self.update_drop_target();
+            }
+            WindowEvent::HoveredFileCancelled => {
+                self.hovered_file = None;
+                self.update_drop_target();
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.hovered_file = None;
+                self.update_drop_target();
+                self.drop_image_file(path);
+            }
+            WindowEvent::PinchGesture { delta, .. } => {
+                // Non-finite deltas (winit allows NaN) are ignored by `zoom`
+                let factor = 1.0 + *delta as f32 * CONFIG.camera.pinch_zoom_sensitivity;
+                self.take_camera_control();
+                self.camera.zoom(factor);
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    if event.state == ElementState::Pressed {
+                        if let Some(action) = self.state.keybindings.action_for(key, self.ctrl_pressed) {
+                            // Holding undo/redo repeats them; other actions fire once per press
+                            if !event.repeat || matches!(action, KeyAction::Undo | KeyAction::Redo) {
+                                self.run_key_action(action);
+                            }
+                            return false;
+                        }
+                    }
+                    match key {
+                        KeyCode::ShiftLeft | KeyCode::ShiftRight => {
+                            self.shift_pressed = event.state == ElementState::Pressed;
+                        }
+                        KeyCode::ControlLeft | KeyCode::ControlRight => {
+                            self.ctrl_pressed = event.state == ElementState::Pressed;
+                        }
+                        KeyCode::KeyT if event.state == ElementState::Pressed => {
+                            // Cycle through object types
+                            let object_types = ObjectType::all_with_registered();
+                            self.current_object_type_index =
+                                (self.current_object_type_index + 1) % object_types.len();
+                            info!(
+                                "Selected: {} (Press {} to add)",
+                                object_types[self.current_object_type_index].display_name(),
+                                self.state.keybindings.get(KeyAction::AddObject).label()
+                            );
+                        }
+                        KeyCode::F3 if event.state == ElementState::Pressed => {
+                            self.ui_state.show_debug_overlay = !self.ui_state.show_debug_overlay;
+                        }
+                        KeyCode::F4 if event.state == ElementState::Pressed && !event.repeat => {
+                            self.debug_draw = !self.debug_draw;
+                            let wireframe = self.renderer.set_wireframe(self.debug_draw);
+                            if self.debug_draw && !wireframe {
+                                self.ui_state.show_toast(self.state.ui_lang.tr(Key::WireframeUnsupported));
+                            }
+                            if !self.debug_draw {
+                                self.renderer.set_debug_lines(None);
+                            }
+                        }
+                        KeyCode::Escape if event.state == ElementState::Pressed => {
+                            // Cancel placement, leave focus mode and close panels
+                            self.cancel_placement();
+                            self.set_focus(None);
+                            self.ui_state.close_context_menu();
+                            self.ui_state.close_customization();
+                            self.ui_state.left_sidebar_open = false;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// While the settings wait for a new binding, turn the next key press into
+    /// it (Escape cancels). Returns whether the event was used.
+    ///
+    /// Modifier keys are skipped so Ctrl can be held as part of the binding.
+    fn capture_keybinding(&mut self, event: &winit::event::KeyEvent) -> bool {
+        let Some(action) = self.ui_state.capturing_key else { return false };
+        let PhysicalKey::Code(key) = event.physical_key else { return false };
+        let is_modifier = matches!(
+            key,
+            KeyCode::ShiftLeft
+                | KeyCode::ShiftRight
+                | KeyCode::ControlLeft
+                | KeyCode::ControlRight
+                | KeyCode::AltLeft
+                | KeyCode::AltRight
+                | KeyCode::SuperLeft
+                | KeyCode::SuperRight
+        );
+        if event.state != ElementState::Pressed || is_modifier {
+            return false;
+        }
+
+        self.ui_state.capturing_key = None;
+        if key != KeyCode::Escape {
+            let binding = KeyBinding { key, ctrl: self.ctrl_pressed };
+            self.process_ui_action(UiAction::SetKeybinding(action, binding));
+            info!("Bound {} to {}", action.label(), binding.label());
+        }
+        true
+    }
+
+    /// Perform a remappable keyboard action
+    fn run_key_action(&mut self, action: KeyAction) {
+        match action {
+            KeyAction::AddObject => {
+                let obj_type = ObjectType::all_with_registered()[self.current_object_type_index];
+                if self.add_object(obj_type).is_some() {
+                    info!(
+                        "Added {} (Press T to cycle types, {} to add)",
+                        obj_type.display_name(),
+                        self.state.keybindings.get(action).label()
+                    );
+                }
+            }
+            KeyAction::Delete => {
+                // Delete dragged object (locked objects are never dragged)
+                if let Some(id) = self.dragging_object_id.take() {
+                    self.state.remove_object(id);
+                    self.remove_object_mesh(id);
+                    self.mark_dirty();
+                    info!("Deleted object");
+                }
+            }
+            KeyAction::Duplicate => {
+                if let Some(id) = self.dragging_object_id.or(self.ui_state.selected_object_id) {
+                    self.process_ui_action(UiAction::Duplicate(id));
+                }
+            }
+            KeyAction::ToggleMenu => {
+                // In clean view the menu key brings the UI back with the menu open
+                if self.ui_state.leave_clean_view() {
+                    self.ui_state.left_sidebar_open = true;
+                } else {
+                    self.ui_state.toggle_left_sidebar();
+                }
+            }
+            KeyAction::Screenshot => self.take_screenshot(),
+            KeyAction::Undo => self.undo(),
+            KeyAction::Redo => self.redo(),
+            KeyAction::Copy => self.copy_selection(),
+            KeyAction::Paste => self.paste(),
+            KeyAction::ToggleConsole => {
+                if self.ui_state.leave_clean_view() {
+                    self.ui_state.console_open = true;
+                } else {
+                    self.ui_state.console_open = !self.ui_state.console_open;
+                }
+            }
+            KeyAction::ToggleGrid => {
+                self.set_grid_visible(!self.state.show_grid);
+                self.mark_dirty();
+            }
+            KeyAction::FrameAll => self.frame_all(),
+            KeyAction::ToggleCleanView => {
+                self.ui_state.toggle_clean_view();
+                if self.ui_state.ui_hidden {
+                    let key = self.state.keybindings.get(KeyAction::ToggleCleanView).label();
+                    self.ui_state
+                        .show_toast(self.state.ui_lang.format(Key::CleanViewHint, &[&key]));
+                }
+            }
+        }
+    }
+
+    /// Fly the camera to fit the selected object, or every visible object,
+    /// in view. An empty desk frames the desk itself.
+    fn frame_all(&mut self) {
+        let selected = self.ui_state.selected_object_id.and_then(|id| self.state.get_object(id));
+        let bounds = match selected {
+            Some(obj) => Some(obj.bounds()),
+            None => self
+                .state
+                .objects
+                .iter()
+                .filter(|obj| !obj.hidden)
+                .map(DeskObject::bounds)
+                .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b))),
+        };
+        let (min, max) = bounds.unwrap_or_else(|| {
+            let desk = &CONFIG.desk;
+            let half = Vec3::new(desk.width / 2.0, 0.0, desk.depth / 2.0);
+            let surface = Vec3::new(0.0, desk.top_y(), 0.0);
+            (surface - half, surface + half)
+        });
+
+        let mut framed = self.camera.clone();
+        framed.fit_bounds(min, max, self.camera.aspect);
+        self.take_camera_control();
+        self.camera_flight = Some(CameraFlight::new(&self.camera, framed));
+    }
+
+    /// Cancel any camera flight and pause the turntable, as the camera is
+    /// being moved by hand
+    fn take_camera_control(&mut self) {
+        self.camera_flight = None;
+        self.last_camera_input = Some(Instant::now());
+    }
+
+    /// Run a debug console line, echoing it and its result to the console.
+    ///
+    /// Toasts raised while running it (e.g. a full desk or a failed load)
+    /// are echoed too, so the console shows the whole outcome.
+    fn run_console_command(&mut self, line: &str) {
+        self.ui_state.console_print(format!("> {}", line));
+        let toasts_before = self.ui_state.toasts.len();
+
+        let output = match Command::parse(line) {
+            Ok(Command::Add(object_type)) => self
+                .add_object(object_type)
+                .map(|id| format!("Added {} #{}", object_type.display_name(), id)),
+            Ok(Command::Clear) => {
+                self.process_ui_action(UiAction::ClearAll { keep_locked: false });
+                Some("Cleared the desk".to_string())
+            }
+            Ok(Command::SetGravity(gravity)) => {
+                self.physics.gravity = gravity;
+                Some(format!("gravity = {}", gravity))
+            }
+            Ok(Command::SetCollisionRadius(multiplier)) => {
+                self.state.collision_radius_multiplier = multiplier;
+                self.physics.collision_radius_multiplier = multiplier;
+                self.mark_dirty();
+                Some(format!("collision_radius = {}", multiplier))
+            }
+            Ok(Command::Save(name)) => {
+                self.process_ui_action(UiAction::SaveLayout(name));
+                None
+            }
+            Ok(Command::Load(name)) => {
+                self.process_ui_action(UiAction::LoadLayout(name));
+                None
+            }
+            Ok(Command::List) => {
+                for obj in &self.state.objects {
+                    let mut line = format!(
+                        "#{} {} at ({:.2}, {:.2}, {:.2})",
+                        obj.id, obj.label(), obj.position.x, obj.position.y, obj.position.z
+                    );
+                    if obj.locked {
+                        line.push_str(" locked");
+                    }
+                    if obj.hidden {
+                        line.push_str(" hidden");
+                    }
+                    self.ui_state.console_print(line);
+                }
+                Some(format!("{} objects", self.state.objects.len()))
+            }
+            Ok(Command::Help) => Some(console::HELP.to_string()),
+            Err(message) => Some(message),
+        };
+
+        let toasts: Vec<String> = self.ui_state.toasts[toasts_before..]
+            .iter()
+            .map(|toast| toast.message.clone())
+            .collect();
+        for line in output.into_iter().chain(toasts) {
+            self.ui_state.console_print(line);
+        }
+    }
+
+    /// Turn low power / reduced motion mode on or off. Idle motion and the
+    /// frame rate cap check the flag each frame; shadows and vsync change here.
+    fn set_low_power(&mut self, enabled: bool) {
+        self.state.low_power = enabled;
+        self.ui_state.low_power = enabled;
+        self.renderer.set_low_power(enabled);
+        self.configure_present_mode();
+    }
+
+    /// Switch the vsync / present mode, without restarting
+    fn set_present_mode(&mut self, mode: PresentMode) {
+        self.state.present_mode = mode;
+        self.ui_state.present_mode = mode;
+        self.configure_present_mode();
+    }
+
+    /// Reconfigure the surface for the chosen present mode and low power mode
+    fn configure_present_mode(&mut self) {
+        self.config.present_mode =
+            present_mode(self.state.present_mode, self.state.low_power, &self.present_modes);
+        self.surface.configure(&self.renderer.device, &self.config);
+        info!("Present mode: {:?}", self.config.present_mode);
+    }
+
+    /// Objects a color picked for `id` applies to: the whole selection if `id`
+    /// is part of one, else just `id`
+    fn color_targets(&self, id: u64) -> Vec<u64> {
+        if self.ui_state.selected_ids.contains(&id) {
+            self.ui_state.selected_ids.clone()
+        } else {
+            vec![id]
+        }
+    }
+
+    /// Add a color applied to an object's main or accent channel to the
+    /// recently used colors. Quick successive changes to the same channel
+    /// replace each other, so only the color settled on is kept.
+    fn remember_color(&mut self, id: u64, accent: bool, color: u32) {
+        let now = Instant::now();
+        let replace_latest = self.last_color_change.is_some_and(|(last_id, last_accent, time)| {
+            last_id == id && last_accent == accent && now - time <= RECENT_COLOR_SETTLE_TIME
+        });
+        self.last_color_change = Some((id, accent, now));
+        self.state.remember_color(color, replace_latest);
+        self.ui_state.recent_colors = self.state.recent_colors.clone();
+    }
+
+    /// Show or hide the layout grid and rulers
+    fn set_grid_visible(&mut self, visible: bool) {
+        self.state.show_grid = visible;
+        self.ui_state.show_grid = visible;
+        self.renderer.set_grid_visible(visible);
+    }
+
+    /// Screen positions and text of the ruler labels along the desk's front
+    /// and left edges. Whole units are labeled, or every line if they're
+    /// further apart.
+    fn grid_ruler_labels(&self) -> Vec<(egui::Pos2, String)> {
+        if !self.state.show_grid || !CONFIG.grid.rulers {
+            return Vec::new();
+        }
+        let spacing = CONFIG.grid.spacing;
+        let labeled = |offset: f32| spacing >= 1.0 || (offset - offset.round()).abs() < 1e-3;
+        let hw = CONFIG.desk.width / 2.0;
+        let hd = CONFIG.desk.depth / 2.0;
+        let y = CONFIG.desk.top_y();
+        const EDGE_GAP: f32 = 0.2;
+
+        let along_front = grid_line_offsets(hw, spacing)
+            .into_iter()
+            .filter(|&x| labeled(x))
+            .map(|x| (Vec3::new(x, y, hd + EDGE_GAP), x));
+        let along_left = grid_line_offsets(hd, spacing)
+            .into_iter()
+            .filter(|&z| labeled(z))
+            .map(|z| (Vec3::new(-hw - EDGE_GAP, y, z), z));
+        along_front
+            .chain(along_left)
+            .filter_map(|(point, offset)| Some((self.world_to_screen(point)?, format!("{}", offset))))
+            .collect()
+    }
+
+    /// Whether egui owns this input even if it didn't mark it consumed, so
+    /// typing into a text field never triggers scene shortcuts and clicks on
+    /// panels never pick objects behind them.
+    ///
+    /// Releases always reach the scene so modifier state and drags can't get stuck.
+    fn egui_wants_input(&self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                event.state == ElementState::Pressed && self.egui_ctx.wants_keyboard_input()
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                ..
+            }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::PinchGesture { .. } => self.egui_ctx.wants_pointer_input(),
+            _ => false,
+        }
+    }
+
+    /// Project a world-space point to window coordinates in egui points
+    fn world_to_screen(&self, point: Vec3) -> Option<egui::Pos2> {
+        let clip = self.camera.view_projection_matrix() * point.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        let scale = self.egui_ctx.pixels_per_point();
+        Some(egui::pos2(
+            (ndc.x + 1.0) * 0.5 * self.size.width as f32 / scale,
+            (1.0 - ndc.y) * 0.5 * self.size.height as f32 / scale,
+        ))
+    }
+
+    /// World-space ray (origin, direction) from the camera through the cursor
+    fn cursor_ray(&self) -> (Vec3, Vec3) {
+        let (mx, my) = self.mouse_position;
+        let ndc_x = (2.0 * mx / self.size.width as f32) - 1.0;
+        let ndc_y = 1.0 - (2.0 * my / self.size.height as f32);
+        self.camera.ray_through(ndc_x, ndc_y)
+    }
+
+    /// Point on the surface under the cursor: the desk, or the floor past its
+    /// edge, clamped to the floor area
+    fn cursor_surface_position(&self) -> Option<Vec3> {
+        let desk_hit = self.cursor_plane_position(self.physics.desk_surface_y());
+        if let Some(hit) = desk_hit.filter(|&hit| self.physics.is_on_desk(hit)) {
+            return Some(hit);
+        }
+        // A floor point under the desk top is hidden by it, so stay on the desk
+        let floor_hit = self
+            .cursor_plane_position(self.physics.floor_y)
+            .map(|hit| self.physics.clamp_to_floor(hit, 0.0))
+            .filter(|&hit| !self.physics.is_on_desk(hit));
+        floor_hit
+            .or_else(|| desk_hit.map(|hit| self.physics.clamp_to_desk(hit, 0.0)))
+            .map(|hit| Vec3::new(hit.x, self.physics.surface_y_at(hit), hit.z))
+    }
+
+    /// Point under the cursor on the horizontal plane at the given height
+    fn cursor_plane_position(&self, plane_y: f32) -> Option<Vec3> {
+        let (ray_origin, ray_direction) = self.cursor_ray();
+        physics::ray_plane_intersection(
+            ray_origin,
+            ray_direction,
+            Vec3::new(0.0, plane_y, 0.0),
+            Vec3::Y,
+        )
+    }
+
+    /// Object under the cursor that a dropped image would be shown on, if it
+    /// shows images (locked objects included)
+    fn image_target_at_cursor(&self) -> Option<u64> {
+        let id = self.find_object_at_cursor(true)?;
+        self.state
+            .get_object(id)
+            .filter(|obj| obj.object_type.shows_image())
+            .map(|obj| obj.id)
+    }
+
+    /// Make the object a hovered image file would be dropped on glow
+    fn update_drop_target(&mut self) {
+        let target = match &self.hovered_file {
+            Some(path) if is_supported_image(path) => self.image_target_at_cursor(),
+            _ => None,
+        };
+        if target == self.drop_target {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.drop_target, target);
+        self.renderer.set_drop_target(target);
+        for id in previous.into_iter().chain(target) {
+            self.update_object_transform(id);
+        }
+    }
+
+    /// Show an image file dropped on the window on the object under the cursor
+    fn drop_image_file(&mut self, path: &Path) {
+        if !is_supported_image(path) {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::OnlyImagesDrop));
+            return;
+        }
+        let Some(id) = self.image_target_at_cursor() else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::DropOntoImageObject));
+            return;
+        };
+        // Check the file before it becomes a cover; images larger than the
+        // GPU allows are scaled down on upload
+        let lang = self.state.ui_lang;
+        match image::image_dimensions(path) {
+            Ok((width, height)) => {
+                let max_size = self.renderer.device.limits().max_texture_dimension_2d;
+                if width > max_size || height > max_size {
+                    let size = format!("{}×{}", width, height);
+                    self.ui_state.show_toast(lang.format(Key::ImageScaledDown, &[&size, &max_size]));
+                }
+            }
+            Err(e) => {
+                log::warn!("Could not read dropped image {:?}: {}", path, e);
+                self.ui_state.show_toast(lang.format(Key::ImageUnreadable, &[&e]));
+                return;
+            }
+        }
+        // A file that failed to load before may have been fixed since
+        self.renderer.retry_image(path);
+        let path = path.to_string_lossy().into_owned();
+        if self.ui_state.selected_object_id == Some(id) {
+            self.ui_state.cover_path_input = path.clone();
+            self.ui_state.current_builtin_image = None;
+        }
+        self.process_ui_action(UiAction::SetCoverImage(id, Some(ImageSource::Path(path))));
+    }
+
+    /// Find object at cursor position (without starting drag), optionally
+    /// looking through locked objects
+    fn find_object_at_cursor(&self, include_locked: bool) -> Option<u64> {
+        let (ray_origin, ray_world) = self.cursor_ray();
+
+        self.state
+            .objects
+            .iter()
+            .filter(|obj| !obj.hidden && (include_locked || !obj.locked))
+            .filter_map(|obj| {
+                obj.ray_intersection(ray_origin, ray_world, PICK_MARGIN)
+                    .map(|t| (obj.id, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    fn try_pick_object(&mut self) {
+        // Clicking a pulsing object (even a locked one) dismisses its alert
+        if let Some(id) = self.find_object_at_cursor(true) {
+            if self.state.get_object_mut(id).and_then(|obj| obj.alert_until.take()).is_some() {
+                self.update_object_transform(id);
+            }
+        }
+
+        // Locked objects are skipped so whatever is behind them can be grabbed
+        let Some(id) = self.find_object_at_cursor(false) else {
+            // Clicking empty space leaves focus mode (locked objects count as
+            // scenery here, so clicking one doesn't exit unexpectedly)
+            if self.find_object_at_cursor(true).is_none() {
+                self.set_focus(None);
+            }
+            self.last_click = None;
+            return;
+        };
+
+        // Double-clicking an object focuses it
+        let now = Instant::now();
+        match self.last_click.take() {
+            Some((last_id, time)) if last_id == id && now - time <= DOUBLE_CLICK_TIME => {
+                self.set_focus(Some(id));
+            }
+            _ => self.last_click = Some((id, now)),
+        }
+
+        // Remember where on the object it was grabbed so it doesn't jump to the cursor
+        let grab_point = self.cursor_plane_position(self.drag_plane_y());
+        self.dragging_object_id = Some(id);
+        if let Some(obj) = self.state.get_object_mut(id) {
+            obj.is_dragging = true;
+            self.drag_yaw = obj.yaw();
+            self.drag_offset = grab_point
+                .map(|hit| Vec3::new(obj.position.x - hit.x, 0.0, obj.position.z - hit.z))
+                .unwrap_or(Vec3::ZERO);
+        }
+    }
+
+    /// Yaw to give the dragged object: `drag_yaw`, in fixed steps while
+    /// rotation snapping is on and Ctrl isn't held
+    fn snapped_drag_yaw(&self) -> f32 {
+        if self.state.rotation_snap && !self.ctrl_pressed {
+            (self.drag_yaw / ROTATION_SNAP_STEP).round() * ROTATION_SNAP_STEP
+        } else {
+            self.drag_yaw
+        }
+    }
+
+    /// Object the transform gizmo is on, with the gizmo's center and size:
+    /// the selected object, unless it's hidden or locked or the gizmo is off
+    fn gizmo(&self) -> Option<(u64, Vec3, f32)> {
+        if !self.state.show_gizmo || self.pending_placement.is_some() || self.playback.is_some() {
+            return None;
+        }
+        let obj = self.ui_state.selected_object_id.and_then(|id| self.state.get_object(id))?;
+        if obj.hidden || obj.locked {
+            return None;
+        }
+        let size = (self.camera.view_height_at(obj.position) * GIZMO_SCREEN_SIZE)
+            .max(obj.collision_radius() * GIZMO_OBJECT_MARGIN);
+        Some((obj.id, obj.position, size))
+    }
+
+    /// Show where the selected lamp's light ends on the surface it stands on
+    fn update_light_ring(&mut self) {
+        let ring = self
+            .ui_state
+            .selected_object_id
+            .and_then(|id| self.state.get_object(id))
+            .and_then(|obj| {
+                let (bulb, radius) = obj.light_source()?;
+                let surface_y = obj.position.y - obj.base_offset();
+                let height = bulb.y - surface_y;
+                // Where the sphere of light meets the surface
+                (radius > height).then(|| {
+                    (Vec3::new(bulb.x, surface_y, bulb.z), (radius * radius - height * height).sqrt())
+                })
+            });
+        if ring != self.light_ring {
+            self.renderer.set_light_ring(ring);
+            self.light_ring = ring;
+        }
+    }
+
+    /// Show the gizmo on the selected object, highlighting the handle being
+    /// dragged or else the one under the cursor
+    fn update_gizmo(&mut self) {
+        let gizmo = self.gizmo().map(|(_, center, size)| {
+            let active = match self.gizmo_drag {
+                Some(drag) => Some(drag.handle),
+                None => {
+                    let (ray_origin, ray_direction) = self.cursor_ray();
+                    gizmo::pick(center, size, ray_origin, ray_direction)
+                }
+            };
+            (center, size, active)
+        });
+        self.renderer.set_gizmo(gizmo);
+    }
+
+    /// Pick what is under the cursor for the cursor feedback
+    fn update_hover(&mut self) {
+        if !CONFIG.window.cursor_feedback || self.dragging_object_id.is_some() {
+            return;
+        }
+        let (ray_origin, ray_direction) = self.cursor_ray();
+        let over_gizmo = self
+            .gizmo()
+            .is_some_and(|(_, center, size)| gizmo::pick(center, size, ray_origin, ray_direction).is_some());
+        self.hovering_grabbable = over_gizmo || self.find_object_at_cursor(false).is_some();
+    }
+
+    /// Cursor over the 3D view: a closed hand while an object is dragged and an
+    /// open one over something a click would grab
+    fn scene_cursor(&self) -> egui::CursorIcon {
+        if self.dragging_object_id.is_some() {
+            egui::CursorIcon::Grabbing
+        } else if self.hovering_grabbable && self.pending_placement.is_none() && self.playback.is_none() {
+            egui::CursorIcon::Grab
+        } else {
+            egui::CursorIcon::Default
+        }
+    }
+
+    /// Start turning or moving the selected object if a gizmo handle is under
+    /// the cursor, returning whether one was
+    fn try_pick_gizmo(&mut self) -> bool {
+        let Some((id, center, size)) = self.gizmo() else { return false };
+        let (ray_origin, ray_direction) = self.cursor_ray();
+        let Some(handle) = gizmo::pick(center, size, ray_origin, ray_direction) else { return false };
+        let Some(start_value) = GizmoDrag::handle_value(handle, center, ray_origin, ray_direction) else {
+            return false;
+        };
+        let Some(obj) = self.state.get_object_mut(id) else { return false };
+        obj.is_dragging = true;
+        self.drag_yaw = obj.yaw();
+        self.gizmo_drag = Some(GizmoDrag {
+            object_id: id,
+            handle,
+            center,
+            start_yaw: obj.yaw(),
+            start_value,
+        });
+        self.dragging_object_id = Some(id);
+        true
+    }
+
+    /// Turn the object with the gizmo's ring, or move it along an arrow
+    fn update_gizmo_drag(&mut self) {
+        let Some(drag) = self.gizmo_drag else { return };
+        let (ray_origin, ray_direction) = self.cursor_ray();
+        let Some(value) = GizmoDrag::handle_value(drag.handle, drag.center, ray_origin, ray_direction) else {
+            return;
+        };
+        if drag.handle == GizmoHandle::Ring {
+            self.drag_yaw = drag.yaw(value);
+        }
+        let yaw = self.snapped_drag_yaw();
+        // Objects moved past the desk edge drop onto the floor
+        let target = self.physics.clamp_to_floor(drag.position(value), 0.0);
+        let Some(obj) = self.state.get_object_mut(drag.object_id) else { return };
+        if drag.handle == GizmoHandle::Ring {
+            obj.rotation = Quat::from_rotation_y(yaw - obj.yaw()) * obj.rotation;
+        } else {
+            obj.position.x = target.x;
+            obj.position.z = target.z;
+        }
+        self.update_object_transform(drag.object_id);
+    }
+
+    /// Height of the plane dragged objects move along
+    fn drag_plane_y(&self) -> f32 {
+        self.physics.desk_surface_y() + 0.5
+    }
+
+    fn update_drag(&mut self) {
+        let plane_y = self.drag_plane_y();
+
+        if let Some(intersection) = self.cursor_plane_position(plane_y) {
+            // Objects dragged past the desk edge drop onto the floor
+            let target = self.physics.clamp_to_floor(intersection + self.drag_offset, 0.0);
+            // Ctrl places freely, like it rotates freely
+            let edge_snap = self.state.edge_snap && !self.ctrl_pressed;
+            if let Some(id) = self.dragging_object_id {
+                if let Some(obj) = self.state.get_object_mut(id) {
+                    let target = if edge_snap {
+                        self.physics.snap_to_desk_edge(obj, target, CONFIG.physics.edge_snap_distance)
+                    } else {
+                        target
+                    };
+                    obj.position.x = target.x;
+                    obj.position.z = target.z;
+                    obj.position.y = plane_y;
+                    self.update_object_transform(id);
+                }
+            }
+        }
+    }
+
+    fn add_object(&mut self, object_type: ObjectType) -> Option<u64> {
+        self.add_object_at(object_type, self.spawn_position())
+    }
+
+    /// Where a new object goes: where the camera looks on the desk (or the
+    /// desk's center if that's off the desk) with `spawn_at_aim`, else a random spot
+    fn spawn_position(&self) -> Vec3 {
+        if !self.state.spawn_at_aim {
+            return self.random_desk_position();
+        }
+        let surface_y = self.physics.desk_surface_y();
+        self.camera
+            .aim_point(surface_y)
+            .filter(|&point| self.physics.is_on_desk(point))
+            .unwrap_or(Vec3::new(0.0, surface_y, 0.0))
+    }
+
+    /// A random spot near the middle of the desk surface
+    fn random_desk_position(&self) -> Vec3 {
+        Vec3::new(
+            rand::random::<f32>() * 4.0 - 2.0,
+            self.physics.desk_surface_y(),
+            rand::random::<f32>() * 3.0 - 1.5,
+        )
+    }
+
+    /// Add a copy of a saved template at the spawn position, returning
+    /// its ID or `None` if there's no such template or the desk is full
+    fn add_from_template(&mut self, index: usize) -> Option<u64> {
+        let mut object = self.state.templates.get(index)?.clone();
+        if !self.can_add_object() {
+            return None;
+        }
+
+        object.id = self.state.next_id();
+        object.position = self.spawn_position();
+        let objects_clone: Vec<DeskObject> = self.state.objects.clone();
+        self.physics.end_drag(&mut object, &objects_clone);
+        object.position.y = object.target_y;
+        object.start_spawn_animation();
+
+        let id = object.id;
+        self.create_object_mesh(&object);
+        info!("Added {} from favorites", object.label());
+        self.state.add_object(object);
+        Some(id)
+    }
+
+    /// Add an object resting on the desk surface at the given position,
+    /// returning its ID or `None` if the object cap has been reached
+    fn add_object_at(&mut self, object_type: ObjectType, position: Vec3) -> Option<u64> {
+        if !self.can_add_object() {
+            return None;
+        }
+
+        let id = self.state.next_id();
+        let mut object = DeskObject::new(id, object_type, position);
+        object.start_spawn_animation();
+        self.create_object_mesh(&object);
+        self.state.add_object(object);
+        self.mark_dirty();
+        Some(id)
+    }
+
+    /// Add a copy of an object next to the original
+    fn duplicate_object(&mut self, id: u64) {
+        let Some(original) = self.state.get_object(id).cloned() else { return };
+        if !self.can_add_object() {
+            return;
+        }
+
+        let mut copy = original;
+        copy.id = self.state.next_id();
+        copy.locked = false;
+        copy.position.x += 0.3;
+        copy.position.z += 0.3;
+        let objects_clone: Vec<DeskObject> = self.state.objects.clone();
+        self.physics.place(&mut copy, &objects_clone);
+        copy.start_spawn_animation();
+
+        self.create_object_mesh(&copy);
+        info!("Duplicated object {} as {}", id, copy.id);
+        self.state.add_object(copy);
+    }
+
+    /// Copy the dragged or selected object to the app and system clipboards
+    fn copy_selection(&mut self) {
+        let Some(id) = self.dragging_object_id.or(self.ui_state.selected_object_id) else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::SelectToCopy));
+            return;
+        };
+        let Some(obj) = self.state.get_object(id) else { return };
+        let clipboard = Clipboard::copy(std::slice::from_ref(obj));
+        self.egui_state.set_clipboard_text(clipboard.to_text());
+        let lang = self.state.ui_lang;
+        self.ui_state.show_toast(lang.format(Key::Copied, &[&lang.object_label(obj)]));
+        self.clipboard = Some(clipboard);
+    }
+
+    /// Paste copied objects at the cursor, preferring objects on the system
+    /// clipboard (e.g. copied in another instance)
+    fn paste(&mut self) {
+        let clipboard = self
+            .egui_state
+            .clipboard_text()
+            .and_then(|text| Clipboard::from_text(&text))
+            .or_else(|| self.clipboard.clone());
+        let Some(clipboard) = clipboard else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::NothingToPaste));
+            return;
+        };
+        let max_objects = CONFIG.objects.max_objects;
+        if self.state.objects.len() + clipboard.len() > max_objects {
+            self.ui_state
+                .show_toast(self.state.ui_lang.format(Key::DeskFull, &[&max_objects]));
+            return;
+        }
+
+        let anchor = self.cursor_surface_position();
+        let pasted = clipboard.paste(|| self.state.next_id(), anchor);
+        for mut copy in pasted {
+            // Clipboard text can come from anywhere, so treat it like a loaded file
+            copy.sanitize();
+            let objects_clone: Vec<DeskObject> = self.state.objects.clone();
+            self.physics.place(&mut copy, &objects_clone);
+            copy.start_spawn_animation();
+            self.create_object_mesh(&copy);
+            self.state.add_object(copy);
+        }
+        self.mark_dirty();
+        info!("Pasted {} object(s)", clipboard.len());
+    }
+
+    /// Replace the unlocked objects with a procedurally generated layout
+    fn shuffle_desk(&mut self, seed: u64) {
+        self.state.objects.retain(|obj| obj.locked);
+        let mut next_id = self.state.next_object_id;
+        let objects = shuffle_layout(
+            seed,
+            || {
+                next_id += 1;
+                next_id - 1
+            },
+            &self.state.objects,
+            CONFIG.objects.max_objects,
+            &self.physics,
+        );
+        self.state.next_object_id = next_id;
+        for mut object in objects {
+            object.start_spawn_animation();
+            self.state.add_object(object);
+        }
+
+        self.rebuild_object_meshes();
+        self.ui_state.close_customization();
+        self.ui_state.show_toast(self.state.ui_lang.format(Key::Shuffled, &[&seed]));
+        info!("Shuffled desk with seed {}", seed);
+    }
+
+    /// Save the desk as a named layout with a thumbnail of the current view
+    fn save_layout(&mut self, name: &str) {
+        match self.state.save_named(name) {
+            Ok(path) => {
+                // Overwriting a slot regenerates its thumbnail
+                self.save_thumbnail(&path.with_extension("png"));
+                if let Some(stem) = path.file_stem() {
+                    self.ui_state.invalidate_thumbnail(&stem.to_string_lossy());
+                }
+                self.ui_state.saved_layouts = AppState::list_named_saves(self.ui_state.layout_sort);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::SavedLayout, &[&name]));
+            }
+            Err(e) => {
+                log::error!("Failed to save layout {:?}: {}", name, e);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::SaveLayoutFailed, &[&e]));
+            }
+        }
+    }
+
+    /// Render the desk offscreen and write it next to a named save
+    fn save_thumbnail(&mut self, path: &std::path::Path) {
+        let (width, height) = CONFIG.persistence.thumbnail_size;
+        let mut camera = self.camera.clone();
+        camera.set_aspect(width as f32 / height as f32);
+
+        let result = self
+            .renderer
+            .render_to_image(&self.state.objects, &camera, width, height)
+            .and_then(|image| Ok(image.save(path)?));
+        if let Err(e) = result {
+            log::warn!("Could not write layout thumbnail {:?}: {}", path, e);
+        }
+    }
+
+    /// Replace the desk with a named layout
+    fn load_layout(&mut self, name: &str) {
+        let mut loaded = match AppState::load_named(name) {
+            Ok(state) => state,
+            Err(e) => {
+                log::error!("Failed to load layout {:?}: {}", name, e);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::LoadLayoutFailed, &[&e]));
+                return;
+            }
+        };
+
+        // Window size and preferences belong to this session, not the layout,
+        // and a read-only state file stays protected whatever is loaded
+        let partial = loaded.read_only;
+        loaded.read_only = self.state.read_only;
+        loaded.window_size = self.state.window_size;
+        loaded.ui_scale = self.state.ui_scale;
+        loaded.rotation_snap = self.state.rotation_snap;
+        loaded.edge_snap = self.state.edge_snap;
+        loaded.show_gizmo = self.state.show_gizmo;
+        loaded.palette_drag = self.state.palette_drag;
+        loaded.spawn_at_aim = self.state.spawn_at_aim;
+        loaded.ui_theme = self.state.ui_theme;
+        loaded.ui_lang = self.state.ui_lang;
+        loaded.keybindings = self.state.keybindings.clone();
+        loaded.show_grid = self.state.show_grid;
+        loaded.idle_motion = self.state.idle_motion;
+        loaded.bloom_intensity = self.state.bloom_intensity;
+        loaded.resolution_divisor = self.state.resolution_divisor;
+        loaded.turntable = self.state.turntable;
+        loaded.turntable_speed = self.state.turntable_speed;
+        loaded.low_power = self.state.low_power;
+        loaded.present_mode = self.state.present_mode;
+        loaded.fps_cap = self.state.fps_cap;
+        loaded.scroll = self.state.scroll;
+        loaded.focus_stats = std::mem::take(&mut self.state.focus_stats);
+        loaded.templates = std::mem::take(&mut self.state.templates);
+        loaded.recent_colors = std::mem::take(&mut self.state.recent_colors);
+        // The layout replaces the desk of the active workspace only
+        loaded.workspaces = std::mem::take(&mut self.state.workspaces);
+        loaded.active_workspace = self.state.active_workspace;
+        loaded.workspace_cameras = self.state.workspace_cameras;
+        self.state = loaded;
+        self.physics.collision_radius_multiplier = self.state.collision_radius_multiplier;
+        self.physics.settle(&mut self.state.objects);
+        self.dragging_object_id = None;
+        self.cancel_placement();
+        self.ui_state.close_context_menu();
+        self.ui_state.close_customization();
+        self.rebuild_object_meshes();
+        let key = if partial { Key::LoadedLayoutPartly } else { Key::LoadedLayout };
+        self.ui_state.show_toast(self.state.ui_lang.format(key, &[&name]));
+    }
+
+    /// Put the desk away in its workspace and bring out workspace `index`,
+    /// saving both right away. The camera moves to the view the workspace was
+    /// left with if each has its own.
+    fn switch_workspace(&mut self, index: usize) {
+        self.stop_playback();
+        let leaving = self.state.active_workspace;
+        let view = self.camera.view();
+        if !self.state.switch_workspace(index) {
+            return;
+        }
+        if self.state.workspace_cameras {
+            self.state.workspaces[leaving].camera = Some(view);
+            if let Some(view) = self.state.workspaces[index].camera {
+                self.take_camera_control();
+                self.camera.set_view(&view);
+            }
+        }
+
+        // Undo steps belong to the desk they were taken on
+        self.history = History::new(&self.state.objects);
+        self.set_focus(None);
+        self.dragging_object_id = None;
+        self.cancel_placement();
+        self.ui_state.close_context_menu();
+        self.ui_state.close_customization();
+        self.rebuild_object_meshes();
+        self.sync_workspace_tabs();
+        self.save_state();
+        let name = &self.state.workspaces[index].name;
+        let label = workspace_label(self.state.ui_lang, name, index);
+        self.ui_state.show_toast(self.state.ui_lang.format(Key::SwitchedWorkspace, &[&label]));
+    }
+
+    /// Show the current workspaces on the palette's tabs
+    fn sync_workspace_tabs(&mut self) {
+        self.ui_state.workspace_names = self.state.workspaces.iter().map(|workspace| workspace.name.clone()).collect();
+        self.ui_state.active_workspace = self.state.active_workspace;
+    }
+
+    /// Record an object change so the debounced autosave picks it up
+    fn mark_dirty(&mut self) {
+        self.history.record(&self.state.objects);
+        self.last_mutation_time = Some(Instant::now());
+    }
+
+    /// Revert the desk to before the last change
+    fn undo(&mut self) {
+        match self.history.undo() {
+            Some(objects) => {
+                self.state.restore_objects(objects);
+                self.restore_history_snapshot();
+            }
+            None => self.ui_state.show_toast(self.state.ui_lang.tr(Key::NothingToUndo)),
+        }
+    }
+
+    /// Reapply the last undone change
+    fn redo(&mut self) {
+        match self.history.redo() {
+            Some(objects) => {
+                self.state.restore_objects(objects);
+                self.restore_history_snapshot();
+            }
+            None => self.ui_state.show_toast(self.state.ui_lang.tr(Key::NothingToRedo)),
+        }
+    }
+
+    /// Sync meshes and panels after the objects were swapped for a snapshot
+    fn restore_history_snapshot(&mut self) {
+        // Snapshots taken mid-drag (e.g. while scroll-rotating) drop the object in place
+        self.dragging_object_id = None;
+        for obj in &mut self.state.objects {
+            obj.is_dragging = false;
+        }
+        self.ui_state.close_context_menu();
+        self.ui_state.close_customization();
+        self.rebuild_object_meshes();
+        // Saved like any other change, without adding another undo step
+        self.last_mutation_time = Some(Instant::now());
+    }
+
+    /// Start recording the session, or stop and save the current recording
+    fn toggle_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            self.ui_state.recording = false;
+            match recorder.finish() {
+                Ok(path) => self.ui_state.show_toast(self.state.ui_lang.format(Key::RecordingSaved, &[&path.display()])),
+                Err(e) => self.ui_state.show_toast(self.state.ui_lang.format(Key::SaveRecordingFailed, &[&e])),
+            }
+            return;
+        }
+
+        let Some(path) = AppState::recording_path() else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::NoRecordingFolder));
+            return;
+        };
+        match Recorder::create(&path) {
+            Ok(recorder) => {
+                info!("Recording session to {:?}", recorder.path());
+                self.recorder = Some(recorder);
+                self.ui_state.recording = true;
+                self.ui_state.show_toast(self.state.ui_lang.tr(Key::RecordingStarted));
+            }
+            Err(e) => self.ui_state.show_toast(self.state.ui_lang.format(Key::StartRecordingFailed, &[&e])),
+        }
+    }
+
+    /// Replay the most recent recording in place of the desk
+    fn play_last_recording(&mut self) {
+        let Some(path) = AppState::latest_recording() else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::NoRecordings));
+            return;
+        };
+        let player = match Player::load(&path) {
+            Ok(player) => player,
+            Err(e) => {
+                log::error!("Failed to load recording {:?}: {}", path, e);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::LoadRecordingFailed, &[&e]));
+                return;
+            }
+        };
+
+        info!("Playing {:?} ({:.1}s)", path, player.duration());
+        self.dragging_object_id = None;
+        self.cancel_placement();
+        self.ui_state.close_context_menu();
+        self.ui_state.close_customization();
+        self.desk_before_playback = std::mem::take(&mut self.state.objects);
+        self.playback = Some(player);
+        self.ui_state.playing = true;
+        self.advance_playback(0.0);
+    }
+
+    /// Move playback forward, showing the replayed desk, and end it when done
+    fn advance_playback(&mut self, dt: f32) {
+        let Some(player) = &mut self.playback else { return };
+        player.advance(dt);
+        let finished = player.is_finished();
+        let objects = player.objects();
+
+        // Moves and interactive state (e.g. a running hourglass) only need new
+        // transforms, since meshes don't show either; anything else rebuilds them
+        let same_objects = objects.len() == self.state.objects.len()
+            && objects
+                .iter()
+                .zip(&self.state.objects)
+                .all(|(replayed, shown)| {
+                    replayed.id == shown.id
+                        && DeskObject {
+                            position: shown.position,
+                            rotation: shown.rotation,
+                            scale: shown.scale,
+                            state: shown.state.clone(),
+                            ..replayed.clone()
+                        } == *shown
+                });
+        self.state.objects = objects;
+        if same_objects {
+            for id in self.state.objects.iter().map(|obj| obj.id).collect::<Vec<_>>() {
+                self.update_object_transform(id);
+            }
+        } else {
+            self.rebuild_object_meshes();
+        }
+
+        if finished {
+            self.stop_playback();
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::PlaybackFinished));
+        }
+    }
+
+    /// End playback and bring back the desk
+    fn stop_playback(&mut self) {
+        if self.playback.take().is_none() {
+            return;
+        }
+        self.ui_state.playing = false;
+        self.state.objects = std::mem::take(&mut self.desk_before_playback);
+        self.rebuild_object_meshes();
+    }
+
+    /// Save the current view (without the UI) as a PNG
+    fn take_screenshot(&mut self) {
+        let Some(path) = AppState::screenshot_path() else {
+            self.ui_state.show_toast(self.state.ui_lang.tr(Key::NoScreenshotFolder));
+            return;
+        };
+        let result = self
+            .renderer
+            .render_to_image(&self.state.objects, &self.camera, self.size.width, self.size.height)
+            .and_then(|image| Ok(image.save(&path)?));
+        match result {
+            Ok(()) => {
+                info!("Saved screenshot to {:?}", path);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::ScreenshotSaved, &[&path.display()]));
+            }
+            Err(e) => {
+                log::error!("Failed to save screenshot {:?}: {}", path, e);
+                self.ui_state.show_toast(self.state.ui_lang.format(Key::ScreenshotFailed, &[&e]));
+            }
+        }
+    }
+
+    pub fn save_state(&mut self) {
+        self.stop_playback();
+        if self.state.workspace_cameras {
+            self.state.workspaces[self.state.active_workspace].camera = Some(self.camera.view());
+        }
+        // A desk with data this run can't load is never saved over (see
+        // `AppState::read_only`)
+        if !self.state.read_only {
+            if let Err(e) = self.state.save() {
+                log::error!("Failed to save state: {}", e);
+            }
+        }
+        self.last_save_time = Instant::now();
+        self.last_mutation_time = None;
+    }
+
+    /// The window the app renders into
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Current surface size in physical pixels
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    /// The desk state (objects and preferences)
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// Whether the window has input focus
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Record a focus change so the host can throttle background redraws
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        if focused {
+            self.window.request_redraw();
+        } else if self.last_mutation_time.is_some() && self.playback.is_none() {
+            // Rendering may pause in the background, so don't leave a change unsaved
+            self.save_state();
+        }
+    }
+
+    /// When a paused window next has to update: a running hourglass
+    /// finishing or a pending change being saved (`None` = nothing to do)
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        let timers = self
+            .state
+            .objects
+            .iter()
+            .filter_map(|obj| obj.state.timer_remaining())
+            .map(|remaining| self.last_frame_time + Duration::from_secs_f32(remaining));
+        let save = self
+            .last_mutation_time
+            .filter(|_| self.playback.is_none())
+            .map(|changed| changed + CONFIG.persistence.save_debounce);
+        timers.chain(save).min()
+    }
+
+    /// Show the first-launch welcome tip as a long-lived toast
+    pub fn show_welcome(&mut self) {
+        self.ui_state.show_hint(self.state.ui_lang.tr(Key::Welcome));
+    }
+
+    /// Whether low power mode caps the frame rate
+    pub fn low_power(&self) -> bool {
+        self.state.low_power
+    }
+
+    /// Minimum time between frames while focused, from the frame rate cap
+    /// and low power mode (`None` = draw as often as possible)
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.state.frame_interval()
+    }
+
+    /// When the last frame was updated
+    pub fn last_frame_time(&self) -> Instant {
+        self.last_frame_time
+    }
+}
+
+/// The surface present mode for the user's choice, falling back when the
+/// surface doesn't support it. Low power mode waits for vsync in a strict
+/// queue, which is always supported.
+fn present_mode(mode: PresentMode, low_power: bool, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    if low_power {
+        wgpu::PresentMode::Fifo
+    } else {
+        mode.to_wgpu(supported)
+    }
+}
@@ -0,0 +1,117 @@
+//! "Arrange" tidy-up of the objects already on the desk
+//!
+//! Lines objects up in an evenly spaced grid, grouped by type in palette
+//! order. Every cell is sized for the largest object, so nothing overlaps no
+//! matter which objects end up next to each other.
+
+use crate::desk_object::{DeskObject, ObjectType};
+use crate::physics::PhysicsEngine;
+use glam::{Quat, Vec3};
+
+/// Space left between the footprints of the largest objects in neighboring cells
+const ARRANGE_GAP: f32 = 0.1;
+
+/// Move every unlocked object into a grid centered on the desk, squared up
+/// and resting on the desk surface. Locked objects stay where they are, and
+/// cells they cover are skipped.
+///
+/// Returns how many objects didn't fit and were left in place.
+pub fn arrange_grid(objects: &mut [DeskObject], physics: &PhysicsEngine) -> usize {
+    let mut order: Vec<usize> = (0..objects.len()).filter(|&i| !objects[i].locked).collect();
+    if order.is_empty() {
+        return 0;
+    }
+    let type_index = |object: &DeskObject| {
+        ObjectType::all().iter().position(|&t| t == object.object_type)
+    };
+    order.sort_by_key(|&i| (type_index(&objects[i]), objects[i].id));
+
+    let cell = order
+        .iter()
+        .map(|&i| physics.world_shape(&objects[i]).bounding_radius())
+        .fold(0.0, f32::max)
+        * 2.0
+        + ARRANGE_GAP;
+    let (min_x, max_x, min_z, max_z) = physics.desk_bounds;
+    let columns = (((max_x - min_x) / cell).floor() as usize).max(1);
+    let max_rows = (((max_z - min_z) / cell).floor() as usize).max(1);
+    let first = Vec3::new(
+        (min_x + max_x) / 2.0 - (columns - 1) as f32 * cell / 2.0,
+        0.0,
+        (min_z + max_z) / 2.0 - (max_rows - 1) as f32 * cell / 2.0,
+    );
+    // Fill the rows around the middle of the desk first, then any others
+    // (when locked objects take up cells)
+    let rows = order.len().div_ceil(columns).min(max_rows);
+    let first_row = (max_rows - rows) / 2;
+    let mut cells = (first_row..max_rows).chain(0..first_row).flat_map(|row| {
+        (0..columns).map(move |column| first + Vec3::new(column as f32, 0.0, row as f32) * cell)
+    });
+
+    let locked: Vec<DeskObject> = objects.iter().filter(|obj| obj.locked).cloned().collect();
+    let mut unplaced = 0;
+    for index in order {
+        let mut object = objects[index].clone();
+        object.rotation = Quat::IDENTITY;
+        let radius = physics.world_shape(&object).bounding_radius();
+        let free_cell = cells.by_ref().find(|&center| {
+            let center = physics.clamp_to_desk(center, radius);
+            object.position.x = center.x;
+            object.position.z = center.z;
+            !locked.iter().any(|other| physics.check_collision(&object, other))
+        });
+        if free_cell.is_none() {
+            unplaced += 1;
+            continue;
+        }
+        let y = physics.desk_surface_y() + object.base_offset();
+        object.position.y = y;
+        object.target_y = y;
+        object.original_y = y;
+        objects[index] = object;
+    }
+    unplaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arranged_objects_are_grouped_on_the_desk_without_overlapping() {
+        let physics = PhysicsEngine::new();
+        let types = [ObjectType::Plant, ObjectType::Coffee, ObjectType::Laptop, ObjectType::Coffee];
+        let mut objects: Vec<DeskObject> = types
+            .iter()
+            .enumerate()
+            .map(|(i, &object_type)| DeskObject::new(i as u64 + 1, object_type, Vec3::new(0.1 * i as f32, 0.75, 0.0)))
+            .collect();
+        let mut locked = DeskObject::new(9, ObjectType::Lamp, Vec3::new(4.0, 0.75, 3.0));
+        locked.locked = true;
+        objects.push(locked.clone());
+
+        assert_eq!(arrange_grid(&mut objects, &physics), 0);
+
+        assert_eq!(objects[4].position, locked.position);
+        for (i, a) in objects.iter().enumerate() {
+            assert!(physics.is_on_desk(a.position));
+            for b in &objects[i + 1..] {
+                assert!(!physics.check_collision(a, b), "{:?} overlaps {:?}", a.object_type, b.object_type);
+            }
+        }
+        // Grouped in palette order: the plant, both mugs, then the laptop
+        let x = |id: u64| objects.iter().find(|obj| obj.id == id).unwrap().position.x;
+        assert!(x(1) < x(2) && x(2) < x(4) && x(4) < x(3));
+    }
+
+    #[test]
+    fn objects_that_do_not_fit_stay_put() {
+        let physics = PhysicsEngine::new();
+        let mut objects: Vec<DeskObject> = (0..200)
+            .map(|id| DeskObject::new(id, ObjectType::Laptop, Vec3::new(0.0, 0.75, 0.0)))
+            .collect();
+        let unplaced = arrange_grid(&mut objects, &physics);
+        assert!(unplaced > 0);
+        assert_eq!(objects.last().unwrap().position, Vec3::new(0.0, 0.75, 0.0));
+    }
+}
@@ -0,0 +1,303 @@
+//! Bloom post-process
+//!
+//! When bloom is on, the scene is drawn into an offscreen texture first. A
+//! bright pass keeps what is above the threshold at half resolution, a
+//! separable blur spreads it, and the composite adds it back over the scene
+//! into the real target, so lamps, screens and glowing objects softly bleed
+//! light.
+
+use crate::config::CONFIG;
+use wgpu::util::DeviceExt;
+
+/// Format of the half-resolution bright-pass and blur targets
+const BLOOM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Bloom uniform buffer data (see `BloomUniform` in bloom.wgsl)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomUniform {
+    params: [f32; 4],
+    spread: [f32; 4],
+}
+
+/// Size of the blur targets for a scene of `width` x `height`
+fn blur_size(width: u32, height: u32) -> (u32, u32) {
+    ((width / 2).max(1), (height / 2).max(1))
+}
+
+/// Pipelines of the bloom passes, shared by every set of targets
+pub struct BloomPass {
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    threshold_pipeline: wgpu::RenderPipeline,
+    blur_horizontal_pipeline: wgpu::RenderPipeline,
+    blur_vertical_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+/// Offscreen textures for bloom at one target size
+pub struct BloomTargets {
+    /// Where the scene is drawn before bloom is added
+    pub scene_view: wgpu::TextureView,
+    /// Size of the bright-pass and blur textures
+    blur_size: (u32, u32),
+    uniform_buffer: wgpu::Buffer,
+    /// Bright pass, and again the finished blur
+    bright_view: wgpu::TextureView,
+    /// Horizontally blurred bright pass
+    blurred_view: wgpu::TextureView,
+    // One per pass: scene to bright pass, bright pass to horizontal blur,
+    // horizontal to vertical blur, and scene plus blur to the target
+    threshold_bind_group: wgpu::BindGroup,
+    blur_horizontal_bind_group: wgpu::BindGroup,
+    blur_vertical_bind_group: wgpu::BindGroup,
+    composite_bind_group: wgpu::BindGroup,
+}
+
+impl BloomPass {
+    /// Create the bloom pipelines, compositing into `color_format`
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("bloom.wgsl").into()),
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("bloom_bind_group_layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, entry_point, format| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_fullscreen",
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        Self {
+            threshold_pipeline: pipeline("Bloom Threshold Pipeline", "fs_threshold", BLOOM_FORMAT),
+            blur_horizontal_pipeline: pipeline("Bloom Horizontal Blur Pipeline", "fs_blur_horizontal", BLOOM_FORMAT),
+            blur_vertical_pipeline: pipeline("Bloom Vertical Blur Pipeline", "fs_blur_vertical", BLOOM_FORMAT),
+            composite_pipeline: pipeline("Bloom Composite Pipeline", "fs_composite", color_format),
+            bind_group_layout,
+            sampler,
+        }
+    }
+
+    /// Create the offscreen textures for a `width` x `height` target whose
+    /// scene is drawn in `color_format`
+    pub fn create_targets(
+        &self,
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> BloomTargets {
+        let texture_view = |label, width, height, format| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let blur_size = blur_size(width, height);
+        let scene_view = texture_view("Bloom Scene Texture", width, height, color_format);
+        let bright_view = texture_view("Bloom Bright Texture", blur_size.0, blur_size.1, BLOOM_FORMAT);
+        let blurred_view = texture_view("Bloom Blur Texture", blur_size.0, blur_size.1, BLOOM_FORMAT);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bloom Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[BloomUniform::new(blur_size, 0.0)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = |label, source: &wgpu::TextureView, bloom: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(bloom),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+
+        BloomTargets {
+            threshold_bind_group: bind_group("bloom_threshold_bind_group", &scene_view, &scene_view),
+            blur_horizontal_bind_group: bind_group("bloom_blur_horizontal_bind_group", &bright_view, &bright_view),
+            blur_vertical_bind_group: bind_group("bloom_blur_vertical_bind_group", &blurred_view, &blurred_view),
+            composite_bind_group: bind_group("bloom_composite_bind_group", &scene_view, &bright_view),
+            scene_view,
+            blur_size,
+            uniform_buffer,
+            bright_view,
+            blurred_view,
+        }
+    }
+
+    /// Add bloom of the scene in `targets.scene_view` and write the result to `view`
+    pub fn apply(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        targets: &BloomTargets,
+        view: &wgpu::TextureView,
+        intensity: f32,
+    ) {
+        queue.write_buffer(
+            &targets.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[BloomUniform::new(targets.blur_size, intensity)]),
+        );
+
+        // The vertical blur writes back into the bright-pass texture, which
+        // the composite then reads
+        let passes = [
+            ("Bloom Threshold Pass", &self.threshold_pipeline, &targets.threshold_bind_group, &targets.bright_view),
+            (
+                "Bloom Horizontal Blur Pass",
+                &self.blur_horizontal_pipeline,
+                &targets.blur_horizontal_bind_group,
+                &targets.blurred_view,
+            ),
+            (
+                "Bloom Vertical Blur Pass",
+                &self.blur_vertical_pipeline,
+                &targets.blur_vertical_bind_group,
+                &targets.bright_view,
+            ),
+            ("Bloom Composite Pass", &self.composite_pipeline, &targets.composite_bind_group, view),
+        ];
+        for (label, pipeline, bind_group, target) in passes {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+impl BloomUniform {
+    fn new(blur_size: (u32, u32), intensity: f32) -> Self {
+        Self {
+            params: [
+                1.0 / blur_size.0 as f32,
+                1.0 / blur_size.1 as f32,
+                CONFIG.render.bloom_threshold,
+                intensity,
+            ],
+            spread: [CONFIG.render.bloom_spread, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blur_targets_are_half_size_but_never_empty() {
+        assert_eq!(blur_size(1280, 720), (640, 360));
+        assert_eq!(blur_size(1, 3), (1, 1));
+    }
+}
@@ -0,0 +1,75 @@
+// Focus Desktop Simulator - Bloom Shader
+// Bright-pass, separable blur and composite over the rendered scene
+
+struct BloomUniform {
+    // Size of one texel of the blur targets (xy), brightness threshold (z), intensity (w)
+    params: vec4<f32>,
+    // Blur tap spacing in texels (x), remaining components are padding
+    spread: vec4<f32>,
+}
+
+// Source of the pass: the scene, or the bright-pass/blur target being blurred
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+// Blurred highlights (composite pass only; the other passes bind the source again)
+@group(0) @binding(1)
+var bloom_texture: texture_2d<f32>;
+@group(0) @binding(2)
+var linear_sampler: sampler;
+@group(0) @binding(3)
+var<uniform> bloom: BloomUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// A single triangle covering the whole target
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// Keep only what is brighter than the threshold, fading in softly above it
+@fragment
+fn fs_threshold(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(source_texture, linear_sampler, in.uv).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    let excess = max(brightness - bloom.params.z, 0.0) / max(1.0 - bloom.params.z, 0.0001);
+    return vec4<f32>(color * min(excess, 1.0), 1.0);
+}
+
+// 9-tap Gaussian along one axis
+fn blur(uv: vec2<f32>, direction: vec2<f32>) -> vec4<f32> {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let step = direction * bloom.params.xy * bloom.spread.x;
+    var color = textureSample(source_texture, linear_sampler, uv).rgb * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = step * f32(i);
+        color += textureSample(source_texture, linear_sampler, uv + offset).rgb * weights[i];
+        color += textureSample(source_texture, linear_sampler, uv - offset).rgb * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_blur_horizontal(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(1.0, 0.0));
+}
+
+@fragment
+fn fs_blur_vertical(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(0.0, 1.0));
+}
+
+// Add the blurred highlights back over the scene
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = textureSample(source_texture, linear_sampler, in.uv).rgb;
+    let glow = textureSample(bloom_texture, linear_sampler, in.uv).rgb;
+    return vec4<f32>(scene + glow * bloom.params.w, 1.0);
+}
@@ -2,10 +2,30 @@
 //!
 //! Implements a first-person style camera with yaw/pitch controls.
 
-use glam::{Mat4, Vec3};
-use crate::config::CONFIG;
+use glam::{Mat4, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+use crate::config::{CameraProjection, CONFIG};
+
+/// Orthographic zoom limits as multiples of the configured visible height
+const ORTHO_ZOOM_RANGE: (f32, f32) = (0.25, 2.0);
+
+/// Highest pitch while orbiting, so the camera never swings below its target
+const ORBIT_MAX_PITCH: f32 = -0.1;
+
+/// Where the camera is and what it looks at, kept per workspace
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraView {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Visible height of an orthographic camera (`None` in perspective)
+    #[serde(default)]
+    pub ortho_scale: Option<f32>,
+}
 
 /// Camera state and controls
+#[derive(Clone)]
 pub struct Camera {
     /// Current position
     pub position: Vec3,
@@ -13,8 +33,8 @@ pub struct Camera {
     pub yaw: f32,
     /// Vertical rotation (pitch) in radians
     pub pitch: f32,
-    /// Field of view in radians
-    pub fov: f32,
+    /// Perspective or orthographic projection
+    pub projection: CameraProjection,
     /// Aspect ratio (width / height)
     pub aspect: f32,
     /// Near clipping plane
@@ -33,24 +53,26 @@ pub struct Camera {
     pub max_yaw: f32,
     /// Default yaw (for calculating limits)
     default_yaw: f32,
+    /// Point the camera is focused on (moved together with position when panning)
+    pub target: Vec3,
+    /// Bounds the target is kept within while panning (min, max)
+    pub target_bounds: (Vec3, Vec3),
 }
 
 impl Camera {
     /// Create a new camera with default settings
     pub fn new(aspect: f32) -> Self {
         let config = &CONFIG.camera;
+        let (position, look_at) = config.start_view(&CONFIG.desk);
 
         // Calculate initial yaw and pitch from look_at target
-        let (yaw, pitch) = Self::calculate_angles_from_look_at(
-            config.position,
-            config.look_at,
-        );
+        let (yaw, pitch) = Self::calculate_angles_from_look_at(position, look_at);
 
         Self {
-            position: config.position,
+            position,
             yaw,
             pitch,
-            fov: config.fov.to_radians(),
+            projection: config.projection(config.orthographic),
             aspect,
             near: config.near,
             far: config.far,
@@ -60,9 +82,22 @@ impl Camera {
             min_yaw: yaw - 1.40,  // ~80 degrees left
             max_yaw: yaw + 1.40,  // ~80 degrees right
             default_yaw: yaw,
+            target: look_at,
+            target_bounds: Self::default_target_bounds(),
         }
     }
 
+    /// Keep the focus point over the desk so it can't be panned out of view
+    fn default_target_bounds() -> (Vec3, Vec3) {
+        let desk = &CONFIG.desk;
+        let half_width = desk.width / 2.0;
+        let half_depth = desk.depth / 2.0;
+        (
+            Vec3::new(-half_width, 0.0, -half_depth),
+            Vec3::new(half_width, desk.top_y() + 2.0, half_depth),
+        )
+    }
+
     /// Calculate yaw and pitch angles from camera position and look-at point
     fn calculate_angles_from_look_at(camera_pos: Vec3, look_at: Vec3) -> (f32, f32) {
         let direction = look_at - camera_pos;
@@ -94,7 +129,61 @@ impl Camera {
 
     /// Get the projection matrix
     pub fn projection_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far)
+        match self.projection {
+            CameraProjection::Perspective { fov } => {
+                Mat4::perspective_rh(fov.to_radians(), self.aspect, self.near, self.far)
+            }
+            CameraProjection::Orthographic { scale } => {
+                let half_height = scale / 2.0;
+                let half_width = half_height * self.aspect;
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+
+    /// Whether the camera uses an orthographic projection
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self.projection, CameraProjection::Orthographic { .. })
+    }
+
+    /// Switch between the configured perspective and orthographic projections
+    pub fn set_orthographic(&mut self, orthographic: bool) {
+        self.projection = CONFIG.camera.projection(orthographic);
+    }
+
+    /// World-space ray (origin, direction) through a point in normalized
+    /// device coordinates.
+    ///
+    /// The ray starts on the near plane rather than at the camera position:
+    /// with an orthographic projection every pixel has its own parallel ray.
+    pub fn ray_through(&self, ndc_x: f32, ndc_y: f32) -> (Vec3, Vec3) {
+        let inverse = self.view_projection_matrix().inverse();
+        let unproject = |depth: f32| {
+            let point = inverse * Vec4::new(ndc_x, ndc_y, depth, 1.0);
+            point.truncate() / point.w
+        };
+        // wgpu clip space depth runs from 0 (near) to 1 (far)
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        (near, (far - near).normalize())
+    }
+
+    /// Where the ray through the middle of the view meets the horizontal
+    /// plane at `plane_y`, if it points towards it
+    pub fn aim_point(&self, plane_y: f32) -> Option<Vec3> {
+        let (origin, direction) = self.ray_through(0.0, 0.0);
+        if direction.y.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = (plane_y - origin.y) / direction.y;
+        (t >= 0.0).then(|| origin + direction * t)
     }
 
     /// Get the combined view-projection matrix
@@ -118,6 +207,148 @@ impl Camera {
             .clamp(self.min_pitch, self.max_pitch);
     }
 
+    /// Pan the camera and its target along the view's right/up axes.
+    ///
+    /// Deltas are screen-space movement as a fraction of the viewport height,
+    /// so the scene follows the cursor regardless of zoom distance.
+    pub fn pan(&mut self, screen_dx: f32, screen_dy: f32) {
+        let world_per_unit = self.view_height_at(self.target);
+
+        let right = self.right();
+        let up = right.cross(self.look_direction()).normalize();
+        let offset = (-right * screen_dx + up * screen_dy) * world_per_unit;
+
+        let (min, max) = self.target_bounds;
+        let new_target = (self.target + offset).clamp(min, max);
+        self.position += new_target - self.target;
+        self.target = new_target;
+        self.clamp_to_bounds();
+    }
+
+    /// World units spanned by the viewport height at the depth of `point`
+    pub fn view_height_at(&self, point: Vec3) -> f32 {
+        match self.projection {
+            CameraProjection::Perspective { fov } => {
+                let depth = (point - self.position).dot(self.look_direction()).max(self.near);
+                2.0 * depth * (fov.to_radians() / 2.0).tan()
+            }
+            CameraProjection::Orthographic { scale } => scale,
+        }
+    }
+
+    /// Move the camera so its target is `point` (within the pan bounds),
+    /// keeping the viewing angle and distance
+    pub fn center_on(&mut self, point: Vec3) {
+        let (min, max) = self.target_bounds;
+        let new_target = point.clamp(min, max);
+        self.position += new_target - self.target;
+        self.target = new_target;
+        self.clamp_to_bounds();
+    }
+
+    /// Aim at the center of the box `min`..`max` from just far enough away to
+    /// fit all of it on a screen with this aspect ratio, keeping the viewing
+    /// angle. The distance stays within the zoom limits.
+    pub fn fit_bounds(&mut self, min: Vec3, max: Vec3, aspect: f32) {
+        let config = &CONFIG.camera;
+        let (bounds_min, bounds_max) = self.target_bounds;
+        self.target = ((min + max) / 2.0).clamp(bounds_min, bounds_max);
+        // Fit the box's bounding sphere, so the result doesn't depend on the angle
+        let radius = (max - min).length() / 2.0 * config.fit_margin;
+        let aspect = aspect.max(f32::EPSILON);
+        match &mut self.projection {
+            CameraProjection::Perspective { fov } => {
+                let half_height = (fov.to_radians() / 2.0).tan();
+                let half_angle = half_height.min(half_height * aspect).atan();
+                let distance = (radius / half_angle.sin())
+                    .clamp(config.min_distance, config.max_distance);
+                self.position = self.target - self.look_direction() * distance;
+            }
+            CameraProjection::Orthographic { scale } => {
+                let (min_zoom, max_zoom) = ORTHO_ZOOM_RANGE;
+                *scale = (2.0 * radius * (1.0 / aspect).max(1.0))
+                    .clamp(config.ortho_scale * min_zoom, config.ortho_scale * max_zoom);
+                let distance = (self.target - self.position).length();
+                self.position = self.target - self.look_direction() * distance;
+            }
+        }
+        self.clamp_to_bounds();
+    }
+
+    /// Orbit the camera around its target by the given angles in radians
+    /// (trackpad two-finger scroll).
+    ///
+    /// Uses the same yaw limits as `rotate`, but keeps the distance to the
+    /// target instead of turning in place.
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let distance = (self.target - self.position).length();
+        self.yaw = (self.yaw + delta_yaw).clamp(self.min_yaw, self.max_yaw);
+        self.pitch = (self.pitch + delta_pitch)
+            .clamp(self.min_pitch, self.max_pitch.min(ORBIT_MAX_PITCH));
+        self.position = self.target - self.look_direction() * distance;
+        self.clamp_to_bounds();
+    }
+
+    /// Turn the camera around its target by `delta_yaw` past the yaw limits,
+    /// which turn along with it so orbiting by hand afterwards keeps the same
+    /// range around the new view
+    pub fn spin(&mut self, delta_yaw: f32) {
+        self.min_yaw += delta_yaw;
+        self.max_yaw += delta_yaw;
+        self.orbit(delta_yaw, 0.0);
+    }
+
+    /// Zoom towards (factor > 1) or away from (factor < 1) the target.
+    ///
+    /// Perspective cameras move along the view direction within the configured
+    /// distance limits; orthographic cameras change their visible height.
+    pub fn zoom(&mut self, factor: f32) {
+        if !factor.is_finite() || factor <= 0.0 {
+            return;
+        }
+        let config = &CONFIG.camera;
+        match &mut self.projection {
+            CameraProjection::Perspective { .. } => {
+                let distance = ((self.target - self.position).length() / factor)
+                    .clamp(config.min_distance, config.max_distance);
+                self.position = self.target - self.look_direction() * distance;
+            }
+            CameraProjection::Orthographic { scale } => {
+                let (min, max) = ORTHO_ZOOM_RANGE;
+                *scale = (*scale / factor).clamp(config.ortho_scale * min, config.ortho_scale * max);
+            }
+        }
+        self.clamp_to_bounds();
+    }
+
+    /// Keep the camera above the floor, and above the desk while over it
+    /// (see `CONFIG.camera.min_height` and `stay_above_desk`), by tilting it
+    /// down towards its target. The distance to the target stays the same.
+    pub fn clamp_to_bounds(&mut self) {
+        let config = &CONFIG.camera;
+        self.keep_above(config.min_height);
+        if config.stay_above_desk {
+            let desk = &CONFIG.desk;
+            let margin = config.desk_clearance;
+            let over_desk = self.position.x.abs() <= desk.width / 2.0 + margin
+                && self.position.z.abs() <= desk.depth / 2.0 + margin;
+            if over_desk {
+                self.keep_above(desk.top_y() + margin);
+            }
+        }
+    }
+
+    /// Tilt the camera down around its target until it is at least `min_y` high
+    fn keep_above(&mut self, min_y: f32) {
+        if self.position.y >= min_y {
+            return;
+        }
+        let distance = (self.target - self.position).length();
+        let sin = ((min_y - self.target.y) / distance.max(f32::EPSILON)).clamp(-1.0, 1.0);
+        self.pitch = (-sin.asin()).max(self.min_pitch);
+        self.position = self.target - self.look_direction() * distance;
+    }
+
     /// Get the forward direction vector (ignoring pitch)
     pub fn forward(&self) -> Vec3 {
         Vec3::new(self.yaw.sin(), 0.0, self.yaw.cos()).normalize()
@@ -137,16 +368,97 @@ impl Camera {
         ).normalize()
     }
 
+    /// The camera's current placement
+    pub fn view(&self) -> CameraView {
+        CameraView {
+            position: self.position,
+            target: self.target,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            ortho_scale: match self.projection {
+                CameraProjection::Orthographic { scale } => Some(scale),
+                CameraProjection::Perspective { .. } => None,
+            },
+        }
+    }
+
+    /// Move the camera to a placement saved with `view`. The yaw limits turn
+    /// along if it lies outside them; the projection stays as it is, taking
+    /// the saved zoom only if both are orthographic.
+    pub fn set_view(&mut self, view: &CameraView) {
+        let finite = view.position.is_finite()
+            && view.target.is_finite()
+            && view.yaw.is_finite()
+            && view.pitch.is_finite();
+        if !finite {
+            return;
+        }
+        let yaw_shift = view.yaw - view.yaw.clamp(self.min_yaw, self.max_yaw);
+        self.min_yaw += yaw_shift;
+        self.max_yaw += yaw_shift;
+        self.position = view.position;
+        self.target = view.target;
+        self.yaw = view.yaw;
+        self.pitch = view.pitch.clamp(self.min_pitch, self.max_pitch);
+        if let (CameraProjection::Orthographic { scale }, Some(saved)) = (&mut self.projection, view.ortho_scale) {
+            let (min, max) = ORTHO_ZOOM_RANGE;
+            let config = &CONFIG.camera;
+            if saved.is_finite() {
+                *scale = saved.clamp(config.ortho_scale * min, config.ortho_scale * max);
+            }
+        }
+        self.clamp_to_bounds();
+    }
+
     /// Reset camera to default position and orientation
     pub fn reset(&mut self) {
-        let config = &CONFIG.camera;
-        self.position = config.position;
+        let (position, look_at) = CONFIG.camera.start_view(&CONFIG.desk);
+        self.position = position;
+        self.target = look_at;
         self.yaw = self.default_yaw;
-        let (_, pitch) = Self::calculate_angles_from_look_at(config.position, config.look_at);
+        let (_, pitch) = Self::calculate_angles_from_look_at(position, look_at);
         self.pitch = pitch;
     }
 }
 
+/// A smooth move of the camera to another position, target and zoom with
+/// the same viewing angle
+pub struct CameraFlight {
+    from: Camera,
+    to: Camera,
+    progress: f32,
+}
+
+impl CameraFlight {
+    /// Fly from the camera's current placement to `to`
+    pub fn new(from: &Camera, to: Camera) -> Self {
+        Self { from: from.clone(), to, progress: 0.0 }
+    }
+
+    /// Move `camera` `dt` seconds further along, returning whether the
+    /// flight is still under way
+    pub fn advance(&mut self, camera: &mut Camera, dt: f32) -> bool {
+        let duration = CONFIG.camera.fit_duration.max(f32::EPSILON);
+        self.progress = (self.progress + dt / duration).min(1.0);
+        // Ease in and out
+        let t = self.progress * self.progress * (3.0 - 2.0 * self.progress);
+        camera.position = self.from.position.lerp(self.to.position, t);
+        camera.target = self.from.target.lerp(self.to.target, t);
+        if let (
+            CameraProjection::Orthographic { scale: from },
+            CameraProjection::Orthographic { scale: to },
+        ) = (self.from.projection, self.to.projection)
+        {
+            camera.projection = CameraProjection::Orthographic { scale: from + (to - from) * t };
+        }
+        self.progress < 1.0
+    }
+}
+
+/// Most lamps lighting the scene at once (the size of the `lamps` array in
+/// shader.wgsl); the nearest to the desk center win
+pub const MAX_LAMP_LIGHTS: usize = 8;
+
 /// Uniform buffer data for camera (GPU-compatible)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -155,6 +467,15 @@ pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
     /// Camera position in world space
     pub position: [f32; 4],
+    /// Scene-wide parameters: focus-mode dim amount (x), shadow map
+    /// enabled (y), specular strength (z), number of lamp lights (w)
+    pub scene: [f32; 4],
+    /// View-projection of the shadow-casting light
+    pub light_view_proj: [[f32; 4]; 4],
+    /// Lamp light color scaled by its intensity (rgb), w is padding
+    pub lamp_color: [f32; 4],
+    /// Bulb position (xyz) and light radius (w) of each lit lamp
+    pub lamps: [[f32; 4]; MAX_LAMP_LIGHTS],
 }
 
 impl CameraUniform {
@@ -162,6 +483,10 @@ impl CameraUniform {
         Self {
             view_proj: Mat4::IDENTITY.to_cols_array_2d(),
             position: [0.0; 4],
+            scene: [0.0; 4],
+            light_view_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            lamp_color: [0.0; 4],
+            lamps: [[0.0; 4]; MAX_LAMP_LIGHTS],
         }
     }
 
@@ -181,3 +506,139 @@ impl Default for CameraUniform {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Project a world point to NDC, cast a ray back through it, and check
+    /// the ray passes through the original point
+    fn assert_ray_round_trips(camera: &Camera, point: Vec3) {
+        let clip = camera.view_projection_matrix() * point.extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        let (origin, direction) = camera.ray_through(ndc.x, ndc.y);
+
+        let t = (point - origin).dot(direction);
+        let closest = origin + direction * t;
+        assert!(t > 0.0, "point should be in front of the ray origin");
+        assert!(
+            closest.distance(point) < 1e-3,
+            "ray misses {point:?} by {}",
+            closest.distance(point)
+        );
+    }
+
+    #[test]
+    fn perspective_ray_passes_through_projected_point() {
+        let camera = Camera::new(16.0 / 9.0);
+        assert!(!camera.is_orthographic());
+        assert_ray_round_trips(&camera, Vec3::new(1.2, 0.8, -0.5));
+        assert_ray_round_trips(&camera, Vec3::new(-2.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn orthographic_ray_passes_through_projected_point() {
+        let mut camera = Camera::new(16.0 / 9.0);
+        camera.set_orthographic(true);
+        assert_ray_round_trips(&camera, Vec3::new(1.2, 0.8, -0.5));
+        assert_ray_round_trips(&camera, Vec3::new(-2.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn aim_point_is_the_target_on_its_plane() {
+        for orthographic in [false, true] {
+            let mut camera = Camera::new(16.0 / 9.0);
+            camera.set_orthographic(orthographic);
+            let aim = camera.aim_point(camera.target.y).unwrap();
+            assert!(aim.distance(camera.target) < 1e-3, "{aim:?} vs {:?}", camera.target);
+            // The camera looks down, so a plane above it is never hit
+            assert!(camera.aim_point(camera.position.y + 1.0).is_none());
+        }
+    }
+
+    /// Whether every corner of the box projects inside the viewport
+    fn fits_on_screen(camera: &Camera, min: Vec3, max: Vec3) -> bool {
+        (0..8).all(|corner| {
+            let point = Vec3::select(
+                glam::BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                max,
+                min,
+            );
+            let clip = camera.view_projection_matrix() * point.extend(1.0);
+            let ndc = clip.truncate() / clip.w;
+            clip.w > 0.0 && ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0
+        })
+    }
+
+    #[test]
+    fn fitted_bounds_are_centered_and_on_screen() {
+        let (min, max) = (Vec3::new(-3.0, 0.75, -2.0), Vec3::new(1.0, 1.5, 0.5));
+        for orthographic in [false, true] {
+            for aspect in [16.0 / 9.0, 0.5] {
+                let mut camera = Camera::new(aspect);
+                camera.set_orthographic(orthographic);
+                camera.fit_bounds(min, max, aspect);
+                assert_eq!(camera.target, (min + max) / 2.0);
+                assert!(fits_on_screen(&camera, min, max), "ortho {orthographic}, aspect {aspect}");
+            }
+        }
+    }
+
+    #[test]
+    fn flight_ends_exactly_at_its_destination() {
+        let mut camera = Camera::new(1.0);
+        let mut framed = camera.clone();
+        framed.fit_bounds(Vec3::splat(-0.5), Vec3::splat(0.5), 1.0);
+        let mut flight = CameraFlight::new(&camera, framed.clone());
+
+        assert!(flight.advance(&mut camera, CONFIG.camera.fit_duration / 2.0));
+        assert!(camera.position != framed.position);
+        assert!(!flight.advance(&mut camera, CONFIG.camera.fit_duration));
+        assert_eq!((camera.position, camera.target), (framed.position, framed.target));
+    }
+
+    #[test]
+    fn camera_stays_above_the_floor_and_desk() {
+        let config = &CONFIG.camera;
+        let mut camera = Camera::new(16.0 / 9.0);
+        // As flat and close as the controls allow, aimed at the floor past the desk edge
+        camera.orbit(0.0, 10.0);
+        camera.zoom(100.0);
+        camera.center_on(Vec3::new(0.0, 0.0, 10.0));
+        assert!(camera.position.y >= config.min_height - 1e-4, "{:?}", camera.position);
+
+        // Over the middle of the desk it keeps clear of the desk top
+        camera.center_on(Vec3::new(0.0, CONFIG.desk.top_y(), 0.0));
+        assert!(camera.position.y >= CONFIG.desk.top_y() + config.desk_clearance - 1e-4);
+        let distance = (camera.target - camera.position).length();
+        assert!((distance - config.min_distance).abs() < 1e-3);
+        assert!(camera.look_direction().dot((camera.target - camera.position).normalize()) > 0.9999);
+    }
+
+    #[test]
+    fn spinning_goes_all_the_way_around_the_target() {
+        let mut camera = Camera::new(16.0 / 9.0);
+        let start = camera.position;
+        let distance = (camera.target - camera.position).length();
+        for _ in 0..8 {
+            camera.spin(std::f32::consts::FRAC_PI_4);
+            assert!(((camera.target - camera.position).length() - distance).abs() < 1e-3);
+        }
+        assert!(camera.position.distance(start) < 1e-3, "{:?}", camera.position);
+
+        // The yaw limits came along, so orbiting by hand still works
+        let yaw = camera.yaw;
+        camera.orbit(0.1, 0.0);
+        assert!((camera.yaw - yaw - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut camera = Camera::new(1.0);
+        camera.set_orthographic(true);
+        let (origin_a, direction_a) = camera.ray_through(-0.5, 0.5);
+        let (origin_b, direction_b) = camera.ray_through(0.5, -0.5);
+        assert!(direction_a.distance(direction_b) < 1e-4);
+        assert!(origin_a.distance(origin_b) > 0.1);
+    }
+}
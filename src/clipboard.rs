@@ -0,0 +1,220 @@
+//! Copying objects between desks
+//!
+//! Copied objects travel as JSON text, so they can go through the system
+//! clipboard into another save slot or another running instance. Cover
+//! images are embedded (base64) so a paste on another machine still shows them.
+
+use crate::desk_object::{DeskObject, ImageSource};
+use crate::state::AppState;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Marks clipboard text as copied desk objects
+const CLIPBOARD_FORMAT: &str = "focus-desktop-simulator/objects";
+
+/// Images larger than this are referenced by path only
+const MAX_EMBEDDED_IMAGE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// How far a paste lands from the originals when there's no cursor position
+const PASTE_OFFSET: Vec3 = Vec3::new(0.3, 0.0, 0.3);
+
+/// Copied objects, with the cover images they use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Clipboard {
+    format: String,
+    objects: Vec<DeskObject>,
+    /// Base64 image file contents keyed by the path the objects refer to
+    #[serde(default)]
+    images: BTreeMap<String, String>,
+}
+
+impl Clipboard {
+    /// Copy objects, embedding their cover images
+    pub fn copy(objects: &[DeskObject]) -> Self {
+        let mut images = BTreeMap::new();
+        for path in objects.iter().filter_map(DeskObject::image_path) {
+            let small_enough = std::fs::metadata(path).is_ok_and(|meta| meta.len() <= MAX_EMBEDDED_IMAGE_BYTES);
+            if !small_enough || images.contains_key(path) {
+                continue;
+            }
+            match std::fs::read(path) {
+                Ok(bytes) => {
+                    images.insert(path.to_string(), base64_encode(&bytes));
+                }
+                Err(e) => log::warn!("Could not embed cover image {:?}: {}", path, e),
+            }
+        }
+
+        Self {
+            format: CLIPBOARD_FORMAT.to_string(),
+            objects: objects.to_vec(),
+            images,
+        }
+    }
+
+    /// Text for the system clipboard
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read copied objects back from clipboard text, or `None` if the text
+    /// is something else
+    pub fn from_text(text: &str) -> Option<Self> {
+        let clipboard: Self = serde_json::from_str(text.trim()).ok()?;
+        (clipboard.format == CLIPBOARD_FORMAT).then_some(clipboard)
+    }
+
+    /// Number of copied objects
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Copies of the objects ready to add to a desk: fresh IDs, unlocked, and
+    /// moved so the first one sits at `anchor` (or just beside the originals).
+    ///
+    /// Cover images missing on this machine are written out from the embedded data.
+    pub fn paste(&self, mut next_id: impl FnMut() -> u64, anchor: Option<Vec3>) -> Vec<DeskObject> {
+        let Some(first) = self.objects.first() else { return Vec::new() };
+        let offset = match anchor {
+            Some(anchor) => Vec3::new(anchor.x - first.position.x, 0.0, anchor.z - first.position.z),
+            None => PASTE_OFFSET,
+        };
+
+        self.objects
+            .iter()
+            .map(|original| {
+                let mut copy = original.clone();
+                copy.id = next_id();
+                copy.locked = false;
+                copy.position += offset;
+                if let Some(ImageSource::Path(path)) = copy.cover_image {
+                    copy.cover_image = Some(ImageSource::Path(self.restore_image(path)));
+                }
+                copy
+            })
+            .collect()
+    }
+
+    /// Path to use for a copied cover image, writing the embedded copy to the
+    /// app's image folder if the original isn't on this machine
+    fn restore_image(&self, path: String) -> String {
+        if Path::new(&path).exists() {
+            return path;
+        }
+        let restored = self
+            .images
+            .get(&path)
+            .and_then(|data| base64_decode(data))
+            .and_then(|bytes| write_pasted_image(&path, &bytes));
+        match restored {
+            Some(restored) => restored.to_string_lossy().into_owned(),
+            None => path,
+        }
+    }
+}
+
+/// Save pasted image data under its original file name, returning the new path
+fn write_pasted_image(original: &str, bytes: &[u8]) -> Option<PathBuf> {
+    let file_name = Path::new(original).file_name()?;
+    let target = AppState::pasted_images_dir()?.join(file_name);
+    // An existing file of the same name and size is taken to be the same image
+    let same = std::fs::metadata(&target).is_ok_and(|meta| meta.len() == bytes.len() as u64);
+    if !same {
+        if let Err(e) = std::fs::write(&target, bytes) {
+            log::warn!("Could not save pasted image {:?}: {}", target, e);
+            return None;
+        }
+    }
+    Some(target)
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64_ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Decode standard padded base64, or `None` if the text isn't valid
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut value = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let digit = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+            value |= digit << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((value >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desk_object::ObjectType;
+
+    #[test]
+    fn base64_round_trips() {
+        assert_eq!(base64_encode(b"desk"), "ZGVzaw==");
+        for length in 0..8 {
+            let bytes: Vec<u8> = (0..length).map(|i| (i * 97 + 13) as u8).collect();
+            assert_eq!(base64_decode(&base64_encode(&bytes)), Some(bytes));
+        }
+        assert_eq!(base64_decode("Z"), None);
+    }
+
+    #[test]
+    fn pasted_objects_get_fresh_ids_and_move_together() {
+        let mut lamp = DeskObject::new(4, ObjectType::Lamp, Vec3::new(1.0, 0.0, 1.0));
+        lamp.locked = true;
+        let mug = DeskObject::new(9, ObjectType::Coffee, Vec3::new(2.0, 0.0, 0.5));
+        let text = Clipboard::copy(&[lamp, mug]).to_text();
+
+        assert!(Clipboard::from_text("just some text").is_none());
+        let clipboard = Clipboard::from_text(&text).unwrap();
+        let mut ids = 20..;
+        let pasted = clipboard.paste(|| ids.next().unwrap(), Some(Vec3::new(-1.0, 5.0, 0.0)));
+
+        assert_eq!(pasted.iter().map(|obj| obj.id).collect::<Vec<_>>(), [20, 21]);
+        assert!(!pasted[0].locked);
+        let xz = |obj: &DeskObject| (obj.position.x, obj.position.z);
+        assert_eq!(xz(&pasted[0]), (-1.0, 0.0));
+        assert_eq!(xz(&pasted[1]), (0.0, -0.5));
+    }
+
+    #[test]
+    fn pasted_floor_objects_stay_on_the_floor() {
+        let physics = crate::physics::PhysicsEngine::new();
+        let x = physics.desk_bounds.1 + 1.0;
+        let mut mug = DeskObject::new(1, ObjectType::Coffee, Vec3::new(x, 0.0, 0.0));
+        physics.place(&mut mug, &[]);
+        let clipboard = Clipboard::from_text(&Clipboard::copy(std::slice::from_ref(&mug)).to_text()).unwrap();
+
+        let mut copy = clipboard.paste(|| 2, None).remove(0);
+        physics.place(&mut copy, &[mug]);
+
+        assert!(!physics.is_on_desk(copy.position));
+        assert_eq!(copy.position.y, physics.calculate_resting_y(&copy, &[]));
+        assert!(copy.position.y < physics.desk_surface_y());
+    }
+}
@@ -2,30 +2,182 @@
 //!
 //! Contains all configurable parameters for the application.
 
+use crate::desk_object::ObjectType;
 use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use winit::event::MouseScrollDelta;
+use winit::keyboard::KeyCode;
+
+/// How the camera projects the scene onto the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraProjection {
+    /// Perspective projection with a vertical field of view in degrees
+    Perspective { fov: f32 },
+    /// Orthographic ("true isometric") projection showing `scale` world units vertically
+    Orthographic { scale: f32 },
+}
 
 /// Camera configuration
 pub struct CameraConfig {
-    /// Field of view in degrees
+    /// Field of view in degrees (perspective projection)
     pub fov: f32,
+    /// Visible height in world units (orthographic projection)
+    pub ortho_scale: f32,
+    /// Whether the camera starts with an orthographic projection
+    pub orthographic: bool,
     /// Near clipping plane
     pub near: f32,
     /// Far clipping plane
     pub far: f32,
-    /// Initial camera position
+    /// Initial camera position, relative to the floor under a desk without
+    /// legs (see `start_view`)
     pub position: Vec3,
-    /// Initial look-at target
+    /// Initial look-at target, relative like `position`
     pub look_at: Vec3,
+    /// Closest the camera can zoom to its target
+    pub min_distance: f32,
+    /// Farthest the camera can zoom from its target
+    pub max_distance: f32,
+    /// Lowest the camera can go above the floor (y = 0), wherever the desk top is
+    pub min_height: f32,
+    /// Whether the camera also has to stay `desk_clearance` above the desk
+    /// top while over it, rather than dipping into it
+    pub stay_above_desk: bool,
+    /// Lowest the camera can go above the desk top with `stay_above_desk`
+    pub desk_clearance: f32,
+    /// Radians of orbit per logical pixel of two-finger trackpad scroll
+    pub trackpad_orbit_sensitivity: f32,
+    /// Zoom factor change per unit of pinch magnification
+    pub pinch_zoom_sensitivity: f32,
+    /// Seconds the camera takes to fly to a "frame all" view
+    pub fit_duration: f32,
+    /// Extra room around framed objects (1.0 = touching the screen edges)
+    pub fit_margin: f32,
+    /// Default turntable speed in degrees per second
+    pub turntable_speed: f32,
+    /// How long the turntable waits after the camera is moved by hand
+    pub turntable_resume_delay: Duration,
+}
+
+impl CameraConfig {
+    /// Initial camera position and look-at target, raised by the desk's
+    /// `surface_height` so the camera sees the desk the same whatever its legs
+    pub fn start_view(&self, desk: &DeskConfig) -> (Vec3, Vec3) {
+        let raise = Vec3::Y * desk.surface_height;
+        (self.position + raise, self.look_at + raise)
+    }
+
+    /// The configured perspective or orthographic projection
+    pub fn projection(&self, orthographic: bool) -> CameraProjection {
+        if orthographic {
+            CameraProjection::Orthographic {
+                scale: self.ortho_scale,
+            }
+        } else {
+            CameraProjection::Perspective { fov: self.fov }
+        }
+    }
 }
 
 impl Default for CameraConfig {
     fn default() -> Self {
         Self {
             fov: 75.0,
+            ortho_scale: 7.0,
+            orthographic: false,
             near: 0.1,
             far: 1000.0,
             position: Vec3::new(0.0, 4.5, 5.5),
             look_at: Vec3::new(0.0, 0.0, -1.5),
+            min_distance: 2.5,
+            max_distance: 15.0,
+            min_height: 0.3,
+            stay_above_desk: true,
+            desk_clearance: 0.5,
+            trackpad_orbit_sensitivity: 0.004,
+            pinch_zoom_sensitivity: 1.0,
+            fit_duration: 0.4,
+            fit_margin: 1.15,
+            turntable_speed: 6.0,
+            turntable_resume_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Window configuration
+pub struct WindowConfig {
+    /// Window title
+    pub title: &'static str,
+    /// Initial window width in logical pixels
+    pub width: u32,
+    /// Initial window height in logical pixels
+    pub height: u32,
+    /// Whether the window starts maximized
+    pub start_maximized: bool,
+    /// Smallest accepted window size in logical pixels
+    pub min_size: (u32, u32),
+    /// Largest restored window size in logical pixels
+    pub max_size: (u32, u32),
+    /// Frame rate cap while the window is unfocused (0 = pause rendering)
+    pub unfocused_fps: u32,
+    /// Whether the cursor turns into an open hand over objects that can be
+    /// dragged and a closed one while dragging
+    pub cursor_feedback: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "Focus Desktop Simulator",
+            width: 1280,
+            height: 720,
+            start_maximized: false,
+            min_size: (640, 360),
+            max_size: (7680, 4320),
+            unfocused_fps: 10,
+            cursor_feedback: true,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Clamp a window size to the configured min/max bounds
+    pub fn clamp_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            width.clamp(self.min_size.0, self.max_size.0),
+            height.clamp(self.min_size.1, self.max_size.1),
+        )
+    }
+
+    /// Minimum time between frames while unfocused, or `None` if rendering pauses
+    pub fn unfocused_frame_interval(&self) -> Option<Duration> {
+        (self.unfocused_fps > 0).then(|| Duration::from_secs_f64(1.0 / self.unfocused_fps as f64))
+    }
+}
+
+/// Finish of the desk's top surface
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeskMaterial {
+    /// Plain `DeskConfig::color`
+    Flat,
+    /// Procedural wood grain in shades of `DeskConfig::color`; the seed picks the pattern
+    Wood { seed: u32 },
+    /// An image file stretched over the desk top
+    Image(PathBuf),
+}
+
+impl DeskMaterial {
+    /// Specular exponent of the desk for the sun's highlight (higher is a
+    /// smaller, sharper highlight; 0 = matte)
+    pub fn shininess(&self) -> f32 {
+        match self {
+            DeskMaterial::Flat => 16.0,
+            // Varnished wood catches a soft sheen
+            DeskMaterial::Wood { .. } => 40.0,
+            DeskMaterial::Image(_) => 8.0,
         }
     }
 }
@@ -38,8 +190,15 @@ pub struct DeskConfig {
     pub depth: f32,
     /// Thickness of the desk surface
     pub height: f32,
+    /// Height of the underside of the desk top above the floor, i.e. the
+    /// length of its legs (0 = a slab lying on the floor)
+    pub surface_height: f32,
     /// Color of the desk (RGB hex)
     pub color: u32,
+    /// Finish of the top surface
+    pub material: DeskMaterial,
+    /// How far past the desk edges objects can be dragged onto the floor
+    pub floor_margin: f32,
 }
 
 impl Default for DeskConfig {
@@ -48,7 +207,68 @@ impl Default for DeskConfig {
             width: 10.0,
             depth: 7.0,
             height: 0.1,
+            surface_height: 3.0,
             color: 0x8b6914,
+            material: DeskMaterial::Wood { seed: 7 },
+            floor_margin: 3.0,
+        }
+    }
+}
+
+impl DeskConfig {
+    /// Height of the desk top above the floor, where objects on it rest
+    pub fn top_y(&self) -> f32 {
+        self.surface_height + self.height
+    }
+}
+
+/// Desk object configuration
+pub struct ObjectConfig {
+    /// Maximum number of objects allowed on the desk
+    pub max_objects: usize,
+    /// Objects on the desk at first launch, with their desk (x, z) positions
+    pub starter_layout: Vec<(ObjectType, f32, f32)>,
+    /// Minimum time between two interactions with the same object (e.g.
+    /// starting and stopping a timer), so repeated events can't toggle it
+    /// back and forth
+    pub interaction_cooldown: Duration,
+    /// Selections of at least this many objects take a second click on
+    /// Delete Selected, so a large selection isn't removed by accident
+    pub delete_confirm_count: usize,
+}
+
+impl Default for ObjectConfig {
+    fn default() -> Self {
+        Self {
+            max_objects: 200,
+            starter_layout: vec![
+                (ObjectType::Lamp, -1.8, 0.0),
+                (ObjectType::Clock, 0.0, -0.6),
+                (ObjectType::Plant, 1.8, -0.1),
+                (ObjectType::Notebook, 0.3, 1.2),
+            ],
+            interaction_cooldown: Duration::from_millis(250),
+            delete_confirm_count: 5,
+        }
+    }
+}
+
+/// State persistence configuration
+pub struct PersistenceConfig {
+    /// Interval between periodic autosaves
+    pub autosave_interval: Duration,
+    /// Delay after the last object change before the state is saved
+    pub save_debounce: Duration,
+    /// Size in pixels of the thumbnail stored next to each named save
+    pub thumbnail_size: (u32, u32),
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            autosave_interval: Duration::from_secs(60),
+            save_debounce: Duration::from_secs(2),
+            thumbnail_size: (256, 144),
         }
     }
 }
@@ -67,6 +287,13 @@ pub struct PhysicsConfig {
     pub friction: f32,
     /// Bounce factor for collisions
     pub bounce_factor: f32,
+    /// Length of one physics substep in seconds (speeds above are per substep)
+    pub fixed_timestep: f32,
+    /// Longest frame time simulated; longer stalls are dropped
+    pub max_frame_time: f32,
+    /// How close (world units) a dragged object's footprint must come to a
+    /// desk edge to snap flush against it, when edge snapping is on
+    pub edge_snap_distance: f32,
 }
 
 impl Default for PhysicsConfig {
@@ -78,6 +305,9 @@ impl Default for PhysicsConfig {
             gravity: 0.02,
             friction: 0.85,
             bounce_factor: 0.4,
+            fixed_timestep: 1.0 / 60.0,
+            max_frame_time: 0.05,
+            edge_snap_distance: 0.2,
         }
     }
 }
@@ -105,6 +335,452 @@ impl Default for ColorConfig {
     }
 }
 
+/// Animation played when an object is added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnAnim {
+    /// Appear instantly (reduced motion)
+    None,
+    /// Grow from nothing with a slight overshoot
+    ScaleUp,
+    /// Fall in from above and bounce using the physics bounce factor
+    DropIn,
+}
+
+/// Animation configuration
+pub struct AnimationConfig {
+    /// Animation for newly added objects
+    pub spawn: SpawnAnim,
+    /// Length of the spawn animation in seconds
+    pub spawn_duration: f32,
+    /// Height objects fall from with `SpawnAnim::DropIn`
+    pub spawn_drop_height: f32,
+    /// How far idle objects bob up and down at full idle motion
+    pub idle_bob_height: f32,
+    /// How far idle objects sway around the vertical axis at full idle motion (radians)
+    pub idle_sway_angle: f32,
+    /// Seconds per idle bob
+    pub idle_period: f32,
+    /// Seconds an object pulses for when its alert fires (e.g. a finished timer)
+    pub alert_duration: f32,
+    /// Alert pulses per second
+    pub alert_pulse_rate: f32,
+    /// Extra scale at the peak of an alert pulse (0.1 = 10% larger)
+    pub alert_scale: f32,
+    /// Glow at the peak of an alert pulse (0 = none, 1 = twice as bright)
+    pub alert_glow: f32,
+    /// Whether a finished timer also shows a toast
+    pub alert_toast: bool,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            spawn: SpawnAnim::ScaleUp,
+            spawn_duration: 0.45,
+            spawn_drop_height: 1.0,
+            idle_bob_height: 0.015,
+            idle_sway_angle: 0.03,
+            idle_period: 4.0,
+            alert_duration: 4.0,
+            alert_pulse_rate: 1.5,
+            alert_scale: 0.12,
+            alert_glow: 0.6,
+            alert_toast: true,
+        }
+    }
+}
+
+/// How object shadows are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowQuality {
+    /// No shadows
+    Off,
+    /// Soft dark disc under each object (cheap)
+    Blob,
+    /// Depth map rendered from the light, sampled with PCF
+    ShadowMap,
+}
+
+/// When finished frames are shown, trading latency against tearing and power
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PresentMode {
+    /// Vsync using the best mode the platform offers
+    #[default]
+    AutoVsync,
+    /// Strict vsync queue; supported everywhere and easiest on the battery
+    Fifo,
+    /// No vsync: lowest latency, uncapped frame rate, may tear
+    Immediate,
+    /// Vsync that replaces queued frames with newer ones: low latency, no tearing
+    Mailbox,
+}
+
+impl PresentMode {
+    /// Every mode, in settings order
+    pub const ALL: [PresentMode; 4] = [
+        PresentMode::AutoVsync,
+        PresentMode::Fifo,
+        PresentMode::Immediate,
+        PresentMode::Mailbox,
+    ];
+
+    /// Name shown in the settings
+    pub fn label(&self) -> &'static str {
+        match self {
+            PresentMode::AutoVsync => "Vsync (auto)",
+            PresentMode::Fifo => "Vsync (Fifo)",
+            PresentMode::Immediate => "Uncapped (Immediate)",
+            PresentMode::Mailbox => "Low latency (Mailbox)",
+        }
+    }
+
+    /// Whether a surface offering `supported` modes can use this one as is
+    pub fn is_supported(&self, supported: &[wgpu::PresentMode]) -> bool {
+        match self {
+            // wgpu picks a supported mode itself, and Fifo is always available
+            PresentMode::AutoVsync | PresentMode::Fifo => true,
+            PresentMode::Immediate => supported.contains(&wgpu::PresentMode::Immediate),
+            PresentMode::Mailbox => supported.contains(&wgpu::PresentMode::Mailbox),
+        }
+    }
+
+    /// The wgpu mode to configure the surface with. Unsupported low-latency
+    /// modes fall back to whichever non-vsync mode the surface has.
+    pub fn to_wgpu(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        match self {
+            PresentMode::AutoVsync => wgpu::PresentMode::AutoVsync,
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Immediate if self.is_supported(supported) => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox if self.is_supported(supported) => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate | PresentMode::Mailbox => wgpu::PresentMode::AutoNoVsync,
+        }
+    }
+}
+
+/// Rendering quality configuration
+pub struct RenderConfig {
+    /// Present mode until the user picks one in the settings
+    pub present_mode: PresentMode,
+    /// Frame rate cap until the user picks one in the settings, on top of
+    /// vsync (`None` = as fast as the present mode allows)
+    pub fps_cap: Option<u32>,
+    /// Shadow technique; lower-end machines can use `Blob` or `Off`
+    pub shadows: ShadowQuality,
+    /// Width and height of the shadow map in texels
+    pub shadow_map_size: u32,
+    /// Opacity of blob shadows directly under an object
+    pub blob_shadow_opacity: f32,
+    /// Distance from the camera beyond which objects cast no shadow, to save
+    /// the shadow pass work on objects too far away to show it
+    pub shadow_distance: f32,
+    /// Width of the band before `shadow_distance` over which blob shadows fade out
+    pub shadow_fade_band: f32,
+    /// Darkness of the faux ambient occlusion where objects touch the desk or
+    /// floor, drawn with any shadow setting to ground them (0 = off)
+    pub contact_shadow_opacity: f32,
+    /// How far the contact darkening reaches past an object's footprint, and
+    /// how high an object can be lifted before it fades out
+    pub contact_shadow_radius: f32,
+    /// Whether the bloom post-process is available; its intensity is a user setting
+    pub bloom: bool,
+    /// Bloom intensity until the user picks one in the settings (0 = off)
+    pub bloom_intensity: f32,
+    /// Linear brightness (0-1, brightest color channel) above which pixels bloom
+    pub bloom_threshold: f32,
+    /// Spacing of the blur taps in half-resolution texels; larger spreads the glow further
+    pub bloom_spread: f32,
+    /// Divisor of the window size the scene is drawn at before a nearest-filtered
+    /// upscale, for a pixel-art look, until the user picks one (1 = full resolution)
+    pub internal_resolution_divisor: u32,
+    /// Whether shiny surfaces (the desk, see `DeskMaterial::shininess`, and
+    /// objects, see `ObjectType::shininess`) catch a highlight from the sun
+    pub specular: bool,
+    /// Brightness of specular highlights at their peak
+    pub specular_strength: f32,
+    /// Color of the light switched-on lamps cast within their light radius
+    pub lamp_light_color: u32,
+    /// Brightness of lamp light right at the bulb (0 = lamps light nothing)
+    pub lamp_light_intensity: f32,
+    /// Opacity of the ring drawn on the desk where a selected lamp's light ends
+    pub light_ring_opacity: f32,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::AutoVsync,
+            fps_cap: None,
+            shadows: ShadowQuality::ShadowMap,
+            shadow_map_size: 2048,
+            blob_shadow_opacity: 0.35,
+            shadow_distance: 18.0,
+            shadow_fade_band: 4.0,
+            contact_shadow_opacity: 0.35,
+            contact_shadow_radius: 0.15,
+            bloom: true,
+            bloom_intensity: 0.0,
+            bloom_threshold: 0.5,
+            bloom_spread: 1.5,
+            internal_resolution_divisor: 1,
+            specular: true,
+            specular_strength: 0.35,
+            lamp_light_color: 0xFFD9A0,
+            lamp_light_intensity: 0.9,
+            light_ring_opacity: 0.35,
+        }
+    }
+}
+
+/// Layout grid overlay configuration
+pub struct GridConfig {
+    /// Distance between grid lines in world units
+    pub spacing: f32,
+    /// Width of a grid line in world units
+    pub line_width: f32,
+    /// Line color (RGB hex)
+    pub color: u32,
+    /// Line opacity (0-1)
+    pub opacity: f32,
+    /// Whether rulers with world-unit labels run along the desk's front and left edges
+    pub rulers: bool,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            spacing: 0.5,
+            line_width: 0.012,
+            color: 0xFFFFFF,
+            opacity: 0.3,
+            rulers: true,
+        }
+    }
+}
+
+/// Room walls around the desk
+pub struct RoomConfig {
+    /// Whether walls are drawn around the desk
+    pub enabled: bool,
+    /// Wall color (RGB hex)
+    pub wall_color: u32,
+    /// Width, depth and wall height of the room, centered on the desk
+    pub room_size: (f32, f32, f32),
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wall_color: 0x3b4457,
+            room_size: (20.0, 16.0, 7.0),
+        }
+    }
+}
+
+/// Low power / reduced motion mode
+pub struct LowPowerConfig {
+    /// Whether the mode starts on (the user's choice is stored in `AppState`)
+    pub enabled: bool,
+    /// Frame rate cap while the mode is on
+    pub max_fps: u32,
+}
+
+impl Default for LowPowerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_fps: 30,
+        }
+    }
+}
+
+impl LowPowerConfig {
+    /// Minimum time between frames while the mode is on
+    pub fn frame_interval(&self) -> Duration {
+        frame_interval(self.max_fps)
+    }
+}
+
+/// Time between frames at `fps` frames per second (at least one)
+pub fn frame_interval(fps: u32) -> Duration {
+    Duration::from_secs_f64(1.0 / fps.max(1) as f64)
+}
+
+/// Session recording configuration
+pub struct RecordingConfig {
+    /// Seconds between transform samples; playback interpolates in between
+    pub sample_interval: f32,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self { sample_interval: 0.1 }
+    }
+}
+
+/// Focus time tracking configuration
+pub struct FocusConfig {
+    /// Focus time a day needs to count towards the streak
+    pub streak_minimum: Duration,
+    /// Longest frame gap counted as focus time without a running timer, so
+    /// time spent paused in the background isn't counted when rendering resumes
+    pub max_idle_gap: Duration,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            streak_minimum: Duration::from_secs(15 * 60),
+            max_idle_gap: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Actions that can be triggered from the keyboard and remapped by the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum KeyAction {
+    AddObject,
+    Delete,
+    Duplicate,
+    ToggleMenu,
+    Screenshot,
+    Undo,
+    Redo,
+    ToggleGrid,
+    Copy,
+    Paste,
+    ToggleConsole,
+    FrameAll,
+    ToggleCleanView,
+}
+
+impl KeyAction {
+    /// Every bindable action, in settings panel order
+    pub const ALL: [KeyAction; 13] = [
+        KeyAction::AddObject,
+        KeyAction::Delete,
+        KeyAction::Duplicate,
+        KeyAction::ToggleMenu,
+        KeyAction::Screenshot,
+        KeyAction::Undo,
+        KeyAction::Redo,
+        KeyAction::ToggleGrid,
+        KeyAction::Copy,
+        KeyAction::Paste,
+        KeyAction::ToggleConsole,
+        KeyAction::FrameAll,
+        KeyAction::ToggleCleanView,
+    ];
+
+    /// Name shown in the keybindings settings
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyAction::AddObject => "Add object",
+            KeyAction::Delete => "Delete object",
+            KeyAction::Duplicate => "Duplicate object",
+            KeyAction::ToggleMenu => "Toggle menu",
+            KeyAction::Screenshot => "Screenshot",
+            KeyAction::Undo => "Undo",
+            KeyAction::Redo => "Redo",
+            KeyAction::ToggleGrid => "Toggle grid",
+            KeyAction::Copy => "Copy object",
+            KeyAction::Paste => "Paste objects",
+            KeyAction::ToggleConsole => "Toggle console",
+            KeyAction::FrameAll => "Frame all",
+            KeyAction::ToggleCleanView => "Clean view",
+        }
+    }
+}
+
+/// A key, optionally pressed together with Ctrl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    #[serde(default)]
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    /// A plain key
+    pub const fn key(key: KeyCode) -> Self {
+        Self { key, ctrl: false }
+    }
+
+    /// Ctrl plus a key
+    pub const fn ctrl(key: KeyCode) -> Self {
+        Self { key, ctrl: true }
+    }
+
+    /// Short human-readable name, e.g. "Ctrl+Z"
+    pub fn label(&self) -> String {
+        let name = format!("{:?}", self.key);
+        let name = name
+            .strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(&name);
+        if self.ctrl {
+            format!("Ctrl+{}", name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// Key assigned to each remappable action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keybindings {
+    bindings: BTreeMap<KeyAction, KeyBinding>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from([
+                (KeyAction::AddObject, KeyBinding::key(KeyCode::KeyA)),
+                (KeyAction::Delete, KeyBinding::key(KeyCode::Delete)),
+                (KeyAction::Duplicate, KeyBinding::ctrl(KeyCode::KeyD)),
+                (KeyAction::ToggleMenu, KeyBinding::key(KeyCode::KeyM)),
+                (KeyAction::Screenshot, KeyBinding::key(KeyCode::F12)),
+                (KeyAction::Undo, KeyBinding::ctrl(KeyCode::KeyZ)),
+                (KeyAction::Redo, KeyBinding::ctrl(KeyCode::KeyY)),
+                (KeyAction::ToggleGrid, KeyBinding::key(KeyCode::KeyG)),
+                (KeyAction::Copy, KeyBinding::ctrl(KeyCode::KeyC)),
+                (KeyAction::Paste, KeyBinding::ctrl(KeyCode::KeyV)),
+                (KeyAction::ToggleConsole, KeyBinding::key(KeyCode::Backquote)),
+                (KeyAction::FrameAll, KeyBinding::key(KeyCode::KeyF)),
+                (KeyAction::ToggleCleanView, KeyBinding::key(KeyCode::KeyH)),
+            ]),
+        }
+    }
+}
+
+impl Keybindings {
+    /// The binding of an action; actions missing from older saves use the default
+    pub fn get(&self, action: KeyAction) -> KeyBinding {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| CONFIG.keybindings.bindings[&action])
+    }
+
+    /// The action bound to a key press, if any
+    pub fn action_for(&self, key: KeyCode, ctrl: bool) -> Option<KeyAction> {
+        let pressed = KeyBinding { key, ctrl };
+        KeyAction::ALL.into_iter().find(|&action| self.get(action) == pressed)
+    }
+
+    /// Bind an action to a key. An action already using that key takes over
+    /// the old binding, so no key ever triggers two actions.
+    pub fn set(&mut self, action: KeyAction, binding: KeyBinding) {
+        let previous = self.get(action);
+        if let Some(other) = self.action_for(binding.key, binding.ctrl) {
+            self.bindings.insert(other, previous);
+        }
+        self.bindings.insert(action, binding);
+    }
+}
+
 /// Pixelation effect configuration (Signalis-style)
 pub struct PixelationConfig {
     /// Whether pixelation effect is enabled
@@ -128,28 +804,104 @@ impl Default for PixelationConfig {
     }
 }
 
+/// Logical pixels of trackpad scrolling that count as one mouse wheel line
+pub const PIXELS_PER_SCROLL_LINE: f32 = 50.0;
+
+/// Scroll sensitivity and direction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrollSettings {
+    /// Multiplier for rotating a held object
+    pub rotate_sensitivity: f32,
+    /// Multiplier for scaling a held object (with Shift)
+    pub scale_sensitivity: f32,
+    /// Multiplier for zooming the camera
+    pub zoom_sensitivity: f32,
+    /// Reverse the scroll direction
+    pub invert: bool,
+}
+
+impl Default for ScrollSettings {
+    fn default() -> Self {
+        Self {
+            rotate_sensitivity: 1.0,
+            scale_sensitivity: 1.0,
+            zoom_sensitivity: 1.0,
+            invert: false,
+        }
+    }
+}
+
+impl ScrollSettings {
+    /// Scroll amount in wheel lines (x, y), the same for mouse wheels and
+    /// trackpads on every platform, with `invert` applied. Pixel deltas are
+    /// in physical pixels, so they're scaled by the window's `scale_factor`.
+    pub fn lines(&self, delta: MouseScrollDelta, scale_factor: f64) -> (f32, f32) {
+        let (x, y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (x, y),
+            MouseScrollDelta::PixelDelta(pos) => {
+                let pos = pos.to_logical::<f32>(scale_factor);
+                (pos.x / PIXELS_PER_SCROLL_LINE, pos.y / PIXELS_PER_SCROLL_LINE)
+            }
+        };
+        if self.invert {
+            (-x, -y)
+        } else {
+            (x, y)
+        }
+    }
+}
+
 /// Main configuration struct containing all settings
 pub struct Config {
+    pub window: WindowConfig,
     pub camera: CameraConfig,
     pub desk: DeskConfig,
+    pub objects: ObjectConfig,
+    pub persistence: PersistenceConfig,
     pub physics: PhysicsConfig,
+    pub animation: AnimationConfig,
     pub colors: ColorConfig,
+    pub render: RenderConfig,
     pub pixelation: PixelationConfig,
+    pub focus: FocusConfig,
+    pub grid: GridConfig,
+    pub room: RoomConfig,
+    pub low_power: LowPowerConfig,
+    pub recording: RecordingConfig,
+    /// Default keybindings (the user's own are stored in `AppState`)
+    pub keybindings: Keybindings,
+    /// Default scroll settings (the user's own are stored in `AppState`)
+    pub scroll: ScrollSettings,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            window: WindowConfig::default(),
             camera: CameraConfig::default(),
             desk: DeskConfig::default(),
+            objects: ObjectConfig::default(),
+            persistence: PersistenceConfig::default(),
             physics: PhysicsConfig::default(),
+            animation: AnimationConfig::default(),
             colors: ColorConfig::default(),
+            render: RenderConfig::default(),
             pixelation: PixelationConfig::default(),
+            focus: FocusConfig::default(),
+            grid: GridConfig::default(),
+            room: RoomConfig::default(),
+            low_power: LowPowerConfig::default(),
+            recording: RecordingConfig::default(),
+            keybindings: Keybindings::default(),
+            scroll: ScrollSettings::default(),
         }
     }
 }
 
 /// Convert a hex color to RGB f32 tuple (0.0-1.0)
+///
+/// Only the low 24 bits (0xRRGGBB) are used; a high alpha byte is ignored.
 pub fn hex_to_rgb(hex: u32) -> (f32, f32, f32) {
     let r = ((hex >> 16) & 0xFF) as f32 / 255.0;
     let g = ((hex >> 8) & 0xFF) as f32 / 255.0;
@@ -163,5 +915,107 @@ pub fn hex_to_rgba(hex: u32) -> [f32; 4] {
     [r, g, b, 1.0]
 }
 
+/// Parse a hex color string into 0xRRGGBB
+///
+/// Accepts an optional `#` followed by 3 (`RGB`), 6 (`RRGGBB`) or 8
+/// (`RRGGBBAA`, alpha discarded) hex digits. Anything else returns `None`
+/// so callers can fall back to a default.
+pub fn parse_hex_color(text: &str) -> Option<u32> {
+    let digits = text.trim();
+    let digits = digits.strip_prefix('#').unwrap_or(digits);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    match digits.len() {
+        // Each digit doubles: #abc -> #aabbcc
+        3 => {
+            let (r, g, b) = ((value >> 8) & 0xF, (value >> 4) & 0xF, value & 0xF);
+            Some(((r * 0x11) << 16) | ((g * 0x11) << 8) | (b * 0x11))
+        }
+        6 => Some(value),
+        8 => Some(value >> 8),
+        _ => None,
+    }
+}
+
 /// Global configuration instance
 pub static CONFIG: std::sync::LazyLock<Config> = std::sync::LazyLock::new(Config::default);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_present_modes_fall_back() {
+        let fifo_only = [wgpu::PresentMode::Fifo];
+        assert_eq!(PresentMode::Fifo.to_wgpu(&fifo_only), wgpu::PresentMode::Fifo);
+        assert_eq!(PresentMode::AutoVsync.to_wgpu(&fifo_only), wgpu::PresentMode::AutoVsync);
+        assert!(!PresentMode::Mailbox.is_supported(&fifo_only));
+        assert_eq!(PresentMode::Mailbox.to_wgpu(&fifo_only), wgpu::PresentMode::AutoNoVsync);
+        assert_eq!(PresentMode::Immediate.to_wgpu(&fifo_only), wgpu::PresentMode::AutoNoVsync);
+
+        let all = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate, wgpu::PresentMode::Mailbox];
+        assert_eq!(PresentMode::Immediate.to_wgpu(&all), wgpu::PresentMode::Immediate);
+        assert_eq!(PresentMode::Mailbox.to_wgpu(&all), wgpu::PresentMode::Mailbox);
+    }
+
+    #[test]
+    fn parses_short_long_and_alpha_hex_colors() {
+        assert_eq!(parse_hex_color("#fff"), Some(0xFFFFFF));
+        assert_eq!(parse_hex_color("a1c"), Some(0xAA11CC));
+        assert_eq!(parse_hex_color("#8b6914"), Some(0x8B6914));
+        assert_eq!(parse_hex_color(" 3B82F6 "), Some(0x3B82F6));
+        assert_eq!(parse_hex_color("#ffffff80"), Some(0xFFFFFF));
+    }
+
+    #[test]
+    fn malformed_hex_colors_are_rejected() {
+        for input in ["", "#", "#ff", "#ffff", "#fffff", "#fffffff", "#fffffffff", "#ggg", "12 34 56", "+fff", "#-12"] {
+            assert_eq!(parse_hex_color(input), None, "{:?} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn hex_to_rgb_ignores_alpha_byte() {
+        assert_eq!(hex_to_rgb(0xFF00_0000), (0.0, 0.0, 0.0));
+        assert_eq!(hex_to_rgba(0x80FF_FFFF), [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn rebinding_a_taken_key_swaps_bindings() {
+        let mut bindings = Keybindings::default();
+        bindings.set(KeyAction::ToggleMenu, KeyBinding::key(KeyCode::KeyA));
+        assert_eq!(bindings.action_for(KeyCode::KeyA, false), Some(KeyAction::ToggleMenu));
+        assert_eq!(bindings.get(KeyAction::AddObject), KeyBinding::key(KeyCode::KeyM));
+        assert_eq!(bindings.get(KeyAction::Undo).label(), "Ctrl+Z");
+        assert_eq!(bindings.action_for(KeyCode::KeyZ, false), None);
+    }
+
+    #[test]
+    fn keybindings_missing_from_saves_use_defaults() {
+        let bindings: Keybindings =
+            serde_json::from_str(r#"{"Undo": {"key": "KeyU", "ctrl": false}}"#).unwrap();
+        assert_eq!(bindings.get(KeyAction::Undo), KeyBinding::key(KeyCode::KeyU));
+        assert_eq!(bindings.get(KeyAction::Redo), CONFIG.keybindings.get(KeyAction::Redo));
+    }
+
+    #[test]
+    fn wheel_and_trackpad_scroll_agree() {
+        let scroll = ScrollSettings::default();
+        let wheel = scroll.lines(MouseScrollDelta::LineDelta(0.0, 2.0), 2.0);
+        let pixels = winit::dpi::PhysicalPosition::new(0.0, 2.0 * PIXELS_PER_SCROLL_LINE as f64 * 2.0);
+        assert_eq!(scroll.lines(MouseScrollDelta::PixelDelta(pixels), 2.0), wheel);
+
+        let inverted = ScrollSettings { invert: true, ..scroll };
+        assert_eq!(inverted.lines(MouseScrollDelta::LineDelta(1.0, -1.0), 1.0), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn low_power_frame_interval_never_divides_by_zero() {
+        let low_power = LowPowerConfig { enabled: true, max_fps: 30 };
+        assert_eq!(low_power.frame_interval(), Duration::from_secs_f64(1.0 / 30.0));
+        let low_power = LowPowerConfig { enabled: true, max_fps: 0 };
+        assert_eq!(low_power.frame_interval(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,143 @@
+//! Debug console commands
+//!
+//! The console takes one command per line, so a desk can be set up the same
+//! way every time for screenshots or bug reports. Commands are parsed here and
+//! run by the app through the same operations as the UI.
+
+use crate::desk_object::ObjectType;
+
+/// Usage shown by `help` and after an unknown command
+pub const HELP: &str = "Commands: add <type>, clear, set gravity <x>, set collision_radius <x>, \
+                        save <name>, load <name>, list, help";
+
+/// A parsed console command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Add an object at a random spot on the desk
+    Add(ObjectType),
+    /// Remove every object
+    Clear,
+    /// Change the physics gravity constant
+    SetGravity(f32),
+    /// Change the global collision radius multiplier
+    SetCollisionRadius(f32),
+    /// Save the desk as a named layout
+    Save(String),
+    /// Replace the desk with a named layout
+    Load(String),
+    /// Print the objects on the desk
+    List,
+    /// Print the available commands
+    Help,
+}
+
+impl Command {
+    /// Parse a console line, or explain what's wrong with it
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match command.to_lowercase().as_str() {
+            "add" => parse_object_type(rest)
+                .map(Command::Add)
+                .ok_or_else(|| format!("Unknown object type \"{}\" (try {})", rest, object_type_names())),
+            "clear" => Ok(Command::Clear),
+            "set" => {
+                let (setting, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let value: f32 = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|value: &f32| value.is_finite())
+                    .ok_or_else(|| format!("\"{}\" is not a number", value.trim()))?;
+                match setting {
+                    "gravity" if value >= 0.0 => Ok(Command::SetGravity(value)),
+                    "collision_radius" if value > 0.0 => Ok(Command::SetCollisionRadius(value)),
+                    "gravity" | "collision_radius" => Err(format!("{} can't be {}", setting, value)),
+                    _ => Err(format!("Unknown setting \"{}\" (try gravity or collision_radius)", setting)),
+                }
+            }
+            "save" | "load" if rest.is_empty() => Err(format!("Usage: {} <name>", command)),
+            "save" => Ok(Command::Save(rest.to_string())),
+            "load" => Ok(Command::Load(rest.to_string())),
+            "list" => Ok(Command::List),
+            "help" | "?" => Ok(Command::Help),
+            _ => Err(format!("Unknown command \"{}\". {}", command, HELP)),
+        }
+    }
+}
+
+/// Lowercase letters and digits only, so "Pen Holder", "pen-holder" and
+/// "penholder" compare equal
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// The object type with this save-file name (e.g. `pen-holder`) or display
+/// name (e.g. `Desk Lamp`)
+fn parse_object_type(name: &str) -> Option<ObjectType> {
+    let name = normalize(name);
+    ObjectType::all_with_registered().into_iter().find(|object_type| {
+        normalize(&saved_name(*object_type)) == name || normalize(object_type.display_name()) == name
+    })
+}
+
+/// Save-file names of every object type, for error messages
+fn object_type_names() -> String {
+    ObjectType::all_with_registered()
+        .into_iter()
+        .map(saved_name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Name an object type is saved under: its registered key for types the
+/// embedding application added
+fn saved_name(object_type: ObjectType) -> String {
+    match object_type {
+        ObjectType::Custom(custom) => custom.descriptor().key.to_string(),
+        _ => serde_json::to_value(object_type)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_rejects_bad_input() {
+        assert_eq!(Command::parse("add pen-holder"), Ok(Command::Add(ObjectType::PenHolder)));
+        assert_eq!(Command::parse("  ADD Desk Lamp "), Ok(Command::Add(ObjectType::Lamp)));
+        assert_eq!(Command::parse("set gravity 0.5"), Ok(Command::SetGravity(0.5)));
+        assert_eq!(Command::parse("set collision_radius 1.2"), Ok(Command::SetCollisionRadius(1.2)));
+        assert_eq!(Command::parse("save my desk"), Ok(Command::Save("my desk".to_string())));
+        assert_eq!(Command::parse("list"), Ok(Command::List));
+
+        assert!(Command::parse("add spaceship").is_err());
+        assert!(Command::parse("set gravity fast").is_err());
+        assert!(Command::parse("set collision_radius 0").is_err());
+        assert!(Command::parse("set friction 1").is_err());
+        assert!(Command::parse("load").is_err());
+        assert!(Command::parse("jump").is_err());
+    }
+
+    #[test]
+    fn adds_registered_types_by_key_or_name() {
+        let shape = crate::physics::CollisionShape::Box { half_extents: glam::Vec3::splat(0.1) };
+        let descriptor = crate::registry::ObjectDescriptor::new("console-stapler", "Stapler", "📎", shape, |_, _| {
+            crate::mesh::MeshData::new()
+        });
+        let stapler = crate::registry::register_object(descriptor).unwrap();
+
+        assert_eq!(Command::parse("add console-stapler"), Ok(Command::Add(stapler)));
+        assert_eq!(Command::parse("add Stapler"), Ok(Command::Add(stapler)));
+        assert!(object_type_names().contains("console-stapler"));
+    }
+}
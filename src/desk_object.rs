@@ -2,8 +2,38 @@
 //!
 //! Defines the various objects that can be placed on the desk.
 
+use crate::config::{SpawnAnim, CONFIG};
+use crate::mesh::mesh_bottom;
+use crate::physics::CollisionShape;
+use crate::registry::{self, CustomType};
+use crate::texture::BUILTIN_IMAGES;
 use glam::{Vec3, Quat};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// `ObjectType::rest_offset` of every type, measured from its mesh once
+static REST_OFFSETS: LazyLock<HashMap<ObjectType, f32>> = LazyLock::new(|| {
+    ObjectType::all()
+        .iter()
+        .map(|&object_type| (object_type, -mesh_bottom(object_type)))
+        .collect()
+});
+
+/// Smallest scale an object can be resized to
+pub const MIN_SCALE: f32 = 0.3;
+/// Largest scale an object can be resized to
+pub const MAX_SCALE: f32 = 3.0;
+
+/// Yaw increment rotation snaps to (15 degrees)
+pub const ROTATION_SNAP_STEP: f32 = std::f32::consts::PI / 12.0;
+
+/// Allowed range for the number of books in a Books stack
+pub const BOOK_COUNT_RANGE: std::ops::RangeInclusive<u32> = 1..=8;
+
+/// Allowed range for an object's animation speed multiplier (0 = still)
+pub const ANIM_SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.0..=4.0;
 
 /// Type of desk object
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -25,6 +55,8 @@ pub enum ObjectType {
     Metronome,
     Paper,
     Magazine,
+    /// A type registered at runtime (see the `registry` module)
+    Custom(CustomType),
 }
 
 impl ObjectType {
@@ -46,6 +78,29 @@ impl ObjectType {
             ObjectType::Metronome => "Metronome",
             ObjectType::Paper => "Paper",
             ObjectType::Magazine => "Magazine",
+            ObjectType::Custom(custom) => custom.descriptor().name,
+        }
+    }
+
+    /// One-line description shown as a tooltip in the palette
+    pub fn description(&self) -> &'static str {
+        match self {
+            ObjectType::Clock => "A desk clock showing the current time",
+            ObjectType::Lamp => "A desk lamp to light up your workspace",
+            ObjectType::Plant => "A potted plant to bring some life to the desk",
+            ObjectType::Coffee => "A mug of coffee to keep you going",
+            ObjectType::Laptop => "A laptop for the work itself",
+            ObjectType::Notebook => "A notebook for jotting down ideas and to-dos",
+            ObjectType::PenHolder => "A cup of pens, always within reach",
+            ObjectType::Books => "A stack of books for reference and inspiration",
+            ObjectType::PhotoFrame => "A photo frame for a picture that motivates you",
+            ObjectType::Globe => "A spinning globe for a bit of wanderlust",
+            ObjectType::Trophy => "A trophy to celebrate finished goals",
+            ObjectType::Hourglass => "An hourglass for timing short focus sprints",
+            ObjectType::Metronome => "A working metronome — set the tempo and focus",
+            ObjectType::Paper => "A loose sheet of paper for quick notes",
+            ObjectType::Magazine => "A magazine to flip through on a break",
+            ObjectType::Custom(custom) => custom.descriptor().description,
         }
     }
 
@@ -67,6 +122,7 @@ impl ObjectType {
             ObjectType::Metronome => "\u{1F3B5}", // Musical note
             ObjectType::Paper => "\u{1F4C4}", // Page
             ObjectType::Magazine => "\u{1F4F0}", // Newspaper
+            ObjectType::Custom(custom) => custom.descriptor().icon,
         }
     }
 
@@ -88,6 +144,7 @@ impl ObjectType {
             ObjectType::Metronome => 0x78350f,
             ObjectType::Paper => 0xffffff,
             ObjectType::Magazine => 0xef4444,
+            ObjectType::Custom(custom) => custom.descriptor().default_color,
         }
     }
 
@@ -109,6 +166,7 @@ impl ObjectType {
             ObjectType::Metronome => 0xfbbf24,
             ObjectType::Paper => 0x000000,
             ObjectType::Magazine => 0xffffff,
+            ObjectType::Custom(custom) => custom.descriptor().default_accent_color,
         }
     }
 
@@ -119,7 +177,6 @@ impl ObjectType {
                 weight: 0.5,
                 stability: 0.5,
                 height: 0.6,
-                base_offset: 0.35,
                 friction: 0.4,
                 no_stacking_on_top: true,
             },
@@ -127,7 +184,6 @@ impl ObjectType {
                 weight: 1.2,
                 stability: 0.85,
                 height: 0.9,
-                base_offset: 0.0,
                 friction: 0.5,
                 no_stacking_on_top: false,
             },
@@ -135,7 +191,6 @@ impl ObjectType {
                 weight: 1.4,
                 stability: 0.9,
                 height: 0.5,
-                base_offset: 0.0,
                 friction: 0.6,
                 no_stacking_on_top: false,
             },
@@ -143,7 +198,6 @@ impl ObjectType {
                 weight: 0.4,
                 stability: 0.6,
                 height: 0.3,
-                base_offset: 0.0,
                 friction: 0.5,
                 no_stacking_on_top: false,
             },
@@ -151,7 +205,6 @@ impl ObjectType {
                 weight: 1.5,
                 stability: 0.95,
                 height: 0.3,
-                base_offset: 0.0,
                 friction: 0.6,
                 no_stacking_on_top: false,
             },
@@ -159,7 +212,6 @@ impl ObjectType {
                 weight: 0.3,
                 stability: 0.95,
                 height: 0.1,
-                base_offset: 0.0,
                 friction: 0.7,
                 no_stacking_on_top: false,
             },
@@ -167,7 +219,6 @@ impl ObjectType {
                 weight: 0.6,
                 stability: 0.6,
                 height: 0.4,
-                base_offset: 0.0,
                 friction: 0.5,
                 no_stacking_on_top: false,
             },
@@ -175,7 +226,6 @@ impl ObjectType {
                 weight: 0.8,
                 stability: 0.9,
                 height: 0.15,
-                base_offset: 0.0,
                 friction: 0.75,
                 no_stacking_on_top: false,
             },
@@ -183,7 +233,6 @@ impl ObjectType {
                 weight: 0.3,
                 stability: 0.35,
                 height: 0.5,
-                base_offset: 0.25,
                 friction: 0.4,
                 no_stacking_on_top: true,
             },
@@ -191,7 +240,6 @@ impl ObjectType {
                 weight: 1.0,
                 stability: 0.7,
                 height: 0.5,
-                base_offset: 0.025,
                 friction: 0.45,
                 no_stacking_on_top: false,
             },
@@ -199,7 +247,6 @@ impl ObjectType {
                 weight: 0.9,
                 stability: 0.6,
                 height: 0.4,
-                base_offset: 0.0,
                 friction: 0.5,
                 no_stacking_on_top: false,
             },
@@ -207,7 +254,6 @@ impl ObjectType {
                 weight: 0.5,
                 stability: 0.45,
                 height: 0.35,
-                base_offset: 0.015,
                 friction: 0.4,
                 no_stacking_on_top: false,
             },
@@ -215,7 +261,6 @@ impl ObjectType {
                 weight: 0.7,
                 stability: 0.7,
                 height: 0.45,
-                base_offset: 0.0,
                 friction: 0.55,
                 no_stacking_on_top: false,
             },
@@ -223,7 +268,6 @@ impl ObjectType {
                 weight: 0.05,
                 stability: 0.98,
                 height: 0.01,
-                base_offset: 0.0,
                 friction: 0.8,
                 no_stacking_on_top: false,
             },
@@ -231,10 +275,82 @@ impl ObjectType {
                 weight: 0.3,
                 stability: 0.95,
                 height: 0.02,
-                base_offset: 0.0,
                 friction: 0.65,
                 no_stacking_on_top: false,
             },
+            ObjectType::Custom(custom) => custom.descriptor().physics,
+        }
+    }
+
+    /// Scale a newly added object starts at, so sizes look right side by side
+    pub fn default_scale(&self) -> f32 {
+        match self {
+            ObjectType::Clock => 0.8,
+            ObjectType::Laptop => 1.25,
+            ObjectType::Globe => 0.9,
+            _ => 1.0,
+        }
+    }
+
+    /// How far the object's origin sits above the surface it rests on, at
+    /// scale 1 (multiply by the object's scale), so the lowest point of its
+    /// mesh touches the surface
+    pub fn rest_offset(&self) -> f32 {
+        match self {
+            ObjectType::Custom(custom) => custom.rest_offset(),
+            _ => REST_OFFSETS.get(self).copied().unwrap_or(0.0),
+        }
+    }
+
+    /// Flat objects lying on a surface (drawn after solid objects, sorted by depth)
+    pub fn is_decal(&self) -> bool {
+        matches!(self, ObjectType::Paper | ObjectType::Magazine)
+    }
+
+    /// Objects with a surface that can show an image: a magazine's cover, a
+    /// photo frame's photo or a laptop's screen
+    pub fn shows_image(&self) -> bool {
+        matches!(self, ObjectType::Magazine | ObjectType::PhotoFrame | ObjectType::Laptop)
+    }
+
+    /// Specular exponent for the sun's highlight on this type (higher is a
+    /// smaller, sharper highlight; 0 = matte, no highlight)
+    pub fn shininess(&self) -> f32 {
+        match self {
+            ObjectType::Trophy | ObjectType::Hourglass => 64.0,
+            ObjectType::Laptop => 48.0,
+            ObjectType::Globe | ObjectType::Metronome | ObjectType::PhotoFrame => 32.0,
+            ObjectType::Clock | ObjectType::Lamp | ObjectType::Coffee => 24.0,
+            ObjectType::PenHolder | ObjectType::Magazine => 12.0,
+            ObjectType::Plant | ObjectType::Notebook | ObjectType::Books | ObjectType::Paper => 0.0,
+            ObjectType::Custom(custom) => custom.descriptor().shininess,
+        }
+    }
+
+    /// Get the unscaled collision shape for the object type
+    pub fn collision_shape(&self) -> CollisionShape {
+        let cylinder = |radius, height| CollisionShape::Cylinder { radius, height };
+        let cuboid = |x, y, z| CollisionShape::Box {
+            half_extents: Vec3::new(x, y, z),
+        };
+        match self {
+            ObjectType::Clock => cylinder(0.25, 0.45),
+            ObjectType::Lamp => cylinder(0.15, 0.8),
+            ObjectType::Plant => cylinder(0.12, 0.34),
+            ObjectType::Coffee => cylinder(0.1, 0.15),
+            ObjectType::Laptop => cuboid(0.2, 0.14, 0.15),
+            ObjectType::Notebook => cuboid(0.125, 0.015, 0.175),
+            ObjectType::PenHolder => cylinder(0.08, 0.3),
+            // Height is per-object, see `DeskObject::collision_shape`
+            ObjectType::Books => cuboid(0.12, 0.0525, 0.16),
+            ObjectType::PhotoFrame => cuboid(0.1, 0.125, 0.06),
+            ObjectType::Globe => CollisionShape::Sphere { radius: 0.19 },
+            ObjectType::Trophy => cylinder(0.12, 0.26),
+            ObjectType::Hourglass => cylinder(0.07, 0.3),
+            ObjectType::Metronome => cuboid(0.06, 0.125, 0.05),
+            ObjectType::Paper => cuboid(0.105, 0.001, 0.1485),
+            ObjectType::Magazine => cuboid(0.11, 0.006, 0.15),
+            ObjectType::Custom(custom) => custom.descriptor().collision_shape,
         }
     }
 
@@ -258,6 +374,14 @@ impl ObjectType {
             ObjectType::Magazine,
         ]
     }
+
+    /// Every object type: the built-in ones, then those the embedding
+    /// application registered
+    pub fn all_with_registered() -> Vec<ObjectType> {
+        let mut types = Self::all().to_vec();
+        types.extend(registry::registered_types());
+        types
+    }
 }
 
 /// Physics properties for an object type
@@ -269,16 +393,237 @@ pub struct ObjectPhysics {
     pub stability: f32,
     /// Height of the object for stacking
     pub height: f32,
-    /// Distance from origin to bottom (for Y correction when scaling)
-    pub base_offset: f32,
     /// Surface friction coefficient (0-1)
     pub friction: f32,
     /// If true, objects cannot be stacked on top of this
     pub no_stacking_on_top: bool,
 }
 
+/// Allowed metronome tempo in beats per minute
+pub const BPM_RANGE: std::ops::RangeInclusive<u32> = 20..=300;
+
+/// Allowed distance in world units a lamp's light reaches
+pub const LIGHT_RADIUS_RANGE: std::ops::RangeInclusive<f32> = 1.0..=6.0;
+
+/// Where the light comes from in a lamp's model space, inside its head (see
+/// `create_lamp` in mesh.rs)
+const LAMP_BULB: Vec3 = Vec3::new(0.0, 0.72, 0.0);
+
+/// Per-type interactive state that persists with the object
+///
+/// Tagged by `kind` in JSON. Missing fields take their defaults, and a kind
+/// this version doesn't know (e.g. from a newer save) loads as `None`, which
+/// `DeskObject::sanitize` then replaces with the type's default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ObjectState {
+    /// Countdown timer
+    Hourglass {
+        #[serde(default = "default_hourglass_duration")]
+        duration_secs: f32,
+        #[serde(default)]
+        elapsed_secs: f32,
+        #[serde(default)]
+        running: bool,
+    },
+    /// Light switch and how far the light reaches, in world units
+    Lamp {
+        #[serde(default = "default_true")]
+        on: bool,
+        #[serde(default = "default_light_radius")]
+        light_radius: f32,
+    },
+    /// Spin angle (radians) and speed (radians per second)
+    Globe {
+        #[serde(default)]
+        angle: f32,
+        #[serde(default)]
+        speed: f32,
+    },
+    /// Growth progress from 0 (sprout) to 1 (fully grown)
+    Plant {
+        #[serde(default)]
+        growth: f32,
+    },
+    /// How full the mug is, from 0 (empty) to 1 (full)
+    Coffee {
+        #[serde(default = "default_fill")]
+        fill: f32,
+    },
+    /// Tempo and whether it is ticking
+    Metronome {
+        #[serde(default = "default_bpm")]
+        bpm: u32,
+        #[serde(default)]
+        running: bool,
+    },
+    /// Objects without interactive behavior
+    #[default]
+    #[serde(other)]
+    None,
+}
+
+fn default_hourglass_duration() -> f32 {
+    300.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_light_radius() -> f32 {
+    2.5
+}
+
+fn default_fill() -> f32 {
+    1.0
+}
+
+fn default_bpm() -> u32 {
+    100
+}
+
+impl ObjectState {
+    /// Initial state for a newly created object of the given type
+    pub fn default_for(object_type: ObjectType) -> Self {
+        match object_type {
+            ObjectType::Hourglass => ObjectState::Hourglass {
+                duration_secs: default_hourglass_duration(),
+                elapsed_secs: 0.0,
+                running: false,
+            },
+            ObjectType::Lamp => ObjectState::Lamp {
+                on: true,
+                light_radius: default_light_radius(),
+            },
+            ObjectType::Globe => ObjectState::Globe { angle: 0.0, speed: 0.0 },
+            ObjectType::Plant => ObjectState::Plant { growth: 0.0 },
+            ObjectType::Coffee => ObjectState::Coffee { fill: default_fill() },
+            ObjectType::Metronome => ObjectState::Metronome {
+                bpm: default_bpm(),
+                running: false,
+            },
+            _ => ObjectState::None,
+        }
+    }
+
+    /// Whether this state belongs to the given object type
+    pub fn matches(&self, object_type: ObjectType) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(&Self::default_for(object_type))
+    }
+
+    /// How far a lamp's light reaches when it is on
+    pub fn light_radius(&self) -> Option<f32> {
+        match self {
+            ObjectState::Lamp { light_radius, .. } => Some(*light_radius),
+            _ => None,
+        }
+    }
+
+    /// Change how far a lamp's light reaches (no-op for other objects)
+    pub fn set_light_radius(&mut self, radius: f32) {
+        if let ObjectState::Lamp { light_radius, .. } = self {
+            *light_radius = radius.clamp(*LIGHT_RADIUS_RANGE.start(), *LIGHT_RADIUS_RANGE.end());
+        }
+    }
+
+    /// Seconds left on a running hourglass timer
+    pub fn timer_remaining(&self) -> Option<f32> {
+        match self {
+            ObjectState::Hourglass {
+                duration_secs,
+                elapsed_secs,
+                running: true,
+            } => Some((duration_secs - elapsed_secs).max(0.0)),
+            _ => None,
+        }
+    }
+
+    /// Start or stop an hourglass timer. Starting a finished timer restarts it.
+    pub fn toggle_timer(&mut self) {
+        if let ObjectState::Hourglass {
+            duration_secs,
+            elapsed_secs,
+            running,
+        } = self
+        {
+            *running = !*running;
+            if *running && *elapsed_secs >= *duration_secs {
+                *elapsed_secs = 0.0;
+            }
+        }
+    }
+
+    /// Advance a running hourglass timer by `dt` seconds, returning whether it
+    /// just ran out (it stops then)
+    pub fn advance_timer(&mut self, dt: f32) -> bool {
+        let ObjectState::Hourglass {
+            duration_secs,
+            elapsed_secs,
+            running: running @ true,
+        } = self
+        else {
+            return false;
+        };
+        *elapsed_secs = (*elapsed_secs + dt).min(*duration_secs);
+        if *elapsed_secs < *duration_secs {
+            return false;
+        }
+        *running = false;
+        true
+    }
+
+    /// Clamp values into their valid ranges, resetting non-finite numbers
+    fn sanitize(&mut self) {
+        let finite_or = |value: f32, fallback: f32| if value.is_finite() { value } else { fallback };
+        match self {
+            ObjectState::None => {}
+            ObjectState::Lamp { light_radius, .. } => {
+                *light_radius = finite_or(*light_radius, default_light_radius())
+                    .clamp(*LIGHT_RADIUS_RANGE.start(), *LIGHT_RADIUS_RANGE.end());
+            }
+            ObjectState::Hourglass {
+                duration_secs,
+                elapsed_secs,
+                ..
+            } => {
+                *duration_secs = finite_or(*duration_secs, default_hourglass_duration()).max(1.0);
+                *elapsed_secs = finite_or(*elapsed_secs, 0.0).clamp(0.0, *duration_secs);
+            }
+            ObjectState::Globe { angle, speed } => {
+                *angle = finite_or(*angle, 0.0).rem_euclid(std::f32::consts::TAU);
+                *speed = finite_or(*speed, 0.0);
+            }
+            ObjectState::Plant { growth } => *growth = finite_or(*growth, 0.0).clamp(0.0, 1.0),
+            ObjectState::Coffee { fill } => *fill = finite_or(*fill, default_fill()).clamp(0.0, 1.0),
+            ObjectState::Metronome { bpm, .. } => {
+                *bpm = (*bpm).clamp(*BPM_RANGE.start(), *BPM_RANGE.end());
+            }
+        }
+    }
+}
+
+/// Desk axis an object is mirrored across (the desk is centered on the origin)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    /// Swap left and right (negates X)
+    X,
+    /// Swap front and back (negates Z)
+    Z,
+}
+
+/// Where an object's image comes from (see `ObjectType::shows_image`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ImageSource {
+    /// One of the images bundled with the app, by index into `BUILTIN_IMAGES`
+    Builtin { builtin: usize },
+    /// An image file on disk (saves from before built-in images hold just the path)
+    Path(String),
+}
+
 /// A desk object instance
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeskObject {
     /// Unique identifier
     #[serde(default = "default_id")]
@@ -296,26 +641,61 @@ pub struct DeskObject {
     #[serde(default = "default_scale")]
     pub scale: f32,
     /// Main color (hex RGB)
-    #[serde(default = "default_color")]
+    #[serde(default = "default_color", deserialize_with = "color_serde::main")]
     pub color: u32,
     /// Accent color (hex RGB)
-    #[serde(default = "default_accent_color")]
+    #[serde(default = "default_accent_color", deserialize_with = "color_serde::accent")]
     pub accent_color: u32,
+    /// Number of books in the stack (Books only)
+    #[serde(default = "default_book_count")]
+    pub book_count: u32,
+    /// Image shown on the object (see `ObjectType::shows_image`); magazines
+    /// get a procedural cover when unset
+    #[serde(default)]
+    pub cover_image: Option<ImageSource>,
+    /// User-given name shown instead of the type name (e.g. "Work laptop")
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Free-form tags for organizing and filtering (e.g. "work", "decor")
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Locked objects can't be dragged, scaled, rotated or deleted
+    #[serde(default)]
+    pub locked: bool,
+    /// Hidden objects aren't drawn and can't be picked
+    #[serde(default)]
+    pub hidden: bool,
+    /// Interactive state (timers, toggles, spin)
+    #[serde(default)]
+    pub state: ObjectState,
     /// Custom collision radius multiplier (1.0 = default)
     #[serde(default = "default_multiplier")]
     pub collision_radius_multiplier: f32,
     /// Custom collision height multiplier (1.0 = default)
     #[serde(default = "default_multiplier")]
     pub collision_height_multiplier: f32,
+    /// How fast the object's idle motion runs (1.0 = normal, 0.0 = still)
+    #[serde(default = "default_multiplier")]
+    pub anim_speed: f32,
     /// Whether the object is currently being dragged
     #[serde(skip)]
     pub is_dragging: bool,
+    /// Progress of the spawn animation (0-1), `None` once settled
+    #[serde(skip)]
+    pub spawn_progress: Option<f32>,
     /// Target Y position for smooth dropping
     #[serde(skip)]
     pub target_y: f32,
     /// Original Y position (on desk surface)
     #[serde(skip)]
     pub original_y: f32,
+    /// When the attention pulse (e.g. for a finished timer) ends, `None` when
+    /// not alerting
+    #[serde(skip)]
+    pub alert_until: Option<Instant>,
+    /// When the object was last interacted with, for the interaction cooldown
+    #[serde(skip)]
+    pub last_interacted: Option<Instant>,
 }
 
 // Default value functions for serde
@@ -343,6 +723,10 @@ fn default_accent_color() -> u32 {
     0x404040
 }
 
+fn default_book_count() -> u32 {
+    3
+}
+
 fn default_multiplier() -> f32 {
     1.0
 }
@@ -350,22 +734,229 @@ fn default_multiplier() -> f32 {
 impl DeskObject {
     /// Create a new desk object
     pub fn new(id: u64, object_type: ObjectType, position: Vec3) -> Self {
-        let physics = object_type.physics();
-        let y = position.y + physics.base_offset;
+        let scale = object_type.default_scale();
+        let y = position.y + object_type.rest_offset() * scale;
 
         Self {
             id,
             object_type,
             position: Vec3::new(position.x, y, position.z),
             rotation: Quat::IDENTITY,
-            scale: 1.0,
+            scale,
             color: object_type.default_color(),
             accent_color: object_type.default_accent_color(),
+            book_count: default_book_count(),
+            cover_image: None,
+            name: None,
+            tags: Vec::new(),
+            locked: false,
+            hidden: false,
+            state: ObjectState::default_for(object_type),
             collision_radius_multiplier: 1.0,
             collision_height_multiplier: 1.0,
+            anim_speed: 1.0,
             is_dragging: false,
+            spawn_progress: None,
             target_y: y,
             original_y: y,
+            alert_until: None,
+            last_interacted: None,
+        }
+    }
+
+    /// Path of the image file shown on the object, if it shows one from disk
+    pub fn image_path(&self) -> Option<&str> {
+        match &self.cover_image {
+            Some(ImageSource::Path(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The user-given name, or the type's display name
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.object_type.display_name())
+    }
+
+    /// Whether any tag contains the text, ignoring case
+    pub fn has_tag_matching(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+    }
+
+    /// A copy keeping only what the object is (type, name, colors, scale and
+    /// settings), without its place on a desk, for saving as a template.
+    /// Running timers are reset.
+    pub fn to_template(&self) -> DeskObject {
+        let state = match self.state {
+            ObjectState::Hourglass { duration_secs, .. } => ObjectState::Hourglass {
+                duration_secs,
+                elapsed_secs: 0.0,
+                running: false,
+            },
+            ObjectState::Metronome { bpm, .. } => ObjectState::Metronome { bpm, running: false },
+            ref other => other.clone(),
+        };
+        DeskObject {
+            id: 0,
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            locked: false,
+            hidden: false,
+            state,
+            is_dragging: false,
+            spawn_progress: None,
+            target_y: 0.0,
+            original_y: 0.0,
+            alert_until: None,
+            last_interacted: None,
+            ..self.clone()
+        }
+    }
+
+    /// A copy with the runtime-only state (animations, drag and interaction
+    /// timestamps) reset, so copies compare equal when their saved form does
+    pub fn without_runtime_state(&self) -> DeskObject {
+        DeskObject {
+            is_dragging: false,
+            spawn_progress: None,
+            target_y: 0.0,
+            original_y: 0.0,
+            alert_until: None,
+            last_interacted: None,
+            ..self.clone()
+        }
+    }
+
+    /// Start the configured spawn animation (no-op when it is `None`)
+    pub fn start_spawn_animation(&mut self) {
+        if CONFIG.animation.spawn != SpawnAnim::None {
+            self.spawn_progress = Some(0.0);
+        }
+    }
+
+    /// Advance the spawn animation by `dt` seconds, returning whether it moved
+    pub fn advance_spawn(&mut self, dt: f32) -> bool {
+        let Some(progress) = self.spawn_progress else { return false };
+        let progress = progress + dt / CONFIG.animation.spawn_duration.max(f32::EPSILON);
+        self.spawn_progress = (progress < 1.0).then_some(progress);
+        true
+    }
+
+    /// Start pulsing to draw attention, for `CONFIG.animation.alert_duration`
+    pub fn start_alert(&mut self, now: Instant) {
+        let duration = Duration::from_secs_f32(CONFIG.animation.alert_duration.max(0.0));
+        self.alert_until = Some(now + duration);
+    }
+
+    /// Record an interaction at `now`, or return false if the last one was
+    /// less than `CONFIG.objects.interaction_cooldown` ago
+    pub fn try_interact(&mut self, now: Instant) -> bool {
+        let cooling_down = self
+            .last_interacted
+            .is_some_and(|last| now.saturating_duration_since(last) < CONFIG.objects.interaction_cooldown);
+        if !cooling_down {
+            self.last_interacted = Some(now);
+        }
+        !cooling_down
+    }
+
+    /// Seconds of the alert pulse left at `now`, `None` when not alerting
+    pub fn alert_remaining(&self, now: Instant) -> Option<f32> {
+        self.alert_until
+            .map(|until| until.saturating_duration_since(now).as_secs_f32())
+    }
+
+    /// Advance the alert pulse to `now`, ending it once it has run out.
+    /// Returns whether it moved, including the frame it ends on.
+    pub fn advance_alert(&mut self, now: Instant) -> bool {
+        let Some(until) = self.alert_until else { return false };
+        if now >= until {
+            self.alert_until = None;
+        }
+        true
+    }
+
+    /// Mirror the object to the other side of the desk, turning it so it
+    /// faces the mirrored direction (e.g. a lamp on the left reaching right
+    /// ends up on the right reaching left)
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        // Reflecting a rotation across a plane keeps the quaternion component
+        // along the plane's normal and negates the other two
+        let [x, y, z, w] = self.rotation.to_array();
+        match axis {
+            MirrorAxis::X => {
+                self.position.x = -self.position.x;
+                self.rotation = Quat::from_xyzw(x, -y, -z, w);
+            }
+            MirrorAxis::Z => {
+                self.position.z = -self.position.z;
+                self.rotation = Quat::from_xyzw(-x, -y, z, w);
+            }
+        }
+    }
+
+    /// How far the object's origin sits above the surface it rests on at its scale
+    pub fn base_offset(&self) -> f32 {
+        self.object_type.rest_offset() * self.scale
+    }
+
+    /// Rotation around the vertical axis in radians
+    pub fn yaw(&self) -> f32 {
+        self.rotation.to_euler(glam::EulerRot::YXZ).0
+    }
+
+    /// Repair values that would render degenerately (e.g. from a hand-edited save).
+    ///
+    /// Zero or non-finite rotations reset to identity, others are renormalized,
+    /// scale is clamped to the range the UI allows, blank and repeated tags are
+    /// dropped, and interactive state that is missing or belongs to another
+    /// type is reset to the type's default.
+    pub fn sanitize(&mut self) {
+        let rotation = self.rotation;
+        if !rotation.is_finite() || rotation.length_squared() < 1e-6 {
+            log::warn!("Object {} had an invalid rotation, resetting", self.id);
+            self.rotation = Quat::IDENTITY;
+        } else {
+            self.rotation = rotation.normalize();
+        }
+
+        if !self.scale.is_finite() {
+            log::warn!("Object {} had an invalid scale, resetting", self.id);
+            self.scale = 1.0;
+        }
+        self.scale = self.scale.clamp(MIN_SCALE, MAX_SCALE);
+        self.book_count = self
+            .book_count
+            .clamp(*BOOK_COUNT_RANGE.start(), *BOOK_COUNT_RANGE.end());
+        if !self.anim_speed.is_finite() {
+            self.anim_speed = 1.0;
+        }
+        self.anim_speed = self.anim_speed.clamp(*ANIM_SPEED_RANGE.start(), *ANIM_SPEED_RANGE.end());
+        self.color &= 0xFFFFFF;
+        self.accent_color &= 0xFFFFFF;
+        if let Some(ImageSource::Builtin { builtin }) = self.cover_image {
+            if builtin >= BUILTIN_IMAGES.len() {
+                log::warn!("Object {} had an unknown built-in image {}, removing it", self.id, builtin);
+                self.cover_image = None;
+            }
+        }
+
+        self.tags = parse_tags(&self.tags.join(","));
+
+        if !self.state.matches(self.object_type) {
+            self.state = ObjectState::default_for(self.object_type);
+        }
+        self.state.sanitize();
+    }
+
+    /// World position of a switched-on lamp's bulb and how far its light
+    /// reaches, `None` for anything else
+    pub fn light_source(&self) -> Option<(Vec3, f32)> {
+        match self.state {
+            ObjectState::Lamp { on: true, light_radius } if !self.hidden => {
+                Some((self.model_matrix().transform_point3(LAMP_BULB), light_radius))
+            }
+            _ => None,
         }
     }
 
@@ -378,44 +969,63 @@ impl DeskObject {
         )
     }
 
-    /// Get the collision radius for this object
-    pub fn collision_radius(&self) -> f32 {
-        let physics = self.object_type.physics();
-        // Base radius based on object type, scaled
-        let base_radius = match self.object_type {
-            ObjectType::Lamp => 0.3,
-            ObjectType::Plant => 0.25,
-            ObjectType::Coffee => 0.15,
-            ObjectType::Laptop => 0.5,
-            ObjectType::Notebook => 0.35,
-            ObjectType::Books => 0.3,
-            ObjectType::Globe => 0.25,
-            ObjectType::Trophy => 0.2,
-            _ => 0.2,
+    /// Get the collision shape for this object, scaled by its size and multipliers
+    pub fn collision_shape(&self) -> CollisionShape {
+        let shape = match self.object_type {
+            // Stack height grows with the number of books
+            ObjectType::Books => CollisionShape::Box {
+                half_extents: Vec3::new(0.12, 0.0175 * self.book_count as f32, 0.16),
+            },
+            object_type => object_type.collision_shape(),
         };
-        base_radius * self.scale * self.collision_radius_multiplier
+        shape.scaled(
+            self.scale * self.collision_radius_multiplier,
+            self.scale * self.collision_height_multiplier,
+        )
+    }
+
+    /// Get the collision radius for this object (encloses its footprint)
+    pub fn collision_radius(&self) -> f32 {
+        self.collision_shape().bounding_radius()
     }
 
     /// Get the collision height for this object
     pub fn collision_height(&self) -> f32 {
-        let physics = self.object_type.physics();
-        physics.height * self.scale * self.collision_height_multiplier
+        self.collision_shape().height()
     }
 
-    /// Check if a point is inside the collision bounds
-    pub fn contains_point(&self, point: Vec3) -> bool {
+    /// World-space box (min, max) around the object's collision shape, at
+    /// any rotation
+    pub fn bounds(&self) -> (Vec3, Vec3) {
         let radius = self.collision_radius();
-        let height = self.collision_height();
-
-        let dx = point.x - self.position.x;
-        let dz = point.z - self.position.z;
-        let horizontal_dist_sq = dx * dx + dz * dz;
+        let bottom = self.position.y - self.base_offset();
+        (
+            Vec3::new(self.position.x - radius, bottom, self.position.z - radius),
+            Vec3::new(self.position.x + radius, bottom + self.collision_height(), self.position.z + radius),
+        )
+    }
 
-        let within_radius = horizontal_dist_sq <= radius * radius;
-        let within_height = point.y >= self.position.y && point.y <= self.position.y + height;
+    /// Distance along a ray to this object's collision shape, if hit
+    pub fn ray_intersection(&self, ray_origin: Vec3, ray_direction: Vec3, margin: f32) -> Option<f32> {
+        self.collision_shape().inflated(margin).ray_intersection(
+            self.position,
+            self.rotation,
+            ray_origin,
+            ray_direction,
+        )
+    }
+}
 
-        within_radius && within_height
+/// Tags typed as comma-separated text, trimmed, without empty entries or
+/// repeats (ignoring case), in the order typed
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|existing| existing.to_lowercase() == tag.to_lowercase()) {
+            tags.push(tag.to_string());
+        }
     }
+    tags
 }
 
 // Custom serialization for Vec3
@@ -485,3 +1095,327 @@ mod quat_serde {
         Ok(Quat::from_xyzw(repr.x, repr.y, repr.z, repr.w))
     }
 }
+
+/// Colors load from either a number or a hex string ("#rgb", "#rrggbb",
+/// "#rrggbbaa"); invalid values fall back to the field's default
+mod color_serde {
+    use crate::config::parse_hex_color;
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ColorRepr {
+        Number(u64),
+        Text(String),
+    }
+
+    fn deserialize<'de, D>(deserializer: D, fallback: u32) -> Result<u32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let color = match ColorRepr::deserialize(deserializer)? {
+            ColorRepr::Number(value) if value <= 0xFFFF_FFFF => Some(value as u32 & 0xFFFFFF),
+            ColorRepr::Number(_) => None,
+            ColorRepr::Text(text) => parse_hex_color(&text),
+        };
+        Ok(color.unwrap_or_else(|| {
+            log::warn!("Invalid color in saved state, using default #{:06x}", fallback);
+            fallback
+        }))
+    }
+
+    pub fn main<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        deserialize(deserializer, super::default_color())
+    }
+
+    pub fn accent<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        deserialize(deserializer, super::default_accent_color())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirroring_reflects_position_and_facing() {
+        let mut obj = DeskObject::new(1, ObjectType::Lamp, Vec3::new(1.2, 0.75, -0.5));
+        obj.rotation = Quat::from_rotation_y(0.6) * Quat::from_rotation_x(0.2);
+        let original = obj.clone();
+        // Each mirrored local axis points where the original one's mirror image does
+        let is_mirror_image = |obj: &DeskObject, flip: Vec3| {
+            [Vec3::X, Vec3::Y, Vec3::Z].into_iter().all(|axis| {
+                let mirrored = obj.rotation * (axis * flip);
+                mirrored.distance(original.rotation * axis * flip) < 1e-5
+            })
+        };
+
+        obj.mirror(MirrorAxis::X);
+        assert_eq!(obj.position, Vec3::new(-1.2, 0.75, -0.5));
+        assert!(is_mirror_image(&obj, Vec3::new(-1.0, 1.0, 1.0)));
+        assert!((obj.yaw() + original.yaw()).abs() < 1e-5);
+
+        obj.mirror(MirrorAxis::X);
+        obj.mirror(MirrorAxis::Z);
+        assert_eq!(obj.position, Vec3::new(1.2, 0.75, 0.5));
+        assert!(is_mirror_image(&obj, Vec3::new(1.0, 1.0, -1.0)));
+    }
+
+    #[test]
+    fn sanitize_resets_zero_quaternion() {
+        let mut obj = DeskObject::new(1, ObjectType::Clock, Vec3::ZERO);
+        obj.rotation = Quat::from_xyzw(0.0, 0.0, 0.0, 0.0);
+        obj.sanitize();
+        assert_eq!(obj.rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn sanitize_resets_nan_quaternion() {
+        let mut obj = DeskObject::new(1, ObjectType::Clock, Vec3::ZERO);
+        obj.rotation = Quat::from_xyzw(f32::NAN, 0.0, 0.0, 1.0);
+        obj.sanitize();
+        assert_eq!(obj.rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn sanitize_normalizes_rotation_and_clamps_scale() {
+        let mut obj = DeskObject::new(1, ObjectType::Clock, Vec3::ZERO);
+        obj.rotation = Quat::from_xyzw(0.0, 2.0, 0.0, 2.0);
+        obj.scale = 10.0;
+        obj.sanitize();
+        assert!(obj.rotation.is_normalized());
+        assert_eq!(obj.scale, MAX_SCALE);
+
+        obj.scale = f32::NAN;
+        obj.sanitize();
+        assert_eq!(obj.scale, 1.0);
+    }
+
+    #[test]
+    fn anim_speed_defaults_to_normal_and_is_clamped() {
+        let mut obj: DeskObject = serde_json::from_str(r#"{"id": 2, "object_type": "globe"}"#).unwrap();
+        assert_eq!(obj.anim_speed, 1.0);
+
+        obj.anim_speed = 12.0;
+        obj.sanitize();
+        assert_eq!(obj.anim_speed, *ANIM_SPEED_RANGE.end());
+
+        obj.anim_speed = f32::INFINITY;
+        obj.sanitize();
+        assert_eq!(obj.anim_speed, 1.0);
+    }
+
+    #[test]
+    fn corrupt_quaternion_from_json_is_repaired() {
+        let json = r#"{"id": 7, "rotation": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 0.0}, "scale": 0.01}"#;
+        let mut obj: DeskObject = serde_json::from_str(json).unwrap();
+        obj.sanitize();
+        assert_eq!(obj.rotation, Quat::IDENTITY);
+        assert_eq!(obj.scale, MIN_SCALE);
+    }
+
+    #[test]
+    fn images_load_as_paths_or_builtin_references() {
+        let json = r#"{"id": 1, "object_type": "photo-frame", "cover_image": "/photos/cat.png"}"#;
+        let obj: DeskObject = serde_json::from_str(json).unwrap();
+        assert_eq!(obj.cover_image, Some(ImageSource::Path("/photos/cat.png".to_string())));
+        assert_eq!(obj.image_path(), Some("/photos/cat.png"));
+
+        let mut obj = DeskObject::new(2, ObjectType::PhotoFrame, Vec3::ZERO);
+        obj.cover_image = Some(ImageSource::Builtin { builtin: 1 });
+        let json = serde_json::to_string(&obj).unwrap();
+        assert!(json.contains(r#""cover_image":{"builtin":1}"#), "{}", json);
+        let mut loaded: DeskObject = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.cover_image, obj.cover_image);
+        assert_eq!(loaded.image_path(), None);
+
+        loaded.cover_image = Some(ImageSource::Builtin { builtin: BUILTIN_IMAGES.len() });
+        loaded.sanitize();
+        assert_eq!(loaded.cover_image, None);
+    }
+
+    #[test]
+    fn colors_load_from_numbers_and_hex_strings() {
+        let json = r##"{"id": 1, "color": "#f00", "accent_color": 4278190335}"##;
+        let obj: DeskObject = serde_json::from_str(json).unwrap();
+        assert_eq!(obj.color, 0xFF0000);
+        // 0xFF0000FF: the high (alpha) byte is dropped
+        assert_eq!(obj.accent_color, 0x0000FF);
+
+        let json = r##"{"id": 1, "color": "#nope", "accent_color": "12345"}"##;
+        let obj: DeskObject = serde_json::from_str(json).unwrap();
+        assert_eq!(obj.color, default_color());
+        assert_eq!(obj.accent_color, default_accent_color());
+    }
+
+    /// Serialize and deserialize an object, returning the loaded state
+    fn round_trip_state(object_type: ObjectType, state: ObjectState) -> ObjectState {
+        let mut obj = DeskObject::new(1, object_type, Vec3::ZERO);
+        obj.state = state;
+        let json = serde_json::to_string(&obj).unwrap();
+        let mut loaded: DeskObject = serde_json::from_str(&json).unwrap();
+        loaded.sanitize();
+        loaded.state
+    }
+
+    #[test]
+    fn object_state_round_trips_for_every_variant() {
+        let cases = [
+            (ObjectType::Clock, ObjectState::None),
+            (
+                ObjectType::Hourglass,
+                ObjectState::Hourglass {
+                    duration_secs: 90.0,
+                    elapsed_secs: 30.5,
+                    running: true,
+                },
+            ),
+            (
+                ObjectType::Lamp,
+                ObjectState::Lamp {
+                    on: false,
+                    light_radius: 4.0,
+                },
+            ),
+            (ObjectType::Globe, ObjectState::Globe { angle: 1.25, speed: 0.5 }),
+            (ObjectType::Plant, ObjectState::Plant { growth: 0.75 }),
+            (ObjectType::Coffee, ObjectState::Coffee { fill: 0.4 }),
+            (
+                ObjectType::Metronome,
+                ObjectState::Metronome {
+                    bpm: 132,
+                    running: true,
+                },
+            ),
+        ];
+        for (object_type, state) in cases {
+            assert_eq!(round_trip_state(object_type, state.clone()), state);
+        }
+    }
+
+    #[test]
+    fn missing_state_loads_type_default() {
+        let json = r#"{"id": 3, "object_type": "lamp"}"#;
+        let mut obj: DeskObject = serde_json::from_str(json).unwrap();
+        obj.sanitize();
+        assert_eq!(obj.state, ObjectState::default_for(ObjectType::Lamp));
+        assert_eq!(obj.state.light_radius(), Some(default_light_radius()));
+    }
+
+    #[test]
+    fn unknown_state_kind_and_fields_fall_back_gracefully() {
+        let json = r#"{"id": 3, "object_type": "coffee", "state": {"kind": "teleporter", "charge": 9}}"#;
+        let mut obj: DeskObject = serde_json::from_str(json).unwrap();
+        obj.sanitize();
+        assert_eq!(obj.state, ObjectState::default_for(ObjectType::Coffee));
+
+        let json = r#"{"id": 4, "object_type": "coffee", "state": {"kind": "coffee", "fill": 0.5, "sugar": 2}}"#;
+        let obj: DeskObject = serde_json::from_str(json).unwrap();
+        assert_eq!(obj.state, ObjectState::Coffee { fill: 0.5 });
+    }
+
+    #[test]
+    fn mismatched_or_out_of_range_state_is_repaired() {
+        let mut obj = DeskObject::new(1, ObjectType::Metronome, Vec3::ZERO);
+        obj.state = ObjectState::default_for(ObjectType::Lamp);
+        obj.sanitize();
+        assert_eq!(obj.state, ObjectState::default_for(ObjectType::Metronome));
+
+        obj.state = ObjectState::Metronome { bpm: 5000, running: true };
+        obj.sanitize();
+        assert_eq!(obj.state, ObjectState::Metronome { bpm: *BPM_RANGE.end(), running: true });
+
+        let mut lamp = DeskObject::new(2, ObjectType::Lamp, Vec3::ZERO);
+        lamp.state = ObjectState::Lamp {
+            on: true,
+            light_radius: 0.0,
+        };
+        lamp.sanitize();
+        assert_eq!(lamp.state.light_radius(), Some(*LIGHT_RADIUS_RANGE.start()));
+    }
+
+    #[test]
+    fn hourglass_timer_runs_out_and_restarts() {
+        let mut state = ObjectState::Hourglass {
+            duration_secs: 10.0,
+            elapsed_secs: 0.0,
+            running: false,
+        };
+        assert!(!state.advance_timer(5.0));
+        assert_eq!(state.timer_remaining(), None);
+
+        state.toggle_timer();
+        assert!(!state.advance_timer(4.0));
+        assert_eq!(state.timer_remaining(), Some(6.0));
+        assert!(state.advance_timer(7.0));
+        assert_eq!(state.timer_remaining(), None);
+
+        state.toggle_timer();
+        assert_eq!(state.timer_remaining(), Some(10.0));
+    }
+
+    #[test]
+    fn templates_keep_looks_and_drop_placement() {
+        let mut obj = DeskObject::new(7, ObjectType::Hourglass, Vec3::new(1.0, 0.5, -2.0));
+        obj.color = 0xff0000;
+        obj.scale = 1.5;
+        obj.locked = true;
+        obj.state.toggle_timer();
+
+        let template = obj.to_template();
+        assert_eq!((template.id, template.position), (0, Vec3::ZERO));
+        assert_eq!((template.color, template.scale), (0xff0000, 1.5));
+        assert!(!template.locked);
+        assert_eq!(template.state.timer_remaining(), None);
+    }
+
+    #[test]
+    fn alerts_pulse_until_they_run_out() {
+        let mut obj = DeskObject::new(3, ObjectType::Hourglass, Vec3::ZERO);
+        let start = Instant::now();
+        assert!(!obj.advance_alert(start));
+
+        obj.start_alert(start);
+        let duration = CONFIG.animation.alert_duration;
+        assert!((obj.alert_remaining(start).unwrap() - duration).abs() < 1e-3);
+        assert!(obj.advance_alert(start + Duration::from_secs_f32(duration / 2.0)));
+        assert!(obj.alert_until.is_some());
+
+        // The last frame still moves so the object settles back
+        let end = start + Duration::from_secs_f32(duration);
+        assert!(obj.advance_alert(end));
+        assert_eq!(obj.alert_remaining(end), None);
+        assert!(!obj.advance_alert(end));
+    }
+
+    #[test]
+    fn interactions_wait_out_the_cooldown() {
+        let mut obj = DeskObject::new(4, ObjectType::Hourglass, Vec3::ZERO);
+        let start = Instant::now();
+        let cooldown = CONFIG.objects.interaction_cooldown;
+        assert!(obj.try_interact(start));
+        assert!(!obj.try_interact(start));
+        assert!(!obj.try_interact(start + cooldown / 2));
+        assert!(obj.try_interact(start + cooldown));
+        // Another object isn't held up
+        assert!(DeskObject::new(5, ObjectType::Hourglass, Vec3::ZERO).try_interact(start));
+    }
+
+    #[test]
+    fn tags_are_trimmed_deduplicated_and_optional_in_saves() {
+        assert_eq!(parse_tags(" work, Decor,,work , WORK,morning "), ["work", "Decor", "morning"]);
+        assert!(parse_tags(" , ").is_empty());
+
+        // Saves from before tags load with none
+        let mut obj = DeskObject::new(1, ObjectType::Plant, Vec3::ZERO);
+        let mut json = serde_json::to_value(&obj).unwrap();
+        json.as_object_mut().unwrap().remove("tags");
+        assert!(serde_json::from_value::<DeskObject>(json).unwrap().tags.is_empty());
+
+        obj.tags = vec!["Decor ".into(), "".into(), "decor".into(), "Window".into()];
+        obj.sanitize();
+        assert_eq!(obj.tags, ["Decor", "Window"]);
+        assert!(obj.has_tag_matching("WIN"));
+        assert!(!obj.has_tag_matching("work"));
+    }
+}
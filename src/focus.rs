@@ -0,0 +1,84 @@
+//! Daily focus time tracking
+//!
+//! Time counts while the window is focused or an hourglass timer runs. Totals
+//! are kept per calendar day in `AppState::focus_stats`.
+
+use chrono::{Days, NaiveDate};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Focused time per local calendar day
+pub type FocusStats = HashMap<NaiveDate, Duration>;
+
+/// Add focused time to a day's total
+pub fn record_focus(stats: &mut FocusStats, day: NaiveDate, time: Duration) {
+    *stats.entry(day).or_default() += time;
+}
+
+/// Number of consecutive days with at least `minimum` focus time, ending
+/// today. A day that hasn't reached the minimum yet doesn't break the streak
+/// until it's over.
+pub fn focus_streak(stats: &FocusStats, today: NaiveDate, minimum: Duration) -> u32 {
+    let reached = |day: NaiveDate| stats.get(&day).is_some_and(|time| *time >= minimum);
+    let mut day = if reached(today) {
+        today
+    } else {
+        match today.checked_sub_days(Days::new(1)) {
+            Some(yesterday) => yesterday,
+            None => return 0,
+        }
+    };
+
+    let mut streak = 0;
+    while reached(day) {
+        streak += 1;
+        match day.checked_sub_days(Days::new(1)) {
+            Some(previous) => day = previous,
+            None => break,
+        }
+    }
+    streak
+}
+
+/// Short display of a duration, e.g. "1h 05m", "12m" or "40s"
+pub fn format_focus_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => format!("{}s", secs),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {:02}m", hours, minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn streak_counts_consecutive_days_reaching_the_minimum() {
+        let minimum = Duration::from_secs(600);
+        let mut stats = FocusStats::new();
+        record_focus(&mut stats, day(1), Duration::from_secs(900));
+        record_focus(&mut stats, day(3), Duration::from_secs(400));
+        record_focus(&mut stats, day(3), Duration::from_secs(400));
+        record_focus(&mut stats, day(4), Duration::from_secs(700));
+        record_focus(&mut stats, day(5), Duration::from_secs(60));
+
+        assert_eq!(stats[&day(3)], Duration::from_secs(800));
+        assert_eq!(focus_streak(&stats, day(4), minimum), 2);
+        // Today is still under the minimum, so yesterday's streak holds
+        assert_eq!(focus_streak(&stats, day(5), minimum), 2);
+        assert_eq!(focus_streak(&stats, day(6), minimum), 0);
+    }
+
+    #[test]
+    fn focus_time_formats_compactly() {
+        assert_eq!(format_focus_time(Duration::from_secs(40)), "40s");
+        assert_eq!(format_focus_time(Duration::from_secs(12 * 60 + 5)), "12m");
+        assert_eq!(format_focus_time(Duration::from_secs(3600 + 5 * 60)), "1h 05m");
+    }
+}
@@ -0,0 +1,296 @@
+//! Transform gizmo on the selected object
+//!
+//! A ring around the object turns it about the vertical axis, and arrows
+//! along X and Z move it along that axis. The gizmo is built at unit size
+//! around the origin and scaled each frame so it keeps the same size on
+//! screen; picking uses the same camera rays as objects do.
+
+use crate::mesh::{rotate_y, MeshData, Vertex};
+use glam::Vec3;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Ring radius in gizmo units
+const RING_RADIUS: f32 = 1.0;
+/// Radius of the ring's tube
+const RING_TUBE: f32 = 0.05;
+/// Where the arrow shafts start and end, measured from the center
+const ARROW_START: f32 = 0.15;
+const ARROW_END: f32 = 1.4;
+/// Half thickness of an arrow shaft
+const ARROW_SHAFT: f32 = 0.035;
+/// Length and base radius of an arrowhead
+const ARROW_HEAD_LENGTH: f32 = 0.3;
+const ARROW_HEAD_RADIUS: f32 = 0.1;
+/// How far from a handle (in gizmo units) a ray still grabs it
+const PICK_TOLERANCE: f32 = 0.12;
+
+const RING_COLOR: [f32; 4] = [0.92, 0.7, 0.03, 1.0];
+const X_COLOR: [f32; 4] = [0.94, 0.27, 0.27, 1.0];
+const Z_COLOR: [f32; 4] = [0.23, 0.51, 0.96, 1.0];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// Part of the gizmo that can be dragged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoHandle {
+    /// Turns the object about the vertical axis
+    Ring,
+    /// Moves the object along the world X axis
+    ArrowX,
+    /// Moves the object along the world Z axis
+    ArrowZ,
+}
+
+impl GizmoHandle {
+    /// Direction an arrow moves the object in (none for the ring)
+    fn axis(self) -> Option<Vec3> {
+        match self {
+            Self::Ring => None,
+            Self::ArrowX => Some(Vec3::X),
+            Self::ArrowZ => Some(Vec3::Z),
+        }
+    }
+}
+
+/// A gizmo drag in progress
+#[derive(Debug, Clone, Copy)]
+pub struct GizmoDrag {
+    pub object_id: u64,
+    pub handle: GizmoHandle,
+    /// Center of the gizmo (the object's position) when the drag started
+    pub center: Vec3,
+    /// Yaw of the object when the drag started
+    pub start_yaw: f32,
+    /// Ring angle or distance along the arrow's axis under the cursor at the start
+    pub start_value: f32,
+}
+
+impl GizmoDrag {
+    /// Where on its handle a ray points: the angle around the ring, or the
+    /// distance along the arrow's axis from the gizmo center
+    pub fn handle_value(handle: GizmoHandle, center: Vec3, ray_origin: Vec3, ray_direction: Vec3) -> Option<f32> {
+        match handle.axis() {
+            None => ring_angle(center, ray_origin, ray_direction),
+            Some(axis) => axis_position(center, axis, ray_origin, ray_direction),
+        }
+    }
+
+    /// Yaw the object should have now, when dragging the ring
+    pub fn yaw(&self, value: f32) -> f32 {
+        self.start_yaw + wrap_angle(value - self.start_value)
+    }
+
+    /// Position the object should have now, when dragging an arrow
+    pub fn position(&self, value: f32) -> Vec3 {
+        let axis = self.handle.axis().unwrap_or(Vec3::ZERO);
+        self.center + axis * (value - self.start_value)
+    }
+}
+
+/// Angle of the point where a ray crosses the horizontal plane through
+/// `center`, around it (0 along +Z, growing towards +X like yaw)
+fn ring_angle(center: Vec3, ray_origin: Vec3, ray_direction: Vec3) -> Option<f32> {
+    if ray_direction.y.abs() < 1e-4 {
+        return None;
+    }
+    let t = (center.y - ray_origin.y) / ray_direction.y;
+    let offset = ray_origin + ray_direction * t - center;
+    Some(offset.x.atan2(offset.z))
+}
+
+/// Distance along the line through `center` in direction `axis` to the point
+/// closest to a ray, unless they are (nearly) parallel
+fn axis_position(center: Vec3, axis: Vec3, ray_origin: Vec3, ray_direction: Vec3) -> Option<f32> {
+    let b = axis.dot(ray_direction);
+    let denominator = 1.0 - b * b;
+    if denominator < 1e-4 {
+        return None;
+    }
+    let w = center - ray_origin;
+    Some((b * w.dot(ray_direction) - w.dot(axis)) / denominator)
+}
+
+/// Angle wrapped into -π..π
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Distance from a ray to a segment, and how far along the ray the closest point is
+fn ray_segment_distance(ray_origin: Vec3, ray_direction: Vec3, start: Vec3, end: Vec3) -> (f32, f32) {
+    let axis = (end - start).normalize();
+    let length = (end - start).length();
+    let along = axis_position(start, axis, ray_origin, ray_direction)
+        .unwrap_or(0.0)
+        .clamp(0.0, length);
+    let point = start + axis * along;
+    let t = (point - ray_origin).dot(ray_direction).max(0.0);
+    ((ray_origin + ray_direction * t).distance(point), t)
+}
+
+/// Handle of a gizmo at `center` with the given size that a ray hits, the
+/// nearest if it hits more than one
+pub fn pick(center: Vec3, size: f32, ray_origin: Vec3, ray_direction: Vec3) -> Option<GizmoHandle> {
+    // Work in gizmo units, where the ring has radius 1
+    let origin = (ray_origin - center) / size;
+    let mut hits = Vec::new();
+
+    for handle in [GizmoHandle::ArrowX, GizmoHandle::ArrowZ] {
+        let axis = handle.axis().unwrap_or(Vec3::ZERO);
+        let end = ARROW_END + ARROW_HEAD_LENGTH;
+        let (distance, t) = ray_segment_distance(origin, ray_direction, axis * ARROW_START, axis * end);
+        if distance <= PICK_TOLERANCE {
+            hits.push((handle, t));
+        }
+    }
+
+    if ray_direction.y.abs() > 1e-4 {
+        let t = -origin.y / ray_direction.y;
+        let hit = origin + ray_direction * t;
+        if t > 0.0 && (hit.length() - RING_RADIUS).abs() <= PICK_TOLERANCE {
+            hits.push((GizmoHandle::Ring, t));
+        }
+    }
+
+    hits.into_iter().min_by(|a, b| a.1.total_cmp(&b.1)).map(|(handle, _)| handle)
+}
+
+/// Gizmo geometry at unit size, with the active handle (if any) highlighted
+pub fn create_gizmo_mesh(active: Option<GizmoHandle>) -> MeshData {
+    let color = |handle: GizmoHandle, color: [f32; 4]| if active == Some(handle) { ACTIVE_COLOR } else { color };
+    let mut mesh = create_ring(color(GizmoHandle::Ring, RING_COLOR));
+    mesh.merge(create_arrow(color(GizmoHandle::ArrowZ, Z_COLOR)));
+    let mut arrow_x = create_arrow(color(GizmoHandle::ArrowX, X_COLOR));
+    rotate_y(&mut arrow_x, FRAC_PI_2);
+    mesh.merge(arrow_x);
+    mesh
+}
+
+/// A flat torus lying in the horizontal plane
+fn create_ring(color: [f32; 4]) -> MeshData {
+    const SEGMENTS: u32 = 48;
+    const SIDES: u32 = 8;
+    let mut mesh = MeshData::new();
+    let vertex = |segment: u32, side: u32| {
+        let around = segment as f32 / SEGMENTS as f32 * 2.0 * PI;
+        let tube = side as f32 / SIDES as f32 * 2.0 * PI;
+        let radial = Vec3::new(around.sin(), 0.0, around.cos());
+        let normal = radial * tube.cos() + Vec3::Y * tube.sin();
+        Vertex {
+            position: (radial * RING_RADIUS + normal * RING_TUBE).to_array(),
+            normal: normal.to_array(),
+            color,
+        }
+    };
+    for segment in 0..SEGMENTS {
+        for side in 0..SIDES {
+            mesh.add_quad(
+                vertex(segment, side),
+                vertex(segment + 1, side),
+                vertex(segment + 1, side + 1),
+                vertex(segment, side + 1),
+            );
+        }
+    }
+    mesh
+}
+
+/// An arrow along +Z: a square shaft and a pointed head
+fn create_arrow(color: [f32; 4]) -> MeshData {
+    const HEAD_SIDES: u32 = 12;
+    let mut mesh = MeshData::new();
+    let vertex = |position: Vec3, normal: Vec3| Vertex {
+        position: position.to_array(),
+        normal: normal.to_array(),
+        color,
+    };
+
+    // Shaft: four long sides
+    let s = ARROW_SHAFT;
+    for (normal, a, b) in [
+        (Vec3::X, Vec3::new(s, -s, 0.0), Vec3::new(s, s, 0.0)),
+        (Vec3::Y, Vec3::new(s, s, 0.0), Vec3::new(-s, s, 0.0)),
+        (Vec3::NEG_X, Vec3::new(-s, s, 0.0), Vec3::new(-s, -s, 0.0)),
+        (Vec3::NEG_Y, Vec3::new(-s, -s, 0.0), Vec3::new(s, -s, 0.0)),
+    ] {
+        let (start, end) = (Vec3::Z * ARROW_START, Vec3::Z * ARROW_END);
+        mesh.add_quad(
+            vertex(a + start, normal),
+            vertex(b + start, normal),
+            vertex(b + end, normal),
+            vertex(a + end, normal),
+        );
+    }
+
+    // Head: a cone with its base at the end of the shaft
+    let tip = Vec3::Z * (ARROW_END + ARROW_HEAD_LENGTH);
+    let base = |side: u32| {
+        let angle = side as f32 / HEAD_SIDES as f32 * 2.0 * PI;
+        let radial = Vec3::new(angle.cos(), angle.sin(), 0.0);
+        (Vec3::Z * ARROW_END + radial * ARROW_HEAD_RADIUS, radial)
+    };
+    for side in 0..HEAD_SIDES {
+        let (p0, r0) = base(side);
+        let (p1, r1) = base(side + 1);
+        let slope = ARROW_HEAD_RADIUS / ARROW_HEAD_LENGTH;
+        let n0 = (r0 + Vec3::Z * slope).normalize();
+        let n1 = (r1 + Vec3::Z * slope).normalize();
+        mesh.add_triangle(vertex(p0, n0), vertex(p1, n1), vertex(tip, (n0 + n1).normalize()));
+        mesh.add_triangle(vertex(p1, Vec3::NEG_Z), vertex(p0, Vec3::NEG_Z), vertex(Vec3::Z * ARROW_END, Vec3::NEG_Z));
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ray straight down onto a point
+    fn from_above(point: Vec3) -> (Vec3, Vec3) {
+        (point + Vec3::Y * 10.0, Vec3::NEG_Y)
+    }
+
+    #[test]
+    fn rays_pick_the_handle_under_them() {
+        let center = Vec3::new(1.0, 0.8, -0.5);
+        let size = 0.5;
+        let pick_at = |offset: Vec3| {
+            let (origin, direction) = from_above(center + offset * size);
+            pick(center, size, origin, direction)
+        };
+        assert_eq!(pick_at(Vec3::new(0.0, 0.0, -1.0)), Some(GizmoHandle::Ring));
+        assert_eq!(pick_at(Vec3::new(0.7, 0.0, 0.0)), Some(GizmoHandle::ArrowX));
+        assert_eq!(pick_at(Vec3::new(0.0, 0.0, 1.6)), Some(GizmoHandle::ArrowZ));
+        assert_eq!(pick_at(Vec3::new(0.5, 0.0, -0.5)), None);
+        assert_eq!(pick_at(Vec3::new(-2.0, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn dragging_follows_the_cursor_around_the_ring_and_along_arrows() {
+        let center = Vec3::new(0.0, 0.8, 0.0);
+        let value = |handle, point: Vec3| {
+            let (origin, direction) = from_above(point);
+            GizmoDrag::handle_value(handle, center, origin, direction).unwrap()
+        };
+
+        // A quarter turn from +Z towards +X turns the object a quarter turn
+        let ring = GizmoDrag {
+            object_id: 1,
+            handle: GizmoHandle::Ring,
+            center,
+            start_yaw: 0.3,
+            start_value: value(GizmoHandle::Ring, Vec3::new(0.0, 0.8, 1.0)),
+        };
+        let turned = ring.yaw(value(GizmoHandle::Ring, Vec3::new(1.0, 0.8, 0.0)));
+        assert!((turned - (0.3 + FRAC_PI_2)).abs() < 1e-4);
+
+        // Moving the cursor diagonally moves the object only along the arrow
+        let arrow = GizmoDrag {
+            object_id: 1,
+            handle: GizmoHandle::ArrowX,
+            center,
+            start_yaw: 0.0,
+            start_value: value(GizmoHandle::ArrowX, Vec3::new(0.5, 0.8, 0.0)),
+        };
+        let moved = arrow.position(value(GizmoHandle::ArrowX, Vec3::new(1.25, 0.8, 0.4)));
+        assert!(moved.abs_diff_eq(Vec3::new(0.75, 0.8, 0.0), 1e-4));
+    }
+}
@@ -0,0 +1,107 @@
+//! Undo/redo history of the objects on the desk
+//!
+//! Keeps whole-desk snapshots: desks hold at most a few dozen small objects,
+//! so copying them is cheaper and far simpler than recording inverse edits.
+
+use crate::desk_object::DeskObject;
+
+/// Oldest snapshots are dropped beyond this many undo steps
+const MAX_UNDO_STEPS: usize = 50;
+
+/// Snapshots of the desk before and after the current one
+pub struct History {
+    undo: Vec<Vec<DeskObject>>,
+    redo: Vec<Vec<DeskObject>>,
+    current: Vec<DeskObject>,
+}
+
+impl History {
+    /// Start a history at the given desk
+    pub fn new(objects: &[DeskObject]) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: objects.to_vec(),
+        }
+    }
+
+    /// Record the desk after a change. Desks that are unchanged apart from
+    /// runtime-only state (e.g. a spawn animation) add no undo step.
+    pub fn record(&mut self, objects: &[DeskObject]) {
+        if same_desk(objects, &self.current) {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current, objects.to_vec());
+        self.undo.push(previous);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Step back, returning the desk to restore
+    pub fn undo(&mut self) -> Option<&[DeskObject]> {
+        let previous = self.undo.pop()?;
+        self.redo.push(std::mem::replace(&mut self.current, previous));
+        Some(&self.current)
+    }
+
+    /// Step forward again after an undo, returning the desk to restore
+    pub fn redo(&mut self) -> Option<&[DeskObject]> {
+        let next = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.current, next));
+        Some(&self.current)
+    }
+}
+
+/// Whether two desks hold the same objects as they would be saved
+fn same_desk(a: &[DeskObject], b: &[DeskObject]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| a.without_runtime_state() == b.without_runtime_state())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desk_object::ObjectType;
+    use glam::Vec3;
+
+    fn desk(count: u64) -> Vec<DeskObject> {
+        (1..=count)
+            .map(|id| DeskObject::new(id, ObjectType::Coffee, Vec3::ZERO))
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_through_snapshots() {
+        let mut history = History::new(&desk(0));
+        history.record(&desk(1));
+        history.record(&desk(2));
+        history.record(&desk(2));
+
+        assert_eq!(history.undo().map(<[_]>::len), Some(1));
+        assert_eq!(history.undo().map(<[_]>::len), Some(0));
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().map(<[_]>::len), Some(1));
+
+        // A new change discards the redo steps
+        history.record(&desk(3));
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().map(<[_]>::len), Some(1));
+    }
+
+    #[test]
+    fn animation_only_changes_add_no_undo_step() {
+        let mut history = History::new(&desk(1));
+        let mut animated = desk(1);
+        animated[0].start_spawn_animation();
+        animated[0].advance_spawn(0.01);
+        animated[0].start_alert(std::time::Instant::now());
+        animated[0].last_interacted = Some(std::time::Instant::now());
+        animated[0].is_dragging = true;
+        animated[0].target_y = 1.0;
+        history.record(&animated);
+
+        assert!(history.undo().is_none());
+    }
+}
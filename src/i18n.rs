@@ -0,0 +1,492 @@
+//! Translations of the UI text
+//!
+//! Every string the UI shows is a [`Key`], looked up in the table of the
+//! chosen [`Lang`]. The tables are written side by side in one list, so a key
+//! can't be added without a translation. Names of objects, themes, actions
+//! and present modes are English on their own types (logs and the console
+//! use them too), and translated here for the other languages.
+//!
+//! Console commands and output stay in English, since they are typed and
+//! copied into bug reports.
+
+use crate::config::{KeyAction, PresentMode};
+use crate::desk_object::{DeskObject, ObjectType};
+use crate::theme::UiTheme;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+
+/// Language of the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Lang {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Lang {
+    /// Every language, in the order shown in the settings
+    pub const ALL: [Lang; 2] = [Lang::English, Lang::Russian];
+
+    /// Name of the language in itself, as shown in the language selector
+    pub fn label(&self) -> &'static str {
+        match self {
+            Lang::English => "English",
+            Lang::Russian => "Русский",
+        }
+    }
+
+    /// The text for `key`
+    pub fn tr(self, key: Key) -> &'static str {
+        match self {
+            Lang::English => english(key),
+            Lang::Russian => russian(key),
+        }
+    }
+
+    /// The text for `key` with each `{}` replaced by the next of `args`
+    pub fn format(self, key: Key, args: &[&dyn fmt::Display]) -> String {
+        let mut args = args.iter();
+        let mut text = String::new();
+        for (i, part) in self.tr(key).split("{}").enumerate() {
+            if i > 0 {
+                if let Some(arg) = args.next() {
+                    let _ = write!(text, "{}", arg);
+                }
+            }
+            text.push_str(part);
+        }
+        text
+    }
+
+    /// A count of days, e.g. "3 days"
+    pub fn days(self, count: u32) -> String {
+        match self {
+            Lang::English if count == 1 => "1 day".to_string(),
+            Lang::English => format!("{} days", count),
+            // Russian picks one of three forms by the last digits
+            Lang::Russian => {
+                let form = match (count % 10, count % 100) {
+                    (1, n) if n != 11 => "день",
+                    (2..=4, n) if !(12..=14).contains(&n) => "дня",
+                    _ => "дней",
+                };
+                format!("{} {}", count, form)
+            }
+        }
+    }
+
+    /// Name of an object type
+    pub fn object_name(self, object_type: ObjectType) -> &'static str {
+        match self {
+            Lang::English => object_type.display_name(),
+            Lang::Russian => match object_type {
+                ObjectType::Clock => "Часы",
+                ObjectType::Lamp => "Настольная лампа",
+                ObjectType::Plant => "Растение в горшке",
+                ObjectType::Coffee => "Кофейная кружка",
+                ObjectType::Laptop => "Ноутбук",
+                ObjectType::Notebook => "Блокнот",
+                ObjectType::PenHolder => "Подставка для ручек",
+                ObjectType::Books => "Книги",
+                ObjectType::PhotoFrame => "Фоторамка",
+                ObjectType::Globe => "Глобус",
+                ObjectType::Trophy => "Кубок",
+                ObjectType::Hourglass => "Песочные часы",
+                ObjectType::Metronome => "Метроном",
+                ObjectType::Paper => "Лист бумаги",
+                ObjectType::Magazine => "Журнал",
+                // Registered types come with a single name
+                ObjectType::Custom(_) => object_type.display_name(),
+            },
+        }
+    }
+
+    /// The user-given name of an object, or its type's name
+    pub fn object_label(self, object: &DeskObject) -> &str {
+        object.name.as_deref().unwrap_or(self.object_name(object.object_type))
+    }
+
+    /// One-line description of an object type, for the palette
+    pub fn object_description(self, object_type: ObjectType) -> &'static str {
+        match self {
+            Lang::English => object_type.description(),
+            Lang::Russian => match object_type {
+                ObjectType::Clock => "Настольные часы с текущим временем",
+                ObjectType::Lamp => "Настольная лампа, чтобы осветить рабочее место",
+                ObjectType::Plant => "Растение в горшке, чтобы оживить стол",
+                ObjectType::Coffee => "Кружка кофе, чтобы не сбавлять темп",
+                ObjectType::Laptop => "Ноутбук для самой работы",
+                ObjectType::Notebook => "Блокнот для идей и списков дел",
+                ObjectType::PenHolder => "Стакан с ручками — всегда под рукой",
+                ObjectType::Books => "Стопка книг для справок и вдохновения",
+                ObjectType::PhotoFrame => "Фоторамка для снимка, который мотивирует",
+                ObjectType::Globe => "Вращающийся глобус для мечты о путешествиях",
+                ObjectType::Trophy => "Кубок в честь достигнутых целей",
+                ObjectType::Hourglass => "Песочные часы для коротких рывков концентрации",
+                ObjectType::Metronome => "Настоящий метроном — задайте темп и сосредоточьтесь",
+                ObjectType::Paper => "Отдельный лист для быстрых заметок",
+                ObjectType::Magazine => "Журнал, чтобы полистать в перерыве",
+                ObjectType::Custom(_) => object_type.description(),
+            },
+        }
+    }
+
+    /// Name of a UI color theme
+    pub fn theme_name(self, theme: UiTheme) -> &'static str {
+        match self {
+            Lang::English => theme.label(),
+            Lang::Russian => match theme {
+                UiTheme::Dark => "Тёмная",
+                UiTheme::Light => "Светлая",
+                UiTheme::HighContrast => "Высокий контраст",
+            },
+        }
+    }
+
+    /// Name of a remappable keyboard action
+    pub fn action_name(self, action: KeyAction) -> &'static str {
+        match self {
+            Lang::English => action.label(),
+            Lang::Russian => match action {
+                KeyAction::AddObject => "Добавить объект",
+                KeyAction::Delete => "Удалить объект",
+                KeyAction::Duplicate => "Дублировать объект",
+                KeyAction::ToggleMenu => "Показать/скрыть меню",
+                KeyAction::Screenshot => "Снимок экрана",
+                KeyAction::Undo => "Отменить",
+                KeyAction::Redo => "Повторить",
+                KeyAction::ToggleGrid => "Показать/скрыть сетку",
+                KeyAction::Copy => "Копировать объект",
+                KeyAction::Paste => "Вставить объекты",
+                KeyAction::ToggleConsole => "Показать/скрыть консоль",
+                KeyAction::FrameAll => "Показать всё",
+                KeyAction::ToggleCleanView => "Чистый вид",
+            },
+        }
+    }
+
+    /// Name of a vsync / present mode
+    pub fn present_mode_name(self, mode: PresentMode) -> &'static str {
+        match self {
+            Lang::English => mode.label(),
+            Lang::Russian => match mode {
+                PresentMode::AutoVsync => "Vsync (авто)",
+                PresentMode::Fifo => "Vsync (Fifo)",
+                PresentMode::Immediate => "Без ограничения (Immediate)",
+                PresentMode::Mailbox => "Низкая задержка (Mailbox)",
+            },
+        }
+    }
+}
+
+/// Declares [`Key`] and the table of each language from one list of
+/// `Key: "English", "Russian";` entries
+macro_rules! translations {
+    ($($key:ident: $english:literal, $russian:literal;)*) => {
+        /// A piece of UI text. `{}` in the text marks where
+        /// [`Lang::format`] puts its arguments.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        #[cfg(test)]
+        impl Key {
+            /// Every key, for checking the tables
+            const ALL: &'static [Key] = &[$(Key::$key,)*];
+        }
+
+        fn english(key: Key) -> &'static str {
+            match key {
+                $(Key::$key => $english,)*
+            }
+        }
+
+        fn russian(key: Key) -> &'static str {
+            match key {
+                $(Key::$key => $russian,)*
+            }
+        }
+    };
+}
+
+translations! {
+    // Palette
+    Palette: "Palette", "Палитра";
+    Favorites: "Favorites", "Избранное";
+    FavoriteHint: "Click to add, right-click to remove", "Щелчок — добавить, правый щелчок — убрать";
+    RemoveFavorite: "Remove favorite", "Убрать из избранного";
+    SearchObjects: "Search objects", "Поиск объектов";
+    AllCategories: "All", "Все";
+    Clocks: "Clocks", "Часы";
+    Lighting: "Lighting", "Освещение";
+    Writing: "Writing", "Письмо";
+    Books: "Books", "Книги";
+    Audio: "Audio", "Звук";
+    Trinkets: "Trinkets", "Безделушки";
+    Frames: "Frames", "Рамки";
+    Tech: "Tech", "Техника";
+    MoreObjects: "More", "Другое";
+    NoMatchingObjects: "No matching objects", "Нет подходящих объектов";
+    EnterAdds: "↵ Enter adds {}", "↵ Enter добавит: {}";
+
+    // Object list and recording
+    ObjectList: "Objects ({})", "Объекты ({})";
+    DeskEmpty: "The desk is empty", "Стол пуст";
+    FilterObjects: "Filter by name or tag", "Фильтр по имени или тегу";
+    Show: "Show", "Показать";
+    Hide: "Hide", "Скрыть";
+    Record: "Record", "Запись";
+    StopRecording: "Stop recording", "Остановить запись";
+    RecordHint: "Record object changes to replay later", "Записать изменения объектов, чтобы повторить их позже";
+    PlayLast: "Play last", "Повторить";
+    PlayLastHint: "Replay the most recent recording (Esc stops)", "Воспроизвести последнюю запись (Esc — остановить)";
+    StopPlayback: "Stop playback", "Остановить";
+
+    // View settings
+    View: "View", "Вид";
+    Isometric: "Isometric (orthographic)", "Изометрия (ортографическая)";
+    Turntable: "Turntable", "Вращение камеры";
+    TurntableHint: "The camera slowly orbits the desk; moving it by hand pauses this for {} s", "Камера медленно облетает стол; ручное управление приостанавливает это на {} с";
+    TurntableSpeed: "Speed", "Скорость";
+    IsometricHint: "Use a parallel projection instead of perspective", "Параллельная проекция вместо перспективы";
+    SnapRotation: "Snap rotation to 15°", "Поворот с шагом 15°";
+    SnapRotationHint: "Hold Ctrl while scrolling to rotate freely", "Удерживайте Ctrl при прокрутке для свободного поворота";
+    SnapToEdges: "Snap to desk edges", "Прилипание к краям стола";
+    SnapToEdgesHint: "Dragged objects line up against a nearby edge; hold Ctrl to place freely", "Перетаскиваемые объекты прижимаются к ближнему краю; удерживайте Ctrl для свободной установки";
+    ShowGizmo: "Transform gizmo", "Гизмо перемещения";
+    PaletteDrag: "Drag from palette", "Перетаскивание из палитры";
+    PaletteDragHint: "Drag palette items onto the desk and let go to place them; a click adds one at a random spot", "Перетащите предмет из палитры на стол и отпустите, чтобы поставить; щелчок добавляет его в случайное место";
+    SpawnAtAim: "Add where the camera looks", "Добавлять туда, куда смотрит камера";
+    SpawnAtAimHint: "New objects ({} or a palette click) appear on the desk at the middle of the view instead of at a random spot", "Новые объекты ({} или щелчок в палитре) появляются на столе в центре вида, а не в случайном месте";
+    ShowGizmoHint: "The selected object gets a ring to turn it and arrows to move it along X or Z", "У выбранного объекта появляются кольцо для поворота и стрелки для сдвига по X или Z";
+    ShowGrid: "Show grid and rulers", "Сетка и линейки";
+    ShowGridHint: "Lines every {} units ({})", "Линии через каждые {} ед. ({})";
+    IdleMotion: "Idle motion", "Фоновое движение";
+    Off: "Off", "Выкл.";
+    IdleMotionHint: "Objects gently bob and sway; Off for reduced motion", "Объекты слегка покачиваются; «Выкл.» — меньше движения";
+    Bloom: "Bloom", "Свечение";
+    BloomHint: "Bright lamps and screens softly bleed light", "Яркие лампы и экраны мягко светятся";
+    PixelSize: "Pixel size", "Размер пикселя";
+    PixelSizeHint: "Draw the scene at a lower resolution and enlarge it with sharp pixels", "Рисовать сцену в меньшем разрешении и увеличивать её с чёткими пикселями";
+    OffInLowPower: "Off while low power mode is on", "Выключено в режиме энергосбережения";
+    LowPower: "Low power / reduced motion", "Энергосбережение / меньше движения";
+    LowPowerHint: "No idle motion, turntable, shadows or bloom, vsync and at most {} FPS", "Без фонового движения, вращения камеры, теней и свечения, vsync и не более {} FPS";
+    PresentMode: "Present mode", "Режим вывода";
+    NotSupported: "Not supported by this display", "Не поддерживается этим дисплеем";
+    FpsCap: "Frame rate cap", "Ограничение FPS";
+    FpsCapHint: "Draw at most this many frames per second, even on a fast display, to save power", "Рисовать не больше стольких кадров в секунду, даже на быстром дисплее, для экономии энергии";
+    Unlimited: "Unlimited", "Без ограничения";
+    PresentModeHint: "Vsync saves power; Immediate and Mailbox lower input latency", "Vsync экономит энергию; Immediate и Mailbox снижают задержку ввода";
+    PresentModeLowPower: "Vsync (Fifo) while low power mode is on", "Vsync (Fifo) в режиме энергосбережения";
+    UiScale: "UI scale", "Масштаб интерфейса";
+    Theme: "Theme", "Тема";
+    Language: "Language", "Язык";
+    Keybindings: "Keybindings", "Клавиши";
+    NewKeyPrompt: "Press a key…", "Нажмите клавишу…";
+    KeybindingHint: "Click, then press the new key (Esc cancels)", "Щёлкните и нажмите новую клавишу (Esc — отмена)";
+    ResetDefaults: "Reset to defaults", "Сбросить настройки";
+    Scroll: "Scroll", "Прокрутка";
+    ScrollRotate: "Rotate", "Поворот";
+    ScrollScale: "Scale (Shift)", "Масштаб (Shift)";
+    ScrollZoom: "Camera zoom", "Зум камеры";
+    InvertScroll: "Invert scroll direction", "Обратное направление прокрутки";
+
+    // Workspaces
+    WorkspaceNumber: "Desk {}", "Стол {}";
+    WorkspaceName: "Workspace name", "Название стола";
+    Rename: "Rename", "Переименовать";
+    DeleteWorkspace: "Delete workspace", "Удалить стол";
+    DeleteWorkspaceHint: "Deletes the workspace and every object on it", "Удаляет стол вместе со всеми объектами на нём";
+    ConfirmDeleteWorkspace: "Click again to delete", "Нажмите ещё раз, чтобы удалить";
+    AddWorkspace: "Add a workspace with an empty desk", "Добавить стол с пустой столешницей";
+    WorkspaceCameras: "Camera view per workspace", "Свой вид камеры у каждого стола";
+    WorkspaceCamerasHint: "Each workspace remembers where the camera was when you left it; otherwise all share one view", "Каждый стол запоминает положение камеры, когда вы с него уходите; иначе вид у всех общий";
+
+    // Layouts, shuffle and clearing
+    Layouts: "Layouts", "Раскладки";
+    LayoutName: "Layout name", "Название раскладки";
+    Save: "Save", "Сохранить";
+    Sort: "Sort:", "Порядок:";
+    SortRecent: "Recent", "Недавние";
+    SortName: "Name", "Имя";
+    LayoutSummary: "{} objects · {}", "Объектов: {} · {}";
+    LayoutDateFormat: "%b %-d, %H:%M", "%d.%m, %H:%M";
+    LoadLayoutHint: "Load this layout\nCreated {}", "Загрузить раскладку\nСоздана {}";
+    LoadLayoutVersionHint: "Load this layout\nCreated {} (v{})", "Загрузить раскладку\nСоздана {} (v{})";
+    ShuffleSeed: "Seed (optional)", "Зерно (необязательно)";
+    Shuffle: "Shuffle", "Перемешать";
+    ShuffleHint: "Replace unlocked objects with a random arrangement", "Заменить незакреплённые объекты случайной расстановкой";
+    Arrange: "Arrange", "Упорядочить";
+    ArrangeHint: "Line up unlocked objects in a tidy grid, grouped by type", "Выстроить незакреплённые объекты в сетку по типам";
+    ClearAll: "Clear All Objects", "Убрать все объекты";
+    KeepLocked: "Keep locked objects", "Оставить закреплённые";
+
+    // Controls help
+    Controls: "Controls:", "Управление:";
+    ControlMove: "• Click+Drag to move", "• Перетаскивание — переместить";
+    ControlRotate: "• Scroll to rotate (Ctrl: no snap)", "• Прокрутка — повернуть (Ctrl: без шага)";
+    ControlScale: "• Shift+Scroll to scale", "• Shift+прокрутка — масштаб";
+    ControlPan: "• Middle-drag to pan", "• Средняя кнопка — сдвинуть вид";
+    ControlCustomize: "• Right-click to customize", "• Правый щелчок — настроить";
+    ControlDelete: "• Delete to remove", "• Delete — удалить";
+
+    // Customization panel
+    Customize: "Customize {}", "Настройка: {}";
+    Object: "Object", "Объект";
+    Name: "NAME", "НАЗВАНИЕ";
+    Tags: "TAGS", "ТЕГИ";
+    TagsHint: "Comma-separated, e.g. work, decor", "Через запятую, например: работа, декор";
+    MainColor: "MAIN COLOR", "ОСНОВНОЙ ЦВЕТ";
+    MixedColors: "MIXED", "РАЗНЫЕ";
+    EditingObjects: "Editing {} objects: colors apply to all of them", "Редактируется объектов: {}; цвета применяются ко всем";
+    MultiSelectHint: "Ctrl+click to add to or remove from the selection", "Ctrl+щелчок добавляет в выделение или убирает из него";
+    AccentColor: "ACCENT COLOR", "ЦВЕТ АКЦЕНТА";
+    RecentlyUsed: "Recently used", "Недавние";
+    PickAnyColor: "Pick any color", "Выбрать любой цвет";
+    Transform: "TRANSFORM", "ТРАНСФОРМАЦИЯ";
+    Position: "Position", "Позиция";
+    Rotation: "Rotation", "Поворот";
+    Scale: "Scale", "Масштаб";
+    BooksInStack: "BOOKS IN STACK", "КНИГ В СТОПКЕ";
+    LightRadius: "LIGHT RADIUS", "РАДИУС СВЕТА";
+    LightRadiusHint: "How far the lamp's light reaches; the ring on the desk shows where it ends", "Как далеко достаёт свет лампы; кольцо на столе показывает, где он заканчивается";
+    AnimSpeed: "ANIMATION SPEED", "СКОРОСТЬ АНИМАЦИИ";
+    AnimSpeedHint: "How fast this object bobs and sways when idle motion is on (View settings); 0 keeps it still", "Как быстро объект покачивается при включённом фоновом движении (настройки вида); 0 — неподвижно";
+    CoverImage: "COVER IMAGE", "ОБЛОЖКА";
+    Photo: "PHOTO", "ФОТО";
+    ScreenImage: "SCREEN IMAGE", "ИЗОБРАЖЕНИЕ НА ЭКРАНЕ";
+    ImagePathHint: "Path to a PNG or JPEG", "Путь к PNG или JPEG";
+    DropImageHint: "Or drop an image file onto the object", "Или перетащите файл изображения на объект";
+    SetImage: "Set Image", "Задать изображение";
+    BuiltinImages: "Or pick one:", "Или выберите готовое:";
+    UseDefault: "Use Default", "По умолчанию";
+    Remove: "Remove", "Убрать";
+    Locked: "Locked", "Закреплён";
+    LockedHint: "Prevent moving, rotating, scaling and deleting", "Запретить перемещение, поворот, масштаб и удаление";
+    SaveTemplate: "Save as template", "Сохранить как шаблон";
+    SaveTemplateHint: "Add this object, as customized, to the palette's favorites", "Добавить объект с текущими настройками в избранное палитры";
+    DeleteObject: "Delete Object", "Удалить объект";
+    DeleteSelected: "Delete {} Selected", "Удалить выбранные ({})";
+    ConfirmDeleteSelected: "Click again to delete {} objects", "Нажмите ещё раз, чтобы удалить {} объектов";
+    DeleteSelectedHint: "Removes every selected object except locked ones, in one undo step", "Убирает все выбранные объекты, кроме закреплённых, одним шагом отмены";
+    KeptLockedObjects: "Kept {} locked objects", "Закреплённые объекты оставлены: {}";
+
+    // Color presets
+    Red: "Red", "Красный";
+    Orange: "Orange", "Оранжевый";
+    Yellow: "Yellow", "Жёлтый";
+    Green: "Green", "Зелёный";
+    Blue: "Blue", "Синий";
+    Purple: "Purple", "Фиолетовый";
+    Pink: "Pink", "Розовый";
+    White: "White", "Белый";
+    Gray: "Gray", "Серый";
+    Dark: "Dark", "Тёмный";
+    Amber: "Amber", "Янтарный";
+    Lime: "Lime", "Лаймовый";
+    Teal: "Teal", "Бирюзовый";
+    LightBlue: "Light Blue", "Голубой";
+    Lavender: "Lavender", "Лавандовый";
+    Rose: "Rose", "Роза";
+    Peach: "Peach", "Персиковый";
+    Silver: "Silver", "Серебристый";
+    Black: "Black", "Чёрный";
+    Cream: "Cream", "Кремовый";
+
+    // Built-in images
+    Sunset: "Sunset", "Закат";
+    Mountains: "Mountains", "Горы";
+    Forest: "Forest", "Лес";
+    NightSky: "Night sky", "Ночное небо";
+    Beach: "Beach", "Пляж";
+    AbstractArt: "Abstract", "Абстракция";
+
+    // Context menu
+    MenuCustomize: "Customize", "Настроить";
+    Duplicate: "Duplicate", "Дублировать";
+    Focus: "Focus", "Фокус";
+    Use: "Use", "Использовать";
+    StartTimer: "Start timer", "Запустить таймер";
+    StopTimer: "Stop timer", "Остановить таймер";
+    BringToFront: "Bring to Front", "На передний план";
+    SendToBack: "Send to Back", "На задний план";
+    MirrorLeftRight: "Mirror Left/Right", "Отразить слева направо";
+    MirrorFrontBack: "Mirror Front/Back", "Отразить спереди назад";
+    Lock: "Lock", "Закрепить";
+    Unlock: "Unlock", "Открепить";
+    Delete: "Delete", "Удалить";
+
+    // Debug overlay
+    DebugFps: "FPS: {}", "FPS: {}";
+    DebugObjects: "Objects: {}/{}", "Объекты: {}/{}";
+
+    // Focus widget
+    FocusToday: "Focus time today", "Время концентрации сегодня";
+
+    // Notifications
+    Welcome: "Welcome! Open the ☰ menu (top left) to add more objects and change settings", "Добро пожаловать! Откройте меню ☰ (слева вверху), чтобы добавить объекты и изменить настройки";
+    DeskFull: "Desk is full ({} objects max)", "Стол заполнен (не больше {} объектов)";
+    TimerFinished: "⏳ {} finished", "⏳ {}: время вышло";
+    StopPlaybackFirst: "Stop the playback first", "Сначала остановите воспроизведение";
+    UnlockBeforeDeleting: "Unlock the object before deleting it", "Открепите объект, чтобы удалить его";
+    SavedToFavorites: "Saved {} to favorites", "{} — в избранном";
+    ArrangeLeftover: "{} objects didn't fit and were left in place", "Не поместилось объектов: {}, они остались на месте";
+    CleanViewHint: "Clean view: press {} to show the interface again", "Чистый вид: нажмите {}, чтобы вернуть интерфейс";
+    WireframeUnsupported: "Wireframe not supported by this GPU; showing collision shapes only", "Каркасный режим не поддерживается этой видеокартой; показаны только формы столкновений";
+    OnlyImagesDrop: "Only PNG and JPEG images can be dropped", "Можно перетащить только изображения PNG и JPEG";
+    DropOntoImageObject: "Drop images onto a Photo Frame, Laptop or Magazine", "Перетащите изображение на фоторамку, ноутбук или журнал";
+    ImageUnreadable: "Could not read the image: {}", "Не удалось прочитать изображение: {}";
+    ImageScaledDown: "Image is {} px; it will be shown at up to {} px", "Изображение {} пикс.; оно будет показано размером до {} пикс.";
+    SelectToCopy: "Select an object to copy", "Выберите объект, чтобы скопировать его";
+    Copied: "Copied {}", "Скопировано: {}";
+    NothingToPaste: "Nothing to paste", "Нечего вставить";
+    Shuffled: "Shuffled desk (seed {})", "Стол перемешан (зерно {})";
+    SavedLayout: "Saved layout \"{}\"", "Раскладка «{}» сохранена";
+    SaveLayoutFailed: "Could not save layout: {}", "Не удалось сохранить раскладку: {}";
+    LoadLayoutFailed: "Could not load layout: {}", "Не удалось загрузить раскладку: {}";
+    LoadedLayout: "Loaded layout \"{}\"", "Раскладка «{}» загружена";
+    SwitchedWorkspace: "Switched to {}", "Открыто: {}";
+    LoadedLayoutPartly: "Loaded layout \"{}\"; anything this version of the app doesn't support was left out", "Раскладка «{}» загружена; то, что эта версия приложения не поддерживает, пропущено";
+    StateReadOnly: "Your desk has things this version of the app doesn't support (it was saved by a newer version, or uses object types that aren't available). It won't be saved over, so nothing is lost", "На столе есть то, что эта версия приложения не поддерживает (он сохранён более новой версией или использует недоступные типы объектов). Он не будет перезаписан, чтобы ничего не потерялось";
+    NothingToUndo: "Nothing to undo", "Нечего отменять";
+    NothingToRedo: "Nothing to redo", "Нечего повторять";
+    RecordingStarted: "Recording started", "Запись начата";
+    RecordingStopped: "Recording stopped: {}", "Запись остановлена: {}";
+    RecordingSaved: "Recording saved to {}", "Запись сохранена в {}";
+    SaveRecordingFailed: "Could not save recording: {}", "Не удалось сохранить запись: {}";
+    StartRecordingFailed: "Could not start recording: {}", "Не удалось начать запись: {}";
+    NoRecordingFolder: "No folder available for recordings", "Нет папки для записей";
+    NoRecordings: "No recordings yet", "Записей пока нет";
+    LoadRecordingFailed: "Could not load recording: {}", "Не удалось загрузить запись: {}";
+    PlaybackFinished: "Playback finished", "Воспроизведение завершено";
+    NoScreenshotFolder: "No folder available for screenshots", "Нет папки для снимков экрана";
+    ScreenshotSaved: "Screenshot saved to {}", "Снимок экрана сохранён в {}";
+    ScreenshotFailed: "Could not save screenshot: {}", "Не удалось сохранить снимок экрана: {}";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_translation_is_filled_in_with_the_same_placeholders() {
+        for &key in Key::ALL {
+            let placeholders = |lang: Lang| lang.tr(key).matches("{}").count();
+            for lang in Lang::ALL {
+                assert!(!lang.tr(key).trim().is_empty(), "{:?} is empty in {:?}", key, lang);
+                assert_eq!(placeholders(lang), placeholders(Lang::English), "{:?} in {:?}", key, lang);
+            }
+        }
+        assert_eq!(Lang::Russian.format(Key::DeskFull, &[&50]), "Стол заполнен (не больше 50 объектов)");
+        assert_eq!(Lang::English.format(Key::ShowGridHint, &[&0.5, &"G"]), "Lines every 0.5 units (G)");
+    }
+
+    #[test]
+    fn russian_day_counts_use_the_right_plural() {
+        let days: Vec<String> = [1, 2, 5, 11, 21, 22, 112].into_iter().map(|n| Lang::Russian.days(n)).collect();
+        assert_eq!(days, ["1 день", "2 дня", "5 дней", "11 дней", "21 день", "22 дня", "112 дней"]);
+        assert_eq!(Lang::English.days(1), "1 day");
+        assert_eq!(Lang::English.days(3), "3 days");
+    }
+}
@@ -0,0 +1,52 @@
+//! Focus Desktop Simulator - A high-performance desktop simulator
+//!
+//! A Rust implementation of the Focus Desktop Simulator with an isometric 3D desk
+//! and interactive objects. Uses wgpu for GPU rendering and egui for UI.
+//!
+//! The crate can be embedded in another winit application. The public API is:
+//!
+//! - [`App`]: the simulator for one window (create with [`App::new`] or
+//!   [`App::from_surface`], then forward events and call `update`/`render`)
+//! - [`AppState`]: the persisted desk layout and preferences
+//! - [`DeskObject`] and [`ObjectType`]: the objects on the desk
+//! - [`registry`]: object types added at runtime, built from [`MeshData`]
+//!   (made of [`Vertex`]es, e.g. with [`create_box`], [`create_cylinder`] or
+//!   [`create_sphere`]) and a [`CollisionShape`]
+//! - [`config`]: compile-time configuration ([`CONFIG`])
+//! - [`render_headless`]: render a layout to an image without a window
+//!
+//! Everything else (the rest of the mesh builders and physics, rendering, UI
+//! widgets) is internal and may change.
+
+mod animation;
+mod app;
+mod arrange;
+mod bloom;
+mod camera;
+mod clipboard;
+mod console;
+pub mod config;
+pub mod desk_object;
+mod focus;
+mod gizmo;
+mod history;
+mod i18n;
+mod mesh;
+mod physics;
+mod recording;
+pub mod registry;
+mod renderer;
+pub mod state;
+mod shuffle;
+mod texture;
+mod theme;
+mod ui;
+mod upscale;
+
+pub use app::App;
+pub use config::CONFIG;
+pub use desk_object::{DeskObject, ObjectType};
+pub use mesh::{create_box, create_cylinder, create_sphere, MeshData, Vertex};
+pub use physics::CollisionShape;
+pub use renderer::render_headless;
+pub use state::AppState;
@@ -3,9 +3,14 @@
 //! Creates 3D meshes for each object type with proper geometry.
 
 use crate::config::hex_to_rgb;
-use crate::desk_object::ObjectType;
+use crate::desk_object::{DeskObject, ObjectType, BOOK_COUNT_RANGE};
+use crate::physics::CollisionShape;
+use glam::{Quat, Vec3};
 use std::f32::consts::PI;
 
+/// Segments per circle in collision wireframes
+const WIREFRAME_SEGMENTS: u32 = 16;
+
 /// Vertex data structure for 3D rendering
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -31,7 +36,173 @@ impl Vertex {
     }
 }
 
+/// Vertex with texture coordinates for image-mapped surfaces
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TexturedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl TexturedVertex {
+    pub const ATTRIBS: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x2,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TexturedVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Create an upward-facing textured quad centered on the origin
+pub fn create_textured_quad(width: f32, depth: f32, y: f32) -> (Vec<TexturedVertex>, Vec<u16>) {
+    let hw = width / 2.0;
+    let hd = depth / 2.0;
+    let normal = [0.0, 1.0, 0.0];
+    let vertices = vec![
+        TexturedVertex { position: [-hw, y, hd], normal, uv: [0.0, 1.0] },
+        TexturedVertex { position: [hw, y, hd], normal, uv: [1.0, 1.0] },
+        TexturedVertex { position: [hw, y, -hd], normal, uv: [1.0, 0.0] },
+        TexturedVertex { position: [-hw, y, -hd], normal, uv: [0.0, 0.0] },
+    ];
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+/// Offsets of grid lines spaced `spacing` apart through the origin, within
+/// `-half_extent..=half_extent`
+pub fn grid_line_offsets(half_extent: f32, spacing: f32) -> Vec<f32> {
+    if spacing.is_nan() || spacing <= 0.0 || !half_extent.is_finite() {
+        return Vec::new();
+    }
+    let steps = (half_extent / spacing + 1e-4).floor() as i32;
+    (-steps..=steps).map(|i| i as f32 * spacing).collect()
+}
+
+/// Create flat grid lines covering a `width` x `depth` area at height `y`
+pub fn create_grid_mesh(
+    width: f32,
+    depth: f32,
+    y: f32,
+    spacing: f32,
+    line_width: f32,
+    color: [f32; 4],
+) -> MeshData {
+    let hw = width / 2.0;
+    let hd = depth / 2.0;
+    let lw = line_width / 2.0;
+    let normal = [0.0, 1.0, 0.0];
+    let vertex = |x: f32, z: f32| Vertex { position: [x, y, z], normal, color };
+
+    let mut mesh = MeshData::new();
+    // Lines along the depth (constant x), then along the width (constant z),
+    // wound counter-clockwise seen from above
+    for x in grid_line_offsets(hw, spacing) {
+        mesh.add_quad(vertex(x - lw, hd), vertex(x + lw, hd), vertex(x + lw, -hd), vertex(x - lw, -hd));
+    }
+    for z in grid_line_offsets(hd, spacing) {
+        mesh.add_quad(vertex(-hw, z + lw), vertex(hw, z + lw), vertex(hw, z - lw), vertex(-hw, z - lw));
+    }
+    mesh
+}
+
+/// Create a flat ring `line_width` wide around `center`, lying in the
+/// horizontal plane through it and facing up
+pub fn create_ring_mesh(center: Vec3, radius: f32, line_width: f32, segments: u32, color: [f32; 4]) -> MeshData {
+    let normal = [0.0, 1.0, 0.0];
+    let (inner, outer) = ((radius - line_width / 2.0).max(0.0), radius + line_width / 2.0);
+    let vertex = |r: f32, angle: f32| Vertex {
+        position: [center.x + r * angle.cos(), center.y, center.z + r * angle.sin()],
+        normal,
+        color,
+    };
+
+    let mut mesh = MeshData::new();
+    for i in 0..segments {
+        // Negative angles keep the quads counter-clockwise seen from above
+        let a0 = -(i as f32) / segments as f32 * std::f32::consts::TAU;
+        let a1 = -((i + 1) as f32) / segments as f32 * std::f32::consts::TAU;
+        mesh.add_quad(vertex(inner, a0), vertex(outer, a0), vertex(outer, a1), vertex(inner, a1));
+    }
+    mesh
+}
+
+/// Create the walls of a `width` x `depth` room, `height` tall, standing on
+/// the floor around the origin.
+///
+/// The walls face inward, so with back-face culling any wall between an outside
+/// camera and the room is skipped and orbiting always shows an open room corner.
+pub fn create_room_mesh(width: f32, depth: f32, height: f32, color: [f32; 4]) -> MeshData {
+    let hw = width / 2.0;
+    let hd = depth / 2.0;
+    // Bottom corners of each wall, left to right as seen from inside the room
+    let walls = [
+        ([0.0, 0.0, 1.0], [-hw, -hd], [hw, -hd]),
+        ([-1.0, 0.0, 0.0], [hw, -hd], [hw, hd]),
+        ([0.0, 0.0, -1.0], [hw, hd], [-hw, hd]),
+        ([1.0, 0.0, 0.0], [-hw, hd], [-hw, -hd]),
+    ];
+
+    let mut mesh = MeshData::new();
+    for (normal, [x0, z0], [x1, z1]) in walls {
+        let vertex = |x: f32, y: f32, z: f32| Vertex { position: [x, y, z], normal, color };
+        mesh.add_quad(
+            vertex(x0, 0.0, z0),
+            vertex(x1, 0.0, z1),
+            vertex(x1, height, z1),
+            vertex(x0, height, z0),
+        );
+    }
+    mesh
+}
+
+/// Create the textured quad an object type shows its image on, matching
+/// the surface in its mesh: a magazine's cover, the photo in a photo frame
+/// or a laptop's display. `None` for types without an image surface.
+pub fn create_image_quad(object_type: ObjectType) -> Option<(Vec<TexturedVertex>, Vec<u16>)> {
+    match object_type {
+        ObjectType::Magazine => Some(create_textured_quad(0.21, 0.29, 0.0105)),
+        // Just in front of the photo box in `create_photo_frame`
+        ObjectType::PhotoFrame => Some(create_upright_quad(0.16, 0.21, 0.02, 0.013)),
+        ObjectType::Laptop => {
+            // Just in front of the display box, tilted back with it as in `create_laptop`
+            let (mut vertices, indices) = create_upright_quad(0.34, 0.20, 0.0, 0.003);
+            for v in &mut vertices {
+                let [_, y, z] = v.position;
+                v.position[1] = y * 0.34 + z * 0.94 + 0.045;
+                v.position[2] = -y * 0.94 + z * 0.34 - 0.13;
+                let [_, ny, nz] = v.normal;
+                v.normal = [0.0, ny * 0.34 + nz * 0.94, -ny * 0.94 + nz * 0.34];
+            }
+            Some((vertices, indices))
+        }
+        _ => None,
+    }
+}
+
+/// Create a textured quad facing +Z, `width` x `height` with its bottom edge
+/// at `y_bottom`, `z` in front of the origin
+fn create_upright_quad(width: f32, height: f32, y_bottom: f32, z: f32) -> (Vec<TexturedVertex>, Vec<u16>) {
+    let hw = width / 2.0;
+    let top = y_bottom + height;
+    let normal = [0.0, 0.0, 1.0];
+    let vertices = vec![
+        TexturedVertex { position: [-hw, y_bottom, z], normal, uv: [0.0, 1.0] },
+        TexturedVertex { position: [hw, y_bottom, z], normal, uv: [1.0, 1.0] },
+        TexturedVertex { position: [hw, top, z], normal, uv: [1.0, 0.0] },
+        TexturedVertex { position: [-hw, top, z], normal, uv: [0.0, 0.0] },
+    ];
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
 /// Mesh data containing vertices and indices
+#[derive(Default)]
 pub struct MeshData {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u16>,
@@ -62,6 +233,13 @@ impl MeshData {
             .extend_from_slice(&[base, base + 1, base + 2]);
     }
 
+    /// Add a line segment (for meshes drawn with a line-list topology)
+    pub fn add_line(&mut self, v0: Vertex, v1: Vertex) {
+        let base = self.vertices.len() as u16;
+        self.vertices.extend_from_slice(&[v0, v1]);
+        self.indices.extend_from_slice(&[base, base + 1]);
+    }
+
     /// Merge another mesh into this one
     pub fn merge(&mut self, other: MeshData) {
         let base = self.vertices.len() as u16;
@@ -71,6 +249,84 @@ impl MeshData {
     }
 }
 
+/// Rotate all vertex positions and normals of a mesh around the Y axis
+pub fn rotate_y(mesh: &mut MeshData, angle: f32) {
+    let (sin, cos) = angle.sin_cos();
+    for v in &mut mesh.vertices {
+        let [x, y, z] = v.position;
+        v.position = [x * cos + z * sin, y, -x * sin + z * cos];
+        let [nx, ny, nz] = v.normal;
+        v.normal = [nx * cos + nz * sin, ny, -nx * sin + nz * cos];
+    }
+}
+
+/// Append a line-list wireframe of a placed collision shape in world space
+pub fn add_collision_wireframe(
+    mesh: &mut MeshData,
+    shape: &CollisionShape,
+    position: Vec3,
+    rotation: Quat,
+    color: [f32; 4],
+) {
+    // Line segments in object-local space
+    let mut segments: Vec<(Vec3, Vec3)> = Vec::new();
+    let mut circle = |center: Vec3, axis_a: Vec3, axis_b: Vec3, radius: f32| {
+        let point = |i: u32| {
+            let angle = i as f32 / WIREFRAME_SEGMENTS as f32 * PI * 2.0;
+            center + (axis_a * angle.cos() + axis_b * angle.sin()) * radius
+        };
+        for i in 0..WIREFRAME_SEGMENTS {
+            segments.push((point(i), point(i + 1)));
+        }
+    };
+
+    match *shape {
+        CollisionShape::Sphere { radius } => {
+            let center = Vec3::new(0.0, radius, 0.0);
+            circle(center, Vec3::X, Vec3::Z, radius);
+            circle(center, Vec3::X, Vec3::Y, radius);
+            circle(center, Vec3::Z, Vec3::Y, radius);
+        }
+        CollisionShape::Cylinder { radius, height } => {
+            circle(Vec3::ZERO, Vec3::X, Vec3::Z, radius);
+            circle(Vec3::new(0.0, height, 0.0), Vec3::X, Vec3::Z, radius);
+            for (x, z) in [(radius, 0.0), (-radius, 0.0), (0.0, radius), (0.0, -radius)] {
+                segments.push((Vec3::new(x, 0.0, z), Vec3::new(x, height, z)));
+            }
+        }
+        CollisionShape::Box { half_extents } => {
+            let (hx, hz, top) = (half_extents.x, half_extents.z, half_extents.y * 2.0);
+            let corners = [(-hx, -hz), (hx, -hz), (hx, hz), (-hx, hz)];
+            for i in 0..4 {
+                let (x0, z0) = corners[i];
+                let (x1, z1) = corners[(i + 1) % 4];
+                segments.push((Vec3::new(x0, 0.0, z0), Vec3::new(x1, 0.0, z1)));
+                segments.push((Vec3::new(x0, top, z0), Vec3::new(x1, top, z1)));
+                segments.push((Vec3::new(x0, 0.0, z0), Vec3::new(x0, top, z0)));
+            }
+        }
+    }
+
+    let vertex = |local: Vec3| Vertex {
+        position: (position + rotation * local).to_array(),
+        normal: [0.0, 1.0, 0.0],
+        color,
+    };
+    for (start, end) in segments {
+        // Indices are 16-bit; drop lines rather than wrap around
+        if mesh.vertices.len() + 2 > u16::MAX as usize {
+            return;
+        }
+        mesh.add_line(vertex(start), vertex(end));
+    }
+}
+
+/// Deterministic pseudo-random value in [-1, 1] for procedural variation
+fn variation(seed: u32) -> f32 {
+    let x = (seed as f32 * 12.9898).sin() * 43758.547;
+    (x - x.floor()) * 2.0 - 1.0
+}
+
 /// Create a cylinder mesh (open at top for objects like mugs)
 pub fn create_cylinder(
     radius: f32,
@@ -610,18 +866,41 @@ pub fn create_pen_holder(main_color: u32, accent_color: u32) -> MeshData {
 }
 
 /// Create a books mesh (stack of books)
-pub fn create_books(main_color: u32, accent_color: u32) -> MeshData {
+pub fn create_books(main_color: u32, accent_color: u32, book_count: u32) -> MeshData {
     let mut mesh = MeshData::new();
 
     let (r, g, b) = hex_to_rgb(main_color);
-    let book1_color = [r, g, b, 1.0];
     let (ar, ag, ab) = hex_to_rgb(accent_color);
-    let book2_color = [ar, ag, ab, 1.0];
 
-    // Stack of 3 books
-    mesh.merge(create_box(0.22, 0.035, 0.3, book1_color, 0.0));
-    mesh.merge(create_box(0.24, 0.04, 0.28, book2_color, 0.035));
-    mesh.merge(create_box(0.2, 0.03, 0.32, book1_color, 0.075));
+    let mut y = 0.0;
+    for i in 0..book_count {
+        // Alternate main/accent covers, darkening each time the pair repeats
+        let shade = 1.0 - 0.15 * ((i / 2) % 3) as f32;
+        let (cover, spine) = if i % 2 == 0 {
+            ([r * shade, g * shade, b * shade, 1.0], [ar * shade, ag * shade, ab * shade, 1.0])
+        } else {
+            ([ar * shade, ag * shade, ab * shade, 1.0], [r * shade, g * shade, b * shade, 1.0])
+        };
+
+        // Vary each book's size and angle slightly for a natural look
+        let width = 0.22 + 0.02 * variation(i);
+        let depth = 0.30 + 0.02 * variation(i + 17);
+        let height = 0.035 + 0.006 * variation(i + 31);
+        let angle = 0.08 * variation(i + 47);
+
+        let mut book = create_box(width, height, depth, cover, y);
+
+        // Tinted spine strip along the +X edge
+        let mut spine_strip = create_box(0.006, height * 0.8, depth * 0.95, spine, y + height * 0.1);
+        for v in &mut spine_strip.vertices {
+            v.position[0] += width / 2.0;
+        }
+        book.merge(spine_strip);
+
+        rotate_y(&mut book, angle);
+        mesh.merge(book);
+        y += height;
+    }
 
     mesh
 }
@@ -763,30 +1042,36 @@ pub fn create_paper(main_color: u32, _accent_color: u32) -> MeshData {
     create_box(0.21, 0.002, 0.297, color, 0.0) // A4 paper proportions scaled down
 }
 
-/// Create a magazine mesh
-pub fn create_magazine(main_color: u32, accent_color: u32) -> MeshData {
-    let mut mesh = MeshData::new();
-
+/// Create a magazine mesh (the cover is drawn as a separate textured quad)
+pub fn create_magazine(main_color: u32, _accent_color: u32) -> MeshData {
     let (r, g, b) = hex_to_rgb(main_color);
     let cover_color = [r, g, b, 1.0];
-    let (ar, ag, ab) = hex_to_rgb(accent_color);
-    let title_color = [ar, ag, ab, 1.0];
 
-    // Magazine body
-    mesh.merge(create_box(0.22, 0.01, 0.3, cover_color, 0.0));
+    create_box(0.22, 0.01, 0.3, cover_color, 0.0)
+}
 
-    // Title stripe
-    let mut title = create_box(0.18, 0.002, 0.04, title_color, 0.01);
-    for v in &mut title.vertices {
-        v.position[2] -= 0.08;
-    }
-    mesh.merge(title);
+/// Generate mesh for a desk object from its type and per-object parameters
+pub fn generate_object_mesh(object: &DeskObject) -> MeshData {
+    generate_type_mesh(object.object_type, object.color, object.accent_color, object.book_count)
+}
 
-    mesh
+/// Lowest point of an object type's mesh relative to its origin, at scale 1
+pub fn mesh_bottom(object_type: ObjectType) -> f32 {
+    let mesh = generate_type_mesh(
+        object_type,
+        object_type.default_color(),
+        object_type.default_accent_color(),
+        *BOOK_COUNT_RANGE.start(),
+    );
+    mesh.vertices
+        .iter()
+        .map(|vertex| vertex.position[1])
+        .reduce(f32::min)
+        .unwrap_or(0.0)
 }
 
-/// Generate mesh for a given object type
-pub fn generate_object_mesh(object_type: ObjectType, main_color: u32, accent_color: u32) -> MeshData {
+/// Mesh of an object type in the given colors
+fn generate_type_mesh(object_type: ObjectType, main_color: u32, accent_color: u32, book_count: u32) -> MeshData {
     match object_type {
         ObjectType::Clock => create_clock(main_color, accent_color),
         ObjectType::Lamp => create_lamp(main_color, accent_color),
@@ -795,7 +1080,7 @@ pub fn generate_object_mesh(object_type: ObjectType, main_color: u32, accent_col
         ObjectType::Laptop => create_laptop(main_color, accent_color),
         ObjectType::Notebook => create_notebook(main_color, accent_color),
         ObjectType::PenHolder => create_pen_holder(main_color, accent_color),
-        ObjectType::Books => create_books(main_color, accent_color),
+        ObjectType::Books => create_books(main_color, accent_color, book_count),
         ObjectType::PhotoFrame => create_photo_frame(main_color, accent_color),
         ObjectType::Globe => create_globe(main_color, accent_color),
         ObjectType::Trophy => create_trophy(main_color, accent_color),
@@ -803,5 +1088,51 @@ pub fn generate_object_mesh(object_type: ObjectType, main_color: u32, accent_col
         ObjectType::Metronome => create_metronome(main_color, accent_color),
         ObjectType::Paper => create_paper(main_color, accent_color),
         ObjectType::Magazine => create_magazine(main_color, accent_color),
+        ObjectType::Custom(custom) => (custom.descriptor().mesh)(main_color, accent_color),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_lines_are_symmetric_and_stay_inside() {
+        assert_eq!(grid_line_offsets(1.0, 0.5), [-1.0, -0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(grid_line_offsets(3.5, 2.0), [-2.0, 0.0, 2.0]);
+        assert!(grid_line_offsets(1.0, 0.0).is_empty());
+
+        let mesh = create_grid_mesh(2.0, 1.0, 0.1, 0.5, 0.01, [1.0; 4]);
+        // Five lines across the width, three across the depth
+        assert_eq!(mesh.vertices.len(), (5 + 3) * 4);
+    }
+
+    #[test]
+    fn room_walls_face_inward() {
+        let mesh = create_room_mesh(4.0, 3.0, 2.0, [1.0; 4]);
+        assert_eq!(mesh.indices.len(), 4 * 6);
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.vertices[triangle[i] as usize].position));
+            // Counter-clockwise winding is the front face
+            let facing = (b - a).cross(c - a);
+            let normal = Vec3::from(mesh.vertices[triangle[0] as usize].normal);
+            assert!(facing.dot(normal) > 0.0);
+            assert!(normal.dot(Vec3::new(0.0, 1.0, 0.0) - a) > 0.0, "wall at {} faces out", a);
+        }
+    }
+
+    #[test]
+    fn light_ring_faces_up_at_its_radius() {
+        let center = Vec3::new(1.0, 0.8, -0.5);
+        let mesh = create_ring_mesh(center, 2.0, 0.1, 24, [1.0; 4]);
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.vertices[triangle[i] as usize].position));
+            assert!((b - a).cross(c - a).y > 0.0);
+        }
+        for vertex in &mesh.vertices {
+            let offset = Vec3::from(vertex.position) - center;
+            assert_eq!(offset.y, 0.0);
+            assert!((offset.length() - 2.0).abs() <= 0.05 + 1e-5);
+        }
     }
 }
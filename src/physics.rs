@@ -2,7 +2,7 @@
 //!
 //! Handles collision detection, object dropping, and stacking.
 
-use glam::Vec3;
+use glam::{Quat, Vec2, Vec3};
 use crate::config::CONFIG;
 use crate::desk_object::DeskObject;
 
@@ -19,6 +19,267 @@ pub struct ObjectPhysicsState {
     pub tilt_velocity: Vec3,
 }
 
+/// Collision volume in object-local space, resting on the local origin (y = 0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionShape {
+    /// Sphere sitting on the origin (center at y = radius)
+    Sphere { radius: f32 },
+    /// Box sitting on the origin (center at y = half_extents.y)
+    Box { half_extents: Vec3 },
+    /// Upright cylinder from y = 0 to y = height
+    Cylinder { radius: f32, height: f32 },
+}
+
+/// Horizontal (XZ) footprint of a placed collision shape
+enum Footprint {
+    Circle { center: Vec2, radius: f32 },
+    Rect { center: Vec2, axes: [Vec2; 2], half: Vec2 },
+}
+
+impl CollisionShape {
+    /// Scale the shape's horizontal and vertical dimensions independently
+    pub fn scaled(&self, horizontal: f32, vertical: f32) -> Self {
+        match *self {
+            Self::Sphere { radius } => Self::Sphere {
+                radius: radius * horizontal.max(vertical),
+            },
+            Self::Box { half_extents } => Self::Box {
+                half_extents: half_extents * Vec3::new(horizontal, vertical, horizontal),
+            },
+            Self::Cylinder { radius, height } => Self::Cylinder {
+                radius: radius * horizontal,
+                height: height * vertical,
+            },
+        }
+    }
+
+    /// Grow the shape by a margin on every side (used for forgiving picking)
+    pub fn inflated(&self, margin: f32) -> Self {
+        match *self {
+            Self::Sphere { radius } => Self::Sphere {
+                radius: radius + margin,
+            },
+            Self::Box { half_extents } => Self::Box {
+                half_extents: half_extents + Vec3::splat(margin),
+            },
+            Self::Cylinder { radius, height } => Self::Cylinder {
+                radius: radius + margin,
+                height: height + margin,
+            },
+        }
+    }
+
+    /// Height of the shape above its base
+    pub fn height(&self) -> f32 {
+        match *self {
+            Self::Sphere { radius } => radius * 2.0,
+            Self::Box { half_extents } => half_extents.y * 2.0,
+            Self::Cylinder { height, .. } => height,
+        }
+    }
+
+    /// Radius of the smallest circle enclosing the horizontal footprint
+    pub fn bounding_radius(&self) -> f32 {
+        match *self {
+            Self::Sphere { radius } | Self::Cylinder { radius, .. } => radius,
+            Self::Box { half_extents } => Vec2::new(half_extents.x, half_extents.z).length(),
+        }
+    }
+
+    /// Half width (x) and half depth (z) of the axis-aligned box around the
+    /// footprint at a rotation
+    pub fn footprint_extents(&self, rotation: Quat) -> Vec2 {
+        match self.footprint(Vec3::ZERO, rotation) {
+            Footprint::Circle { radius, .. } => Vec2::splat(radius),
+            Footprint::Rect { axes, half, .. } => axes[0].abs() * half.x + axes[1].abs() * half.y,
+        }
+    }
+
+    fn footprint(&self, position: Vec3, rotation: Quat) -> Footprint {
+        let center = Vec2::new(position.x, position.z);
+        match *self {
+            Self::Sphere { radius } | Self::Cylinder { radius, .. } => {
+                Footprint::Circle { center, radius }
+            }
+            Self::Box { half_extents } => {
+                let x_axis = rotation * Vec3::X;
+                let z_axis = rotation * Vec3::Z;
+                Footprint::Rect {
+                    center,
+                    axes: [
+                        Vec2::new(x_axis.x, x_axis.z).normalize_or(Vec2::X),
+                        Vec2::new(z_axis.x, z_axis.z).normalize_or(Vec2::Y),
+                    ],
+                    half: Vec2::new(half_extents.x, half_extents.z),
+                }
+            }
+        }
+    }
+
+    /// Check whether the horizontal footprints of two placed shapes overlap
+    pub fn footprints_overlap(
+        &self,
+        position: Vec3,
+        rotation: Quat,
+        other: &CollisionShape,
+        other_position: Vec3,
+        other_rotation: Quat,
+    ) -> bool {
+        let a = self.footprint(position, rotation);
+        let b = other.footprint(other_position, other_rotation);
+
+        match (&a, &b) {
+            (
+                Footprint::Circle { center: c1, radius: r1 },
+                Footprint::Circle { center: c2, radius: r2 },
+            ) => c1.distance_squared(*c2) < (r1 + r2) * (r1 + r2),
+            (Footprint::Circle { center, radius }, rect @ Footprint::Rect { .. })
+            | (rect @ Footprint::Rect { .. }, Footprint::Circle { center, radius }) => {
+                let Footprint::Rect { center: rc, axes, half } = rect else { unreachable!() };
+                let d = *center - *rc;
+                let local = Vec2::new(d.dot(axes[0]), d.dot(axes[1]));
+                let closest = local.clamp(-*half, *half);
+                local.distance_squared(closest) < radius * radius
+            }
+            (
+                Footprint::Rect { center: c1, axes: a1, half: h1 },
+                Footprint::Rect { center: c2, axes: a2, half: h2 },
+            ) => {
+                // Separating axis test on both rectangles' edge normals
+                let d = *c2 - *c1;
+                [a1[0], a1[1], a2[0], a2[1]].iter().all(|axis| {
+                    let r1 = h1.x * a1[0].dot(*axis).abs() + h1.y * a1[1].dot(*axis).abs();
+                    let r2 = h2.x * a2[0].dot(*axis).abs() + h2.y * a2[1].dot(*axis).abs();
+                    d.dot(*axis).abs() < r1 + r2
+                })
+            }
+        }
+    }
+
+    /// Distance along a ray to the placed shape, if the ray hits it
+    pub fn ray_intersection(
+        &self,
+        position: Vec3,
+        rotation: Quat,
+        ray_origin: Vec3,
+        ray_direction: Vec3,
+    ) -> Option<f32> {
+        // Work in object-local space so boxes can be tested axis-aligned
+        let inverse = rotation.inverse();
+        let origin = inverse * (ray_origin - position);
+        let dir = inverse * ray_direction;
+
+        let t = match *self {
+            Self::Sphere { radius } => {
+                let oc = origin - Vec3::new(0.0, radius, 0.0);
+                let b = oc.dot(dir);
+                let c = oc.length_squared() - radius * radius;
+                let disc = b * b - c * dir.length_squared();
+                if disc < 0.0 {
+                    return None;
+                }
+                let sqrt = disc.sqrt();
+                let near = (-b - sqrt) / dir.length_squared();
+                let far = (-b + sqrt) / dir.length_squared();
+                if near >= 0.0 { near } else { far }
+            }
+            Self::Box { half_extents } => {
+                let min = Vec3::new(-half_extents.x, 0.0, -half_extents.z);
+                let max = Vec3::new(half_extents.x, half_extents.y * 2.0, half_extents.z);
+                let inv = dir.recip();
+                let t1 = (min - origin) * inv;
+                let t2 = (max - origin) * inv;
+                let near = t1.min(t2).max_element();
+                let far = t1.max(t2).min_element();
+                if near > far {
+                    return None;
+                }
+                if near >= 0.0 { near } else { far }
+            }
+            Self::Cylinder { radius, height } => {
+                let mut best = f32::MAX;
+
+                // Curved side
+                let a = dir.x * dir.x + dir.z * dir.z;
+                if a > f32::EPSILON {
+                    let b = origin.x * dir.x + origin.z * dir.z;
+                    let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+                    let disc = b * b - a * c;
+                    if disc >= 0.0 {
+                        for t in [(-b - disc.sqrt()) / a, (-b + disc.sqrt()) / a] {
+                            let y = origin.y + dir.y * t;
+                            if t >= 0.0 && (0.0..=height).contains(&y) {
+                                best = best.min(t);
+                            }
+                        }
+                    }
+                }
+
+                // Flat caps
+                if dir.y.abs() > f32::EPSILON {
+                    for cap_y in [0.0, height] {
+                        let t = (cap_y - origin.y) / dir.y;
+                        let hit = origin + dir * t;
+                        if t >= 0.0 && hit.x * hit.x + hit.z * hit.z <= radius * radius {
+                            best = best.min(t);
+                        }
+                    }
+                }
+
+                if best == f32::MAX {
+                    return None;
+                }
+                best
+            }
+        };
+
+        (t >= 0.0).then_some(t)
+    }
+}
+
+/// Fixed-timestep accumulator so physics behaves the same at any frame rate
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    /// Length of one substep in seconds
+    step: f32,
+    /// Frame times are clamped to this before accumulating
+    max_frame_time: f32,
+    /// Simulated time not yet consumed by a substep
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32, max_frame_time: f32) -> Self {
+        Self {
+            step,
+            max_frame_time,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Add a frame's `dt` and return how many substeps to run.
+    ///
+    /// `dt` is clamped to the max frame time, so a long stall (window drag,
+    /// breakpoint, sleep) runs a bounded number of substeps instead of
+    /// flinging objects across the desk.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        let dt = if dt.is_finite() { dt.clamp(0.0, self.max_frame_time) } else { 0.0 };
+        self.accumulator += dt;
+        let steps = (self.accumulator / self.step).floor();
+        self.accumulator -= steps * self.step;
+        steps as u32
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(CONFIG.physics.fixed_timestep, CONFIG.physics.max_frame_time)
+    }
+}
+
+/// Footprint shrink factor when deciding whether a dropped object rests on another
+const STACK_OVERLAP_FACTOR: f32 = 0.7;
+
 /// Physics engine for the desk simulation
 pub struct PhysicsEngine {
     /// Global collision radius multiplier
@@ -35,6 +296,10 @@ pub struct PhysicsEngine {
     pub desk_bounds: (f32, f32, f32, f32),
     /// Desk surface Y position
     pub desk_surface_y: f32,
+    /// Floor bounds around the desk (min x, max x, min z, max z)
+    pub floor_bounds: (f32, f32, f32, f32),
+    /// Floor surface Y position
+    pub floor_y: f32,
 }
 
 impl Default for PhysicsEngine {
@@ -50,7 +315,14 @@ impl Default for PhysicsEngine {
             bounce_factor: config.physics.bounce_factor,
             gravity: config.physics.gravity,
             desk_bounds: (-half_width, half_width, -half_depth, half_depth),
-            desk_surface_y: config.desk.height,
+            desk_surface_y: config.desk.top_y(),
+            floor_bounds: (
+                -half_width - config.desk.floor_margin,
+                half_width + config.desk.floor_margin,
+                -half_depth - config.desk.floor_margin,
+                half_depth + config.desk.floor_margin,
+            ),
+            floor_y: 0.0,
         }
     }
 }
@@ -83,21 +355,74 @@ impl PhysicsEngine {
         )
     }
 
+    /// Clamp a position to the floor area objects can be dragged across,
+    /// keeping `radius` inside its edges
+    pub fn clamp_to_floor(&self, position: Vec3, radius: f32) -> Vec3 {
+        Vec3::new(
+            position.x.clamp(self.floor_bounds.0 + radius, self.floor_bounds.1 - radius),
+            position.y,
+            position.z.clamp(self.floor_bounds.2 + radius, self.floor_bounds.3 - radius),
+        )
+    }
+
+    /// Set a new object (e.g. a copy) down where it is: kept within the floor
+    /// area and resting on whatever is under it, desk or floor
+    pub fn place(&self, object: &mut DeskObject, other_objects: &[DeskObject]) {
+        let radius = self.world_shape(object).bounding_radius();
+        object.position = self.clamp_to_floor(object.position, radius);
+        self.end_drag(object, other_objects);
+        object.position.y = object.target_y;
+    }
+
+    /// Move a dragged object's position so its footprint sits flush against
+    /// any desk edge it comes within `distance` of. Each axis snaps on its
+    /// own, so near a corner the object lines up with both edges.
+    pub fn snap_to_desk_edge(&self, object: &DeskObject, position: Vec3, distance: f32) -> Vec3 {
+        let extents = self.world_shape(object).footprint_extents(object.rotation);
+        let snap = |value: f32, extent: f32, min: f32, max: f32| {
+            let (low, high) = (min + extent, max - extent);
+            if (value - low).abs() <= distance {
+                low
+            } else if (value - high).abs() <= distance {
+                high
+            } else {
+                value
+            }
+        };
+        let (min_x, max_x, min_z, max_z) = self.desk_bounds;
+        Vec3::new(
+            snap(position.x, extents.x, min_x, max_x),
+            position.y,
+            snap(position.z, extents.y, min_z, max_z),
+        )
+    }
+
+    /// Height of the surface under a position: the desk if the point is over
+    /// it, otherwise the floor
+    pub fn surface_y_at(&self, position: Vec3) -> f32 {
+        if self.is_on_desk(position) {
+            self.desk_surface_y
+        } else {
+            self.floor_y
+        }
+    }
+
     /// Check collision between two objects
     pub fn check_collision(&self, obj1: &DeskObject, obj2: &DeskObject) -> bool {
         if obj1.id == obj2.id {
             return false;
         }
 
-        let r1 = obj1.collision_radius() * self.collision_radius_multiplier;
-        let r2 = obj2.collision_radius() * self.collision_radius_multiplier;
-        let min_dist = r1 + r2;
-
-        let dx = obj1.position.x - obj2.position.x;
-        let dz = obj1.position.z - obj2.position.z;
-        let dist_sq = dx * dx + dz * dz;
+        let shape1 = self.world_shape(obj1);
+        let shape2 = self.world_shape(obj2);
+        shape1.footprints_overlap(obj1.position, obj1.rotation, &shape2, obj2.position, obj2.rotation)
+    }
 
-        dist_sq < min_dist * min_dist
+    /// An object's collision shape with the global multipliers applied
+    pub fn world_shape(&self, object: &DeskObject) -> CollisionShape {
+        object
+            .collision_shape()
+            .scaled(self.collision_radius_multiplier, self.collision_height_multiplier)
     }
 
     /// Find the best position to place an object (avoiding collisions)
@@ -143,9 +468,11 @@ impl PhysicsEngine {
         object: &DeskObject,
         other_objects: &[DeskObject],
     ) -> f32 {
-        let radius = object.collision_radius() * self.collision_radius_multiplier;
-        let physics = object.object_type.physics();
-        let base_y = self.desk_surface_y + physics.base_offset * object.scale;
+        let shape = self
+            .world_shape(object)
+            .scaled(STACK_OVERLAP_FACTOR, 1.0);
+        let rest_offset = object.base_offset();
+        let base_y = self.surface_y_at(object.position) + rest_offset;
 
         let mut highest_y = base_y;
 
@@ -162,17 +489,20 @@ impl PhysicsEngine {
                 continue;
             }
 
-            let other_radius = other.collision_radius() * self.collision_radius_multiplier;
-            let combined_radius = radius + other_radius;
-
-            let dx = object.position.x - other.position.x;
-            let dz = object.position.z - other.position.z;
-            let dist_sq = dx * dx + dz * dz;
+            let other_shape = self
+                .world_shape(other)
+                .scaled(STACK_OVERLAP_FACTOR, 1.0);
 
             // Check if we're above this object
-            if dist_sq < combined_radius * combined_radius * 0.5 {
-                let other_top = other.position.y + other.collision_height() * self.collision_height_multiplier;
-                let stack_y = other_top + physics.base_offset * object.scale;
+            if shape.footprints_overlap(
+                object.position,
+                object.rotation,
+                &other_shape,
+                other.position,
+                other.rotation,
+            ) {
+                let other_top = other.position.y + other_shape.height();
+                let stack_y = other_top + rest_offset;
 
                 if stack_y > highest_y {
                     highest_y = stack_y;
@@ -221,6 +551,41 @@ impl PhysicsEngine {
         false
     }
 
+    /// Drop speed per substep, scaled by `gravity` relative to its configured value
+    fn drop_speed(&self) -> f32 {
+        let scale = self.gravity / CONFIG.physics.gravity;
+        (CONFIG.physics.drop_speed * scale).clamp(0.0, 1.0)
+    }
+
+    /// Run one fixed substep of settling animation, returning the IDs of
+    /// objects that moved
+    pub fn step(&self, objects: &mut [DeskObject]) -> Vec<u64> {
+        let objects_clone: Vec<DeskObject> = objects.to_vec();
+        let drop_speed = self.drop_speed();
+        let mut updated_ids = Vec::new();
+        for obj in objects.iter_mut() {
+            if !obj.is_dragging && self.update_dropping(obj, &objects_clone, drop_speed) {
+                updated_ids.push(obj.id);
+            }
+        }
+        updated_ids
+    }
+
+    /// Rest loaded objects on whatever is under them, lowest first so stacks
+    /// rebuild bottom-up (resting heights aren't saved)
+    pub fn settle(&self, objects: &mut [DeskObject]) {
+        let mut order: Vec<usize> = (0..objects.len()).collect();
+        order.sort_by(|&a, &b| objects[a].position.y.total_cmp(&objects[b].position.y));
+        let mut settled: Vec<DeskObject> = Vec::with_capacity(objects.len());
+        for index in order {
+            let object = &mut objects[index];
+            object.target_y = self.calculate_resting_y(object, &settled);
+            object.original_y = object.target_y;
+            object.position.y = object.target_y;
+            settled.push(object.clone());
+        }
+    }
+
     /// End drag operation and calculate final position
     pub fn end_drag(&self, object: &mut DeskObject, other_objects: &[DeskObject]) {
         object.is_dragging = false;
@@ -250,3 +615,128 @@ pub fn ray_plane_intersection(
 
     Some(ray_origin + ray_direction * t)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desk_object::ObjectType;
+
+    /// An object hovering above its resting height, as if just released
+    fn falling_object() -> DeskObject {
+        let mut object = DeskObject::new(1, ObjectType::Coffee, Vec3::ZERO);
+        object.position.y = object.target_y + 1.0;
+        object
+    }
+
+    /// Drop distance after simulating one frame of length `dt`
+    fn drop_after_frame(dt: f32) -> f32 {
+        let physics = PhysicsEngine::new();
+        let mut timestep = FixedTimestep::default();
+        let mut objects = vec![falling_object()];
+        let start_y = objects[0].position.y;
+        for _ in 0..timestep.advance(dt) {
+            physics.step(&mut objects);
+        }
+        start_y - objects[0].position.y
+    }
+
+    #[test]
+    fn new_objects_rest_where_dropping_would_leave_them() {
+        let physics = PhysicsEngine::new();
+        for &object_type in ObjectType::all() {
+            let position = Vec3::new(0.0, physics.desk_surface_y(), 0.0);
+            let object = DeskObject::new(1, object_type, position);
+            let resting_y = physics.calculate_resting_y(&object, &[]);
+            assert!(
+                (object.position.y - resting_y).abs() < 1e-6,
+                "{:?} would jump from {} to {} when dropped",
+                object_type,
+                object.position.y,
+                resting_y
+            );
+        }
+    }
+
+    #[test]
+    fn objects_rest_on_the_surface_under_them() {
+        let physics = PhysicsEngine::new();
+        let mut object = DeskObject::new(1, ObjectType::Coffee, Vec3::ZERO);
+        assert_eq!(physics.calculate_resting_y(&object, &[]), physics.desk_surface_y());
+
+        object.position.x = physics.desk_bounds.1 + 1.0;
+        assert_eq!(physics.calculate_resting_y(&object, &[]), physics.floor_y);
+
+        let clamped = physics.clamp_to_floor(Vec3::new(100.0, 0.0, -100.0), 0.0);
+        assert_eq!((clamped.x, clamped.z), (physics.floor_bounds.1, physics.floor_bounds.2));
+    }
+
+    #[test]
+    fn loaded_objects_settle_with_their_lowest_point_on_the_desk() {
+        let physics = PhysicsEngine::new();
+        let surface = physics.desk_surface_y();
+        // As loaded from a save: hovering at an old offset, no resting height
+        let mut clock = DeskObject::new(1, ObjectType::Clock, Vec3::new(0.0, surface, 0.0));
+        clock.scale = 1.5;
+        clock.position.y = surface + 0.7;
+        clock.target_y = 0.0;
+        clock.original_y = 0.0;
+        let mut objects = vec![clock];
+
+        physics.settle(&mut objects);
+
+        let lowest = objects[0].position.y + crate::mesh::mesh_bottom(ObjectType::Clock) * 1.5;
+        assert!((lowest - surface).abs() < 1e-5);
+        assert_eq!(objects[0].target_y, objects[0].position.y);
+        assert!(physics.step(&mut objects).is_empty());
+    }
+
+    #[test]
+    fn long_stall_is_clamped_to_max_frame_time() {
+        let stalled = drop_after_frame(2.0);
+        let capped = drop_after_frame(CONFIG.physics.max_frame_time);
+        assert!(stalled > 0.0);
+        assert_eq!(stalled, capped);
+    }
+
+    #[test]
+    fn long_stall_runs_a_bounded_number_of_substeps() {
+        let mut timestep = FixedTimestep::new(1.0 / 60.0, 0.05);
+        assert_eq!(timestep.advance(2.0), 3);
+        assert_eq!(timestep.advance(f32::NAN), 0);
+    }
+
+    #[test]
+    fn substeps_are_independent_of_frame_rate() {
+        let mut fast = FixedTimestep::new(0.01, 0.05);
+        let mut slow = FixedTimestep::new(0.01, 0.05);
+        let fast_steps: u32 = (0..40).map(|_| fast.advance(0.0025)).sum();
+        let slow_steps: u32 = (0..5).map(|_| slow.advance(0.02)).sum();
+        assert_eq!(fast_steps, slow_steps);
+    }
+
+    #[test]
+    fn objects_near_a_desk_edge_snap_flush_against_it() {
+        let physics = PhysicsEngine::new();
+        let (min_x, max_x, _, max_z) = physics.desk_bounds;
+        let mut laptop = DeskObject::new(1, ObjectType::Laptop, Vec3::ZERO);
+        let extents = physics.world_shape(&laptop).footprint_extents(laptop.rotation);
+
+        // Far from every edge nothing moves
+        let middle = Vec3::new(0.3, 1.0, -0.2);
+        assert_eq!(physics.snap_to_desk_edge(&laptop, middle, 0.2), middle);
+
+        // Near the right edge, or just over it, the footprint ends at the edge
+        for x in [max_x - extents.x - 0.15, max_x - extents.x + 0.1] {
+            let snapped = physics.snap_to_desk_edge(&laptop, Vec3::new(x, 1.0, 0.0), 0.2);
+            assert!((snapped.x + extents.x - max_x).abs() < 1e-5);
+            assert_eq!(snapped.z, 0.0);
+        }
+
+        // Turned a quarter, its depth lines up with the edge, here in the back-left corner
+        laptop.rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let near_corner = Vec3::new(min_x + extents.y + 0.1, 1.0, max_z - extents.x - 0.1);
+        let snapped = physics.snap_to_desk_edge(&laptop, near_corner, 0.2);
+        assert!((snapped.x - extents.y - min_x).abs() < 1e-5);
+        assert!((snapped.z + extents.x - max_z).abs() < 1e-5);
+    }
+}
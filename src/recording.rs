@@ -0,0 +1,390 @@
+//! Session recording and playback
+//!
+//! A recording is a JSON-lines file of timestamped events: objects being
+//! added, changed or removed, transform keyframes sampled while objects move,
+//! and the UI actions that caused them (kept for debugging, not replayed).
+//! Playback rebuilds the desk at any point in time and interpolates
+//! transforms between keyframes, so sparse samples still move smoothly.
+
+use crate::config::CONFIG;
+use crate::desk_object::DeskObject;
+use crate::ui::UiAction;
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// One line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Seconds since the recording started
+    pub time: f32,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// What happened at a recorded moment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum EventKind {
+    /// An object appeared on the desk
+    Added { object: DeskObject },
+    /// An object changed in a way other than its transform (color, name, …)
+    Updated { object: DeskObject },
+    /// An object left the desk
+    Removed { id: u64 },
+    /// An object's transform at this moment
+    Transform {
+        id: u64,
+        position: Vec3,
+        rotation: Quat,
+        scale: f32,
+    },
+    /// A UI action, for reading along while debugging
+    Action { action: String },
+}
+
+/// Whether two snapshots of an object differ only in their transform
+fn same_except_transform(a: &DeskObject, b: &DeskObject) -> bool {
+    let strip = |obj: &DeskObject| DeskObject {
+        position: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: 1.0,
+        ..obj.without_runtime_state()
+    };
+    strip(a) == strip(b)
+}
+
+fn same_transform(a: &DeskObject, b: &DeskObject) -> bool {
+    a.position == b.position && a.rotation == b.rotation && a.scale == b.scale
+}
+
+fn transform_event(obj: &DeskObject) -> EventKind {
+    EventKind::Transform {
+        id: obj.id,
+        position: obj.position,
+        rotation: obj.rotation,
+        scale: obj.scale,
+    }
+}
+
+/// Writes a session to a recording file as it happens
+pub struct Recorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+    /// Desk as of the last sample
+    objects: Vec<DeskObject>,
+    /// Time of the last sample, `None` before the first
+    last_sample: Option<f32>,
+    /// Time of each object's latest transform keyframe
+    keyframe_times: HashMap<u64, f32>,
+}
+
+impl Recorder {
+    /// Start recording to a new file
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            objects: Vec::new(),
+            last_sample: None,
+            keyframe_times: HashMap::new(),
+        })
+    }
+
+    /// File being recorded to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn elapsed(&self) -> f32 {
+        self.started.elapsed().as_secs_f32()
+    }
+
+    fn write(&mut self, time: f32, kind: EventKind) -> std::io::Result<()> {
+        let line = serde_json::to_string(&RecordedEvent { time, kind })?;
+        writeln!(self.writer, "{}", line)
+    }
+
+    /// Note a UI action in the recording
+    pub fn record_action(&mut self, action: &UiAction) -> std::io::Result<()> {
+        let time = self.elapsed();
+        self.write(time, EventKind::Action { action: format!("{:?}", action) })
+    }
+
+    /// Record changes to the desk since the last sample. Samples are taken at
+    /// most every `RecordingConfig::sample_interval`; calls in between are ignored.
+    pub fn capture(&mut self, objects: &[DeskObject]) -> std::io::Result<()> {
+        let time = self.elapsed();
+        self.capture_at(time, objects)
+    }
+
+    fn capture_at(&mut self, time: f32, objects: &[DeskObject]) -> std::io::Result<()> {
+        if let Some(last) = self.last_sample {
+            if time - last < CONFIG.recording.sample_interval {
+                return Ok(());
+            }
+        }
+
+        let previous_objects = std::mem::take(&mut self.objects);
+        for previous in &previous_objects {
+            let current = objects.iter().find(|obj| obj.id == previous.id);
+            let Some(current) = current else {
+                self.keyframe_times.remove(&previous.id);
+                self.write(time, EventKind::Removed { id: previous.id })?;
+                continue;
+            };
+            if !same_except_transform(previous, current) {
+                self.write(time, EventKind::Updated { object: current.clone() })?;
+            }
+            if !same_transform(previous, current) {
+                // An object starting to move after resting keeps its old pose
+                // until the previous sample, rather than drifting from its last keyframe
+                if let Some(last) = self.last_sample {
+                    if self.keyframe_times.get(&current.id).is_some_and(|&t| t < last) {
+                        self.write(last, transform_event(previous))?;
+                    }
+                }
+                self.write(time, transform_event(current))?;
+                self.keyframe_times.insert(current.id, time);
+            }
+        }
+
+        for obj in objects {
+            if !self.keyframe_times.contains_key(&obj.id) {
+                self.write(time, EventKind::Added { object: obj.clone() })?;
+                self.keyframe_times.insert(obj.id, time);
+            }
+        }
+
+        self.objects = objects.to_vec();
+        self.last_sample = Some(time);
+        Ok(())
+    }
+
+    /// Flush the file, returning its path
+    pub fn finish(mut self) -> std::io::Result<PathBuf> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// The desk replayed up to some event
+#[derive(Default)]
+struct Replay {
+    /// Number of events applied
+    applied: usize,
+    objects: Vec<DeskObject>,
+    /// Time of each object's latest keyframe
+    keyframe_times: HashMap<u64, f32>,
+}
+
+impl Replay {
+    fn apply(&mut self, event: &RecordedEvent) {
+        self.applied += 1;
+        match &event.kind {
+            EventKind::Added { object } | EventKind::Updated { object } => {
+                match self.objects.iter_mut().find(|obj| obj.id == object.id) {
+                    Some(existing) => {
+                        let (position, rotation, scale) =
+                            (existing.position, existing.rotation, existing.scale);
+                        *existing = object.clone();
+                        if matches!(event.kind, EventKind::Updated { .. }) {
+                            // Transforms only change through keyframes
+                            existing.position = position;
+                            existing.rotation = rotation;
+                            existing.scale = scale;
+                        }
+                    }
+                    None => self.objects.push(object.clone()),
+                }
+                self.keyframe_times.entry(object.id).or_insert(event.time);
+            }
+            EventKind::Removed { id } => {
+                self.objects.retain(|obj| obj.id != *id);
+                self.keyframe_times.remove(id);
+            }
+            &EventKind::Transform {
+                id,
+                position,
+                rotation,
+                scale,
+            } => {
+                if let Some(obj) = self.objects.iter_mut().find(|obj| obj.id == id) {
+                    obj.position = position;
+                    obj.rotation = rotation;
+                    obj.scale = scale;
+                    self.keyframe_times.insert(id, event.time);
+                }
+            }
+            EventKind::Action { .. } => {}
+        }
+    }
+}
+
+/// Plays a recording back, rebuilding the desk at the current playback time
+pub struct Player {
+    events: Vec<RecordedEvent>,
+    /// Indices of each object's transform keyframes, in time order
+    keyframes: HashMap<u64, Vec<usize>>,
+    /// Desk as of the current playback time, advanced as playback moves on
+    replay: Replay,
+    elapsed: f32,
+}
+
+impl Player {
+    /// Play the given events
+    pub fn new(mut events: Vec<RecordedEvent>) -> Self {
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let mut keyframes: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, event) in events.iter().enumerate() {
+            if let EventKind::Transform { id, .. } = event.kind {
+                keyframes.entry(id).or_default().push(index);
+            }
+        }
+        Self {
+            events,
+            keyframes,
+            replay: Replay::default(),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Load a recording file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = Vec::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line)
+                .map_err(|e| format!("line {}: {}", number + 1, e))?;
+            events.push(event);
+        }
+        Ok(Self::new(events))
+    }
+
+    /// Length of the recording in seconds
+    pub fn duration(&self) -> f32 {
+        self.events.last().map_or(0.0, |event| event.time)
+    }
+
+    /// Move playback forward by `dt` seconds
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    /// Whether playback has reached the end
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration()
+    }
+
+    /// The desk at the current playback time
+    pub fn objects(&mut self) -> Vec<DeskObject> {
+        self.objects_at(self.elapsed)
+    }
+
+    /// The desk at `time`, with transforms interpolated towards each object's
+    /// next keyframe. Moving forward only replays the events since the last
+    /// call; going back starts over.
+    pub fn objects_at(&mut self, time: f32) -> Vec<DeskObject> {
+        let split = self.events.partition_point(|event| event.time <= time);
+        if split < self.replay.applied {
+            self.replay = Replay::default();
+        }
+        for event in &self.events[self.replay.applied..split] {
+            self.replay.apply(event);
+        }
+
+        let replay = &self.replay;
+        let mut objects = replay.objects.clone();
+        for obj in &mut objects {
+            let Some(keyframes) = self.keyframes.get(&obj.id) else {
+                continue;
+            };
+            let next = keyframes
+                .get(keyframes.partition_point(|&index| index < replay.applied))
+                .map(|&index| &self.events[index]);
+            let (Some(next), Some(&from_time)) = (next, replay.keyframe_times.get(&obj.id)) else {
+                continue;
+            };
+            let EventKind::Transform {
+                position,
+                rotation,
+                scale,
+                ..
+            } = next.kind
+            else {
+                continue;
+            };
+            let t = ((time - from_time) / (next.time - from_time).max(f32::EPSILON)).clamp(0.0, 1.0);
+            obj.position = obj.position.lerp(position, t);
+            obj.rotation = obj.rotation.slerp(rotation, t);
+            obj.scale += (scale - obj.scale) * t;
+        }
+        objects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desk_object::ObjectType;
+
+    #[test]
+    fn interacting_with_a_moved_object_still_records_a_move() {
+        let lamp = DeskObject::new(1, ObjectType::Lamp, Vec3::ZERO);
+        let mut moved = lamp.clone();
+        moved.position.x = 1.0;
+        moved.last_interacted = Some(Instant::now());
+        assert!(same_except_transform(&lamp, &moved));
+    }
+
+    #[test]
+    fn recorded_moves_replay_with_interpolation() {
+        let path = std::env::temp_dir().join(format!("focus-recording-test-{}.jsonl", std::process::id()));
+        let mut recorder = Recorder::create(&path).unwrap();
+        let step = CONFIG.recording.sample_interval;
+
+        let mut mug = DeskObject::new(1, ObjectType::Coffee, Vec3::ZERO);
+        recorder.capture_at(0.0, &[mug.clone()]).unwrap();
+        recorder.capture_at(step, &[mug.clone()]).unwrap();
+        mug.position.x = 2.0;
+        recorder.capture_at(step * 2.0, &[mug.clone()]).unwrap();
+        recorder.record_action(&UiAction::DeleteObject(1)).unwrap();
+        recorder.capture_at(step * 3.0, &[]).unwrap();
+        let path = recorder.finish().unwrap();
+
+        let mut player = Player::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        // The mug rests until the sample before it moved, then slides over
+        assert_eq!(player.objects_at(step * 0.5)[0].position.x, 0.0);
+        let halfway = player.objects_at(step * 1.5)[0].position.x;
+        assert!((halfway - 1.0).abs() < 1e-4, "halfway at {}", halfway);
+        assert_eq!(player.objects_at(step * 2.5)[0].position.x, 2.0);
+        assert!(player.objects_at(step * 3.0).is_empty());
+    }
+
+    #[test]
+    fn seeking_back_replays_from_the_start() {
+        let mut mug = DeskObject::new(1, ObjectType::Coffee, Vec3::ZERO);
+        let mut events = vec![RecordedEvent { time: 0.0, kind: EventKind::Added { object: mug.clone() } }];
+        for i in 1..=4 {
+            mug.position.x = i as f32;
+            events.push(RecordedEvent { time: i as f32, kind: transform_event(&mug) });
+        }
+        let mut player = Player::new(events);
+
+        assert!((player.objects_at(3.5)[0].position.x - 3.5).abs() < 1e-4);
+        assert!((player.objects_at(1.25)[0].position.x - 1.25).abs() < 1e-4);
+        assert!((player.objects_at(4.0)[0].position.x - 4.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,226 @@
+//! Object types registered at runtime by an embedding application
+//!
+//! Built-in types are variants of `ObjectType`. A registered type is an
+//! `ObjectType::Custom`, whose looks and behavior come from its
+//! `ObjectDescriptor`. Register types before creating the `App` so the
+//! palette lists them:
+//!
+//! ```no_run
+//! use focus_desktop_simulator::config::hex_to_rgba;
+//! use focus_desktop_simulator::registry::{register_object, ObjectDescriptor};
+//! use focus_desktop_simulator::{create_box, CollisionShape};
+//! use glam::Vec3;
+//!
+//! let shape = CollisionShape::Box { half_extents: Vec3::new(0.1, 0.05, 0.1) };
+//! let descriptor = ObjectDescriptor::new("coaster", "Coaster", "🟫", shape, |color, _accent| {
+//!     create_box(0.2, 0.1, 0.2, hex_to_rgba(color), 0.0)
+//! });
+//! let coaster = register_object(descriptor).expect("a new key");
+//! ```
+//!
+//! Objects of a registered type are saved under its key; a save holding a
+//! type that isn't registered when it is loaded leaves those objects out.
+
+use crate::desk_object::{DeskObject, ObjectPhysics, ObjectType};
+use crate::mesh::MeshData;
+use crate::physics::CollisionShape;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::{PoisonError, RwLock};
+
+/// Builds the mesh of a registered type from an object's main and accent colors
+pub type MeshBuilder = Box<dyn Fn(u32, u32) -> MeshData + Send + Sync>;
+
+/// Advances an object by a frame of `dt` seconds, returning whether its
+/// position, rotation or scale changed and needs redrawing
+pub type UpdateCallback = Box<dyn Fn(&mut DeskObject, f32) -> bool + Send + Sync>;
+
+/// Runs when the user picks Use in an object's context menu
+pub type InteractCallback = Box<dyn Fn(&mut DeskObject) + Send + Sync>;
+
+/// Everything the simulator needs to know about a registered object type
+pub struct ObjectDescriptor {
+    /// Stable name the type's objects are saved under (unique, kebab-case)
+    pub key: &'static str,
+    /// Name shown in the palette and the customization sidebar
+    pub name: &'static str,
+    /// Emoji icon for the palette
+    pub icon: &'static str,
+    /// One-line description shown as a tooltip in the palette
+    pub description: &'static str,
+    /// Main color of a new object (hex RGB)
+    pub default_color: u32,
+    /// Accent color of a new object (hex RGB)
+    pub default_accent_color: u32,
+    /// Unscaled collision shape, resting on the local origin
+    pub collision_shape: CollisionShape,
+    /// Weight, stability and stacking behavior
+    pub physics: ObjectPhysics,
+    /// Specular exponent for the sun's highlight (0 = matte)
+    pub shininess: f32,
+    /// Mesh of an object in the given colors
+    pub mesh: MeshBuilder,
+    /// Called every frame for each object of the type
+    pub update: Option<UpdateCallback>,
+    /// Called when the user uses an object of the type
+    pub interact: Option<InteractCallback>,
+}
+
+impl ObjectDescriptor {
+    /// A descriptor with neutral colors, physics derived from the collision
+    /// shape and no callbacks; set the other fields as needed
+    pub fn new(
+        key: &'static str,
+        name: &'static str,
+        icon: &'static str,
+        collision_shape: CollisionShape,
+        mesh: impl Fn(u32, u32) -> MeshData + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            key,
+            name,
+            icon,
+            description: "",
+            default_color: 0x94a3b8,
+            default_accent_color: 0x475569,
+            collision_shape,
+            physics: ObjectPhysics {
+                weight: 1.0,
+                stability: 0.7,
+                height: collision_shape.height(),
+                friction: 0.5,
+                no_stacking_on_top: false,
+            },
+            shininess: 0.0,
+            mesh: Box::new(mesh),
+            update: None,
+            interact: None,
+        }
+    }
+}
+
+/// Handle of a registered object type, only handed out by `register_object`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomType(u16);
+
+/// A registered descriptor and what is worked out from it once
+struct Registered {
+    descriptor: &'static ObjectDescriptor,
+    rest_offset: f32,
+}
+
+static REGISTRY: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
+/// Add an object type, returning the `ObjectType` its objects are created
+/// with. Fails if the key is taken by a built-in or registered type.
+pub fn register_object(descriptor: ObjectDescriptor) -> Result<ObjectType, Box<dyn std::error::Error>> {
+    let builtin_key = |object_type: &ObjectType| {
+        serde_json::to_value(object_type).ok().and_then(|value| value.as_str().map(str::to_owned))
+    };
+    if ObjectType::all().iter().any(|object_type| builtin_key(object_type).as_deref() == Some(descriptor.key)) {
+        return Err(format!("{:?} is a built-in object type", descriptor.key).into());
+    }
+
+    // The lowest point of the mesh rests on the surface, as for built-in types
+    let mesh = (descriptor.mesh)(descriptor.default_color, descriptor.default_accent_color);
+    let bottom = mesh.vertices.iter().map(|vertex| vertex.position[1]).reduce(f32::min).unwrap_or(0.0);
+
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    if registry.iter().any(|registered| registered.descriptor.key == descriptor.key) {
+        return Err(format!("object type {:?} is already registered", descriptor.key).into());
+    }
+    let index = u16::try_from(registry.len()).map_err(|_| "too many registered object types")?;
+    log::info!("Registered object type {:?}", descriptor.key);
+    registry.push(Registered {
+        descriptor: Box::leak(Box::new(descriptor)),
+        rest_offset: -bottom,
+    });
+    Ok(ObjectType::Custom(CustomType(index)))
+}
+
+/// Every registered object type, in registration order
+pub fn registered_types() -> Vec<ObjectType> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    (0..registry.len() as u16).map(|index| ObjectType::Custom(CustomType(index))).collect()
+}
+
+impl CustomType {
+    fn registered<T>(self, read: impl FnOnce(&Registered) -> T) -> T {
+        let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+        // Handles only come from `register_object`, and nothing is unregistered
+        read(&registry[self.0 as usize])
+    }
+
+    /// The type's descriptor
+    pub fn descriptor(self) -> &'static ObjectDescriptor {
+        self.registered(|registered| registered.descriptor)
+    }
+
+    /// How far the origin sits above the surface at scale 1 (see `ObjectType::rest_offset`)
+    pub fn rest_offset(self) -> f32 {
+        self.registered(|registered| registered.rest_offset)
+    }
+
+    /// The registered type saved under `key`
+    fn find(key: &str) -> Option<Self> {
+        let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+        let index = registry.iter().position(|registered| registered.descriptor.key == key)?;
+        Some(Self(index as u16))
+    }
+}
+
+impl Serialize for CustomType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.descriptor().key)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        Self::find(&key).ok_or_else(|| serde::de::Error::custom(format!("object type {:?} is not registered", key)))
+    }
+}
+
+/// Whether an object's JSON has a type this run can load: a built-in one, or
+/// a registered one (see `ObjectType::Custom`)
+pub fn is_loadable(object: &serde_json::Value) -> bool {
+    match object.get("object_type").and_then(|object_type| object_type.get("custom")) {
+        Some(key) => key.as_str().and_then(CustomType::find).is_some(),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::create_box;
+    use glam::Vec3;
+
+    #[test]
+    fn registered_types_round_trip_through_saves() {
+        let shape = CollisionShape::Box { half_extents: Vec3::new(0.1, 0.05, 0.1) };
+        let descriptor = ObjectDescriptor::new("test-coaster", "Coaster", "🟫", shape, |_, _| {
+            create_box(0.2, 0.1, 0.2, [1.0; 4], -0.02)
+        });
+        let coaster = register_object(descriptor).unwrap();
+        let again = ObjectDescriptor::new("test-coaster", "Coaster", "🟫", shape, |_, _| MeshData::new());
+        assert!(register_object(again).is_err());
+        let builtin = ObjectDescriptor::new("lamp", "Lamp", "💡", shape, |_, _| MeshData::new());
+        assert!(register_object(builtin).is_err());
+
+        assert!(registered_types().contains(&coaster));
+        assert_eq!(coaster.display_name(), "Coaster");
+        assert!((coaster.rest_offset() - 0.02).abs() < 1e-6);
+
+        let object = DeskObject::new(1, coaster, Vec3::ZERO);
+        let json = serde_json::to_value(&object).unwrap();
+        assert_eq!(json["object_type"], serde_json::json!({"custom": "test-coaster"}));
+        assert!(is_loadable(&json));
+        let loaded: DeskObject = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.object_type, coaster);
+
+        let unknown = serde_json::json!({"id": 2, "object_type": {"custom": "test-unregistered"}});
+        assert!(!is_loadable(&unknown));
+        assert!(is_loadable(&serde_json::json!({"id": 3, "object_type": "lamp"})));
+    }
+}
@@ -0,0 +1,1973 @@
+//! Renderer module
+//!
+//! Owns the wgpu device, pipelines and per-object GPU resources, and draws the
+//! desk scene into any color target: the window surface or an offscreen
+//! texture for thumbnails and tests.
+
+use crate::animation::{alert_pulse, idle_offset, spawn_transform};
+use crate::bloom::{BloomPass, BloomTargets};
+use crate::camera::{Camera, CameraUniform, MAX_LAMP_LIGHTS};
+use crate::config::{hex_to_rgb, hex_to_rgba, DeskMaterial, ShadowQuality, CONFIG};
+use crate::desk_object::{DeskObject, ImageSource, ObjectType};
+use crate::gizmo::{create_gizmo_mesh, GizmoHandle};
+use crate::mesh::{
+    create_box, create_grid_mesh, create_image_quad, create_ring_mesh, create_room_mesh, create_textured_quad, generate_object_mesh, MeshData,
+    TexturedVertex, Vertex,
+};
+use crate::state::AppState;
+use crate::texture::{generate_magazine_cover, generate_wood_texture, TextureKey, TextureManager};
+use crate::upscale::{internal_size, LowResTarget, UpscalePass};
+
+use glam::{Mat4, Quat, Vec3};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use wgpu::util::DeviceExt;
+
+/// How strongly focus mode dims everything but the focused object (0-1)
+const FOCUS_DIM: f32 = 0.8;
+
+/// Glow of the object a dragged file would be dropped on
+const DROP_TARGET_GLOW: f32 = 0.35;
+
+/// Direction towards the sun light (matches `light_dir` in shader.wgsl)
+const LIGHT_DIRECTION: Vec3 = Vec3::new(0.5, 1.0, 0.3);
+
+/// Segments in a blob shadow disc
+const BLOB_SEGMENTS: usize = 20;
+
+/// Height above the desk at which blob shadows fade out completely
+const BLOB_FADE_HEIGHT: f32 = 1.5;
+
+/// Model buffer slot holding the identity transform for static meshes
+const IDENTITY_SLOT: u32 = 0;
+
+/// Model buffer slot of the placement preview
+const GHOST_SLOT: u32 = 1;
+
+/// Model buffer slot of the transform gizmo
+const GIZMO_SLOT: u32 = 2;
+
+/// Model buffer slot of the desk: the identity transform with the desk
+/// material's shininess
+const DESK_SLOT: u32 = 3;
+
+/// Color format used for offscreen rendering
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Model uniform buffer data for per-object transforms
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ModelUniform {
+    model: [[f32; 4]; 4],
+    /// Opacity multiplier applied to the vertex alpha
+    opacity: f32,
+    /// 1.0 for the object spotlighted in focus mode (exempt from dimming)
+    highlight: f32,
+    /// Emissive brightness added on top of lighting (0 = none)
+    glow: f32,
+    /// Specular exponent of the sun's highlight (0 = matte)
+    shininess: f32,
+}
+
+impl ModelUniform {
+    pub fn new() -> Self {
+        Self {
+            model: Mat4::IDENTITY.to_cols_array_2d(),
+            opacity: 1.0,
+            highlight: 0.0,
+            glow: 0.0,
+            shininess: 0.0,
+        }
+    }
+
+    pub fn from_transform(position: Vec3, rotation: Quat, scale: f32) -> Self {
+        let model = Mat4::from_scale_rotation_translation(Vec3::splat(scale), rotation, position);
+        Self {
+            model: model.to_cols_array_2d(),
+            ..Self::new()
+        }
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_highlight(mut self, highlight: bool) -> Self {
+        self.highlight = if highlight { 1.0 } else { 0.0 };
+        self
+    }
+
+    pub fn with_glow(mut self, glow: f32) -> Self {
+        self.glow = glow;
+        self
+    }
+
+    pub fn with_shininess(mut self, shininess: f32) -> Self {
+        self.shininess = shininess;
+        self
+    }
+}
+
+/// GPU mesh handle
+pub struct GpuMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl GpuMesh {
+    fn from_mesh_data(device: &wgpu::Device, data: &MeshData) -> Self {
+        Self::new(device, &data.vertices, &data.indices)
+    }
+
+    fn new<V: bytemuck::Pod>(device: &wgpu::Device, vertices: &[V], indices: &[u16]) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Object Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Object Index Buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            num_indices: indices.len() as u32,
+        }
+    }
+
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.bind(render_pass);
+        self.draw_bound(render_pass);
+    }
+
+    /// Bind the vertex and index buffers, for several `draw_bound` calls
+    fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+    }
+
+    /// Draw, assuming this mesh is the one currently bound
+    fn draw_bound(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+/// One desk face: its four corners, outward normal and color
+type DeskFace = ([[f32; 3]; 4], [f32; 3], [f32; 4]);
+
+/// What an object's generated mesh depends on; objects with equal keys share a mesh
+type MeshKey = (ObjectType, u32, u32, u32);
+
+fn mesh_key(obj: &DeskObject) -> MeshKey {
+    (obj.object_type, obj.color, obj.accent_color, obj.book_count)
+}
+
+/// An object's (possibly shared) mesh and its slot in the model buffer
+struct ObjectGpuData {
+    mesh: Arc<GpuMesh>,
+    slot: u32,
+}
+
+impl ObjectGpuData {
+    /// Identifies the mesh, for grouping draws that share it
+    fn mesh_id(&self) -> usize {
+        Arc::as_ptr(&self.mesh) as usize
+    }
+}
+
+/// Image-mapped surface drawn on top of an object (e.g. a magazine cover)
+struct GpuTexturedSurface {
+    mesh: GpuMesh,
+    texture_key: TextureKey,
+}
+
+/// Size-dependent textures a frame is drawn with, besides its color target.
+/// Depth and bloom match the internal resolution when it is lowered.
+pub struct FrameTargets {
+    depth_view: wgpu::TextureView,
+    /// Offscreen scene and blur textures, if bloom is available
+    bloom: Option<BloomTargets>,
+    /// Frame upscaled to the color target, if the internal resolution is lowered
+    low_res: Option<LowResTarget>,
+}
+
+/// Scene renderer shared by the window and offscreen targets
+pub struct Renderer {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// Color format the pipelines were built for
+    color_format: wgpu::TextureFormat,
+    render_pipeline: wgpu::RenderPipeline,
+    /// Line-mode object pipeline, if the adapter supports it
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    line_pipeline: wgpu::RenderPipeline,
+    textured_pipeline: wgpu::RenderPipeline,
+    /// Depth-only pipeline rendering the scene from the light
+    shadow_pipeline: wgpu::RenderPipeline,
+    textures: TextureManager,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    /// Light view-projection used as the camera in the shadow pass
+    shadow_camera_bind_group: wgpu::BindGroup,
+    /// Depth target of the shadow pass, sampled by the main pass
+    shadow_map_view: wgpu::TextureView,
+    /// Camera uniform, updated in place each frame
+    camera_uniform: CameraUniform,
+    /// Model uniforms of everything drawn, one slot each: the identity
+    /// transform for static meshes, the placement preview, the gizmo, the
+    /// desk, then objects
+    model_buffer: wgpu::Buffer,
+    model_bind_group_layout: wgpu::BindGroupLayout,
+    /// Binds one model slot at a time through a dynamic offset
+    model_bind_group: wgpu::BindGroup,
+    /// Bytes between model slots (the uniform offset alignment)
+    model_stride: u64,
+    /// Number of slots the model buffer holds
+    model_capacity: u32,
+    /// Slots handed out so far, including the reserved ones
+    next_model_slot: u32,
+    /// Slots of removed objects, ready for reuse
+    free_model_slots: Vec<u32>,
+    /// Object meshes by what they depend on, shared between objects
+    mesh_cache: HashMap<MeshKey, Arc<GpuMesh>>,
+    desk_mesh: GpuMesh,
+    /// Textured top face of the desk, unless its material is flat
+    desk_top: Option<GpuTexturedSurface>,
+    floor_mesh: GpuMesh,
+    /// Walls around the desk, if the room is enabled
+    room_mesh: Option<GpuMesh>,
+    object_meshes: HashMap<u64, ObjectGpuData>,
+    /// Image quad shared by every object of a type that shows an image
+    image_quads: HashMap<ObjectType, GpuMesh>,
+    /// Image textures keyed by object ID, drawn on their type's image quad with the object's transform
+    object_surfaces: HashMap<u64, TextureKey>,
+    /// Object glowing as the target of a file being dragged over the window
+    drop_target: Option<u64>,
+    /// Mesh of the placement preview, while one is shown
+    ghost_mesh: Option<Arc<GpuMesh>>,
+    /// Transform gizmo on the selected object and the handle it was built
+    /// highlighting, while one is shown
+    gizmo: Option<(GpuMesh, Option<GizmoHandle>)>,
+    /// Whether objects are drawn as wireframes
+    wireframe: bool,
+    /// Debug overlay lines in world space (e.g. collision shapes)
+    debug_lines: Option<GpuMesh>,
+    /// Object kept fully lit while the rest of the scene is dimmed
+    focused_object: Option<u64>,
+    /// Idle motion amplitude (0 = off)
+    idle_amplitude: f32,
+    /// Seconds of idle motion each object has played, at its own speed
+    idle_times: HashMap<u64, f32>,
+    /// Objects held still during idle motion (e.g. dragged or selected)
+    idle_exempt: Vec<u64>,
+    shadows: ShadowQuality,
+    /// Low power mode, which turns shadows off without changing `shadows`
+    low_power: bool,
+    /// Discs under the objects: contact darkening, and blob shadows when
+    /// those are the shadow setting
+    shadow_decals: Option<GpuMesh>,
+    /// Layout grid lying on the desk, while the grid overlay is on
+    grid_mesh: Option<GpuMesh>,
+    /// Ring where the selected lamp's light reaches the surface under it
+    light_ring: Option<GpuMesh>,
+    /// Bloom post-process, unless disabled in `RenderConfig`
+    bloom: Option<BloomPass>,
+    /// Strength of the bloom (0 = off)
+    bloom_intensity: f32,
+    upscale: UpscalePass,
+    /// Divisor of the target size the scene is drawn at (1 = full resolution)
+    resolution_divisor: u32,
+}
+
+impl Renderer {
+    /// Create the pipelines and static meshes for the given color format
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue, color_format: wgpu::TextureFormat) -> Self {
+        // Create shader module
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+
+        // Create camera uniform buffer
+        let camera_uniform = CameraUniform::new();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Shadow map (a single texel stands in when shadow mapping is off)
+        let shadows = CONFIG.render.shadows;
+        let shadow_map_size = match shadows {
+            ShadowQuality::ShadowMap => CONFIG.render.shadow_map_size,
+            _ => 1,
+        };
+        let shadow_map_view = Self::depth_view(&device, shadow_map_size, shadow_map_size, "Shadow Map");
+        let shadow_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let camera_uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        // Create camera bind group layout
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    camera_uniform_entry,
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&shadow_map_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&shadow_sampler),
+                },
+            ],
+            label: Some("camera_bind_group"),
+        });
+
+        // The shadow pass can't sample the map it renders into, so it gets a
+        // camera layout with just the uniform
+        let shadow_camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[camera_uniform_entry],
+                label: Some("shadow_camera_bind_group_layout"),
+            });
+        let shadow_camera_uniform = CameraUniform {
+            view_proj: Self::light_view_projection().to_cols_array_2d(),
+            ..CameraUniform::new()
+        };
+        let shadow_camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Camera Buffer"),
+            contents: bytemuck::cast_slice(&[shadow_camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let shadow_camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &shadow_camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: shadow_camera_buffer.as_entire_binding(),
+            }],
+            label: Some("shadow_camera_bind_group"),
+        });
+
+        // Create model bind group layout for per-object transforms
+        let model_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<ModelUniform>() as u64),
+                    },
+                    count: None,
+                }],
+                label: Some("model_bind_group_layout"),
+            });
+
+        // Create render pipeline
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&camera_bind_group_layout, &model_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = Self::create_color_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            color_format,
+            "Render Pipeline",
+            wgpu::PrimitiveTopology::TriangleList,
+            wgpu::PolygonMode::Fill,
+        );
+
+        // Wireframe objects need POLYGON_MODE_LINE, which not every adapter has
+        let wireframe_pipeline = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| {
+                Self::create_color_pipeline(
+                    &device,
+                    &render_pipeline_layout,
+                    &shader,
+                    color_format,
+                    "Wireframe Pipeline",
+                    wgpu::PrimitiveTopology::TriangleList,
+                    wgpu::PolygonMode::Line,
+                )
+            });
+
+        // Debug lines (collision shapes) are drawn as a line list on every adapter
+        let line_pipeline = Self::create_color_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            color_format,
+            "Debug Line Pipeline",
+            wgpu::PrimitiveTopology::LineList,
+            wgpu::PolygonMode::Fill,
+        );
+
+        // Create pipeline for image-mapped surfaces (covers, photos)
+        let mut textures = TextureManager::new(&device, &queue);
+        let textured_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Textured Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera_bind_group_layout,
+                    &model_bind_group_layout,
+                    textures.bind_group_layout(),
+                ],
+                push_constant_ranges: &[],
+            });
+
+        let textured_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Textured Pipeline"),
+            layout: Some(&textured_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_textured",
+                buffers: &[TexturedVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_textured",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        let shadow_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Shadow Pipeline Layout"),
+                bind_group_layouts: &[&shadow_camera_bind_group_layout, &model_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let shadow_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&shadow_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_shadow",
+                buffers: &[Vertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // Thin and open meshes still need to cast shadows
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                // Pushes depths back slightly to avoid self-shadowing acne
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        // Room for a full desk up front, so the buffer rarely has to grow
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let model_stride = (std::mem::size_of::<ModelUniform>() as u64).div_ceil(alignment) * alignment;
+        let model_capacity = DESK_SLOT + 1 + CONFIG.objects.max_objects as u32;
+        let model_buffer = Self::create_model_buffer(&device, model_stride, model_capacity);
+        let model_bind_group = Self::create_model_bind_group(&device, &model_bind_group_layout, &model_buffer);
+        queue.write_buffer(
+            &model_buffer,
+            IDENTITY_SLOT as u64 * model_stride,
+            bytemuck::cast_slice(&[ModelUniform::new()]),
+        );
+        queue.write_buffer(
+            &model_buffer,
+            DESK_SLOT as u64 * model_stride,
+            bytemuck::cast_slice(&[ModelUniform::new().with_shininess(CONFIG.desk.material.shininess())]),
+        );
+
+        // The light never moves, so its view-projection is set once
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.light_view_proj = Self::light_view_projection().to_cols_array_2d();
+        camera_uniform.scene[1] = if shadows == ShadowQuality::ShadowMap { 1.0 } else { 0.0 };
+        camera_uniform.scene[2] = if CONFIG.render.specular { CONFIG.render.specular_strength } else { 0.0 };
+        let (r, g, b) = hex_to_rgb(CONFIG.render.lamp_light_color);
+        camera_uniform.lamp_color = [r, g, b, 0.0].map(|c| c * CONFIG.render.lamp_light_intensity.max(0.0));
+
+        // Create static meshes
+        let desk_top = Self::create_desk_top(&device, &queue, &mut textures);
+        let desk_mesh = Self::create_desk_mesh(&device, desk_top.is_none());
+        let floor_mesh = Self::create_floor_mesh(&device);
+        let image_quads = ObjectType::all()
+            .iter()
+            .filter_map(|&object_type| {
+                let (vertices, indices) = create_image_quad(object_type)?;
+                Some((object_type, GpuMesh::new(&device, &vertices, &indices)))
+            })
+            .collect();
+        let room_mesh = CONFIG.room.enabled.then(|| Self::create_room_mesh(&device));
+        let bloom = CONFIG.render.bloom.then(|| BloomPass::new(&device, color_format));
+        let upscale = UpscalePass::new(&device, color_format);
+
+        Self {
+            device,
+            queue,
+            color_format,
+            render_pipeline,
+            wireframe_pipeline,
+            line_pipeline,
+            textured_pipeline,
+            shadow_pipeline,
+            textures,
+            camera_buffer,
+            camera_bind_group,
+            shadow_camera_bind_group,
+            shadow_map_view,
+            camera_uniform,
+            model_buffer,
+            model_bind_group_layout,
+            model_bind_group,
+            model_stride,
+            model_capacity,
+            next_model_slot: DESK_SLOT + 1,
+            free_model_slots: Vec::new(),
+            mesh_cache: HashMap::new(),
+            desk_mesh,
+            desk_top,
+            floor_mesh,
+            room_mesh,
+            object_meshes: HashMap::new(),
+            image_quads,
+            object_surfaces: HashMap::new(),
+            drop_target: None,
+            ghost_mesh: None,
+            gizmo: None,
+            wireframe: false,
+            debug_lines: None,
+            focused_object: None,
+            idle_amplitude: 0.0,
+            idle_times: HashMap::new(),
+            idle_exempt: Vec::new(),
+            shadows,
+            low_power: false,
+            shadow_decals: None,
+            grid_mesh: None,
+            light_ring: None,
+            bloom,
+            bloom_intensity: CONFIG.render.bloom_intensity,
+            upscale,
+            resolution_divisor: CONFIG.render.internal_resolution_divisor,
+        }
+    }
+
+    /// Orthographic view-projection of the sun, covering the desk and the
+    /// floor around it that objects can be dragged onto
+    fn light_view_projection() -> Mat4 {
+        let margin = 2.0 * CONFIG.desk.floor_margin;
+        let half_diagonal = (CONFIG.desk.width + margin).hypot(CONFIG.desk.depth + margin) / 2.0 + 1.0;
+        let target = Vec3::new(0.0, CONFIG.desk.top_y(), 0.0);
+        let eye = target + LIGHT_DIRECTION.normalize() * 20.0;
+        let view = Mat4::look_at_rh(eye, target, Vec3::Y);
+        let projection = Mat4::orthographic_rh(
+            -half_diagonal,
+            half_diagonal,
+            -half_diagonal,
+            half_diagonal,
+            1.0,
+            40.0,
+        );
+        projection * view
+    }
+
+    /// Build a pipeline for `Vertex` meshes with the shared camera/model layout
+    fn create_color_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        color_format: wgpu::TextureFormat,
+        label: &str,
+        topology: wgpu::PrimitiveTopology,
+        polygon_mode: wgpu::PolygonMode,
+    ) -> wgpu::RenderPipeline {
+        let is_lines = topology == wgpu::PrimitiveTopology::LineList;
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // Wireframes and debug lines should show back edges too
+                cull_mode: (polygon_mode == wgpu::PolygonMode::Fill && !is_lines)
+                    .then_some(wgpu::Face::Back),
+                polygon_mode,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                // Debug lines draw on top of everything so hidden shapes stay visible
+                depth_write_enabled: !is_lines,
+                depth_compare: if is_lines {
+                    wgpu::CompareFunction::Always
+                } else {
+                    wgpu::CompareFunction::Less
+                },
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Switch object rendering to wireframe, returning whether it is now active
+    /// (always `false` when the adapter lacks line polygon mode)
+    pub fn set_wireframe(&mut self, enabled: bool) -> bool {
+        self.wireframe = enabled && self.wireframe_pipeline.is_some();
+        self.wireframe
+    }
+
+    /// Replace the debug overlay lines, or clear them with `None`
+    pub fn set_debug_lines(&mut self, lines: Option<&MeshData>) {
+        self.debug_lines = lines
+            .filter(|mesh| !mesh.indices.is_empty())
+            .map(|mesh| GpuMesh::from_mesh_data(&self.device, mesh));
+    }
+
+    /// Current idle motion amplitude (0 = off)
+    pub fn idle_amplitude(&self) -> f32 {
+        self.idle_amplitude
+    }
+
+    /// Set the idle motion applied to drawn objects (not their stored
+    /// transforms) and move it on by `dt` seconds, scaled by each object's
+    /// animation speed so changing it doesn't jump. The `exempt` objects are
+    /// kept still. Takes effect as object transforms are next updated.
+    pub fn set_idle_motion(&mut self, amplitude: f32, dt: f32, objects: &[DeskObject], exempt: &[u64]) {
+        self.idle_amplitude = amplitude;
+        self.idle_times = objects
+            .iter()
+            .map(|obj| {
+                let time = self.idle_times.get(&obj.id).copied().unwrap_or(0.0);
+                (obj.id, time + dt * obj.anim_speed)
+            })
+            .collect();
+        self.idle_exempt.clear();
+        self.idle_exempt.extend_from_slice(exempt);
+    }
+
+    /// Show or hide the layout grid on the desk surface
+    pub fn set_grid_visible(&mut self, visible: bool) {
+        self.grid_mesh = visible.then(|| {
+            let grid = &CONFIG.grid;
+            let (r, g, b) = hex_to_rgb(grid.color);
+            let mesh = create_grid_mesh(
+                CONFIG.desk.width,
+                CONFIG.desk.depth,
+                // Just above the desk top so the lines don't z-fight with it
+                CONFIG.desk.top_y() + 0.002,
+                grid.spacing,
+                grid.line_width,
+                [r, g, b, grid.opacity.clamp(0.0, 1.0)],
+            );
+            GpuMesh::from_mesh_data(&self.device, &mesh)
+        });
+    }
+
+    /// Light the scene with the switched-on lamps, as of the next camera update
+    pub fn update_lamps(&mut self, objects: &[DeskObject]) {
+        let mut lights: Vec<(Vec3, f32)> = objects.iter().filter_map(DeskObject::light_source).collect();
+        lights.sort_by(|a, b| a.0.length_squared().total_cmp(&b.0.length_squared()));
+        lights.truncate(MAX_LAMP_LIGHTS);
+        for (slot, (bulb, radius)) in self.camera_uniform.lamps.iter_mut().zip(&lights) {
+            *slot = bulb.extend(*radius).to_array();
+        }
+        self.camera_uniform.scene[3] = lights.len() as f32;
+    }
+
+    /// Show a faint ring of the given radius lying flat around `center`, or
+    /// hide it with `None`
+    pub fn set_light_ring(&mut self, ring: Option<(Vec3, f32)>) {
+        self.light_ring = ring.map(|(center, radius)| {
+            let (r, g, b) = hex_to_rgb(CONFIG.render.lamp_light_color);
+            let color = [r, g, b, CONFIG.render.light_ring_opacity.clamp(0.0, 1.0)];
+            // Just above the surface so the ring doesn't z-fight with it
+            let center = center + Vec3::Y * 0.003;
+            GpuMesh::from_mesh_data(&self.device, &create_ring_mesh(center, radius, 0.03, 64, color))
+        });
+    }
+
+    /// Upload the camera's view-projection for the next frame
+    pub fn update_camera(&mut self, camera: &Camera) {
+        self.camera_uniform.update(camera);
+        self.camera_uniform.scene[0] = self.focus_dim();
+        self.queue
+            .write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+    }
+
+    /// Turn low power mode on or off; while on, no shadows or bloom are drawn
+    pub fn set_low_power(&mut self, enabled: bool) {
+        self.low_power = enabled;
+        self.camera_uniform.scene[1] = if self.shadow_quality() == ShadowQuality::ShadowMap { 1.0 } else { 0.0 };
+        if enabled {
+            self.shadow_decals = None;
+        }
+    }
+
+    /// Set how strongly bright parts of the scene bleed light (0 = off)
+    pub fn set_bloom_intensity(&mut self, intensity: f32) {
+        self.bloom_intensity = intensity;
+    }
+
+    /// Draw the scene at 1/`divisor` of the target size, upscaled with nearest
+    /// filtering. Takes effect for frame targets created afterwards.
+    pub fn set_resolution_divisor(&mut self, divisor: u32) {
+        self.resolution_divisor = divisor.max(1);
+    }
+
+    /// Shadows currently drawn: the configured quality unless low power mode is on
+    fn shadow_quality(&self) -> ShadowQuality {
+        if self.low_power {
+            ShadowQuality::Off
+        } else {
+            self.shadows
+        }
+    }
+
+    /// Rebuild the decals under the objects: contact darkening where they
+    /// touch the desk or floor (not in low power mode), and blob shadows when
+    /// that is the shadow setting, which objects far from the camera cast
+    /// fainter or not at all
+    pub fn update_shadows(&mut self, objects: &[DeskObject], camera: &Camera) {
+        let blobs = self.shadow_quality() == ShadowQuality::Blob;
+        let contact = !self.low_power && CONFIG.render.contact_shadow_opacity > 0.0;
+        if !blobs && !contact {
+            return;
+        }
+
+        let (half_width, half_depth) = (CONFIG.desk.width / 2.0, CONFIG.desk.depth / 2.0);
+        // Contact darkening goes first so the blob shadows, drawn just above
+        // it, aren't hidden behind it by the depth test
+        let mut mesh = MeshData::new();
+        let mut blob_mesh = MeshData::new();
+        for obj in objects.iter().filter(|obj| !obj.hidden) {
+            // Objects off the desk cast onto the floor
+            let over_desk = obj.position.x.abs() <= half_width && obj.position.z.abs() <= half_depth;
+            let surface_y = if over_desk { CONFIG.desk.top_y() } else { 0.0 };
+            let base_y = obj.position.y - obj.base_offset();
+            let shape = obj.collision_shape();
+
+            if contact {
+                let opacity = contact_shadow_opacity(base_y - surface_y, shape.height());
+                if opacity > 0.0 {
+                    // Solid under the footprint, fading out just past it
+                    let extents = shape.footprint_extents(Quat::IDENTITY);
+                    let inner = extents.x.min(extents.y);
+                    let outer = shape.bounding_radius() + CONFIG.render.contact_shadow_radius;
+                    let center = Vec3::new(obj.position.x, surface_y + 0.001, obj.position.z);
+                    Self::add_contact_shadow(&mut mesh, center, inner, outer, opacity);
+                }
+            }
+            if !blobs {
+                continue;
+            }
+
+            // Shadows fade as objects are lifted off the surface
+            let fade = 1.0 - ((base_y - surface_y) / BLOB_FADE_HEIGHT).clamp(0.0, 1.0);
+            let opacity = CONFIG.render.blob_shadow_opacity
+                * fade
+                * shadow_distance_fade(camera.position.distance(obj.position));
+            if opacity <= 0.0 {
+                continue;
+            }
+            let radius = shape.bounding_radius() * 1.1;
+            // Just above the surface so the disc doesn't z-fight with it
+            let center = Vec3::new(obj.position.x, surface_y + 0.0015, obj.position.z);
+            Self::add_blob(&mut blob_mesh, center, radius, opacity);
+        }
+        mesh.merge(blob_mesh);
+        self.shadow_decals = (!mesh.indices.is_empty() && mesh.vertices.len() <= u16::MAX as usize)
+            .then(|| GpuMesh::from_mesh_data(&self.device, &mesh));
+    }
+
+    /// Append a flat upward-facing disc that is `opacity` dark out to
+    /// `inner_radius` and fades to clear at `outer_radius`
+    fn add_contact_shadow(mesh: &mut MeshData, center: Vec3, inner_radius: f32, outer_radius: f32, opacity: f32) {
+        let normal = [0.0, 1.0, 0.0];
+        let dark = [0.0, 0.0, 0.0, opacity];
+        let base = mesh.vertices.len() as u16;
+        mesh.vertices.push(Vertex { position: center.to_array(), normal, color: dark });
+        for i in 0..BLOB_SEGMENTS {
+            // Negative angle keeps the triangles counter-clockwise seen from above
+            let angle = -(i as f32) / BLOB_SEGMENTS as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            for (radius, color) in [(inner_radius, dark), (outer_radius, [0.0; 4])] {
+                mesh.vertices.push(Vertex {
+                    position: [center.x + radius * cos, center.y, center.z + radius * sin],
+                    normal,
+                    color,
+                });
+            }
+        }
+        for i in 0..BLOB_SEGMENTS as u16 {
+            let next = (i + 1) % BLOB_SEGMENTS as u16;
+            let (inner, outer) = (base + 1 + 2 * i, base + 2 + 2 * i);
+            let (next_inner, next_outer) = (base + 1 + 2 * next, base + 2 + 2 * next);
+            mesh.indices.extend_from_slice(&[base, inner, next_inner]);
+            mesh.indices.extend_from_slice(&[inner, outer, next_outer, inner, next_outer, next_inner]);
+        }
+    }
+
+    /// Append a flat upward-facing shadow disc that fades towards its rim
+    fn add_blob(mesh: &mut MeshData, center: Vec3, radius: f32, opacity: f32) {
+        let normal = [0.0, 1.0, 0.0];
+        let base = mesh.vertices.len() as u16;
+        mesh.vertices.push(Vertex {
+            position: center.to_array(),
+            normal,
+            color: [0.0, 0.0, 0.0, opacity],
+        });
+        for i in 0..BLOB_SEGMENTS {
+            // Negative angle keeps the fan counter-clockwise seen from above
+            let angle = -(i as f32) / BLOB_SEGMENTS as f32 * std::f32::consts::TAU;
+            mesh.vertices.push(Vertex {
+                position: [
+                    center.x + radius * angle.cos(),
+                    center.y,
+                    center.z + radius * angle.sin(),
+                ],
+                normal,
+                color: [0.0, 0.0, 0.0, 0.0],
+            });
+        }
+        for i in 0..BLOB_SEGMENTS as u16 {
+            let next = (i + 1) % BLOB_SEGMENTS as u16;
+            mesh.indices.extend_from_slice(&[base, base + 1 + i, base + 1 + next]);
+        }
+    }
+
+    pub fn rebuild_object_meshes(&mut self, objects: &[DeskObject]) {
+        self.clear_object_meshes();
+        for obj in objects {
+            self.create_object_mesh(obj);
+        }
+    }
+
+    /// Spotlight one object and dim the rest of the scene, or clear with `None`
+    pub fn set_focus(&mut self, objects: &[DeskObject], focus: Option<u64>) {
+        self.focused_object = focus;
+        for obj in objects {
+            self.update_object_transform(obj);
+        }
+    }
+
+    /// Object currently spotlighted by focus mode
+    pub fn focused_object(&self) -> Option<u64> {
+        self.focused_object
+    }
+
+    /// Dim amount currently applied to unfocused surfaces
+    fn focus_dim(&self) -> f32 {
+        if self.focused_object.is_some() {
+            FOCUS_DIM
+        } else {
+            0.0
+        }
+    }
+
+    /// Model uniform for an object's transform (including any spawn
+    /// animation or alert pulse), focus highlight and glow
+    fn object_uniform(&self, obj: &DeskObject) -> ModelUniform {
+        let (lift, scale) = obj
+            .spawn_progress
+            .map_or((0.0, 1.0), |progress| spawn_transform(CONFIG.animation.spawn, progress));
+        let (alert_scale, mut glow) = obj.alert_remaining(Instant::now()).map_or((1.0, 0.0), alert_pulse);
+        if self.drop_target == Some(obj.id) {
+            glow = glow.max(DROP_TARGET_GLOW);
+        }
+        let (idle_lift, idle_yaw) = if obj.object_type.is_decal() || self.idle_exempt.contains(&obj.id) {
+            (0.0, 0.0)
+        } else {
+            let time = self.idle_times.get(&obj.id).copied().unwrap_or(0.0);
+            idle_offset(obj.id, time, self.idle_amplitude)
+        };
+        let position = obj.position + Vec3::Y * (lift + idle_lift);
+        let rotation = Quat::from_rotation_y(idle_yaw) * obj.rotation;
+        ModelUniform::from_transform(position, rotation, obj.scale * scale * alert_scale)
+            .with_highlight(self.focused_object == Some(obj.id))
+            .with_glow(glow)
+            .with_shininess(obj.object_type.shininess())
+    }
+
+    pub fn create_object_mesh(&mut self, obj: &DeskObject) {
+        let mesh = self.object_mesh(obj);
+        // Rebuilding an object (e.g. after a color change) keeps its slot
+        let slot = match self.object_meshes.get(&obj.id) {
+            Some(existing) => existing.slot,
+            None => self.allocate_model_slot(),
+        };
+        self.object_meshes.insert(obj.id, ObjectGpuData { mesh, slot });
+        self.write_model_uniform(slot, self.object_uniform(obj));
+
+        match self.object_image_texture(obj) {
+            Some(texture_key) => {
+                self.object_surfaces.insert(obj.id, texture_key);
+            }
+            None => {
+                self.object_surfaces.remove(&obj.id);
+            }
+        }
+        self.prune_mesh_cache();
+    }
+
+    /// Let an image file that failed to load be read again on next use
+    pub fn retry_image(&mut self, path: &Path) {
+        self.textures.retry_path(path);
+    }
+
+    pub fn remove_object_mesh(&mut self, id: u64) {
+        if let Some(data) = self.object_meshes.remove(&id) {
+            self.free_model_slots.push(data.slot);
+        }
+        self.object_surfaces.remove(&id);
+        self.prune_mesh_cache();
+    }
+
+    pub fn clear_object_meshes(&mut self) {
+        self.object_meshes.clear();
+        self.object_surfaces.clear();
+        self.free_model_slots.clear();
+        self.next_model_slot = DESK_SLOT + 1;
+        self.prune_mesh_cache();
+    }
+
+    /// The mesh for an object, shared with any object that looks the same
+    fn object_mesh(&mut self, obj: &DeskObject) -> Arc<GpuMesh> {
+        let device = &self.device;
+        self.mesh_cache
+            .entry(mesh_key(obj))
+            .or_insert_with(|| Arc::new(GpuMesh::from_mesh_data(device, &generate_object_mesh(obj))))
+            .clone()
+    }
+
+    /// Drop cached meshes no object or preview uses any more
+    fn prune_mesh_cache(&mut self) {
+        self.mesh_cache.retain(|_, mesh| Arc::strong_count(mesh) > 1);
+    }
+
+    /// A free slot in the model buffer, growing the buffer if it's full
+    fn allocate_model_slot(&mut self) -> u32 {
+        if let Some(slot) = self.free_model_slots.pop() {
+            return slot;
+        }
+        if self.next_model_slot == self.model_capacity {
+            self.grow_model_buffer();
+        }
+        self.next_model_slot += 1;
+        self.next_model_slot - 1
+    }
+
+    /// Double the model buffer's capacity, keeping the uniforms written so far
+    fn grow_model_buffer(&mut self) {
+        let capacity = self.model_capacity * 2;
+        let buffer = Self::create_model_buffer(&self.device, self.model_stride, capacity);
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Model Buffer Copy Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&self.model_buffer, 0, &buffer, 0, self.model_buffer.size());
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.model_bind_group = Self::create_model_bind_group(&self.device, &self.model_bind_group_layout, &buffer);
+        self.model_buffer = buffer;
+        self.model_capacity = capacity;
+    }
+
+    fn create_model_buffer(device: &wgpu::Device, stride: u64, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Model Buffer"),
+            size: stride * capacity as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_model_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<ModelUniform>() as u64),
+                }),
+            }],
+            label: Some("model_bind_group"),
+        })
+    }
+
+    /// Dynamic offset binding a model slot
+    fn model_offset(&self, slot: u32) -> [wgpu::DynamicOffset; 1] {
+        [(slot as u64 * self.model_stride) as wgpu::DynamicOffset]
+    }
+
+    /// Image texture for objects that display one: the chosen image, or a
+    /// procedural cover for magazines without one
+    fn object_image_texture(&mut self, obj: &DeskObject) -> Option<TextureKey> {
+        if !obj.object_type.shows_image() {
+            return None;
+        }
+
+        let texture_key = match &obj.cover_image {
+            Some(ImageSource::Path(path)) => {
+                let path = std::path::PathBuf::from(path);
+                self.textures.get_or_load(&self.device, &self.queue, &path);
+                TextureKey::Path(path)
+            }
+            &Some(ImageSource::Builtin { builtin }) => {
+                self.textures.get_or_decode_builtin(&self.device, &self.queue, builtin);
+                TextureKey::Builtin(builtin)
+            }
+            None if obj.object_type == ObjectType::Magazine => {
+                let name = format!("magazine-cover-{:06x}-{:06x}", obj.color, obj.accent_color);
+                self.textures.get_or_generate(&self.device, &self.queue, &name, || {
+                    generate_magazine_cover(obj.color, obj.accent_color)
+                });
+                TextureKey::Generated(name)
+            }
+            None => return None,
+        };
+        Some(texture_key)
+    }
+
+    /// Make an object glow as the target of a file being dragged over the
+    /// window. Takes effect as object transforms are next updated.
+    pub fn set_drop_target(&mut self, id: Option<u64>) {
+        self.drop_target = id;
+    }
+
+    /// Show a placement preview of an object, drawn with the given transform
+    pub fn set_ghost(&mut self, preview: &DeskObject, model_uniform: ModelUniform) {
+        self.ghost_mesh = Some(self.object_mesh(preview));
+        self.write_model_uniform(GHOST_SLOT, model_uniform);
+    }
+
+    /// Move the placement preview
+    pub fn update_ghost(&self, model_uniform: ModelUniform) {
+        self.write_model_uniform(GHOST_SLOT, model_uniform);
+    }
+
+    /// Hide the placement preview
+    pub fn clear_ghost(&mut self) {
+        self.ghost_mesh = None;
+        self.prune_mesh_cache();
+    }
+
+    /// Show the transform gizmo at `center` with the given size, highlighting
+    /// the `active` handle, or hide it with `None`
+    pub fn set_gizmo(&mut self, gizmo: Option<(Vec3, f32, Option<GizmoHandle>)>) {
+        let Some((center, size, active)) = gizmo else {
+            self.gizmo = None;
+            return;
+        };
+        if self.gizmo.as_ref().map(|(_, built)| *built) != Some(active) {
+            let mesh = GpuMesh::from_mesh_data(&self.device, &create_gizmo_mesh(active));
+            self.gizmo = Some((mesh, active));
+        }
+        self.write_model_uniform(
+            GIZMO_SLOT,
+            ModelUniform::from_transform(center, Quat::IDENTITY, size).with_highlight(true),
+        );
+    }
+
+    /// Write a model uniform into a slot of the model buffer
+    fn write_model_uniform(&self, slot: u32, model_uniform: ModelUniform) {
+        self.queue.write_buffer(
+            &self.model_buffer,
+            slot as u64 * self.model_stride,
+            bytemuck::cast_slice(&[model_uniform]),
+        );
+    }
+
+    /// Sync an object's GPU transform with its position, rotation and scale
+    pub fn update_object_transform(&self, obj: &DeskObject) {
+        if let Some(data) = self.object_meshes.get(&obj.id) {
+            self.write_model_uniform(data.slot, self.object_uniform(obj));
+        }
+    }
+
+    /// Create the depth buffer and offscreen textures for a color target's size
+    pub fn create_frame_targets(&self, width: u32, height: u32) -> FrameTargets {
+        let (internal_width, internal_height) = internal_size(width, height, self.resolution_divisor);
+        let low_res = (self.resolution_divisor > 1).then(|| {
+            self.upscale
+                .create_target(&self.device, self.color_format, internal_width, internal_height)
+        });
+        FrameTargets {
+            depth_view: Self::depth_view(&self.device, internal_width, internal_height, "Depth Texture"),
+            bloom: self.bloom.as_ref().map(|bloom| {
+                bloom.create_targets(&self.device, self.color_format, internal_width, internal_height)
+            }),
+            low_res,
+        }
+    }
+
+    /// Create a depth texture that can be rendered to and sampled
+    fn depth_view(device: &wgpu::Device, width: u32, height: u32, label: &str) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Draw the floor, desk, objects and, if `with_overlays`, the placement
+    /// preview and transform gizmo into a target, adding bloom unless it is
+    /// off or low power mode is on, and upscaling if the internal resolution
+    /// is lowered
+    pub fn render_scene(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        targets: &FrameTargets,
+        camera: &Camera,
+        objects: &[DeskObject],
+        with_overlays: bool,
+    ) {
+        let bloom = self
+            .bloom
+            .as_ref()
+            .zip(targets.bloom.as_ref())
+            .filter(|_| self.bloom_intensity > 0.0 && !self.low_power);
+        let output = targets.low_res.as_ref().map_or(view, |low_res| &low_res.view);
+        match bloom {
+            Some((pass, bloom_targets)) => {
+                let scene_view = &bloom_targets.scene_view;
+                self.draw_scene(encoder, scene_view, &targets.depth_view, camera, objects, with_overlays);
+                pass.apply(&self.queue, encoder, bloom_targets, output, self.bloom_intensity);
+            }
+            None => self.draw_scene(encoder, output, &targets.depth_view, camera, objects, with_overlays),
+        }
+        if let Some((mesh, _)) = self.gizmo.as_ref().filter(|_| with_overlays) {
+            self.draw_gizmo(encoder, output, &targets.depth_view, mesh);
+        }
+        if let Some(low_res) = &targets.low_res {
+            self.upscale.apply(encoder, low_res, view);
+        }
+    }
+
+    /// Draw the scene itself into a color target, before any post-processing
+    fn draw_scene(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        camera: &Camera,
+        objects: &[DeskObject],
+        with_overlays: bool,
+    ) {
+        // Decals last in stored order; solids grouped so each mesh is bound once
+        let mut draw_order = sort_objects_by_depth(camera, objects);
+        group_solids_by_mesh(objects, &mut draw_order, |obj| {
+            self.object_meshes.get(&obj.id).map_or(0, ObjectGpuData::mesh_id)
+        });
+
+        if self.shadow_quality() == ShadowQuality::ShadowMap {
+            // Objects beyond the shadow distance are left out of the shadow pass
+            let casters: Vec<usize> = draw_order
+                .iter()
+                .copied()
+                .filter(|&i| shadow_distance_fade(camera.position.distance(objects[i].position)) > 0.0)
+                .collect();
+            self.render_shadow_map(encoder, objects, &casters);
+        }
+
+        // The background dims along with the scene in focus mode
+        let bg_color = hex_to_rgba(CONFIG.colors.background).map(|c| c * (1.0 - self.focus_dim() * 0.65));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: bg_color[0] as f64,
+                        g: bg_color[1] as f64,
+                        b: bg_color[2] as f64,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let object_pipeline = match &self.wireframe_pipeline {
+            Some(pipeline) if self.wireframe => pipeline,
+            _ => &self.render_pipeline,
+        };
+
+        render_pass.set_pipeline(object_pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(IDENTITY_SLOT));
+
+        // Render floor and desk
+        self.floor_mesh.draw(&mut render_pass);
+        if let Some(room) = &self.room_mesh {
+            // Filled even in wireframe view, which would not cull the near walls
+            render_pass.set_pipeline(&self.render_pipeline);
+            room.draw(&mut render_pass);
+            render_pass.set_pipeline(object_pipeline);
+        }
+        render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(DESK_SLOT));
+        self.desk_mesh.draw(&mut render_pass);
+        if let Some(top) = &self.desk_top {
+            render_pass.set_pipeline(&self.textured_pipeline);
+            render_pass.set_bind_group(2, &self.textures.get(&top.texture_key).bind_group, &[]);
+            top.mesh.draw(&mut render_pass);
+            render_pass.set_pipeline(object_pipeline);
+        }
+        render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(IDENTITY_SLOT));
+        if let Some(decals) = &self.shadow_decals {
+            decals.draw(&mut render_pass);
+        }
+        if let Some(grid) = &self.grid_mesh {
+            // Always filled, even in wireframe view
+            render_pass.set_pipeline(&self.render_pipeline);
+            grid.draw(&mut render_pass);
+            render_pass.set_pipeline(object_pipeline);
+        }
+        if let Some(ring) = self.light_ring.as_ref().filter(|_| with_overlays) {
+            render_pass.set_pipeline(&self.render_pipeline);
+            ring.draw(&mut render_pass);
+            render_pass.set_pipeline(object_pipeline);
+        }
+
+        // Render objects with their transforms
+        self.draw_objects(&mut render_pass, objects, &draw_order);
+
+        // Render images on their type's shared quad using their object's transform
+        if !self.object_surfaces.is_empty() {
+            render_pass.set_pipeline(&self.textured_pipeline);
+            let mut bound_type = None;
+            for obj in draw_order.iter().map(|&i| &objects[i]) {
+                let Some(texture_key) = self.object_surfaces.get(&obj.id) else { continue };
+                let Some(data) = self.object_meshes.get(&obj.id) else { continue };
+                let Some(quad) = self.image_quads.get(&obj.object_type) else { continue };
+                if bound_type != Some(obj.object_type) {
+                    quad.bind(&mut render_pass);
+                    bound_type = Some(obj.object_type);
+                }
+                render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(data.slot));
+                render_pass.set_bind_group(2, &self.textures.get(texture_key).bind_group, &[]);
+                quad.draw_bound(&mut render_pass);
+            }
+            render_pass.set_pipeline(object_pipeline);
+        }
+
+        // Render placement preview last so it blends over the scene
+        if let Some(mesh) = self.ghost_mesh.as_ref().filter(|_| with_overlays) {
+            render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(GHOST_SLOT));
+            mesh.draw(&mut render_pass);
+        }
+
+        // Debug lines are already in world space
+        if let Some(lines) = &self.debug_lines {
+            render_pass.set_pipeline(&self.line_pipeline);
+            render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(IDENTITY_SLOT));
+            lines.draw(&mut render_pass);
+        }
+    }
+
+    /// Draw the gizmo over everything already in `view`, after post-processing
+    /// so it neither blooms nor hides behind the object it is on
+    fn draw_gizmo(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        mesh: &GpuMesh,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gizmo Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            // A fresh depth buffer so the gizmo only occludes itself
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(GIZMO_SLOT));
+        mesh.draw(&mut render_pass);
+    }
+
+    /// Draw objects in the given order with their model slots, binding each
+    /// mesh's buffers only when it differs from the previous object's
+    fn draw_objects<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, objects: &[DeskObject], order: &[usize]) {
+        let mut bound_mesh = None;
+        for obj in order.iter().map(|&i| &objects[i]) {
+            let Some(data) = self.object_meshes.get(&obj.id) else { continue };
+            render_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(data.slot));
+            if bound_mesh != Some(data.mesh_id()) {
+                data.mesh.bind(render_pass);
+                bound_mesh = Some(data.mesh_id());
+            }
+            data.mesh.draw_bound(render_pass);
+        }
+    }
+
+    /// Render the desk and objects' depth from the light into the shadow map
+    fn render_shadow_map(&self, encoder: &mut wgpu::CommandEncoder, objects: &[DeskObject], draw_order: &[usize]) {
+        let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.shadow_map_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        shadow_pass.set_pipeline(&self.shadow_pipeline);
+        shadow_pass.set_bind_group(0, &self.shadow_camera_bind_group, &[]);
+        shadow_pass.set_bind_group(1, &self.model_bind_group, &self.model_offset(IDENTITY_SLOT));
+        self.desk_mesh.draw(&mut shadow_pass);
+        self.draw_objects(&mut shadow_pass, objects, draw_order);
+    }
+
+    /// Render the scene from a camera into an offscreen texture and read it back.
+    /// Sizes beyond the GPU's texture limit are an error.
+    pub fn render_to_image(
+        &mut self,
+        objects: &[DeskObject],
+        camera: &Camera,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        if width > max_size || height > max_size {
+            return Err(format!(
+                "{}x{} is larger than this GPU can render ({} pixels per side at most)",
+                width, height, max_size
+            )
+            .into());
+        }
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let target = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.color_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let frame_targets = self.create_frame_targets(width, height);
+
+        // Rows in a texture-to-buffer copy must be padded to 256 bytes
+        let unpadded_row = 4 * width;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = unpadded_row.div_ceil(align) * align;
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback Buffer"),
+            size: (padded_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        self.update_camera(camera);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Encoder"),
+            });
+        self.render_scene(&mut encoder, &view, &frame_targets, camera, objects, false);
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            size,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let mut pixels = Vec::with_capacity((unpadded_row * height) as usize);
+        for row in slice.get_mapped_range().chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+        readback.unmap();
+
+        // Surface formats are often BGRA; the image is always RGBA
+        if matches!(
+            self.color_format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "Offscreen image has an unexpected size".into())
+    }
+
+    /// The desk top as a box on its legs; the top face is left out when a
+    /// textured top covers it
+    fn create_desk_mesh(device: &wgpu::Device, with_top: bool) -> GpuMesh {
+        let desk = &CONFIG.desk;
+        let (r, g, b) = hex_to_rgb(desk.color);
+        let hw = desk.width / 2.0;
+        let hd = desk.depth / 2.0;
+        let (y0, h) = (desk.surface_height, desk.top_y());
+
+        let top_color = [r, g, b, 1.0];
+        let side_color = [r * 0.8, g * 0.8, b * 0.8, 1.0];
+        let bottom_color = [r * 0.6, g * 0.6, b * 0.6, 1.0];
+
+        // Each face is wound counter-clockwise when viewed from outside the desk
+        let faces: [DeskFace; 6] = [
+            // Top
+            (
+                [[-hw, h, hd], [hw, h, hd], [hw, h, -hd], [-hw, h, -hd]],
+                [0.0, 1.0, 0.0],
+                top_color,
+            ),
+            // Bottom
+            (
+                [[-hw, y0, -hd], [hw, y0, -hd], [hw, y0, hd], [-hw, y0, hd]],
+                [0.0, -1.0, 0.0],
+                bottom_color,
+            ),
+            // Front
+            (
+                [[-hw, y0, hd], [hw, y0, hd], [hw, h, hd], [-hw, h, hd]],
+                [0.0, 0.0, 1.0],
+                side_color,
+            ),
+            // Back
+            (
+                [[hw, y0, -hd], [-hw, y0, -hd], [-hw, h, -hd], [hw, h, -hd]],
+                [0.0, 0.0, -1.0],
+                side_color,
+            ),
+            // Right
+            (
+                [[hw, y0, hd], [hw, y0, -hd], [hw, h, -hd], [hw, h, hd]],
+                [1.0, 0.0, 0.0],
+                side_color,
+            ),
+            // Left
+            (
+                [[-hw, y0, -hd], [-hw, y0, hd], [-hw, h, hd], [-hw, h, -hd]],
+                [-1.0, 0.0, 0.0],
+                side_color,
+            ),
+        ];
+
+        let mut mesh = MeshData::new();
+        let faces = if with_top { &faces[..] } else { &faces[1..] };
+        for &(corners, normal, color) in faces {
+            let [v0, v1, v2, v3] =
+                corners.map(|position| Vertex { position, normal, color });
+            mesh.add_quad(v0, v1, v2, v3);
+        }
+
+        // A square leg under each corner, set in a little from the edges
+        if desk.surface_height > 0.0 {
+            const LEG_WIDTH: f32 = 0.3;
+            const LEG_INSET: f32 = 0.25;
+            let (x, z) = (hw - LEG_INSET - LEG_WIDTH / 2.0, hd - LEG_INSET - LEG_WIDTH / 2.0);
+            for (leg_x, leg_z) in [(-x, -z), (x, -z), (x, z), (-x, z)] {
+                let mut leg = create_box(LEG_WIDTH, desk.surface_height, LEG_WIDTH, side_color, 0.0);
+                for vertex in &mut leg.vertices {
+                    vertex.position[0] += leg_x;
+                    vertex.position[2] += leg_z;
+                }
+                mesh.merge(leg);
+            }
+        }
+
+        GpuMesh::from_mesh_data(device, &mesh)
+    }
+
+    /// Textured top face for wood or image desk materials, with UVs spanning
+    /// the whole desk
+    fn create_desk_top(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        textures: &mut TextureManager,
+    ) -> Option<GpuTexturedSurface> {
+        let desk = &CONFIG.desk;
+        let texture_key = match &desk.material {
+            DeskMaterial::Flat => return None,
+            DeskMaterial::Wood { seed } => {
+                let name = format!("desk-wood-{:06x}-{}", desk.color, seed);
+                textures.get_or_generate(device, queue, &name, || {
+                    generate_wood_texture(desk.color, *seed, desk.width / desk.depth)
+                });
+                TextureKey::Generated(name)
+            }
+            DeskMaterial::Image(path) => {
+                textures.get_or_load(device, queue, path);
+                TextureKey::Path(path.clone())
+            }
+        };
+
+        let (vertices, indices) = create_textured_quad(desk.width, desk.depth, desk.top_y());
+        Some(GpuTexturedSurface {
+            mesh: GpuMesh::new(device, &vertices, &indices),
+            texture_key,
+        })
+    }
+
+    fn create_floor_mesh(device: &wgpu::Device) -> GpuMesh {
+        let (r, g, b) = hex_to_rgb(CONFIG.colors.ground);
+        let s = 50.0;
+
+        let vertices = vec![
+            Vertex {
+                position: [-s, 0.0, -s],
+                normal: [0.0, 1.0, 0.0],
+                color: [r, g, b, 1.0],
+            },
+            Vertex {
+                position: [s, 0.0, -s],
+                normal: [0.0, 1.0, 0.0],
+                color: [r, g, b, 1.0],
+            },
+            Vertex {
+                position: [s, 0.0, s],
+                normal: [0.0, 1.0, 0.0],
+                color: [r, g, b, 1.0],
+            },
+            Vertex {
+                position: [-s, 0.0, s],
+                normal: [0.0, 1.0, 0.0],
+                color: [r, g, b, 1.0],
+            },
+        ];
+
+        // Counter-clockwise seen from above so the floor isn't back-face culled
+        let indices: Vec<u16> = vec![0, 2, 1, 0, 3, 2];
+
+        GpuMesh::new(device, &vertices, &indices)
+    }
+
+    fn create_room_mesh(device: &wgpu::Device) -> GpuMesh {
+        let (width, depth, height) = CONFIG.room.room_size;
+        let mesh = create_room_mesh(width, depth, height, hex_to_rgba(CONFIG.room.wall_color));
+        GpuMesh::from_mesh_data(device, &mesh)
+    }
+}
+
+/// Find a GPU adapter, relaxing the requirements step by step: a high-performance
+/// GPU, then any GPU, then a software fallback adapter (e.g. WARP or llvmpipe).
+///
+/// Logs the adapter that was chosen, or returns an error explaining the GPU
+/// requirement if none is available.
+pub async fn request_adapter(
+    instance: &wgpu::Instance,
+    compatible_surface: Option<&wgpu::Surface<'_>>,
+) -> Result<wgpu::Adapter, Box<dyn std::error::Error>> {
+    let attempts = [
+        (wgpu::PowerPreference::HighPerformance, false),
+        (wgpu::PowerPreference::LowPower, false),
+        (wgpu::PowerPreference::None, true),
+    ];
+    for (power_preference, force_fallback_adapter) in attempts {
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference,
+                compatible_surface,
+                force_fallback_adapter,
+            })
+            .await;
+        if let Some(adapter) = adapter {
+            let info = adapter.get_info();
+            log::info!(
+                "Using GPU adapter \"{}\" ({:?}, {:?})",
+                info.name,
+                info.backend,
+                info.device_type
+            );
+            return Ok(adapter);
+        }
+        log::warn!(
+            "No GPU adapter for {:?} (software fallback: {})",
+            power_preference,
+            force_fallback_adapter
+        );
+    }
+    Err("No compatible graphics adapter was found. Focus Desktop Simulator needs a GPU \
+         with Vulkan, Metal, DirectX 12 or OpenGL ES 3 support; updating your graphics \
+         drivers often helps."
+        .into())
+}
+
+/// Render a saved layout to an image without opening a window
+///
+/// Creates its own GPU device with no surface, so it works on machines
+/// without a display as long as some wgpu adapter (including a software
+/// fallback) is available.
+pub fn render_headless(
+    state: &AppState,
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+
+    let adapter = pollster::block_on(request_adapter(&instance, None))?;
+
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("Headless Device"),
+            required_features: wgpu::Features::empty(),
+            // Allow images as large as the adapter supports
+            required_limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+            memory_hints: wgpu::MemoryHints::default(),
+        },
+        None,
+    ))?;
+
+    let mut renderer = Renderer::new(device, queue, OFFSCREEN_FORMAT);
+    renderer.set_bloom_intensity(state.bloom_intensity);
+    renderer.set_resolution_divisor(state.resolution_divisor);
+    renderer.rebuild_object_meshes(&state.objects);
+    let camera = Camera::new(width as f32 / height as f32);
+    renderer.update_shadows(&state.objects, &camera);
+    renderer.update_lamps(&state.objects);
+    renderer.render_to_image(&state.objects, &camera, width, height)
+}
+
+/// Strength of the shadow of an object `distance` away from the camera: full up
+/// to `RenderConfig::shadow_fade_band` before `shadow_distance`, then fading to
+/// none at it
+fn shadow_distance_fade(distance: f32) -> f32 {
+    let render = &CONFIG.render;
+    let band = render.shadow_fade_band.max(f32::EPSILON);
+    ((render.shadow_distance - distance) / band).clamp(0.0, 1.0)
+}
+
+/// Darkness of the contact darkening under an object `height` tall whose
+/// base is `gap` above the surface: strongest touching it, gone once lifted
+/// `RenderConfig::contact_shadow_radius`, and fainter for flat objects,
+/// which hide little of the light around them
+fn contact_shadow_opacity(gap: f32, height: f32) -> f32 {
+    let render = &CONFIG.render;
+    let reach = render.contact_shadow_radius.max(f32::EPSILON);
+    let touching = 1.0 - (gap / reach).clamp(0.0, 1.0);
+    render.contact_shadow_opacity * touching * (height / reach).min(1.0)
+}
+
+/// Indices of `objects` in the order they should be drawn, leaving out
+/// hidden objects.
+///
+/// Solid objects come first, nearest to the camera first so the depth test
+/// rejects as much hidden geometry as possible. Decals (flat papers and
+/// magazines) follow in their stored order, so where they overlap the one
+/// brought to front is drawn over the others from every angle.
+pub fn sort_objects_by_depth(camera: &Camera, objects: &[DeskObject]) -> Vec<usize> {
+    let look = camera.look_direction();
+    let depth = |obj: &DeskObject| (obj.position - camera.position).dot(look);
+    let is_decal = |obj: &DeskObject| obj.object_type.is_decal();
+
+    let mut order: Vec<usize> = (0..objects.len()).filter(|&i| !objects[i].hidden).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&objects[a], &objects[b]);
+        match (is_decal(a), is_decal(b)) {
+            (false, false) => depth(a).total_cmp(&depth(b)),
+            // The sort is stable, so decals stay in stored order
+            (true, true) => std::cmp::Ordering::Equal,
+            (decal_a, decal_b) => decal_a.cmp(&decal_b),
+        }
+    });
+    order
+}
+
+/// Regroup the solid objects at the start of a draw order so objects sharing
+/// a mesh (same `mesh_id`) are drawn one after another, letting the mesh be
+/// bound once per group. Each group stays nearest first; decals keep their
+/// stored order.
+pub fn group_solids_by_mesh(objects: &[DeskObject], order: &mut [usize], mesh_id: impl Fn(&DeskObject) -> usize) {
+    let solids = order.iter().take_while(|&&i| !objects[i].object_type.is_decal()).count();
+    order[..solids].sort_by_key(|&i| mesh_id(&objects[i]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decals_draw_after_solids_in_stored_order_and_hidden_objects_are_skipped() {
+        let camera = Camera::new(1.0);
+        let toward_camera = -camera.forward();
+        let at = |id, object_type, distance: f32| {
+            DeskObject::new(id, object_type, camera.target + toward_camera * distance)
+        };
+        let mut hidden = at(5, ObjectType::Coffee, 0.0);
+        hidden.hidden = true;
+        let objects = vec![
+            at(1, ObjectType::Paper, 1.0),
+            at(2, ObjectType::Coffee, -1.0),
+            at(3, ObjectType::Magazine, -1.0),
+            hidden,
+            at(4, ObjectType::Coffee, 1.0),
+        ];
+
+        let ids: Vec<u64> = sort_objects_by_depth(&camera, &objects)
+            .into_iter()
+            .map(|i| objects[i].id)
+            .collect();
+        assert_eq!(ids, vec![4, 2, 1, 3]);
+    }
+
+    #[test]
+    fn shadows_fade_out_towards_the_shadow_distance() {
+        let render = &CONFIG.render;
+        assert_eq!(shadow_distance_fade(0.0), 1.0);
+        assert_eq!(shadow_distance_fade(render.shadow_distance - render.shadow_fade_band), 1.0);
+        let halfway = shadow_distance_fade(render.shadow_distance - render.shadow_fade_band / 2.0);
+        assert!((halfway - 0.5).abs() < 1e-5);
+        assert_eq!(shadow_distance_fade(render.shadow_distance), 0.0);
+        assert_eq!(shadow_distance_fade(render.shadow_distance * 2.0), 0.0);
+    }
+
+    #[test]
+    fn contact_darkening_is_strongest_under_tall_objects_touching_the_surface() {
+        let render = &CONFIG.render;
+        let reach = render.contact_shadow_radius;
+        assert_eq!(contact_shadow_opacity(0.0, 1.0), render.contact_shadow_opacity);
+        assert!((contact_shadow_opacity(reach / 2.0, 1.0) - render.contact_shadow_opacity / 2.0).abs() < 1e-5);
+        assert_eq!(contact_shadow_opacity(reach, 1.0), 0.0);
+        // A sheet of paper barely darkens what it lies on
+        assert!(contact_shadow_opacity(0.0, 0.01) < render.contact_shadow_opacity * 0.1);
+    }
+
+    #[test]
+    fn solids_sharing_a_mesh_draw_together_nearest_first() {
+        let objects: Vec<DeskObject> = [ObjectType::Coffee, ObjectType::Lamp, ObjectType::Coffee, ObjectType::Paper]
+            .into_iter()
+            .enumerate()
+            .map(|(i, object_type)| DeskObject::new(i as u64, object_type, Vec3::ZERO))
+            .collect();
+        // Depth sorted with the lamp between the mugs, then the paper decal
+        let mut order = vec![2, 1, 0, 3];
+        let mesh_of = |obj: &DeskObject| ObjectType::all().iter().position(|&t| t == obj.object_type).unwrap();
+        group_solids_by_mesh(&objects, &mut order, mesh_of);
+        assert_eq!(order, vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn headless_render_matches_requested_size() {
+        let mut state = AppState::default();
+        let id = state.next_id();
+        state.add_object(DeskObject::new(id, ObjectType::Coffee, Vec3::new(0.0, CONFIG.desk.top_y(), 0.0)));
+
+        // Machines without any wgpu adapter (e.g. bare CI runners) can't render
+        let image = match render_headless(&state, 64, 48) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("Skipping headless render test: {}", e);
+                return;
+            }
+        };
+
+        assert_eq!(image.dimensions(), (64, 48));
+        let background = image.get_pixel(0, 0);
+        assert!(
+            image.pixels().any(|pixel| pixel != background),
+            "rendered frame should not be a single flat color"
+        );
+    }
+}
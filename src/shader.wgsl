@@ -5,19 +5,41 @@
 struct CameraUniform {
     view_proj: mat4x4<f32>,
     position: vec4<f32>,
+    // Focus-mode dim amount (x), shadow map enabled (y), specular strength (z), lamp count (w)
+    scene: vec4<f32>,
+    // View-projection of the shadow-casting light
+    light_view_proj: mat4x4<f32>,
+    // Lamp light color times intensity (rgb), w is padding
+    lamp_color: vec4<f32>,
+    // Bulb position (xyz) and light radius (w) of each lit lamp (MAX_LAMP_LIGHTS in camera.rs)
+    lamps: array<vec4<f32>, 8>,
 }
 
 // Model uniform buffer for per-object transforms
 struct ModelUniform {
     model: mat4x4<f32>,
+    // Opacity multiplier (x), focus highlight flag (y), emissive glow (z), shininess (w, 0 = matte)
+    params: vec4<f32>,
 }
 
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+// Depth from the light's point of view (main passes only)
+@group(0) @binding(1)
+var shadow_map: texture_depth_2d;
+@group(0) @binding(2)
+var shadow_sampler: sampler_comparison;
+
 @group(1) @binding(0)
 var<uniform> model: ModelUniform;
 
+// Surface texture for image-mapped quads (textured pipeline only)
+@group(2) @binding(0)
+var surface_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var surface_sampler: sampler;
+
 // Vertex input
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -55,38 +77,143 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     );
     out.world_normal = normalize(normal_transform * in.normal);
 
-    out.color = in.color;
+    out.color = vec4<f32>(in.color.rgb, in.color.a * model.params.x);
 
     return out;
 }
 
-// Fragment shader with basic lighting
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Light direction (from top-right)
+// Depth-only vertex shader for the shadow pass; `camera` holds the light's
+// view-projection here
+@vertex
+fn vs_shadow(in: VertexInput) -> @builtin(position) vec4<f32> {
+    return camera.view_proj * model.model * vec4<f32>(in.position, 1.0);
+}
+
+// Fraction of direct light reaching a point (1 = fully lit), using a 3x3 PCF kernel
+fn shadow_factor(world_position: vec3<f32>) -> f32 {
+    let light_clip = camera.light_view_proj * vec4<f32>(world_position, 1.0);
+    let ndc = light_clip.xyz / light_clip.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5, 0.5);
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_map));
+
+    var lit = 0.0;
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, ndc.z);
+        }
+    }
+    lit /= 9.0;
+
+    // Nothing outside the light's frustum is shadowed
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0)) && ndc.z <= 1.0;
+    return select(1.0, mix(1.0, lit, camera.scene.y), inside);
+}
+
+// Light from the lamps, fading smoothly to nothing at each lamp's radius
+fn lamp_light(world_position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var total = 0.0;
+    for (var i = 0u; i < min(u32(camera.scene.w), 8u); i++) {
+        let lamp = camera.lamps[i];
+        let to_lamp = lamp.xyz - world_position;
+        let dist = length(to_lamp);
+        let falloff = clamp(1.0 - dist / lamp.w, 0.0, 1.0);
+        // Surfaces right at the bulb have no direction to it and are fully lit
+        let facing = select(1.0, max(dot(normal, to_lamp / dist), 0.0), dist > 1e-4);
+        total += falloff * falloff * facing;
+    }
+    return camera.lamp_color.rgb * total;
+}
+
+// Shared lighting and fog for all surfaces
+fn shade(world_position: vec3<f32>, world_normal: vec3<f32>, color: vec4<f32>) -> vec4<f32> {
+    // Light direction (from top-right, matches LIGHT_DIRECTION in renderer.rs)
     let light_dir = normalize(vec3<f32>(0.5, 1.0, 0.3));
 
     // Ambient light
     let ambient_color = vec3<f32>(0.25, 0.25, 0.35);
 
     // Directional light
-    let normal = normalize(in.world_normal);
+    let normal = normalize(world_normal);
     let diffuse = max(dot(normal, light_dir), 0.0);
 
     // Combine lighting
-    let light = ambient_color + diffuse * vec3<f32>(0.8, 0.8, 0.75);
+    let sun = shadow_factor(world_position) * vec3<f32>(0.8, 0.8, 0.75);
+    let light = ambient_color + diffuse * sun + lamp_light(world_position, normal);
 
-    // Apply lighting to base color
-    let lit_color = in.color.rgb * light;
+    // Blinn-Phong highlight of the sun on shiny surfaces, moving with the camera
+    let view_dir = normalize(camera.position.xyz - world_position);
+    let half_dir = normalize(light_dir + view_dir);
+    let shininess = model.params.w;
+    let highlight = pow(max(dot(normal, half_dir), 0.0), max(shininess, 1.0)) * camera.scene.z;
+    let specular = select(0.0, highlight, shininess > 0.0 && diffuse > 0.0) * sun;
+
+    // Apply lighting to base color, plus any glow (e.g. an alert pulse) and
+    // the highlight, which takes the light's color rather than the surface's
+    let lit_color = color.rgb * (light + vec3<f32>(model.params.z)) + specular;
 
     // Simple fog effect based on distance from camera
-    let dist = length(in.world_position - camera.position.xyz);
+    let dist = length(world_position - camera.position.xyz);
     let fog_factor = 1.0 - clamp((dist - 10.0) / 40.0, 0.0, 0.6);
 
     // Background/fog color
     let fog_color = vec3<f32>(0.1, 0.1, 0.18);
 
-    let final_color = mix(fog_color, lit_color, fog_factor);
+    let fogged_color = mix(fog_color, lit_color, fog_factor);
+
+    // Focus mode: desaturate and darken everything except the highlighted object
+    let dim = camera.scene.x * (1.0 - model.params.y);
+    let gray = dot(fogged_color, vec3<f32>(0.299, 0.587, 0.114));
+    let final_color = mix(fogged_color, vec3<f32>(gray * 0.35), dim);
+
+    return vec4<f32>(final_color, color.a);
+}
+
+// Fragment shader with basic lighting
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return shade(in.world_position, in.world_normal, in.color);
+}
 
-    return vec4<f32>(final_color, in.color.a);
+// Textured vertex input
+struct TexturedVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+}
+
+struct TexturedVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) opacity: f32,
+}
+
+// Vertex shader for image-mapped quads
+@vertex
+fn vs_textured(in: TexturedVertexInput) -> TexturedVertexOutput {
+    var out: TexturedVertexOutput;
+
+    let world_pos = model.model * vec4<f32>(in.position, 1.0);
+    out.clip_position = camera.view_proj * world_pos;
+    out.world_position = world_pos.xyz;
+
+    let normal_transform = mat3x3<f32>(
+        model.model[0].xyz,
+        model.model[1].xyz,
+        model.model[2].xyz
+    );
+    out.world_normal = normalize(normal_transform * in.normal);
+    out.uv = in.uv;
+    out.opacity = model.params.x;
+
+    return out;
+}
+
+// Fragment shader sampling the surface texture
+@fragment
+fn fs_textured(in: TexturedVertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(surface_texture, surface_sampler, in.uv);
+    return shade(in.world_position, in.world_normal, vec4<f32>(texel.rgb, texel.a * in.opacity));
 }
@@ -0,0 +1,170 @@
+//! Procedural "shuffle desk" layout generator
+//!
+//! Places a weighted assortment of objects at random non-overlapping spots on
+//! the desk. The same seed always produces the same layout.
+
+use crate::desk_object::{DeskObject, ObjectType, BOOK_COUNT_RANGE, ROTATION_SNAP_STEP};
+use crate::physics::PhysicsEngine;
+use crate::ui::{ACCENT_COLOR_PRESETS, COLOR_PRESETS};
+use glam::{Quat, Vec3};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Relative likelihood of each object type appearing in a shuffled layout.
+/// Everyday desk items are common, centerpieces like the laptop are rare.
+const SHUFFLE_WEIGHTS: &[(ObjectType, u32)] = &[
+    (ObjectType::Coffee, 6),
+    (ObjectType::Notebook, 5),
+    (ObjectType::Paper, 4),
+    (ObjectType::PenHolder, 4),
+    (ObjectType::Books, 4),
+    (ObjectType::Plant, 4),
+    (ObjectType::Clock, 3),
+    (ObjectType::PhotoFrame, 3),
+    (ObjectType::Magazine, 2),
+    (ObjectType::Lamp, 2),
+    (ObjectType::Hourglass, 2),
+    (ObjectType::Globe, 1),
+    (ObjectType::Trophy, 1),
+    (ObjectType::Metronome, 1),
+    (ObjectType::Laptop, 1),
+];
+
+/// Range of how many objects a shuffle tries to place
+const SHUFFLE_COUNT: std::ops::RangeInclusive<usize> = 6..=10;
+
+/// Random positions tried per object before giving up on it
+const PLACEMENT_ATTEMPTS: usize = 30;
+
+/// Chance that an object keeps its default colors instead of a random preset
+const DEFAULT_COLOR_CHANCE: f64 = 0.4;
+
+/// Generate a random layout around `existing` objects (e.g. locked ones kept
+/// on the desk), returning only the new objects.
+///
+/// New objects take their IDs from `next_id`. At most `max_objects` objects
+/// (existing plus new) end up on the desk; objects that can't find a free spot
+/// are skipped.
+pub fn shuffle_layout(
+    seed: u64,
+    mut next_id: impl FnMut() -> u64,
+    existing: &[DeskObject],
+    max_objects: usize,
+    physics: &PhysicsEngine,
+) -> Vec<DeskObject> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let target = rng
+        .random_range(SHUFFLE_COUNT)
+        .min(max_objects.saturating_sub(existing.len()));
+    let total_weight: u32 = SHUFFLE_WEIGHTS.iter().map(|(_, weight)| weight).sum();
+    let (min_x, max_x, min_z, max_z) = physics.desk_bounds;
+
+    let mut placed: Vec<DeskObject> = Vec::new();
+    let mut unused_id = None;
+    for _ in 0..target {
+        let object_type = pick_weighted(&mut rng, total_weight);
+        // An ID is only consumed once an object actually gets placed
+        let id = *unused_id.get_or_insert_with(&mut next_id);
+        let mut object = DeskObject::new(id, object_type, Vec3::ZERO);
+        // Rotations land on snap steps so shuffled objects line up with hand-rotated ones
+        let steps = rng.random_range(0..24) as f32;
+        object.rotation = Quat::from_rotation_y(steps * ROTATION_SNAP_STEP);
+        if object_type == ObjectType::Books {
+            object.book_count = rng.random_range(BOOK_COUNT_RANGE);
+        }
+        if !rng.random_bool(DEFAULT_COLOR_CHANCE) {
+            object.color = COLOR_PRESETS[rng.random_range(0..COLOR_PRESETS.len())].0;
+            object.accent_color =
+                ACCENT_COLOR_PRESETS[rng.random_range(0..ACCENT_COLOR_PRESETS.len())].0;
+        }
+
+        let radius = physics.world_shape(&object).bounding_radius();
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let candidate = Vec3::new(
+                rng.random_range(min_x..=max_x),
+                0.0,
+                rng.random_range(min_z..=max_z),
+            );
+            let candidate = physics.clamp_to_desk(candidate, radius);
+            object.position.x = candidate.x;
+            object.position.z = candidate.z;
+
+            let overlaps = existing
+                .iter()
+                .chain(placed.iter())
+                .any(|other| physics.check_collision(&object, other));
+            if !overlaps {
+                let y = physics.desk_surface_y() + object.base_offset();
+                object.position.y = y;
+                object.target_y = y;
+                object.original_y = y;
+                placed.push(object);
+                unused_id = None;
+                break;
+            }
+        }
+    }
+
+    placed
+}
+
+/// Pick an object type according to `SHUFFLE_WEIGHTS`
+fn pick_weighted(rng: &mut StdRng, total_weight: u32) -> ObjectType {
+    let mut roll = rng.random_range(0..total_weight);
+    for &(object_type, weight) in SHUFFLE_WEIGHTS {
+        if roll < weight {
+            return object_type;
+        }
+        roll -= weight;
+    }
+    SHUFFLE_WEIGHTS[0].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ID source counting up from `start`
+    fn counter(start: u64) -> impl FnMut() -> u64 {
+        let mut next = start;
+        move || {
+            next += 1;
+            next - 1
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_layout() {
+        let physics = PhysicsEngine::new();
+        let a = shuffle_layout(42, counter(1), &[], 50, &physics);
+        let b = shuffle_layout(42, counter(1), &[], 50, &physics);
+        assert!(!a.is_empty());
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(&b) {
+            assert_eq!(x.object_type, y.object_type);
+            assert_eq!(x.position, y.position);
+            assert_eq!(x.color, y.color);
+        }
+    }
+
+    #[test]
+    fn shuffled_objects_do_not_overlap() {
+        let physics = PhysicsEngine::new();
+        for seed in 0..20 {
+            let objects = shuffle_layout(seed, counter(1), &[], 50, &physics);
+            for (i, a) in objects.iter().enumerate() {
+                for b in &objects[i + 1..] {
+                    assert!(!physics.check_collision(a, b), "seed {} overlaps", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shuffle_respects_max_objects() {
+        let physics = PhysicsEngine::new();
+        let existing = vec![DeskObject::new(1, ObjectType::Lamp, Vec3::ZERO)];
+        let objects = shuffle_layout(7, counter(2), &existing, 3, &physics);
+        assert!(objects.len() <= 2);
+    }
+}
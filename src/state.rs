@@ -2,10 +2,40 @@
 //!
 //! Handles saving and loading application state to/from disk.
 
+use crate::camera::CameraView;
+use crate::config::{frame_interval, Keybindings, PresentMode, ScrollSettings, CONFIG};
 use crate::desk_object::DeskObject;
+use crate::focus::FocusStats;
+use crate::i18n::Lang;
+use crate::registry;
+use crate::theme::UiTheme;
+use chrono::{DateTime, Utc};
+use glam::Vec3;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Version of the state format this build writes
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Range of the user-adjustable UI scale
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+/// Number of recently used colors kept for the customization sidebar
+pub const MAX_RECENT_COLORS: usize = 10;
+
+/// Range of the user-adjustable scroll sensitivity multipliers
+pub const SCROLL_SENSITIVITY_RANGE: std::ops::RangeInclusive<f32> = 0.1..=5.0;
+
+/// Internal resolution divisors offered in the settings (1 = full resolution)
+pub const RESOLUTION_DIVISOR_RANGE: std::ops::RangeInclusive<u32> = 1..=8;
+
+/// Frame rate caps offered in the settings (`None` = uncapped)
+pub const FPS_CAP_CHOICES: [Option<u32>; 4] = [None, Some(30), Some(60), Some(120)];
+
+/// Range of the user-adjustable turntable speed, in degrees per second
+pub const TURNTABLE_SPEED_RANGE: std::ops::RangeInclusive<f32> = 1.0..=45.0;
 
 /// Application state that gets persisted
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,16 +50,296 @@ pub struct AppState {
     pub collision_height_multiplier: f32,
     /// Next object ID to use
     pub next_object_id: u64,
+    /// Height of the desk top the object positions were saved with; objects
+    /// over the desk are moved onto a desk top at another height on loading
+    #[serde(default = "legacy_desk_top")]
+    pub desk_top: f32,
+    /// Last window size in logical pixels
+    #[serde(default)]
+    pub window_size: Option<(u32, u32)>,
+    /// User UI scale on top of the monitor's scale factor
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f32,
+    /// Whether scroll-wheel rotation snaps to fixed increments
+    #[serde(default = "default_rotation_snap")]
+    pub rotation_snap: bool,
+    /// Whether dragged objects snap flush against nearby desk edges
+    #[serde(default = "default_edge_snap")]
+    pub edge_snap: bool,
+    /// Whether the selected object shows a gizmo to turn and move it with
+    #[serde(default = "default_show_gizmo")]
+    pub show_gizmo: bool,
+    /// Whether palette items are dragged onto the desk (a click adds one at a
+    /// random spot) instead of clicked and then placed with a second click
+    #[serde(default)]
+    pub palette_drag: bool,
+    /// Whether new objects appear where the camera is looking on the desk
+    /// instead of at a random spot
+    #[serde(default)]
+    pub spawn_at_aim: bool,
+    /// UI color theme
+    #[serde(default)]
+    pub ui_theme: UiTheme,
+    /// UI language
+    #[serde(default)]
+    pub ui_lang: Lang,
+    /// Whether the layout grid and rulers are drawn on the desk
+    #[serde(default)]
+    pub show_grid: bool,
+    /// Idle bob/sway amplitude (0 = off, for reduced motion; 1 = full)
+    #[serde(default)]
+    pub idle_motion: f32,
+    /// Low power / reduced motion mode, which overrides idle motion, shadows
+    /// and frame rate without changing their own settings
+    #[serde(default = "default_low_power")]
+    pub low_power: bool,
+    /// Vsync / present mode (low power mode always uses Fifo)
+    #[serde(default = "default_present_mode")]
+    pub present_mode: PresentMode,
+    /// Frame rate cap while the window is focused, on top of vsync (`None` =
+    /// uncapped; low power mode's cap wins if it is lower)
+    #[serde(default = "default_fps_cap")]
+    pub fps_cap: Option<u32>,
+    /// How strongly bright lamps and screens bleed light (0 = off)
+    #[serde(default = "default_bloom_intensity")]
+    pub bloom_intensity: f32,
+    /// The scene is drawn at 1/N of the window size and upscaled, for a
+    /// pixel-art look (1 = full resolution)
+    #[serde(default = "default_resolution_divisor")]
+    pub resolution_divisor: u32,
+    /// Whether the camera slowly orbits the desk on its own
+    #[serde(default)]
+    pub turntable: bool,
+    /// Turntable speed in degrees per second
+    #[serde(default = "default_turntable_speed")]
+    pub turntable_speed: f32,
+    /// Focused time per day, for the focus widget and streak
+    #[serde(default)]
+    pub focus_stats: FocusStats,
+    /// User's keyboard shortcuts
+    #[serde(default = "default_keybindings")]
+    pub keybindings: Keybindings,
+    /// User's scroll sensitivity and direction
+    #[serde(default = "default_scroll")]
+    pub scroll: ScrollSettings,
+    /// Customized objects saved as templates, shown as palette favorites
+    #[serde(default)]
+    pub templates: Vec<DeskObject>,
+    /// Colors last applied to objects (main or accent), most recent first
+    #[serde(default)]
+    pub recent_colors: Vec<u32>,
+    /// Independent desks the user switches between; never empty
+    #[serde(default = "default_workspaces")]
+    pub workspaces: Vec<Workspace>,
+    /// Index of the workspace on screen, whose objects are `objects`
+    #[serde(default)]
+    pub active_workspace: usize,
+    /// Whether each workspace keeps its own camera view instead of all of
+    /// them sharing one
+    #[serde(default)]
+    pub workspace_cameras: bool,
+    /// Loaded from a state file written by a newer build, or holding objects
+    /// of custom types this run doesn't register. It is never saved over, so
+    /// nothing this run can't load is lost.
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+/// One of the desks in `AppState::workspaces`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Name shown on the workspace's tab (numbered if empty)
+    #[serde(default)]
+    pub name: String,
+    /// Objects of the workspace while another one is active (empty while it
+    /// is active, as they are then `AppState::objects`)
+    #[serde(default)]
+    pub objects: Vec<DeskObject>,
+    /// Next object ID of the workspace while another one is active
+    #[serde(default)]
+    pub next_object_id: u64,
+    /// Last camera view in the workspace, if views are per workspace
+    #[serde(default)]
+    pub camera: Option<CameraView>,
+}
+
+impl Workspace {
+    /// An empty workspace
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            next_object_id: 1,
+            ..Self::default()
+        }
+    }
+}
+
+/// Details about a named save, stored in the save file and shown in the load menu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveMeta {
+    /// Save name (the file stem)
+    pub name: String,
+    /// When the save was first written
+    pub created: DateTime<Utc>,
+    /// When the save was last overwritten
+    pub modified: DateTime<Utc>,
+    /// Number of objects in the layout
+    pub object_count: usize,
+    /// Version of the app that wrote the save
+    pub app_version: String,
+}
+
+impl SaveMeta {
+    /// Read the metadata of a named save's JSON. Saves written before
+    /// metadata existed get it derived from their contents and `file_time`.
+    fn from_json(name: &str, content: &str, file_time: DateTime<Utc>) -> serde_json::Result<Self> {
+        /// Just the parts of a save file needed for its metadata
+        #[derive(Deserialize)]
+        struct SaveHeader {
+            #[serde(default)]
+            meta: Option<SaveMeta>,
+            #[serde(default)]
+            objects: Vec<serde::de::IgnoredAny>,
+        }
+
+        let header: SaveHeader = serde_json::from_str(content)?;
+        Ok(match header.meta {
+            Some(meta) => SaveMeta {
+                name: name.to_string(),
+                ..meta
+            },
+            None => SaveMeta {
+                name: name.to_string(),
+                created: file_time,
+                modified: file_time,
+                object_count: header.objects.len(),
+                app_version: String::new(),
+            },
+        })
+    }
+}
+
+/// Order of the named saves in the load menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveSort {
+    /// Most recently modified first
+    #[default]
+    Modified,
+    /// Alphabetical by name
+    Name,
+}
+
+impl SaveSort {
+    /// Sort saves in place
+    pub fn sort(self, saves: &mut [SaveMeta]) {
+        match self {
+            SaveSort::Modified => saves.sort_by_key(|save| std::cmp::Reverse(save.modified)),
+            SaveSort::Name => saves.sort_by_key(|save| save.name.to_lowercase()),
+        }
+    }
+}
+
+/// A named save on disk: the state with its metadata alongside
+#[derive(Serialize)]
+struct NamedSave<'a> {
+    meta: &'a SaveMeta,
+    #[serde(flatten)]
+    state: &'a AppState,
+}
+
+fn default_rotation_snap() -> bool {
+    true
+}
+
+fn default_edge_snap() -> bool {
+    true
+}
+
+fn default_show_gizmo() -> bool {
+    true
+}
+
+/// Saves from before desks had legs had the top at the desk's thickness
+fn legacy_desk_top() -> f32 {
+    CONFIG.desk.height
+}
+
+fn default_low_power() -> bool {
+    CONFIG.low_power.enabled
+}
+
+fn default_present_mode() -> PresentMode {
+    CONFIG.render.present_mode
+}
+
+fn default_fps_cap() -> Option<u32> {
+    CONFIG.render.fps_cap
+}
+
+fn default_bloom_intensity() -> f32 {
+    CONFIG.render.bloom_intensity
+}
+
+fn default_resolution_divisor() -> u32 {
+    CONFIG.render.internal_resolution_divisor
+}
+
+fn default_turntable_speed() -> f32 {
+    CONFIG.camera.turntable_speed
+}
+
+fn default_ui_scale() -> f32 {
+    1.0
+}
+
+fn default_workspaces() -> Vec<Workspace> {
+    vec![Workspace::new("")]
+}
+
+fn default_keybindings() -> Keybindings {
+    CONFIG.keybindings.clone()
+}
+
+fn default_scroll() -> ScrollSettings {
+    CONFIG.scroll
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: CURRENT_VERSION,
+            desk_top: CONFIG.desk.top_y(),
             objects: Vec::new(),
             collision_radius_multiplier: 1.0,
             collision_height_multiplier: 1.0,
             next_object_id: 1,
+            window_size: None,
+            ui_scale: default_ui_scale(),
+            rotation_snap: default_rotation_snap(),
+            edge_snap: default_edge_snap(),
+            show_gizmo: default_show_gizmo(),
+            palette_drag: false,
+            spawn_at_aim: false,
+            ui_theme: UiTheme::default(),
+            ui_lang: Lang::default(),
+            show_grid: false,
+            idle_motion: 0.0,
+            low_power: default_low_power(),
+            present_mode: default_present_mode(),
+            fps_cap: default_fps_cap(),
+            bloom_intensity: default_bloom_intensity(),
+            resolution_divisor: default_resolution_divisor(),
+            turntable: false,
+            turntable_speed: default_turntable_speed(),
+            focus_stats: FocusStats::new(),
+            keybindings: default_keybindings(),
+            scroll: default_scroll(),
+            templates: Vec::new(),
+            recent_colors: Vec::new(),
+            workspaces: default_workspaces(),
+            active_workspace: 0,
+            workspace_cameras: false,
+            read_only: false,
         }
     }
 }
@@ -40,6 +350,22 @@ impl AppState {
         Self::default()
     }
 
+    /// Whether this is the first launch, i.e. no state has been saved yet
+    pub fn is_first_run() -> bool {
+        Self::state_file_path().is_some_and(|path| !path.exists())
+    }
+
+    /// A fresh state with the starter objects (`ObjectConfig::starter_layout`)
+    /// on the desk, so a first launch doesn't open on an empty desk
+    pub fn default_layout() -> Self {
+        let mut state = Self::default();
+        for &(object_type, x, z) in &CONFIG.objects.starter_layout {
+            let id = state.next_id();
+            state.add_object(DeskObject::new(id, object_type, Vec3::new(x, CONFIG.desk.top_y(), z)));
+        }
+        state
+    }
+
     /// Get the path to the state file
     fn state_file_path() -> Option<PathBuf> {
         dirs::data_dir().map(|mut path| {
@@ -50,25 +376,195 @@ impl AppState {
         })
     }
 
+    /// Get the directory holding named saves
+    fn saves_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|mut path| {
+            path.push("focus-desktop-simulator");
+            path.push("saves");
+            fs::create_dir_all(&path).ok();
+            path
+        })
+    }
+
+    /// Path for a new screenshot, named after the current time
+    pub fn screenshot_path() -> Option<PathBuf> {
+        let mut path = dirs::picture_dir().or_else(dirs::data_dir)?;
+        path.push("focus-desktop-simulator");
+        fs::create_dir_all(&path).ok();
+        let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        path.push(format!("screenshot_{}.png", stamp));
+        Some(path)
+    }
+
+    /// Directory holding session recordings
+    fn recordings_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|mut path| {
+            path.push("focus-desktop-simulator");
+            path.push("recordings");
+            fs::create_dir_all(&path).ok();
+            path
+        })
+    }
+
+    /// Directory for cover images pasted from another machine
+    pub fn pasted_images_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|mut path| {
+            path.push("focus-desktop-simulator");
+            path.push("images");
+            fs::create_dir_all(&path).ok();
+            path
+        })
+    }
+
+    /// Path for a new session recording, named after the current time
+    pub fn recording_path() -> Option<PathBuf> {
+        let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        Self::recordings_dir().map(|dir| dir.join(format!("session_{}.jsonl", stamp)))
+    }
+
+    /// The most recent session recording, if any
+    pub fn latest_recording() -> Option<PathBuf> {
+        fs::read_dir(Self::recordings_dir()?)
+            .ok()?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            // Timestamped names sort chronologically
+            .max()
+    }
+
+    /// Path of a named save's JSON file
+    pub fn named_save_path(name: &str) -> Option<PathBuf> {
+        let name = sanitize_save_name(name)?;
+        Self::saves_dir().map(|dir| dir.join(format!("{}.json", name)))
+    }
+
+    /// Path of a named save's thumbnail image
+    pub fn thumbnail_path(name: &str) -> Option<PathBuf> {
+        Self::named_save_path(name).map(|path| path.with_extension("png"))
+    }
+
+    /// List all named saves with their metadata, in the given order
+    pub fn list_named_saves(sort: SaveSort) -> Vec<SaveMeta> {
+        let Some(dir) = Self::saves_dir() else { return Vec::new() };
+        let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+
+        let mut saves: Vec<SaveMeta> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                let name = path.file_stem()?.to_string_lossy().into_owned();
+                Self::read_save_meta(&name, &path)
+            })
+            .collect();
+        sort.sort(&mut saves);
+        saves
+    }
+
+    /// Read a named save's metadata, skipping files that aren't valid saves
+    fn read_save_meta(name: &str, path: &Path) -> Option<SaveMeta> {
+        let content = fs::read_to_string(path).ok()?;
+        let file_time = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+        match SaveMeta::from_json(name, &content, file_time) {
+            Ok(meta) => Some(meta),
+            Err(e) => {
+                log::warn!("Skipping unreadable save {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Save state under a name, returning the path written
+    ///
+    /// Overwriting a save keeps its original creation time.
+    pub fn save_named(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = Self::named_save_path(name).ok_or("Invalid save name")?;
+        let now = Utc::now();
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let created = Self::read_save_meta(&stem, &path).map_or(now, |meta| meta.created);
+        let meta = SaveMeta {
+            name: stem,
+            created,
+            modified: now,
+            object_count: self.objects.len(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+
+        // Only the desk on screen goes into the layout, not the other workspaces
+        let desk = AppState {
+            workspaces: Vec::new(),
+            ..self.clone()
+        };
+        let save = NamedSave { meta: &meta, state: &desk };
+        write_atomic(&path, &serde_json::to_string_pretty(&save)?)?;
+        log::info!("Saved layout {:?} with {} objects", name, self.objects.len());
+        Ok(path)
+    }
+
+    /// Load a named save
+    pub fn load_named(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Self::named_save_path(name).ok_or("Invalid save name")?;
+        let state = Self::load_file(&path)?;
+        log::info!("Loaded layout {:?} with {} objects", name, state.objects.len());
+        Ok(state)
+    }
+
+    /// Load a state or named save file, failing if it's missing or unreadable
+    pub fn load_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut state = Self::parse(&fs::read_to_string(path)?)?;
+        for object in &mut state.objects {
+            object.sanitize();
+        }
+        state.next_object_id = next_free_id(&state.objects, state.next_object_id);
+        state.fit_to_desk_top();
+        Ok(state)
+    }
+
     /// Load state from disk
     pub fn load() -> Self {
-        let path = match Self::state_file_path() {
-            Some(p) => p,
+        match Self::state_file_path() {
+            Some(path) => Self::load_from(&path),
             None => {
                 log::warn!("Could not determine data directory, using default state");
-                return Self::default();
+                Self::default()
             }
-        };
+        }
+    }
 
+    /// Load state from a file, falling back to the default state if it's
+    /// missing or unreadable. A file that can't be parsed is first copied
+    /// beside it with a `.json.backup` extension so it can be recovered by hand.
+    /// A file from a newer build is loaded as far as possible and read-only.
+    pub fn load_from(path: &Path) -> Self {
         if !path.exists() {
             log::info!("No saved state found, using default");
             return Self::default();
         }
 
-        match fs::read_to_string(&path) {
+        match fs::read_to_string(path) {
             Ok(content) => {
-                match serde_json::from_str::<AppState>(&content) {
-                    Ok(state) => {
+                match Self::parse(&content) {
+                    Ok(mut state) => {
+                        for object in &mut state.objects {
+                            object.sanitize();
+                        }
+                        state.ui_scale = sanitize_ui_scale(state.ui_scale);
+                        state.idle_motion = sanitize_amount(state.idle_motion);
+                        state.bloom_intensity = sanitize_amount(state.bloom_intensity);
+                        state.resolution_divisor = state
+                            .resolution_divisor
+                            .clamp(*RESOLUTION_DIVISOR_RANGE.start(), *RESOLUTION_DIVISOR_RANGE.end());
+                        state.turntable_speed = sanitize_turntable_speed(state.turntable_speed);
+                        state.fps_cap = state.fps_cap.filter(|&fps| fps > 0);
+                        state.scroll = sanitize_scroll(state.scroll);
+                        state.recent_colors.truncate(MAX_RECENT_COLORS);
+                        state.next_object_id = next_free_id(&state.objects, state.next_object_id);
+                        state.sanitize_workspaces();
+                        state.fit_to_desk_top();
                         log::info!("Loaded state with {} objects", state.objects.len());
                         state
                     }
@@ -79,7 +575,7 @@ impl AppState {
                             e
                         );
                         // Try to backup the corrupted file for potential recovery
-                        Self::backup_corrupted_state(&path);
+                        Self::backup_corrupted_state(path);
                         Self::default()
                     }
                 }
@@ -91,8 +587,61 @@ impl AppState {
         }
     }
 
+    /// Parse the contents of a state file. One written by a newer build (a
+    /// higher `version`) is marked read-only and read field by field, keeping
+    /// every field and object this build understands and defaulting the rest.
+    /// One with objects of unregistered types loads without them, read-only.
+    fn parse(content: &str) -> serde_json::Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(content)?;
+        let left_out = drop_unregistered_objects(&mut value);
+        let version = value.get("version").and_then(serde_json::Value::as_u64).unwrap_or(0);
+        if version <= CURRENT_VERSION as u64 {
+            let mut state: Self = serde_json::from_value(value)?;
+            // Saving would erase the objects left out
+            state.read_only = left_out;
+            return Ok(state);
+        }
+
+        log::warn!(
+            "State file has version {} but this build only supports up to {}; \
+            loading what it can and leaving the file untouched",
+            version,
+            CURRENT_VERSION
+        );
+        let serde_json::Value::Object(fields) = value else {
+            return serde_json::from_value(value);
+        };
+        let serde_json::Value::Object(mut merged) = serde_json::to_value(Self::default())? else {
+            unreachable!("the state serializes to a JSON object");
+        };
+        for (key, field) in fields {
+            // The data is now in this build's format, whatever the file says
+            if key == "version" || !merged.contains_key(&key) {
+                continue;
+            }
+            let field = match field {
+                serde_json::Value::Array(objects) if key == "objects" => serde_json::Value::Array(
+                    objects
+                        .into_iter()
+                        .filter(|object| serde_json::from_value::<DeskObject>(object.clone()).is_ok())
+                        .collect(),
+                ),
+                field => field,
+            };
+            let previous = merged.insert(key.clone(), field);
+            if serde_json::from_value::<Self>(serde_json::Value::Object(merged.clone())).is_err() {
+                if let Some(previous) = previous {
+                    merged.insert(key, previous);
+                }
+            }
+        }
+        let mut state: Self = serde_json::from_value(serde_json::Value::Object(merged))?;
+        state.read_only = true;
+        Ok(state)
+    }
+
     /// Backup a corrupted state file so user doesn't lose data
-    fn backup_corrupted_state(path: &PathBuf) {
+    fn backup_corrupted_state(path: &Path) {
         let backup_path = path.with_extension("json.backup");
         if let Err(e) = fs::copy(path, &backup_path) {
             log::warn!("Could not backup corrupted state file: {}", e);
@@ -106,12 +655,23 @@ impl AppState {
     }
 
     /// Save state to disk
+    ///
+    /// Writes to a temporary file first and renames it over the state file,
+    /// so a crash mid-write never leaves a truncated `desk-state.json`.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let path = Self::state_file_path()
             .ok_or("Could not determine data directory")?;
+        self.save_to(&path)
+    }
 
+    /// Save state to a file, the same way as [`AppState::save`]. Fails for a
+    /// read-only state (see `AppState::read_only`).
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if self.read_only {
+            return Err("State has data this build can't load and is read-only".into());
+        }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
+        write_atomic(path, &content)?;
 
         log::info!("Saved state with {} objects to {:?}", self.objects.len(), path);
         Ok(())
@@ -124,6 +684,13 @@ impl AppState {
         id
     }
 
+    /// Swap the desk for an undo snapshot, keeping the ID counter past every
+    /// restored object so new objects don't reuse an ID that is back on the desk
+    pub fn restore_objects(&mut self, objects: &[DeskObject]) {
+        self.objects = objects.to_vec();
+        self.next_object_id = next_free_id(&self.objects, self.next_object_id);
+    }
+
     /// Add an object to the state
     pub fn add_object(&mut self, object: DeskObject) {
         self.objects.push(object);
@@ -148,8 +715,379 @@ impl AppState {
         self.objects.iter_mut().find(|o| o.id == id)
     }
 
+    /// Move an object to the end of the list so it is drawn last (on top of
+    /// overlapping papers and magazines)
+    pub fn bring_to_front(&mut self, id: u64) {
+        if let Some(object) = self.remove_object(id) {
+            self.objects.push(object);
+        }
+    }
+
+    /// Move an object to the start of the list so it is drawn first
+    pub fn send_to_back(&mut self, id: u64) {
+        if let Some(object) = self.remove_object(id) {
+            self.objects.insert(0, object);
+        }
+    }
+
     /// Clear all objects
     pub fn clear_objects(&mut self) {
         self.objects.clear();
     }
+
+    /// Make sure there is an active workspace, with the objects of the others sane
+    fn sanitize_workspaces(&mut self) {
+        if self.workspaces.is_empty() {
+            self.workspaces = default_workspaces();
+        }
+        self.active_workspace = self.active_workspace.min(self.workspaces.len() - 1);
+        for workspace in &mut self.workspaces {
+            for object in &mut workspace.objects {
+                object.sanitize();
+            }
+            workspace.next_object_id = next_free_id(&workspace.objects, workspace.next_object_id);
+        }
+    }
+
+    /// Move objects over the desk (in every workspace) by however much the
+    /// desk top is higher or lower than when they were saved, e.g. after
+    /// `DeskConfig::surface_height` changed. Objects on the floor stay put.
+    fn fit_to_desk_top(&mut self) {
+        let rise = CONFIG.desk.top_y() - self.desk_top;
+        self.desk_top = CONFIG.desk.top_y();
+        if rise.abs() < 1e-4 {
+            return;
+        }
+        let (half_width, half_depth) = (CONFIG.desk.width / 2.0, CONFIG.desk.depth / 2.0);
+        let workspace_objects = self.workspaces.iter_mut().flat_map(|workspace| &mut workspace.objects);
+        for object in self.objects.iter_mut().chain(workspace_objects) {
+            if object.position.x.abs() <= half_width && object.position.z.abs() <= half_depth {
+                object.position.y += rise;
+            }
+        }
+        log::info!("Moved objects onto a desk top {:+.2} higher than when saved", rise);
+    }
+
+    /// Add an empty workspace after the others, returning its index
+    pub fn add_workspace(&mut self, name: &str) -> usize {
+        self.workspaces.push(Workspace::new(name));
+        self.workspaces.len() - 1
+    }
+
+    /// Put the current objects away in the active workspace and bring out
+    /// those of workspace `index`. Returns whether the workspace changed.
+    pub fn switch_workspace(&mut self, index: usize) -> bool {
+        if index == self.active_workspace || index >= self.workspaces.len() {
+            return false;
+        }
+        let leaving = &mut self.workspaces[self.active_workspace];
+        leaving.objects = std::mem::take(&mut self.objects);
+        leaving.next_object_id = self.next_object_id;
+
+        let entering = &mut self.workspaces[index];
+        self.objects = std::mem::take(&mut entering.objects);
+        self.next_object_id = entering.next_object_id.max(1);
+        self.active_workspace = index;
+        true
+    }
+
+    /// Delete workspace `index` and its objects, switching to a neighbour
+    /// first if it is active. The last workspace can't be deleted.
+    pub fn remove_workspace(&mut self, index: usize) -> bool {
+        if self.workspaces.len() <= 1 || index >= self.workspaces.len() {
+            return false;
+        }
+        if index == self.active_workspace {
+            let neighbour = if index == 0 { 1 } else { index - 1 };
+            self.switch_workspace(neighbour);
+        }
+        self.workspaces.remove(index);
+        if self.active_workspace > index {
+            self.active_workspace -= 1;
+        }
+        true
+    }
+
+    /// Put `color` first among the recently used colors, dropping any older
+    /// copy. With `replace_latest` it takes the newest entry's place instead,
+    /// so colors passed through on the way (e.g. dragging a picker) aren't kept.
+    pub fn remember_color(&mut self, color: u32, replace_latest: bool) {
+        if replace_latest && !self.recent_colors.is_empty() {
+            self.recent_colors.remove(0);
+        }
+        self.recent_colors.retain(|&recent| recent != color);
+        self.recent_colors.insert(0, color);
+        self.recent_colors.truncate(MAX_RECENT_COLORS);
+    }
+
+    /// Minimum time between frames while the window is focused: the slower
+    /// of the frame rate cap and low power mode's, if either is on
+    pub fn frame_interval(&self) -> Option<Duration> {
+        let capped = self.fps_cap.map(frame_interval);
+        let low_power = self.low_power.then(|| CONFIG.low_power.frame_interval());
+        // `None` orders below any interval, so this keeps the longer one
+        capped.max(low_power)
+    }
+}
+
+/// Leave out objects (on the desk, in other workspaces or as templates) of
+/// registered types that aren't registered in this run, logging how many.
+/// Returns whether any were left out.
+fn drop_unregistered_objects(state: &mut serde_json::Value) -> bool {
+    let mut lists: Vec<&mut serde_json::Value> = Vec::new();
+    let serde_json::Value::Object(fields) = state else { return false };
+    for (key, field) in fields.iter_mut() {
+        match (key.as_str(), field) {
+            ("objects" | "templates", list) => lists.push(list),
+            ("workspaces", serde_json::Value::Array(workspaces)) => {
+                lists.extend(workspaces.iter_mut().filter_map(|workspace| workspace.get_mut("objects")))
+            }
+            _ => {}
+        }
+    }
+    let mut left_out = false;
+    for list in lists {
+        let serde_json::Value::Array(objects) = list else { continue };
+        let count = objects.len();
+        objects.retain(registry::is_loadable);
+        if objects.len() < count {
+            log::warn!("Left out {} objects of unregistered types", count - objects.len());
+            left_out = true;
+        }
+    }
+    left_out
+}
+
+/// Write a file via a temporary sibling and a rename, so readers never see
+/// a partially written file
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)
+}
+
+/// `next_id` raised past the highest object ID if needed, so a file with an
+/// out-of-date counter doesn't hand out IDs that are already taken
+fn next_free_id(objects: &[DeskObject], next_id: u64) -> u64 {
+    objects
+        .iter()
+        .map(|object| object.id.saturating_add(1))
+        .fold(next_id.max(1), u64::max)
+}
+
+/// Reduce a user-entered save name to a safe file stem, or `None` if empty
+fn sanitize_save_name(name: &str) -> Option<String> {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .collect();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+/// Keep a loaded UI scale within the range the settings slider offers
+fn sanitize_ui_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end())
+    } else {
+        default_ui_scale()
+    }
+}
+
+/// Keep a loaded turntable speed within the range offered in the settings
+fn sanitize_turntable_speed(speed: f32) -> f32 {
+    if speed.is_finite() {
+        speed.clamp(*TURNTABLE_SPEED_RANGE.start(), *TURNTABLE_SPEED_RANGE.end())
+    } else {
+        default_turntable_speed()
+    }
+}
+
+/// Keep a loaded idle motion amplitude or bloom intensity within 0 (off) to 1
+fn sanitize_amount(amount: f32) -> f32 {
+    if amount.is_finite() {
+        amount.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Keep loaded scroll sensitivities within the range the settings allow
+fn sanitize_scroll(scroll: ScrollSettings) -> ScrollSettings {
+    let sanitize = |sensitivity: f32| {
+        if sensitivity.is_finite() {
+            sensitivity.clamp(*SCROLL_SENSITIVITY_RANGE.start(), *SCROLL_SENSITIVITY_RANGE.end())
+        } else {
+            1.0
+        }
+    };
+    ScrollSettings {
+        rotate_sensitivity: sanitize(scroll.rotate_sensitivity),
+        scale_sensitivity: sanitize(scroll.scale_sensitivity),
+        zoom_sensitivity: sanitize(scroll.zoom_sensitivity),
+        invert: scroll.invert,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::PhysicsEngine;
+    use chrono::TimeZone;
+
+    #[test]
+    fn starter_layout_fits_on_the_desk_without_overlaps() {
+        let physics = PhysicsEngine::new();
+        let state = AppState::default_layout();
+        assert_eq!(state.objects.len(), CONFIG.objects.starter_layout.len());
+        for (i, a) in state.objects.iter().enumerate() {
+            let inside = physics.clamp_to_desk(a.position, a.collision_radius());
+            assert_eq!((inside.x, inside.z), (a.position.x, a.position.z), "{:?} hangs off the desk", a.object_type);
+            assert_eq!(a.position.y, physics.calculate_resting_y(a, &[]));
+            for b in &state.objects[i + 1..] {
+                assert!(!physics.check_collision(a, b), "{:?} overlaps {:?}", a.object_type, b.object_type);
+            }
+        }
+    }
+
+    fn meta(name: &str, modified_secs: i64) -> SaveMeta {
+        let time = Utc.timestamp_opt(modified_secs, 0).unwrap();
+        SaveMeta {
+            name: name.to_string(),
+            created: time,
+            modified: time,
+            object_count: 0,
+            app_version: String::new(),
+        }
+    }
+
+    #[test]
+    fn named_save_meta_round_trips_and_state_still_loads() {
+        let mut state = AppState::default();
+        let id = state.next_id();
+        state.add_object(DeskObject::new(id, crate::desk_object::ObjectType::Lamp, glam::Vec3::ZERO));
+        let written = SaveMeta {
+            object_count: 1,
+            app_version: "1.2.3".to_string(),
+            ..meta("Morning", 1_700_000_000)
+        };
+
+        let json = serde_json::to_string(&NamedSave { meta: &written, state: &state }).unwrap();
+        let read = SaveMeta::from_json("Morning", &json, Utc::now()).unwrap();
+        assert_eq!(read, written);
+
+        let loaded: AppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.objects.len(), 1);
+    }
+
+    #[test]
+    fn legacy_save_meta_comes_from_contents_and_file_time() {
+        let file_time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let json = r#"{"version": 1, "objects": [{"id": 1}, {"id": 2}], "collision_radius_multiplier": 1.0,
+            "collision_height_multiplier": 1.0, "next_object_id": 3}"#;
+        let read = SaveMeta::from_json("Old", json, file_time).unwrap();
+        assert_eq!(read.object_count, 2);
+        assert_eq!(read.modified, file_time);
+        assert!(SaveMeta::from_json("Broken", "{not json", file_time).is_err());
+    }
+
+    #[test]
+    fn objects_of_unregistered_types_are_left_out() {
+        let json = r#"{"version": 1, "collision_radius_multiplier": 1.0, "collision_height_multiplier": 1.0,
+            "next_object_id": 3, "objects": [{"id": 1, "object_type": "lamp"},
+            {"id": 2, "object_type": {"custom": "not-registered"}}]}"#;
+        let state = AppState::parse(json).unwrap();
+        let ids: Vec<u64> = state.objects.iter().map(|object| object.id).collect();
+        assert_eq!(ids, [1]);
+    }
+
+    #[test]
+    fn undoing_a_layout_load_keeps_new_ids_unique() {
+        let mut state = AppState::default();
+        for _ in 0..2 {
+            let id = state.next_id();
+            state.add_object(DeskObject::new(id, crate::desk_object::ObjectType::Lamp, glam::Vec3::ZERO));
+        }
+        let layout = state.clone();
+        for _ in 0..5 {
+            let id = state.next_id();
+            state.add_object(DeskObject::new(id, crate::desk_object::ObjectType::Lamp, glam::Vec3::ZERO));
+        }
+        let mut history = crate::history::History::new(&state.objects);
+
+        state = layout;
+        history.record(&state.objects);
+        state.restore_objects(history.undo().unwrap());
+
+        let id = state.next_id();
+        assert_eq!(state.objects.len(), 7);
+        assert!(state.objects.iter().all(|object| object.id != id));
+    }
+
+    #[test]
+    fn the_slower_frame_cap_wins() {
+        let mut state = AppState::new();
+        state.low_power = false;
+        state.fps_cap = None;
+        assert_eq!(state.frame_interval(), None);
+        state.fps_cap = Some(60);
+        assert_eq!(state.frame_interval(), Some(frame_interval(60)));
+        state.low_power = true;
+        let low_power = CONFIG.low_power.frame_interval();
+        assert_eq!(state.frame_interval(), Some(low_power.max(frame_interval(60))));
+        state.fps_cap = Some(1);
+        assert_eq!(state.frame_interval(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn recent_colors_are_unique_newest_first_and_capped() {
+        let mut state = AppState::new();
+        for color in 0..12 {
+            state.remember_color(color, false);
+        }
+        assert_eq!(state.recent_colors, (2..12).rev().collect::<Vec<u32>>());
+
+        state.remember_color(5, false);
+        assert_eq!(state.recent_colors[..3], [5, 11, 10]);
+        assert_eq!(state.recent_colors.len(), MAX_RECENT_COLORS);
+
+        state.remember_color(0xff0000, true);
+        assert_eq!(state.recent_colors[..3], [0xff0000, 11, 10]);
+    }
+
+    #[test]
+    fn workspaces_keep_their_own_objects_and_ids() {
+        let mut state = AppState::default_layout();
+        let home_count = state.objects.len();
+        let work = state.add_workspace("Work");
+        assert!(state.switch_workspace(work));
+        assert!(state.objects.is_empty());
+        let id = state.next_id();
+        assert_eq!(id, 1);
+        state.add_object(DeskObject::new(id, crate::desk_object::ObjectType::Laptop, glam::Vec3::ZERO));
+
+        assert!(state.switch_workspace(0));
+        assert_eq!(state.objects.len(), home_count);
+        assert_eq!(state.next_object_id, home_count as u64 + 1);
+        assert!(!state.switch_workspace(0));
+
+        // Deleting the active workspace moves to its neighbour
+        assert!(state.remove_workspace(0));
+        assert_eq!((state.active_workspace, state.workspaces.len()), (0, 1));
+        assert_eq!(state.objects.len(), 1);
+        assert!(!state.remove_workspace(0));
+    }
+
+    #[test]
+    fn saves_sort_by_modified_or_name() {
+        let mut saves = vec![meta("beta", 10), meta("Alpha", 5), meta("gamma", 20)];
+        SaveSort::Modified.sort(&mut saves);
+        let names: Vec<&str> = saves.iter().map(|save| save.name.as_str()).collect();
+        assert_eq!(names, ["gamma", "beta", "Alpha"]);
+
+        SaveSort::Name.sort(&mut saves);
+        let names: Vec<&str> = saves.iter().map(|save| save.name.as_str()).collect();
+        assert_eq!(names, ["Alpha", "beta", "gamma"]);
+    }
 }
@@ -0,0 +1,462 @@
+//! Texture module
+//!
+//! Loads images from disk or generates them procedurally and uploads them
+//! as cached GPU textures for object surfaces (magazine covers, photos, etc.).
+
+use crate::i18n::Key;
+use image::{Rgba, RgbaImage};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Cache key for a texture: an image file on disk or named generated content
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextureKey {
+    /// Image loaded from a file path
+    Path(PathBuf),
+    /// Procedurally generated image, identified by a content-derived name
+    Generated(String),
+    /// Image bundled with the app, by index into `BUILTIN_IMAGES`
+    Builtin(usize),
+}
+
+/// An image bundled with the app that objects showing an image can use
+pub struct BuiltinImage {
+    /// Name shown as the picker tooltip
+    pub name: Key,
+    /// Encoded PNG
+    pub bytes: &'static [u8],
+}
+
+/// Images bundled with the app. Saves refer to them by index, so new ones
+/// go at the end.
+pub const BUILTIN_IMAGES: &[BuiltinImage] = &[
+    BuiltinImage { name: Key::Sunset, bytes: include_bytes!("../assets/images/sunset.png") },
+    BuiltinImage { name: Key::Mountains, bytes: include_bytes!("../assets/images/mountains.png") },
+    BuiltinImage { name: Key::Forest, bytes: include_bytes!("../assets/images/forest.png") },
+    BuiltinImage { name: Key::NightSky, bytes: include_bytes!("../assets/images/night-sky.png") },
+    BuiltinImage { name: Key::Beach, bytes: include_bytes!("../assets/images/beach.png") },
+    BuiltinImage { name: Key::AbstractArt, bytes: include_bytes!("../assets/images/abstract.png") },
+];
+
+/// A GPU texture, held by its ready-to-use bind group
+pub struct Texture {
+    pub bind_group: wgpu::BindGroup,
+}
+
+/// Owns and caches all GPU textures, sharing one sampler and bind group layout
+pub struct TextureManager {
+    textures: HashMap<TextureKey, Texture>,
+    /// Image files that failed to load, so they aren't read again on every rebuild
+    failed: HashSet<PathBuf>,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// 1x1 white texture used when an image is missing or failed to load
+    fallback: Texture,
+}
+
+impl TextureManager {
+    /// Create a texture manager with its sampler and fallback texture
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let layout = Self::create_bind_group_layout(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Surface Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let white = RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        let fallback = Self::upload(device, queue, &layout, &sampler, &white, "Fallback Texture");
+
+        Self {
+            textures: HashMap::new(),
+            failed: HashSet::new(),
+            layout,
+            sampler,
+            fallback,
+        }
+    }
+
+    /// Bind group layout for a texture and its sampler (fragment stage)
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// Get a cached texture, or the fallback if it isn't loaded
+    pub fn get(&self, key: &TextureKey) -> &Texture {
+        self.textures.get(key).unwrap_or(&self.fallback)
+    }
+
+    /// Get an image file's texture, loading and caching it on first use.
+    ///
+    /// Missing or unreadable files log a warning once and yield the fallback
+    /// texture until `retry_path` is called for them.
+    pub fn get_or_load(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+    ) -> &Texture {
+        if self.failed.contains(path) {
+            return &self.fallback;
+        }
+        let key = TextureKey::Path(path.to_path_buf());
+        if !self.textures.contains_key(&key) {
+            match load_image(path) {
+                Ok(image) => {
+                    let label = path.to_string_lossy();
+                    let texture =
+                        Self::upload(device, queue, &self.layout, &self.sampler, &image, &label);
+                    self.textures.insert(key.clone(), texture);
+                }
+                Err(e) => {
+                    log::warn!("Could not load texture {:?}: {}", path, e);
+                    self.failed.insert(path.to_path_buf());
+                    return &self.fallback;
+                }
+            }
+        }
+        self.get(&key)
+    }
+
+    /// Let an image file that failed to load be tried again on next use
+    pub fn retry_path(&mut self, path: &Path) {
+        self.failed.remove(path);
+    }
+
+    /// Get a built-in image's texture, decoding and caching it on first use.
+    ///
+    /// Unknown indices log a warning and yield the fallback texture.
+    pub fn get_or_decode_builtin(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        index: usize,
+    ) -> &Texture {
+        let key = TextureKey::Builtin(index);
+        if !self.textures.contains_key(&key) {
+            match load_builtin_image(index) {
+                Ok(image) => {
+                    let label = format!("Built-in Image {}", index);
+                    let texture =
+                        Self::upload(device, queue, &self.layout, &self.sampler, &image, &label);
+                    self.textures.insert(key.clone(), texture);
+                }
+                Err(e) => {
+                    log::warn!("Could not decode built-in image {}: {}", index, e);
+                    return &self.fallback;
+                }
+            }
+        }
+        self.get(&key)
+    }
+
+    /// Get a generated texture, creating and caching it on first use
+    pub fn get_or_generate(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        name: &str,
+        generate: impl FnOnce() -> RgbaImage,
+    ) -> &Texture {
+        let key = TextureKey::Generated(name.to_string());
+        if !self.textures.contains_key(&key) {
+            let image = generate();
+            let texture = Self::upload(device, queue, &self.layout, &self.sampler, &image, name);
+            self.textures.insert(key.clone(), texture);
+        }
+        self.get(&key)
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("texture_bind_group_layout"),
+        })
+    }
+
+    /// Upload an RGBA image as an sRGB texture with its bind group, scaled
+    /// down first if it is larger than the device allows
+    fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        image: &RgbaImage,
+        label: &str,
+    ) -> Texture {
+        let max_size = device.limits().max_texture_dimension_2d;
+        let scaled = fit_within(image, max_size);
+        if let Some(scaled) = &scaled {
+            log::warn!(
+                "Texture {} is {}x{}, larger than the GPU allows; using it at {}x{}",
+                label,
+                image.width(),
+                image.height(),
+                scaled.width(),
+                scaled.height()
+            );
+        }
+        let image = scaled.as_ref().unwrap_or(image);
+        let (width, height) = image.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("texture_bind_group"),
+        });
+
+        Texture { bind_group }
+    }
+}
+
+/// Whether a file is an image `load_image` can read, judging by its extension
+pub fn is_supported_image(path: &Path) -> bool {
+    matches!(
+        image::ImageFormat::from_path(path),
+        Ok(image::ImageFormat::Png | image::ImageFormat::Jpeg)
+    )
+}
+
+/// A copy of `image` scaled down to at most `max_size` pixels on its longer
+/// side, keeping its aspect, or `None` if it already fits
+pub fn fit_within(image: &RgbaImage, max_size: u32) -> Option<RgbaImage> {
+    let (width, height) = image.dimensions();
+    let longer = width.max(height);
+    if longer <= max_size {
+        return None;
+    }
+    let scale = |side: u32| ((side as u64 * max_size as u64 / longer as u64) as u32).max(1);
+    Some(image::imageops::resize(image, scale(width), scale(height), image::imageops::FilterType::Triangle))
+}
+
+/// Load an image file from disk as RGBA8
+pub fn load_image(path: &Path) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    Ok(image::open(path)?.to_rgba8())
+}
+
+/// Decode one of the `BUILTIN_IMAGES` as RGBA8
+pub fn load_builtin_image(index: usize) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    let builtin = BUILTIN_IMAGES
+        .get(index)
+        .ok_or_else(|| format!("there is no built-in image {}", index))?;
+    Ok(image::load_from_memory_with_format(builtin.bytes, image::ImageFormat::Png)?.to_rgba8())
+}
+
+/// Generate a printed-looking magazine cover from the object's colors
+pub fn generate_magazine_cover(main_color: u32, accent_color: u32) -> RgbaImage {
+    const WIDTH: u32 = 128;
+    const HEIGHT: u32 = 176;
+
+    let to_rgba = |hex: u32| {
+        Rgba([
+            ((hex >> 16) & 0xFF) as u8,
+            ((hex >> 8) & 0xFF) as u8,
+            (hex & 0xFF) as u8,
+            255,
+        ])
+    };
+    let background = to_rgba(main_color);
+    let accent = to_rgba(accent_color);
+    let ink = Rgba([30, 30, 30, 255]);
+
+    RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let margin = 8;
+        let inside = x >= margin && x < WIDTH - margin;
+
+        // Masthead band across the top
+        if (10..34).contains(&y) && inside {
+            return accent;
+        }
+        // Cover photo block
+        if (44..116).contains(&y) && inside {
+            let shade = 140 + ((x + y) % 32) as u8;
+            return Rgba([shade, shade, shade, 255]);
+        }
+        // Headline lines of "text" below the photo
+        if (124..HEIGHT - margin).contains(&y) && inside && (y - 124) % 10 < 4 {
+            let line = (y - 124) / 10;
+            let line_end = WIDTH - margin - 12 * (line % 3);
+            return if x < line_end { ink } else { background };
+        }
+        background
+    })
+}
+
+/// Generate a wood grain texture in shades of `base_color` for a surface of
+/// the given aspect (width / depth), with growth rings running along its width.
+/// Different seeds give different patterns.
+pub fn generate_wood_texture(base_color: u32, seed: u32, aspect: f32) -> RgbaImage {
+    const WIDTH: u32 = 1024;
+    let height = ((WIDTH as f32 / aspect.max(0.1)).round() as u32).clamp(1, 2048);
+
+    let base = [
+        ((base_color >> 16) & 0xFF) as f32,
+        ((base_color >> 8) & 0xFF) as f32,
+        (base_color & 0xFF) as f32,
+    ];
+
+    RgbaImage::from_fn(WIDTH, height, |x, y| {
+        let u = x as f32 / WIDTH as f32;
+        let v = y as f32 / height as f32;
+
+        // Rings: stripes across the depth, bent by low-frequency noise
+        let warp = fbm(u * 3.0, v * 1.5, seed) * 3.0;
+        let rings = ((v * 14.0 + warp) * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+        // Fine fibres stretched along the grain
+        let fibres = fbm(u * 6.0, v * 160.0, seed.wrapping_add(1));
+        let shade = 0.78 + 0.22 * rings.powf(3.0) + 0.12 * (fibres - 0.5);
+
+        let [r, g, b] = base.map(|channel| (channel * shade).clamp(0.0, 255.0) as u8);
+        Rgba([r, g, b, 255])
+    })
+}
+
+/// Fractal (4-octave) value noise in 0..1
+fn fbm(x: f32, y: f32, seed: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 0.5;
+    let mut frequency = 1.0;
+    for octave in 0..4 {
+        sum += amplitude * value_noise(x * frequency, y * frequency, seed.wrapping_add(octave));
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / 0.9375
+}
+
+/// Smoothly interpolated lattice noise in 0..1
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+
+    let corner = |dx: i32, dy: i32| lattice_hash(ix.wrapping_add(dx), iy.wrapping_add(dy), seed);
+    let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * sx;
+    let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * sx;
+    top + (bottom - top) * sy
+}
+
+/// Pseudo-random value in 0..1 for an integer lattice point
+fn lattice_hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32)
+        .wrapping_mul(0x27D4_EB2D)
+        ^ (y as u32).wrapping_mul(0x1656_67B1)
+        ^ seed.wrapping_mul(0x9E37_79B9);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 13;
+    (h & 0xFFFF) as f32 / 65535.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wood_texture_is_seeded_and_grained() {
+        let a = generate_wood_texture(0x8b6914, 7, 10.0 / 7.0);
+        let b = generate_wood_texture(0x8b6914, 7, 10.0 / 7.0);
+        let c = generate_wood_texture(0x8b6914, 8, 10.0 / 7.0);
+        assert_eq!(a.dimensions(), (1024, 717));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let reds: Vec<u8> = a.pixels().map(|pixel| pixel[0]).collect();
+        let (min, max) = (reds.iter().min().unwrap(), reds.iter().max().unwrap());
+        assert!(max - min > 20, "wood should not be a flat color");
+    }
+
+    #[test]
+    fn oversized_images_are_scaled_down_to_fit() {
+        let image = RgbaImage::new(300, 120);
+        assert!(fit_within(&image, 300).is_none());
+        let scaled = fit_within(&image, 100).unwrap();
+        assert_eq!(scaled.dimensions(), (100, 40));
+        let sliver = fit_within(&RgbaImage::new(1, 500), 100).unwrap();
+        assert_eq!(sliver.dimensions(), (1, 100));
+    }
+
+    #[test]
+    fn builtin_images_decode() {
+        for index in 0..BUILTIN_IMAGES.len() {
+            let image = load_builtin_image(index).unwrap();
+            assert_eq!(image.dimensions(), (256, 192));
+        }
+        assert!(load_builtin_image(BUILTIN_IMAGES.len()).is_err());
+    }
+
+    #[test]
+    fn only_png_and_jpeg_files_are_supported_images() {
+        for name in ["photo.png", "Photo.JPG", "dir/cover.jpeg"] {
+            assert!(is_supported_image(Path::new(name)), "{} should be supported", name);
+        }
+        for name in ["notes.txt", "anim.gif", "no_extension"] {
+            assert!(!is_supported_image(Path::new(name)), "{} should not be supported", name);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! UI color themes
+//!
+//! Every color the sidebars and overlays draw with comes from the active
+//! theme's [`ThemePalette`], so switching themes restyles the whole UI.
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+/// Selectable look of the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UiTheme {
+    /// Translucent navy panels (the original look)
+    #[default]
+    Dark,
+    /// Light panels with dark text
+    Light,
+    /// Black panels, white text and bright yellow highlights for low vision
+    HighContrast,
+}
+
+/// Colors used by the UI widgets for one theme
+#[derive(Debug, Clone, Copy)]
+pub struct ThemePalette {
+    /// Titles
+    pub heading: Color32,
+    /// Regular labels and button text
+    pub text: Color32,
+    /// Section captions
+    pub muted: Color32,
+    /// Hints and secondary details
+    pub faint: Color32,
+    /// Primary action color (menu button)
+    pub accent: Color32,
+    /// Text and icons drawn on `accent`
+    pub on_accent: Color32,
+    /// Background of palette entries
+    pub accent_fill: Color32,
+    /// Background of category headers and placeholders
+    pub surface: Color32,
+    /// Destructive actions (delete, clear)
+    pub danger: Color32,
+    /// Background of destructive buttons
+    pub danger_fill: Color32,
+    /// Outline of the selected color swatch
+    pub selection: Color32,
+    /// Outline of unselected swatches, so dark or light ones stay visible
+    pub swatch_border: Color32,
+    /// Background of lock badges drawn over the scene
+    pub badge: Color32,
+    /// Lock badge icon
+    pub badge_icon: Color32,
+    /// Side panel and window background
+    pub panel: Color32,
+}
+
+impl UiTheme {
+    /// Every theme, in settings order
+    pub const ALL: [UiTheme; 3] = [UiTheme::Dark, UiTheme::Light, UiTheme::HighContrast];
+
+    /// Name shown in the settings
+    pub fn label(&self) -> &'static str {
+        match self {
+            UiTheme::Dark => "Dark",
+            UiTheme::Light => "Light",
+            UiTheme::HighContrast => "High contrast",
+        }
+    }
+
+    /// Colors for this theme
+    pub fn palette(&self) -> ThemePalette {
+        match self {
+            UiTheme::Dark => ThemePalette {
+                heading: Color32::WHITE,
+                text: Color32::from_gray(220),
+                muted: Color32::from_gray(150),
+                faint: Color32::from_gray(120),
+                accent: Color32::from_rgb(79, 70, 229),
+                on_accent: Color32::WHITE,
+                accent_fill: Color32::from_rgba_unmultiplied(79, 70, 229, 51),
+                surface: Color32::from_rgba_unmultiplied(255, 255, 255, 13),
+                danger: Color32::from_rgb(239, 68, 68),
+                danger_fill: Color32::from_rgba_unmultiplied(239, 68, 68, 51),
+                selection: Color32::WHITE,
+                swatch_border: Color32::from_gray(100),
+                badge: Color32::from_rgba_unmultiplied(26, 26, 46, 200),
+                badge_icon: Color32::from_rgb(251, 191, 36),
+                panel: Color32::from_rgba_unmultiplied(26, 26, 46, 242),
+            },
+            UiTheme::Light => ThemePalette {
+                heading: Color32::from_gray(15),
+                text: Color32::from_gray(40),
+                muted: Color32::from_gray(85),
+                faint: Color32::from_gray(105),
+                accent: Color32::from_rgb(67, 56, 202),
+                on_accent: Color32::WHITE,
+                accent_fill: Color32::from_rgba_unmultiplied(79, 70, 229, 40),
+                surface: Color32::from_rgba_unmultiplied(0, 0, 0, 15),
+                danger: Color32::from_rgb(185, 28, 28),
+                danger_fill: Color32::from_rgba_unmultiplied(239, 68, 68, 40),
+                selection: Color32::from_gray(20),
+                swatch_border: Color32::from_gray(150),
+                badge: Color32::from_rgba_unmultiplied(255, 255, 255, 220),
+                badge_icon: Color32::from_rgb(180, 83, 9),
+                panel: Color32::from_rgba_unmultiplied(245, 245, 250, 245),
+            },
+            UiTheme::HighContrast => ThemePalette {
+                heading: Color32::WHITE,
+                text: Color32::WHITE,
+                muted: Color32::from_gray(230),
+                faint: Color32::from_gray(210),
+                accent: Color32::from_rgb(0, 0, 160),
+                on_accent: Color32::WHITE,
+                accent_fill: Color32::from_rgb(0, 0, 110),
+                surface: Color32::from_gray(30),
+                danger: Color32::from_rgb(255, 120, 120),
+                danger_fill: Color32::from_rgb(90, 0, 0),
+                selection: Color32::from_rgb(255, 221, 0),
+                swatch_border: Color32::WHITE,
+                badge: Color32::BLACK,
+                badge_icon: Color32::from_rgb(255, 221, 0),
+                panel: Color32::BLACK,
+            },
+        }
+    }
+
+    /// Style egui's built-in widgets (buttons, sliders, text fields) to match
+    pub fn apply(&self, ctx: &egui::Context) {
+        let palette = self.palette();
+        let mut visuals = match self {
+            UiTheme::Light => egui::Visuals::light(),
+            UiTheme::Dark | UiTheme::HighContrast => egui::Visuals::dark(),
+        };
+        visuals.window_fill = palette.panel;
+        visuals.panel_fill = palette.panel;
+
+        if *self == UiTheme::HighContrast {
+            let outline = egui::Stroke::new(1.5, Color32::WHITE);
+            visuals.override_text_color = Some(Color32::WHITE);
+            visuals.extreme_bg_color = Color32::BLACK;
+            visuals.window_stroke = outline;
+            visuals.selection.bg_fill = Color32::from_rgb(0, 0, 160);
+            visuals.selection.stroke = egui::Stroke::new(2.0, palette.selection);
+            visuals.hyperlink_color = palette.selection;
+            for widget in [
+                &mut visuals.widgets.inactive,
+                &mut visuals.widgets.hovered,
+                &mut visuals.widgets.active,
+                &mut visuals.widgets.open,
+            ] {
+                widget.bg_stroke = outline;
+                widget.fg_stroke = egui::Stroke::new(1.5, Color32::WHITE);
+            }
+            visuals.widgets.inactive.weak_bg_fill = Color32::from_gray(20);
+            visuals.widgets.hovered.bg_stroke = egui::Stroke::new(2.0, palette.selection);
+            visuals.widgets.noninteractive.fg_stroke = egui::Stroke::new(1.0, Color32::WHITE);
+        }
+
+        ctx.set_visuals(visuals);
+    }
+}
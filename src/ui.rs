@@ -2,15 +2,126 @@
 //!
 //! Implements:
 //! - Left sidebar: Object palette with categories (like the reference Electron app)
-//! - Right sidebar: Object customization panel (colors, delete)
+//! - Right sidebar: Object customization panel (transform, colors, delete)
 
-use crate::desk_object::ObjectType;
+use crate::config::{parse_hex_color, KeyAction, KeyBinding, Keybindings, PresentMode, ScrollSettings, CONFIG};
+use crate::desk_object::{
+    parse_tags, DeskObject, ImageSource, MirrorAxis, ObjectType, ANIM_SPEED_RANGE, BOOK_COUNT_RANGE,
+    LIGHT_RADIUS_RANGE, MAX_SCALE, MIN_SCALE,
+};
+use crate::focus::format_focus_time;
+use crate::i18n::{Key, Lang};
+use crate::registry;
+use crate::state::{
+    AppState, SaveMeta, SaveSort, FPS_CAP_CHOICES, RESOLUTION_DIVISOR_RANGE, SCROLL_SENSITIVITY_RANGE, TURNTABLE_SPEED_RANGE,
+    UI_SCALE_RANGE,
+};
+use crate::texture::{load_builtin_image, load_image, BUILTIN_IMAGES};
+use crate::theme::{ThemePalette, UiTheme};
 use egui::{Color32, RichText, Vec2};
+use glam::{EulerRot, Quat, Vec3};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long a toast notification stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// How long hints (e.g. the first-run welcome) stay on screen
+const HINT_DURATION: Duration = Duration::from_secs(8);
+
+/// Console lines kept in the scrollback
+const CONSOLE_SCROLLBACK: usize = 200;
+
+/// Display size of a layout thumbnail in the load grid (16:9)
+const THUMBNAIL_DISPLAY_SIZE: Vec2 = Vec2::new(104.0, 58.5);
+
+/// Display size of a built-in image in the customization panel (4:3)
+const BUILTIN_IMAGE_DISPLAY_SIZE: Vec2 = Vec2::new(52.0, 39.0);
+
+/// A short-lived notification shown at the bottom of the screen
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub expires_at: Instant,
+}
+
+/// The selected object's transform as edited in the inspector
+#[derive(Debug, Clone, Copy)]
+pub struct TransformFields {
+    pub position: Vec3,
+    /// Euler angles in degrees about X, Y and Z, applied yaw first
+    pub rotation: Vec3,
+    pub scale: f32,
+    /// Rotation the euler angles were derived from or last written as
+    rotation_source: Quat,
+    /// Whether an edit is being dragged or typed and not yet an undo step
+    editing: bool,
+}
+
+impl Default for TransformFields {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            scale: 1.0,
+            rotation_source: Quat::IDENTITY,
+            editing: false,
+        }
+    }
+}
+
+impl TransformFields {
+    /// Follow an object's transform. The euler angles are only derived anew
+    /// when the object was turned some other way, so angles being typed
+    /// aren't rewritten into an equivalent set (e.g. 200° into -160°), which
+    /// would make the fields jump near gimbal lock.
+    pub fn sync(&mut self, object: &DeskObject) {
+        self.position = object.position;
+        self.scale = object.scale;
+        if self.rotation_source.dot(object.rotation).abs() < 1.0 - 1e-6 {
+            let (yaw, pitch, roll) = object.rotation.to_euler(EulerRot::YXZ);
+            self.rotation = Vec3::new(pitch, yaw, roll).map(f32::to_degrees);
+            self.rotation_source = object.rotation;
+        }
+    }
+
+    /// Rotation given by the edited euler angles
+    pub fn rotation_quat(&self) -> Quat {
+        let radians = self.rotation.map(f32::to_radians);
+        Quat::from_euler(EulerRot::YXZ, radians.y, radians.x, radians.z)
+    }
+}
+
+/// Panels that were open when clean view hid them
+#[derive(Debug, Clone, Copy, Default)]
+struct PanelSnapshot {
+    left_sidebar: bool,
+    console: bool,
+    debug_overlay: bool,
+}
+
+/// Frame statistics shown in the debug overlay
+#[derive(Debug, Clone, Copy)]
+pub struct DebugStats {
+    pub fps: f32,
+    pub object_count: usize,
+    pub max_objects: usize,
+}
+
+/// Focus time shown in the focus widget
+#[derive(Debug, Clone, Copy)]
+pub struct FocusSummary {
+    /// Time focused so far today
+    pub today: Duration,
+    /// Consecutive days reaching the streak minimum
+    pub streak: u32,
+    /// Time left on the running hourglass timer with the least remaining
+    pub timer_remaining: Option<Duration>,
+}
 
 /// Palette category for organizing object types
 #[derive(Debug, Clone)]
 pub struct PaletteCategory {
-    pub name: &'static str,
+    pub name: Key,
     pub icon: &'static str,
     pub variants: Vec<PaletteVariant>,
     pub expanded: bool,
@@ -20,35 +131,57 @@ pub struct PaletteCategory {
 #[derive(Debug, Clone)]
 pub struct PaletteVariant {
     pub object_type: ObjectType,
-    pub name: &'static str,
     pub icon: &'static str,
 }
 
+impl PaletteVariant {
+    /// Whether the name or description contains the search text (ignoring
+    /// case), in `lang` or in English
+    fn matches_search(&self, query: &str, lang: Lang) -> bool {
+        let query = query.trim().to_lowercase();
+        let matches = |lang: Lang| {
+            lang.object_name(self.object_type).to_lowercase().contains(&query)
+                || lang.object_description(self.object_type).to_lowercase().contains(&query)
+        };
+        query.is_empty() || matches(lang) || matches(Lang::English)
+    }
+}
+
+/// Whether an object's name (in `lang` or English) or one of its tags
+/// contains the search text, ignoring case
+fn object_matches_search(object: &DeskObject, query: &str, lang: Lang) -> bool {
+    let query = query.trim().to_lowercase();
+    query.is_empty()
+        || lang.object_label(object).to_lowercase().contains(&query)
+        || Lang::English.object_label(object).to_lowercase().contains(&query)
+        || object.has_tag_matching(&query)
+}
+
 /// Color presets for object customization
-pub const COLOR_PRESETS: &[(u32, &str)] = &[
-    (0xEF4444, "Red"),
-    (0xF97316, "Orange"),
-    (0xEAB308, "Yellow"),
-    (0x22C55E, "Green"),
-    (0x3B82F6, "Blue"),
-    (0x8B5CF6, "Purple"),
-    (0xEC4899, "Pink"),
-    (0xFFFFFF, "White"),
-    (0x64748B, "Gray"),
-    (0x1E293B, "Dark"),
+pub const COLOR_PRESETS: &[(u32, Key)] = &[
+    (0xEF4444, Key::Red),
+    (0xF97316, Key::Orange),
+    (0xEAB308, Key::Yellow),
+    (0x22C55E, Key::Green),
+    (0x3B82F6, Key::Blue),
+    (0x8B5CF6, Key::Purple),
+    (0xEC4899, Key::Pink),
+    (0xFFFFFF, Key::White),
+    (0x64748B, Key::Gray),
+    (0x1E293B, Key::Dark),
 ];
 
-pub const ACCENT_COLOR_PRESETS: &[(u32, &str)] = &[
-    (0xFBBF24, "Amber"),
-    (0xA3E635, "Lime"),
-    (0x2DD4BF, "Teal"),
-    (0x60A5FA, "Light Blue"),
-    (0xC084FC, "Lavender"),
-    (0xF472B6, "Rose"),
-    (0xFB923C, "Peach"),
-    (0xD4D4D4, "Silver"),
-    (0x000000, "Black"),
-    (0xFFFFFF, "White"),
+pub const ACCENT_COLOR_PRESETS: &[(u32, Key)] = &[
+    (0xFBBF24, Key::Amber),
+    (0xA3E635, Key::Lime),
+    (0x2DD4BF, Key::Teal),
+    (0x60A5FA, Key::LightBlue),
+    (0xC084FC, Key::Lavender),
+    (0xF472B6, Key::Rose),
+    (0xFB923C, Key::Peach),
+    (0xD4D4D4, Key::Silver),
+    (0x000000, Key::Black),
+    (0xFEF3C7, Key::Cream),
 ];
 
 /// UI state for menus
@@ -59,12 +192,155 @@ pub struct UiState {
     pub right_sidebar_open: bool,
     /// Palette categories
     pub categories: Vec<PaletteCategory>,
+    /// Text typed into the palette search field
+    pub palette_search: String,
+    /// Variants the search and filter chips leave, in palette order; Enter
+    /// in the search field adds the first
+    pub palette_results: Vec<ObjectType>,
+    /// Text filtering the object list by name or tag
+    pub object_search: String,
+    /// Names of the categories picked with the filter chips (all shown if empty)
+    pub category_filters: HashSet<Key>,
     /// Currently selected object for customization
     pub selected_object_id: Option<u64>,
+    /// Every selected object, the one being customized first; colors are
+    /// applied to all of them
+    pub selected_ids: Vec<u64>,
+    /// Whether Delete Selected was clicked once for a selection large enough
+    /// to need a second click
+    pub delete_selected_armed: bool,
+    /// Workspace whose Delete was clicked once; a second click deletes it
+    pub delete_workspace_armed: Option<usize>,
+    /// Whether the selected objects differ in main color
+    pub main_color_mixed: bool,
+    /// Whether the selected objects differ in accent color
+    pub accent_color_mixed: bool,
     /// Current main color for selected object
     pub current_main_color: u32,
     /// Current accent color for selected object
     pub current_accent_color: u32,
+    /// Hex text of the main color field
+    pub main_color_input: String,
+    /// Hex text of the accent color field
+    pub accent_color_input: String,
+    /// Name being edited for the selected object
+    pub name_input: String,
+    /// Comma-separated tags being edited for the selected object
+    pub tags_input: String,
+    /// Type of the selected object
+    pub selected_object_type: Option<ObjectType>,
+    /// Transform of the selected object in the inspector
+    pub transform: TransformFields,
+    /// Current book count for a selected Books stack
+    pub current_book_count: u32,
+    /// Animation speed multiplier of the selected object
+    pub current_anim_speed: f32,
+    /// Light radius of a selected lamp
+    pub current_light_radius: f32,
+    /// Image path being edited for a selected object that shows one
+    pub cover_path_input: String,
+    /// Built-in image shown by the selected object, if it uses one
+    pub current_builtin_image: Option<usize>,
+    /// Active toast notifications
+    pub toasts: Vec<Toast>,
+    /// Whether the FPS/debug overlay is visible
+    pub show_debug_overlay: bool,
+    /// Whether clean view hides all UI chrome
+    pub ui_hidden: bool,
+    /// Panels to reopen when clean view ends
+    hidden_panels: PanelSnapshot,
+    /// Object whose context menu is open, and the menu position in points
+    pub context_menu: Option<(u64, egui::Pos2)>,
+    /// Whether the object under the context menu is locked
+    pub context_menu_locked: bool,
+    /// Whether the hourglass under the context menu is running (`None` for
+    /// other objects)
+    pub context_menu_timer: Option<bool>,
+    /// Whether the object under the context menu is of a registered type
+    /// that can be used
+    pub context_menu_interact: bool,
+    /// Whether the selected object is locked
+    pub current_locked: bool,
+    /// Whether Clear All spares locked objects
+    pub keep_locked_on_clear: bool,
+    /// Whether the camera uses the orthographic (isometric) projection
+    pub orthographic: bool,
+    /// UI scale being edited in the settings slider
+    pub ui_scale: f32,
+    /// Whether scroll-wheel rotation snaps to 15° steps
+    pub rotation_snap: bool,
+    /// Whether dragged objects snap to nearby desk edges
+    pub edge_snap: bool,
+    /// Whether the selected object shows a transform gizmo
+    pub show_gizmo: bool,
+    /// Whether palette items are dragged onto the desk
+    pub palette_drag: bool,
+    /// Whether new objects appear where the camera is looking
+    pub spawn_at_aim: bool,
+    /// Whether the layout grid and rulers are shown
+    pub show_grid: bool,
+    /// Idle motion amplitude being edited (0 = off)
+    pub idle_motion: f32,
+    /// Bloom intensity being edited (0 = off)
+    pub bloom_intensity: f32,
+    /// Internal resolution divisor being edited (1 = full resolution)
+    pub resolution_divisor: u32,
+    /// Whether the camera slowly orbits the desk on its own
+    pub turntable: bool,
+    /// Turntable speed being edited, in degrees per second
+    pub turntable_speed: f32,
+    /// Whether low power / reduced motion mode is on
+    pub low_power: bool,
+    /// Chosen vsync / present mode
+    pub present_mode: PresentMode,
+    /// Present modes the window surface supports
+    pub supported_present_modes: Vec<PresentMode>,
+    /// Frame rate cap (`None` = uncapped)
+    pub fps_cap: Option<u32>,
+    /// Scroll settings being edited
+    pub scroll: ScrollSettings,
+    /// Whether the session is being recorded
+    pub recording: bool,
+    /// Whether a recording is being played back
+    pub playing: bool,
+    /// Whether the debug console is open
+    pub console_open: bool,
+    /// Command being typed into the console
+    pub console_input: String,
+    /// Console commands and their output, oldest first
+    pub console_log: Vec<String>,
+    /// Active UI color theme
+    pub theme: UiTheme,
+    /// Language of the UI text
+    pub lang: Lang,
+    /// Keybindings shown in the settings
+    pub keybindings: Keybindings,
+    /// Action waiting for the next key press to become its binding
+    pub capturing_key: Option<KeyAction>,
+    /// Name typed into the layout save field
+    pub save_name_input: String,
+    /// Optional seed typed in for a reproducible shuffle
+    pub shuffle_seed_input: String,
+    /// Named layouts on disk, in `layout_sort` order
+    pub saved_layouts: Vec<SaveMeta>,
+    /// Workspace names, in tab order
+    pub workspace_names: Vec<String>,
+    /// Index of the workspace on screen
+    pub active_workspace: usize,
+    /// Whether each workspace keeps its own camera view
+    pub workspace_cameras: bool,
+    /// Name being typed in a workspace tab's rename field
+    pub workspace_name_input: String,
+    /// Saved object templates, shown as favorites
+    pub templates: Vec<DeskObject>,
+    /// Colors last applied to objects, most recent first
+    pub recent_colors: Vec<u32>,
+    /// Order of the layout grid
+    pub layout_sort: SaveSort,
+    /// Loaded layout thumbnails by save name (`None` if missing or unreadable)
+    layout_thumbnails: HashMap<String, Option<egui::TextureHandle>>,
+    /// Decoded built-in images for the picker, by index (`None` if unreadable)
+    builtin_image_textures: HashMap<usize, Option<egui::TextureHandle>>,
 }
 
 impl Default for UiState {
@@ -75,139 +351,206 @@ impl Default for UiState {
 
 impl UiState {
     pub fn new() -> Self {
-        let categories = vec![
+        let mut categories = vec![
             PaletteCategory {
-                name: "Clocks",
+                name: Key::Clocks,
                 icon: "🕐",
                 variants: vec![
                     PaletteVariant {
                         object_type: ObjectType::Clock,
-                        name: "Clock",
                         icon: "🕐",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Hourglass,
-                        name: "Hourglass",
                         icon: "⏳",
                     },
                 ],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Lighting",
+                name: Key::Lighting,
                 icon: "💡",
                 variants: vec![PaletteVariant {
                     object_type: ObjectType::Lamp,
-                    name: "Desk Lamp",
                     icon: "💡",
                 }],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Writing",
+                name: Key::Writing,
                 icon: "📝",
                 variants: vec![
                     PaletteVariant {
                         object_type: ObjectType::Notebook,
-                        name: "Notebook",
                         icon: "📓",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Paper,
-                        name: "Paper",
                         icon: "📄",
                     },
                     PaletteVariant {
                         object_type: ObjectType::PenHolder,
-                        name: "Pen Holder",
                         icon: "🖊️",
                     },
                 ],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Books",
+                name: Key::Books,
                 icon: "📚",
                 variants: vec![
                     PaletteVariant {
                         object_type: ObjectType::Books,
-                        name: "Books",
                         icon: "📕",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Magazine,
-                        name: "Magazine",
                         icon: "📰",
                     },
                 ],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Audio",
+                name: Key::Audio,
                 icon: "🎵",
                 variants: vec![PaletteVariant {
                     object_type: ObjectType::Metronome,
-                    name: "Metronome",
                     icon: "🎵",
                 }],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Trinkets",
+                name: Key::Trinkets,
                 icon: "🎁",
                 variants: vec![
                     PaletteVariant {
                         object_type: ObjectType::Coffee,
-                        name: "Coffee Mug",
                         icon: "☕",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Plant,
-                        name: "Plant",
                         icon: "🌱",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Globe,
-                        name: "Globe",
                         icon: "🌍",
                     },
                     PaletteVariant {
                         object_type: ObjectType::Trophy,
-                        name: "Trophy",
                         icon: "🏆",
                     },
                 ],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Frames",
+                name: Key::Frames,
                 icon: "🖼️",
                 variants: vec![PaletteVariant {
                     object_type: ObjectType::PhotoFrame,
-                    name: "Photo Frame",
                     icon: "🖼️",
                 }],
                 expanded: false,
             },
             PaletteCategory {
-                name: "Tech",
+                name: Key::Tech,
                 icon: "💻",
                 variants: vec![PaletteVariant {
                     object_type: ObjectType::Laptop,
-                    name: "Laptop",
                     icon: "💻",
                 }],
                 expanded: false,
             },
         ];
+        // Types the embedding application registered
+        let registered = registry::registered_types();
+        if !registered.is_empty() {
+            categories.push(PaletteCategory {
+                name: Key::MoreObjects,
+                icon: "🧩",
+                variants: registered
+                    .into_iter()
+                    .map(|object_type| PaletteVariant { object_type, icon: object_type.icon() })
+                    .collect(),
+                expanded: false,
+            });
+        }
 
         Self {
             left_sidebar_open: false,
             right_sidebar_open: false,
             categories,
+            palette_search: String::new(),
+            palette_results: Vec::new(),
+            object_search: String::new(),
+            category_filters: HashSet::new(),
             selected_object_id: None,
+            selected_ids: Vec::new(),
+            delete_selected_armed: false,
+            delete_workspace_armed: None,
+            main_color_mixed: false,
+            accent_color_mixed: false,
             current_main_color: 0xFFFFFF,
             current_accent_color: 0x1E293B,
+            main_color_input: String::new(),
+            accent_color_input: String::new(),
+            name_input: String::new(),
+            tags_input: String::new(),
+            selected_object_type: None,
+            transform: TransformFields::default(),
+            current_book_count: 3,
+            current_anim_speed: 1.0,
+            current_light_radius: 1.0,
+            cover_path_input: String::new(),
+            current_builtin_image: None,
+            toasts: Vec::new(),
+            show_debug_overlay: false,
+            ui_hidden: false,
+            hidden_panels: PanelSnapshot::default(),
+            context_menu: None,
+            context_menu_locked: false,
+            context_menu_timer: None,
+            context_menu_interact: false,
+            current_locked: false,
+            keep_locked_on_clear: true,
+            orthographic: false,
+            ui_scale: 1.0,
+            rotation_snap: true,
+            edge_snap: true,
+            show_gizmo: true,
+            palette_drag: false,
+            spawn_at_aim: false,
+            show_grid: false,
+            idle_motion: 0.0,
+            bloom_intensity: 0.0,
+            resolution_divisor: 1,
+            turntable: false,
+            turntable_speed: CONFIG.camera.turntable_speed,
+            low_power: false,
+            present_mode: PresentMode::default(),
+            supported_present_modes: vec![PresentMode::AutoVsync, PresentMode::Fifo],
+            fps_cap: None,
+            scroll: ScrollSettings::default(),
+            recording: false,
+            playing: false,
+            console_open: false,
+            console_input: String::new(),
+            console_log: Vec::new(),
+            theme: UiTheme::default(),
+            lang: Lang::default(),
+            keybindings: Keybindings::default(),
+            capturing_key: None,
+            save_name_input: String::new(),
+            shuffle_seed_input: String::new(),
+            saved_layouts: Vec::new(),
+            workspace_names: Vec::new(),
+            active_workspace: 0,
+            workspace_cameras: false,
+            workspace_name_input: String::new(),
+            templates: Vec::new(),
+            recent_colors: Vec::new(),
+            layout_sort: SaveSort::default(),
+            layout_thumbnails: HashMap::new(),
+            builtin_image_textures: HashMap::new(),
         }
     }
 
@@ -215,21 +558,227 @@ impl UiState {
         self.left_sidebar_open = !self.left_sidebar_open;
     }
 
+    /// Whether the category filter chips let a category through
+    pub fn category_shown(&self, name: Key) -> bool {
+        self.category_filters.is_empty() || self.category_filters.contains(&name)
+    }
+
+    /// Turn a category's filter chip on or off
+    pub fn toggle_category_filter(&mut self, name: Key) {
+        if !self.category_filters.remove(&name) {
+            self.category_filters.insert(name);
+        }
+    }
+
+    /// Recompute `palette_results` for the current search and filter chips
+    pub fn update_palette_results(&mut self, lang: Lang) {
+        let results = self
+            .categories
+            .iter()
+            .filter(|category| self.category_shown(category.name))
+            .flat_map(|category| &category.variants)
+            .filter(|variant| variant.matches_search(&self.palette_search, lang))
+            .map(|variant| variant.object_type)
+            .collect();
+        self.palette_results = results;
+    }
+
     pub fn toggle_right_sidebar(&mut self) {
         self.right_sidebar_open = !self.right_sidebar_open;
     }
 
-    pub fn open_customization(&mut self, object_id: u64, main_color: u32, accent_color: u32) {
-        self.selected_object_id = Some(object_id);
-        self.current_main_color = main_color;
-        self.current_accent_color = accent_color;
+    pub fn open_customization(&mut self, object: &DeskObject) {
+        self.selected_object_id = Some(object.id);
+        self.selected_ids = vec![object.id];
+        self.delete_selected_armed = false;
+        self.main_color_mixed = false;
+        self.accent_color_mixed = false;
+        self.selected_object_type = Some(object.object_type);
+        self.name_input = object.name.clone().unwrap_or_default();
+        self.tags_input = object.tags.join(", ");
+        self.current_main_color = object.color;
+        self.current_accent_color = object.accent_color;
+        self.main_color_input = format_hex_color(object.color);
+        self.accent_color_input = format_hex_color(object.accent_color);
+        self.transform.sync(object);
+        self.transform.editing = false;
+        self.current_book_count = object.book_count;
+        self.current_anim_speed = object.anim_speed;
+        self.current_light_radius = object.state.light_radius().unwrap_or(1.0);
+        self.cover_path_input = object.image_path().unwrap_or_default().to_string();
+        self.current_builtin_image = match object.cover_image {
+            Some(ImageSource::Builtin { builtin }) => Some(builtin),
+            _ => None,
+        };
+        self.current_locked = object.locked;
         self.right_sidebar_open = true;
     }
 
+    /// Select several objects, customizing the first. Colors they don't
+    /// all share show as mixed.
+    pub fn select_many(&mut self, objects: &[&DeskObject]) {
+        let Some(first) = objects.first() else {
+            self.close_customization();
+            return;
+        };
+        self.open_customization(first);
+        self.selected_ids = objects.iter().map(|obj| obj.id).collect();
+        self.main_color_mixed = objects.iter().any(|obj| obj.color != first.color);
+        self.accent_color_mixed = objects.iter().any(|obj| obj.accent_color != first.accent_color);
+        if self.main_color_mixed {
+            self.main_color_input.clear();
+        }
+        if self.accent_color_mixed {
+            self.accent_color_input.clear();
+        }
+    }
+
     pub fn close_customization(&mut self) {
         self.selected_object_id = None;
+        self.selected_ids.clear();
+        self.delete_selected_armed = false;
+        self.selected_object_type = None;
         self.right_sidebar_open = false;
     }
+
+    /// Handle a click on Delete Selected, returning whether to delete now. A
+    /// selection of `ObjectConfig::delete_confirm_count` or more objects is
+    /// only deleted on a second click.
+    pub fn confirm_delete_selected(&mut self) -> bool {
+        if self.selected_ids.len() < CONFIG.objects.delete_confirm_count || self.delete_selected_armed {
+            self.delete_selected_armed = false;
+            return true;
+        }
+        self.delete_selected_armed = true;
+        false
+    }
+
+    /// Handle a click on a workspace's Delete, returning whether to delete it
+    /// now. Workspaces are only deleted on a second click.
+    pub fn confirm_delete_workspace(&mut self, index: usize) -> bool {
+        if self.delete_workspace_armed == Some(index) {
+            self.delete_workspace_armed = None;
+            return true;
+        }
+        self.delete_workspace_armed = Some(index);
+        false
+    }
+
+    pub fn open_context_menu(&mut self, object: &DeskObject, position: egui::Pos2) {
+        self.context_menu = Some((object.id, position));
+        self.context_menu_locked = object.locked;
+        self.context_menu_timer = (object.object_type == ObjectType::Hourglass)
+            .then(|| object.state.timer_remaining().is_some());
+        self.context_menu_interact = match object.object_type {
+            ObjectType::Custom(custom) => custom.descriptor().interact.is_some(),
+            _ => false,
+        };
+    }
+
+    pub fn close_context_menu(&mut self) {
+        self.context_menu = None;
+    }
+
+    /// Hide every panel for an unobstructed view of the desk, or bring back
+    /// the ones that were open before
+    pub fn toggle_clean_view(&mut self) {
+        if !self.leave_clean_view() {
+            self.hidden_panels = PanelSnapshot {
+                left_sidebar: self.left_sidebar_open,
+                console: self.console_open,
+                debug_overlay: self.show_debug_overlay,
+            };
+            self.left_sidebar_open = false;
+            self.right_sidebar_open = false;
+            self.console_open = false;
+            self.show_debug_overlay = false;
+            self.close_context_menu();
+            self.ui_hidden = true;
+        }
+    }
+
+    /// Leave clean view, reopening the panels it hid. The customization
+    /// sidebar comes back for whatever is selected by then. Returns whether
+    /// clean view was on.
+    pub fn leave_clean_view(&mut self) -> bool {
+        if !self.ui_hidden {
+            return false;
+        }
+        self.left_sidebar_open = self.hidden_panels.left_sidebar;
+        self.right_sidebar_open = self.selected_object_id.is_some();
+        self.console_open = self.hidden_panels.console;
+        self.show_debug_overlay = self.hidden_panels.debug_overlay;
+        self.ui_hidden = false;
+        true
+    }
+
+    /// Forget a layout's cached thumbnail so it is reloaded from disk
+    pub fn invalidate_thumbnail(&mut self, name: &str) {
+        self.layout_thumbnails.remove(name);
+    }
+
+    /// Get a layout's thumbnail texture, loading it from disk on first use
+    fn layout_thumbnail(&mut self, ctx: &egui::Context, name: &str) -> Option<egui::TextureHandle> {
+        self.layout_thumbnails
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let path = AppState::thumbnail_path(name)?;
+                let image = load_image(&path)
+                    .map_err(|e| log::debug!("No thumbnail for layout {:?}: {}", name, e))
+                    .ok()?;
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                Some(ctx.load_texture(
+                    format!("layout-thumbnail-{}", name),
+                    color_image,
+                    egui::TextureOptions::LINEAR,
+                ))
+            })
+            .clone()
+    }
+
+    /// Get a built-in image's texture for the picker, decoding it on first use
+    fn builtin_image_texture(&mut self, ctx: &egui::Context, index: usize) -> Option<egui::TextureHandle> {
+        self.builtin_image_textures
+            .entry(index)
+            .or_insert_with(|| {
+                let image = load_builtin_image(index)
+                    .map_err(|e| log::warn!("Could not decode built-in image {}: {}", index, e))
+                    .ok()?;
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                Some(ctx.load_texture(
+                    format!("builtin-image-{}", index),
+                    color_image,
+                    egui::TextureOptions::LINEAR,
+                ))
+            })
+            .clone()
+    }
+
+    /// Show a toast notification
+    pub fn show_toast(&mut self, message: impl Into<String>) {
+        self.show_toast_for(message, TOAST_DURATION);
+    }
+
+    /// Show a toast that stays up long enough to read a tip
+    pub fn show_hint(&mut self, message: impl Into<String>) {
+        self.show_toast_for(message, HINT_DURATION);
+    }
+
+    fn show_toast_for(&mut self, message: impl Into<String>, duration: Duration) {
+        self.toasts.push(Toast {
+            message: message.into(),
+            expires_at: Instant::now() + duration,
+        });
+    }
+
+    /// Add a line to the console scrollback
+    pub fn console_print(&mut self, line: impl Into<String>) {
+        self.console_log.push(line.into());
+        let excess = self.console_log.len().saturating_sub(CONSOLE_SCROLLBACK);
+        self.console_log.drain(..excess);
+    }
 }
 
 /// UI action that can be returned from rendering
@@ -237,14 +786,145 @@ impl UiState {
 pub enum UiAction {
     /// Add an object of the specified type
     AddObject(ObjectType),
+    /// Add an object of the specified type right away at the spawn position,
+    /// skipping the placement preview (Enter in the palette search)
+    AddObjectNow(ObjectType),
+    /// Start dragging a new object of the specified type out of the palette
+    BeginPaletteDrag(ObjectType),
+    /// Let go of a palette drag; `over_ui` drops it on a panel, which cancels it
+    EndPaletteDrag { over_ui: bool },
+    /// Delete every selected object that isn't locked, in one undo step
+    DeleteSelected,
     /// Delete the currently selected object
     DeleteObject(u64),
-    /// Change main color of selected object
+    /// Change main color of selected object (of every selected object if it
+    /// is part of a multi-selection)
     ChangeMainColor(u64, u32),
-    /// Change accent color of selected object
+    /// Change accent color of selected object (of every selected object if it
+    /// is part of a multi-selection)
     ChangeAccentColor(u64, u32),
-    /// Clear all objects from the desk
-    ClearAll,
+    /// Change the number of books in a Books stack
+    ChangeBookCount(u64, u32),
+    /// Change how fast an object bobs and sways
+    ChangeAnimSpeed(u64, f32),
+    /// Change how far a lamp's light reaches
+    SetLightRadius(u64, f32),
+    /// Set or clear the image of a Magazine, Photo Frame or Laptop
+    SetCoverImage(u64, Option<ImageSource>),
+    /// Open the customization panel for an object
+    Customize(u64),
+    /// Select an object from the object list and center the view on it
+    SelectObject(u64),
+    /// Add an object to the selection, or take it out if it's already in it
+    ToggleSelected(u64),
+    /// Rename an object (`None` restores its type name)
+    RenameObject(u64, Option<String>),
+    /// Replace an object's tags
+    SetTags(u64, Vec<String>),
+    /// Move, turn and resize an object to values typed into the inspector.
+    /// Edits still being dragged or typed are shown without an undo step;
+    /// the `finished` one records the whole edit as one.
+    SetTransform {
+        id: u64,
+        position: Vec3,
+        rotation: Quat,
+        scale: f32,
+        finished: bool,
+    },
+    /// Hide or show an object
+    SetHidden(u64, bool),
+    /// Add a copy of an object next to it
+    Duplicate(u64),
+    /// Save an object's type, colors and settings as a favorite template
+    SaveTemplate(u64),
+    /// Add a copy of a favorite template to the desk
+    AddTemplate(usize),
+    /// Remove a favorite template
+    RemoveTemplate(usize),
+    /// Dim everything except this object
+    Focus(u64),
+    /// Move an object to the end of the draw order
+    BringToFront(u64),
+    /// Move an object to the start of the draw order
+    SendToBack(u64),
+    /// Mirror an object to the other side of the desk
+    Mirror(u64, MirrorAxis),
+    /// Replace unlocked objects with a random layout (random seed if `None`)
+    Shuffle(Option<u64>),
+    /// Line up unlocked objects in a grid, grouped by type
+    Arrange,
+    /// Clear all objects from the desk, optionally keeping locked ones
+    ClearAll { keep_locked: bool },
+    /// Lock or unlock an object
+    SetLocked(u64, bool),
+    /// Start or stop an hourglass timer
+    ToggleTimer(u64),
+    /// Use an object of a registered type (see `ObjectDescriptor::interact`)
+    Interact(u64),
+    /// Change the UI scale (multiplies the monitor's pixels-per-point)
+    SetUiScale(f32),
+    /// Turn scroll-wheel rotation snapping on or off
+    SetRotationSnap(bool),
+    /// Turn snapping dragged objects to the desk edges on or off
+    SetEdgeSnap(bool),
+    /// Show or hide the transform gizmo on the selected object
+    SetShowGizmo(bool),
+    /// Switch between dragging and click-to-place for palette items
+    SetPaletteDrag(bool),
+    /// Switch between adding objects where the camera looks and at random spots
+    SetSpawnAtAim(bool),
+    /// Switch the camera between perspective and orthographic projection
+    SetOrthographic(bool),
+    /// Show or hide the layout grid and rulers
+    SetShowGrid(bool),
+    /// Change the idle motion amplitude (0 = off)
+    SetIdleMotion(f32),
+    /// Change the bloom intensity (0 = off)
+    SetBloomIntensity(f32),
+    /// Draw the scene at 1/N of the window size (1 = full resolution)
+    SetResolutionDivisor(u32),
+    /// Turn the turntable camera on or off
+    SetTurntable(bool),
+    /// Change the turntable speed, in degrees per second
+    SetTurntableSpeed(f32),
+    /// Turn low power / reduced motion mode on or off
+    SetLowPower(bool),
+    /// Change scroll sensitivity and direction
+    SetScroll(ScrollSettings),
+    /// Switch the vsync / present mode
+    SetPresentMode(PresentMode),
+    /// Cap the frame rate (`None` = uncapped)
+    SetFpsCap(Option<u32>),
+    /// Switch the UI color theme
+    SetTheme(UiTheme),
+    /// Switch the UI language
+    SetLanguage(Lang),
+    /// Bind a keyboard action to a key
+    SetKeybinding(KeyAction, KeyBinding),
+    /// Restore the default keybindings
+    ResetKeybindings,
+    /// Start recording the session, or stop and save the recording
+    ToggleRecording,
+    /// Replay the most recent recording
+    PlayLastRecording,
+    /// Stop playback and restore the desk
+    StopPlayback,
+    /// Save the current desk as a named layout
+    SaveLayout(String),
+    /// Replace the desk with a named layout
+    LoadLayout(String),
+    /// Put the desk away and bring out another workspace's
+    SwitchWorkspace(usize),
+    /// Add an empty workspace and switch to it
+    AddWorkspace,
+    /// Rename a workspace (an empty name numbers it)
+    RenameWorkspace(usize, String),
+    /// Delete a workspace and its objects
+    RemoveWorkspace(usize),
+    /// Give each workspace its own camera view, or share one
+    SetWorkspaceCameras(bool),
+    /// Run a debug console command line
+    RunCommand(String),
     /// Close the customization panel
     CloseCustomization,
     /// No action
@@ -252,15 +932,17 @@ pub enum UiAction {
 }
 
 /// Render the left sidebar (object palette)
-pub fn render_left_sidebar(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<UiAction> {
+pub fn render_left_sidebar(ctx: &egui::Context, ui_state: &mut UiState, objects: &[DeskObject]) -> Vec<UiAction> {
     let mut actions = Vec::new();
+    let palette = ui_state.theme.palette();
+    let lang = ui_state.lang;
 
     // Menu toggle button (always visible)
     egui::Area::new(egui::Id::new("menu_toggle_area"))
         .fixed_pos(egui::pos2(20.0, 20.0))
         .show(ctx, |ui| {
-            let button = egui::Button::new(RichText::new("☰").size(24.0).color(Color32::WHITE))
-                .fill(Color32::from_rgb(79, 70, 229))
+            let button = egui::Button::new(RichText::new("☰").size(24.0).color(palette.on_accent))
+                .fill(palette.accent)
                 .min_size(Vec2::new(50.0, 50.0));
 
             if ui.add(button).clicked() {
@@ -279,27 +961,174 @@ pub fn render_left_sidebar(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<U
                 // Header
                 ui.horizontal(|ui| {
                     ui.add_space(10.0);
-                    ui.label(RichText::new("🎨 Palette").size(18.0).strong().color(Color32::WHITE));
+                    ui.label(RichText::new(format!("🎨 {}", lang.tr(Key::Palette))).size(18.0).strong().color(palette.heading));
                 });
 
                 ui.add_space(10.0);
                 ui.separator();
+                ui.add_space(5.0);
+
+                // Workspace tabs; right-click one to rename or delete it
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing = Vec2::new(4.0, 4.0);
+                    let count = ui_state.workspace_names.len();
+                    for index in 0..count {
+                        let label = workspace_label(lang, &ui_state.workspace_names[index], index);
+                        let response = ui.selectable_label(index == ui_state.active_workspace, label);
+                        if response.clicked() && index != ui_state.active_workspace {
+                            actions.push(UiAction::SwitchWorkspace(index));
+                        }
+                        if response.secondary_clicked() {
+                            ui_state.workspace_name_input = ui_state.workspace_names[index].clone();
+                            ui_state.delete_workspace_armed = None;
+                        }
+                        response.context_menu(|ui| {
+                            let field = ui.add(
+                                egui::TextEdit::singleline(&mut ui_state.workspace_name_input)
+                                    .hint_text(lang.tr(Key::WorkspaceName))
+                                    .desired_width(140.0),
+                            );
+                            let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            if ui.button(lang.tr(Key::Rename)).clicked() || submitted {
+                                let name = ui_state.workspace_name_input.trim().to_string();
+                                actions.push(UiAction::RenameWorkspace(index, name));
+                                ui.close_menu();
+                            }
+                            let delete_label = if ui_state.delete_workspace_armed == Some(index) {
+                                lang.tr(Key::ConfirmDeleteWorkspace)
+                            } else {
+                                lang.tr(Key::DeleteWorkspace)
+                            };
+                            let delete = egui::Button::new(RichText::new(delete_label).color(palette.danger));
+                            if ui
+                                .add_enabled(count > 1, delete)
+                                .on_hover_text(lang.tr(Key::DeleteWorkspaceHint))
+                                .clicked()
+                                && ui_state.confirm_delete_workspace(index)
+                            {
+                                actions.push(UiAction::RemoveWorkspace(index));
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                    if ui.small_button("➕").on_hover_text(lang.tr(Key::AddWorkspace)).clicked() {
+                        actions.push(UiAction::AddWorkspace);
+                    }
+                });
+                if ui_state.workspace_names.len() > 1
+                    && ui
+                        .checkbox(&mut ui_state.workspace_cameras, lang.tr(Key::WorkspaceCameras))
+                        .on_hover_text(lang.tr(Key::WorkspaceCamerasHint))
+                        .changed()
+                {
+                    actions.push(UiAction::SetWorkspaceCameras(ui_state.workspace_cameras));
+                }
+
+                ui.add_space(5.0);
+                ui.separator();
+                ui.add_space(10.0);
+
+                // Favorites: saved templates, each added with one click
+                if !ui_state.templates.is_empty() {
+                    ui.label(RichText::new(format!("⭐ {}", lang.tr(Key::Favorites))).size(14.0).strong().color(palette.heading));
+                    ui.add_space(5.0);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing = Vec2::new(4.0, 4.0);
+                        for (index, template) in ui_state.templates.iter().enumerate() {
+                            let favorite = egui::Button::new(
+                                RichText::new(format!("{} {}", template.object_type.icon(), lang.object_label(template)))
+                                    .size(12.0)
+                                    .color(palette.text),
+                            )
+                            .fill(palette.accent_fill)
+                            .stroke(egui::Stroke::new(2.0, hex_to_color32(template.color)));
+                            let response = ui.add(favorite).on_hover_text(lang.tr(Key::FavoriteHint));
+                            if response.clicked() {
+                                actions.push(UiAction::AddTemplate(index));
+                            }
+                            response.context_menu(|ui| {
+                                if ui.button(lang.tr(Key::RemoveFavorite)).clicked() {
+                                    actions.push(UiAction::RemoveTemplate(index));
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    });
+                    ui.add_space(10.0);
+                }
+
+                // Search field and category filter chips
+                let search_response = ui.add(
+                    egui::TextEdit::singleline(&mut ui_state.palette_search)
+                        .hint_text(format!("🔍 {}", lang.tr(Key::SearchObjects)))
+                        .desired_width(f32::INFINITY),
+                );
+                ui.add_space(5.0);
+                let mut chip_clicked = None;
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing = Vec2::new(4.0, 4.0);
+                    if ui
+                        .selectable_label(ui_state.category_filters.is_empty(), lang.tr(Key::AllCategories))
+                        .clicked()
+                    {
+                        ui_state.category_filters.clear();
+                    }
+                    for category in &ui_state.categories {
+                        let active = ui_state.category_filters.contains(&category.name);
+                        let chip = RichText::new(format!("{} {}", category.icon, lang.tr(category.name))).size(12.0);
+                        if ui.selectable_label(active, chip).clicked() {
+                            chip_clicked = Some(category.name);
+                        }
+                    }
+                });
+                if let Some(name) = chip_clicked {
+                    ui_state.toggle_category_filter(name);
+                }
+
+                // Enter adds the top match, so typing "lamp" and Enter places a lamp
+                ui_state.update_palette_results(lang);
+                let top_result =
+                    ui_state.palette_results.first().copied().filter(|_| !ui_state.palette_search.trim().is_empty());
+                if let Some(object_type) = top_result {
+                    ui.add_space(3.0);
+                    ui.label(
+                        RichText::new(lang.format(Key::EnterAdds, &[&lang.object_name(object_type)]))
+                            .size(11.0)
+                            .color(palette.faint),
+                    );
+                    if search_response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                        actions.push(UiAction::AddObjectNow(object_type));
+                    }
+                }
                 ui.add_space(10.0);
 
                 // Palette categories
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     let mut category_clicked = None;
                     let mut variant_clicked = None;
+                    let searching = !ui_state.palette_search.trim().is_empty();
+                    let mut any_shown = false;
 
                     for (cat_idx, category) in ui_state.categories.iter().enumerate() {
+                        if !ui_state.category_shown(category.name) {
+                            continue;
+                        }
+                        let matching: Vec<usize> = (0..category.variants.len())
+                            .filter(|&i| category.variants[i].matches_search(&ui_state.palette_search, lang))
+                            .collect();
+                        if matching.is_empty() {
+                            continue;
+                        }
+                        any_shown = true;
+
                         // Category header
                         let header_response = ui.add(
                             egui::Button::new(
-                                RichText::new(format!("{} {}", category.icon, category.name))
+                                RichText::new(format!("{} {}", category.icon, lang.tr(category.name)))
                                     .size(14.0)
-                                    .color(Color32::from_gray(220)),
+                                    .color(palette.text),
                             )
-                            .fill(Color32::from_rgba_unmultiplied(255, 255, 255, 13))
+                            .fill(palette.surface)
                             .min_size(Vec2::new(ui.available_width(), 40.0)),
                         );
 
@@ -307,29 +1136,58 @@ pub fn render_left_sidebar(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<U
                             category_clicked = Some(cat_idx);
                         }
 
-                        // Expanded variants
-                        if category.expanded {
+                        // Expanded variants (a search expands every category with matches)
+                        if category.expanded || searching {
                             ui.add_space(5.0);
-                            for (var_idx, variant) in category.variants.iter().enumerate() {
+                            for var_idx in matching {
+                                let variant = &category.variants[var_idx];
                                 ui.horizontal(|ui| {
                                     ui.add_space(20.0);
                                     let variant_button = egui::Button::new(
-                                        RichText::new(format!("{} {}", variant.icon, variant.name))
+                                        RichText::new(format!("{} {}", variant.icon, lang.object_name(variant.object_type)))
                                             .size(12.0)
-                                            .color(Color32::from_gray(200)),
+                                            .color(palette.text),
                                     )
-                                    .fill(Color32::from_rgba_unmultiplied(79, 70, 229, 51))
+                                    .fill(palette.accent_fill)
                                     .min_size(Vec2::new(ui.available_width() - 30.0, 35.0));
+                                    // Outline what Enter in the search field would add
+                                    let variant_button = if top_result == Some(variant.object_type) {
+                                        variant_button.stroke(egui::Stroke::new(1.5, palette.accent))
+                                    } else {
+                                        variant_button
+                                    };
+                                    let sense = if ui_state.palette_drag {
+                                        egui::Sense::click_and_drag()
+                                    } else {
+                                        egui::Sense::click()
+                                    };
 
-                                    if ui.add(variant_button).clicked() {
+                                    let response = ui
+                                        .add(variant_button.sense(sense))
+                                        .on_hover_text(lang.object_description(variant.object_type));
+                                    if response.clicked() {
                                         variant_clicked = Some((cat_idx, var_idx));
                                     }
+                                    if response.drag_started() {
+                                        actions.push(UiAction::BeginPaletteDrag(variant.object_type));
+                                    }
+                                    if response.dragged() {
+                                        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                                    }
+                                    if response.drag_stopped() {
+                                        let over_ui = ui.ctx().is_pointer_over_area();
+                                        actions.push(UiAction::EndPaletteDrag { over_ui });
+                                    }
                                 });
                             }
                             ui.add_space(5.0);
                         }
                     }
 
+                    if !any_shown {
+                        ui.label(RichText::new(lang.tr(Key::NoMatchingObjects)).color(palette.faint));
+                    }
+
                     // Handle category toggle
                     if let Some(cat_idx) = category_clicked {
                         ui_state.categories[cat_idx].expanded = !ui_state.categories[cat_idx].expanded;
@@ -343,33 +1201,503 @@ pub fn render_left_sidebar(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<U
 
                     ui.add_space(20.0);
 
+                    // Object list (scene outliner)
+                    ui.separator();
+                    egui::CollapsingHeader::new(
+                        RichText::new(format!("📋 {}", lang.format(Key::ObjectList, &[&objects.len()]))).size(14.0).color(palette.text),
+                    )
+                    .id_salt("object_list")
+                    .show(ui, |ui| {
+                        if objects.is_empty() {
+                            ui.label(RichText::new(lang.tr(Key::DeskEmpty)).color(palette.faint));
+                        } else {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut ui_state.object_search)
+                                    .hint_text(format!("🔍 {}", lang.tr(Key::FilterObjects)))
+                                    .desired_width(f32::INFINITY),
+                            );
+                        }
+                        let query = ui_state.object_search.as_str();
+                        let mut shown = objects.iter().filter(|obj| object_matches_search(obj, query, lang)).peekable();
+                        if !objects.is_empty() && shown.peek().is_none() {
+                            ui.label(RichText::new(lang.tr(Key::NoMatchingObjects)).color(palette.faint));
+                        }
+                        for obj in shown {
+                            ui.horizontal(|ui| {
+                                let eye = if obj.hidden { "◌" } else { "👁" };
+                                let eye_hint = lang.tr(if obj.hidden { Key::Show } else { Key::Hide });
+                                if ui.small_button(eye).on_hover_text(eye_hint).clicked() {
+                                    actions.push(UiAction::SetHidden(obj.id, !obj.hidden));
+                                }
+                                let color = if obj.hidden { palette.faint } else { palette.text };
+                                let mut text = format!("{} {}", obj.object_type.icon(), lang.object_label(obj));
+                                if obj.locked {
+                                    text.push_str(" 🔒");
+                                }
+                                let selected = ui_state.selected_ids.contains(&obj.id);
+                                let label = ui
+                                    .selectable_label(selected, RichText::new(text).color(color))
+                                    .on_hover_text(lang.tr(Key::MultiSelectHint));
+                                if label.clicked() {
+                                    // Ctrl+click (Cmd on macOS) builds a selection to recolor at once
+                                    if ui.input(|input| input.modifiers.command) {
+                                        actions.push(UiAction::ToggleSelected(obj.id));
+                                    } else {
+                                        actions.push(UiAction::SelectObject(obj.id));
+                                    }
+                                }
+                                for tag in &obj.tags {
+                                    ui.label(RichText::new(format!("#{}", tag)).small().color(palette.faint));
+                                }
+                            });
+                        }
+                    });
+                    ui.add_space(10.0);
+
+                    // Session recording
+                    ui.horizontal(|ui| {
+                        let record_label = if ui_state.recording {
+                            format!("⏹ {}", lang.tr(Key::StopRecording))
+                        } else {
+                            format!("⏺ {}", lang.tr(Key::Record))
+                        };
+                        let record = ui
+                            .add_enabled(!ui_state.playing, egui::Button::new(record_label))
+                            .on_hover_text(lang.tr(Key::RecordHint));
+                        if record.clicked() {
+                            actions.push(UiAction::ToggleRecording);
+                        }
+                        if ui_state.playing {
+                            if ui.button(format!("⏹ {}", lang.tr(Key::StopPlayback))).clicked() {
+                                actions.push(UiAction::StopPlayback);
+                            }
+                        } else if ui
+                            .add_enabled(!ui_state.recording, egui::Button::new(format!("▶ {}", lang.tr(Key::PlayLast))))
+                            .on_hover_text(lang.tr(Key::PlayLastHint))
+                            .clicked()
+                        {
+                            actions.push(UiAction::PlayLastRecording);
+                        }
+                    });
+                    ui.add_space(10.0);
+
+                    // View settings
+                    ui.separator();
+                    ui.add_space(10.0);
+                    ui.label(RichText::new(format!("👁 {}", lang.tr(Key::View))).size(14.0).color(palette.text));
+                    ui.add_space(5.0);
+                    if ui
+                        .checkbox(&mut ui_state.orthographic, lang.tr(Key::Isometric))
+                        .on_hover_text(lang.tr(Key::IsometricHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetOrthographic(ui_state.orthographic));
+                    }
+
+                    let resume_delay = CONFIG.camera.turntable_resume_delay.as_secs();
+                    if ui
+                        .add_enabled(!ui_state.low_power, egui::Checkbox::new(&mut ui_state.turntable, lang.tr(Key::Turntable)))
+                        .on_hover_text(lang.format(Key::TurntableHint, &[&resume_delay]))
+                        .on_disabled_hover_text(lang.tr(Key::OffInLowPower))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetTurntable(ui_state.turntable));
+                    }
+                    if ui_state.turntable {
+                        let speed_slider = egui::Slider::new(&mut ui_state.turntable_speed, TURNTABLE_SPEED_RANGE)
+                            .text(lang.tr(Key::TurntableSpeed))
+                            .suffix("°/s");
+                        if ui.add_enabled(!ui_state.low_power, speed_slider).changed() {
+                            actions.push(UiAction::SetTurntableSpeed(ui_state.turntable_speed));
+                        }
+                    }
+
+                    if ui
+                        .checkbox(&mut ui_state.rotation_snap, lang.tr(Key::SnapRotation))
+                        .on_hover_text(lang.tr(Key::SnapRotationHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetRotationSnap(ui_state.rotation_snap));
+                    }
+
+                    if ui
+                        .checkbox(&mut ui_state.edge_snap, lang.tr(Key::SnapToEdges))
+                        .on_hover_text(lang.tr(Key::SnapToEdgesHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetEdgeSnap(ui_state.edge_snap));
+                    }
+
+                    if ui
+                        .checkbox(&mut ui_state.show_gizmo, lang.tr(Key::ShowGizmo))
+                        .on_hover_text(lang.tr(Key::ShowGizmoHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetShowGizmo(ui_state.show_gizmo));
+                    }
+
+                    if ui
+                        .checkbox(&mut ui_state.palette_drag, lang.tr(Key::PaletteDrag))
+                        .on_hover_text(lang.tr(Key::PaletteDragHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetPaletteDrag(ui_state.palette_drag));
+                    }
+
+                    let add_key = ui_state.keybindings.get(KeyAction::AddObject).label();
+                    if ui
+                        .checkbox(&mut ui_state.spawn_at_aim, lang.tr(Key::SpawnAtAim))
+                        .on_hover_text(lang.format(Key::SpawnAtAimHint, &[&add_key]))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetSpawnAtAim(ui_state.spawn_at_aim));
+                    }
+
+                    let grid_key = ui_state.keybindings.get(KeyAction::ToggleGrid).label();
+                    if ui
+                        .checkbox(&mut ui_state.show_grid, lang.tr(Key::ShowGrid))
+                        .on_hover_text(lang.format(Key::ShowGridHint, &[&CONFIG.grid.spacing, &grid_key]))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetShowGrid(ui_state.show_grid));
+                    }
+
+                    let idle_slider = egui::Slider::new(&mut ui_state.idle_motion, 0.0..=1.0)
+                        .text(lang.tr(Key::IdleMotion))
+                        .custom_formatter(|value, _| {
+                            if value <= 0.0 { lang.tr(Key::Off).to_string() } else { format!("{:.0}%", value * 100.0) }
+                        });
+                    if ui
+                        .add_enabled(!ui_state.low_power, idle_slider)
+                        .on_hover_text(lang.tr(Key::IdleMotionHint))
+                        .on_disabled_hover_text(lang.tr(Key::OffInLowPower))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetIdleMotion(ui_state.idle_motion));
+                    }
+
+                    if CONFIG.render.bloom {
+                        let bloom_slider = egui::Slider::new(&mut ui_state.bloom_intensity, 0.0..=1.0)
+                            .text(lang.tr(Key::Bloom))
+                            .custom_formatter(|value, _| {
+                                if value <= 0.0 { lang.tr(Key::Off).to_string() } else { format!("{:.0}%", value * 100.0) }
+                            });
+                        if ui
+                            .add_enabled(!ui_state.low_power, bloom_slider)
+                            .on_hover_text(lang.tr(Key::BloomHint))
+                            .on_disabled_hover_text(lang.tr(Key::OffInLowPower))
+                            .changed()
+                        {
+                            actions.push(UiAction::SetBloomIntensity(ui_state.bloom_intensity));
+                        }
+                    }
+
+                    let pixel_slider = egui::Slider::new(&mut ui_state.resolution_divisor, RESOLUTION_DIVISOR_RANGE)
+                        .text(lang.tr(Key::PixelSize))
+                        .custom_formatter(|value, _| {
+                            if value <= 1.0 { lang.tr(Key::Off).to_string() } else { format!("{}×", value) }
+                        });
+                    if ui
+                        .add(pixel_slider)
+                        .on_hover_text(lang.tr(Key::PixelSizeHint))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetResolutionDivisor(ui_state.resolution_divisor));
+                    }
+
+                    if ui
+                        .checkbox(&mut ui_state.low_power, lang.tr(Key::LowPower))
+                        .on_hover_text(lang.format(Key::LowPowerHint, &[&CONFIG.low_power.max_fps]))
+                        .changed()
+                    {
+                        actions.push(UiAction::SetLowPower(ui_state.low_power));
+                    }
+
+                    let before = ui_state.present_mode;
+                    ui.add_enabled_ui(!ui_state.low_power, |ui| {
+                        egui::ComboBox::from_label(lang.tr(Key::PresentMode))
+                            .selected_text(lang.present_mode_name(ui_state.present_mode))
+                            .show_ui(ui, |ui| {
+                                for mode in PresentMode::ALL {
+                                    let supported = ui_state.supported_present_modes.contains(&mode);
+                                    ui.add_enabled_ui(supported, |ui| {
+                                        ui.selectable_value(&mut ui_state.present_mode, mode, lang.present_mode_name(mode))
+                                            .on_disabled_hover_text(lang.tr(Key::NotSupported));
+                                    });
+                                }
+                            })
+                            .response
+                            .on_hover_text(lang.tr(Key::PresentModeHint))
+                            .on_disabled_hover_text(lang.tr(Key::PresentModeLowPower));
+                    });
+                    if ui_state.present_mode != before {
+                        actions.push(UiAction::SetPresentMode(ui_state.present_mode));
+                    }
+
+                    let before = ui_state.fps_cap;
+                    let fps_cap_name = |cap: Option<u32>| match cap {
+                        Some(fps) => format!("{} FPS", fps),
+                        None => lang.tr(Key::Unlimited).to_string(),
+                    };
+                    egui::ComboBox::from_label(lang.tr(Key::FpsCap))
+                        .selected_text(fps_cap_name(ui_state.fps_cap))
+                        .show_ui(ui, |ui| {
+                            // A cap set in the config may not be one of the choices
+                            let mut choices = FPS_CAP_CHOICES.to_vec();
+                            if !choices.contains(&before) {
+                                choices.push(before);
+                            }
+                            for cap in choices {
+                                ui.selectable_value(&mut ui_state.fps_cap, cap, fps_cap_name(cap));
+                            }
+                        })
+                        .response
+                        .on_hover_text(lang.tr(Key::FpsCapHint));
+                    if ui_state.fps_cap != before {
+                        actions.push(UiAction::SetFpsCap(ui_state.fps_cap));
+                    }
+
+                    let scale_slider = egui::Slider::new(&mut ui_state.ui_scale, UI_SCALE_RANGE)
+                        .text(lang.tr(Key::UiScale))
+                        .step_by(0.05);
+                    let response = ui.add(scale_slider);
+                    // Rescaling mid-drag moves the slider under the cursor, so apply on release
+                    if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                        actions.push(UiAction::SetUiScale(ui_state.ui_scale));
+                    }
+
+                    let before = ui_state.theme;
+                    egui::ComboBox::from_label(lang.tr(Key::Theme))
+                        .selected_text(lang.theme_name(ui_state.theme))
+                        .show_ui(ui, |ui| {
+                            for theme in UiTheme::ALL {
+                                ui.selectable_value(&mut ui_state.theme, theme, lang.theme_name(theme));
+                            }
+                        });
+                    if ui_state.theme != before {
+                        actions.push(UiAction::SetTheme(ui_state.theme));
+                    }
+
+                    let before = ui_state.lang;
+                    egui::ComboBox::from_label(lang.tr(Key::Language))
+                        .selected_text(ui_state.lang.label())
+                        .show_ui(ui, |ui| {
+                            for lang in Lang::ALL {
+                                ui.selectable_value(&mut ui_state.lang, lang, lang.label());
+                            }
+                        });
+                    if ui_state.lang != before {
+                        actions.push(UiAction::SetLanguage(ui_state.lang));
+                    }
+
+                    egui::CollapsingHeader::new(format!("⌨ {}", lang.tr(Key::Keybindings))).show(ui, |ui| {
+                        egui::Grid::new("keybindings").num_columns(2).show(ui, |ui| {
+                            for action in KeyAction::ALL {
+                                ui.label(lang.action_name(action));
+                                let capturing = ui_state.capturing_key == Some(action);
+                                let text = if capturing {
+                                    lang.tr(Key::NewKeyPrompt).to_string()
+                                } else {
+                                    ui_state.keybindings.get(action).label()
+                                };
+                                let button = ui
+                                    .selectable_label(capturing, text)
+                                    .on_hover_text(lang.tr(Key::KeybindingHint));
+                                if button.clicked() {
+                                    ui_state.capturing_key = (!capturing).then_some(action);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                        if ui.button(lang.tr(Key::ResetDefaults)).clicked() {
+                            ui_state.capturing_key = None;
+                            actions.push(UiAction::ResetKeybindings);
+                        }
+                    });
+
+                    egui::CollapsingHeader::new(format!("🖱 {}", lang.tr(Key::Scroll))).show(ui, |ui| {
+                        let scroll = &mut ui_state.scroll;
+                        let mut changed = false;
+                        for (sensitivity, label) in [
+                            (&mut scroll.rotate_sensitivity, Key::ScrollRotate),
+                            (&mut scroll.scale_sensitivity, Key::ScrollScale),
+                            (&mut scroll.zoom_sensitivity, Key::ScrollZoom),
+                        ] {
+                            let slider = egui::Slider::new(sensitivity, SCROLL_SENSITIVITY_RANGE)
+                                .logarithmic(true)
+                                .text(lang.tr(label))
+                                .custom_formatter(|value, _| format!("{:.1}×", value));
+                            changed |= ui.add(slider).changed();
+                        }
+                        changed |= ui
+                            .checkbox(&mut scroll.invert, lang.tr(Key::InvertScroll))
+                            .changed();
+                        if ui.button(lang.tr(Key::ResetDefaults)).clicked() {
+                            *scroll = CONFIG.scroll;
+                            changed = true;
+                        }
+                        if changed {
+                            actions.push(UiAction::SetScroll(*scroll));
+                        }
+                    });
+
+                    ui.add_space(20.0);
+
+                    // Named layouts
+                    ui.separator();
+                    ui.add_space(10.0);
+                    ui.label(RichText::new(format!("💾 {}", lang.tr(Key::Layouts))).size(14.0).color(palette.text));
+                    ui.add_space(5.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut ui_state.save_name_input)
+                                .hint_text(lang.tr(Key::LayoutName))
+                                .desired_width(ui.available_width() - 60.0),
+                        );
+                        let can_save = !ui_state.save_name_input.trim().is_empty();
+                        if ui.add_enabled(can_save, egui::Button::new(lang.tr(Key::Save))).clicked() {
+                            actions.push(UiAction::SaveLayout(ui_state.save_name_input.trim().to_string()));
+                        }
+                    });
+
+                    ui.add_space(5.0);
+                    if !ui_state.saved_layouts.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(lang.tr(Key::Sort)).size(11.0).color(palette.muted));
+                            let before = ui_state.layout_sort;
+                            ui.selectable_value(&mut ui_state.layout_sort, SaveSort::Modified, lang.tr(Key::SortRecent));
+                            ui.selectable_value(&mut ui_state.layout_sort, SaveSort::Name, lang.tr(Key::SortName));
+                            if ui_state.layout_sort != before {
+                                ui_state.layout_sort.sort(&mut ui_state.saved_layouts);
+                            }
+                        });
+                        ui.add_space(5.0);
+                    }
+
+                    let mut layout_clicked = None;
+                    let saves = ui_state.saved_layouts.clone();
+                    egui::Grid::new("layout_grid")
+                        .num_columns(2)
+                        .spacing(Vec2::new(8.0, 8.0))
+                        .show(ui, |ui| {
+                            for (index, save) in saves.iter().enumerate() {
+                                let name = &save.name;
+                                let thumbnail = ui_state.layout_thumbnail(ctx, name);
+                                let response = ui
+                                    .vertical(|ui| {
+                                        let response = match &thumbnail {
+                                            Some(texture) => ui.add(egui::ImageButton::new(
+                                                egui::Image::new(texture)
+                                                    .fit_to_exact_size(THUMBNAIL_DISPLAY_SIZE),
+                                            )),
+                                            None => ui.add(
+                                                egui::Button::new(
+                                                    RichText::new("🖼").size(20.0).color(palette.faint),
+                                                )
+                                                .fill(palette.surface)
+                                                .min_size(THUMBNAIL_DISPLAY_SIZE),
+                                            ),
+                                        };
+                                        ui.label(RichText::new(name).size(11.0).color(palette.text));
+                                        ui.label(
+                                            RichText::new(lang.format(
+                                                Key::LayoutSummary,
+                                                &[
+                                                    &save.object_count,
+                                                    &save
+                                                        .modified
+                                                        .with_timezone(&chrono::Local)
+                                                        .format(lang.tr(Key::LayoutDateFormat)),
+                                                ],
+                                            ))
+                                            .size(9.0)
+                                            .color(palette.faint),
+                                        );
+                                        response
+                                    })
+                                    .inner;
+
+                                let created = save.created.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+                                let hover = match save.app_version.as_str() {
+                                    "" => lang.format(Key::LoadLayoutHint, &[&created]),
+                                    version => lang.format(Key::LoadLayoutVersionHint, &[&created, &version]),
+                                };
+                                if response.on_hover_text(hover).clicked() {
+                                    layout_clicked = Some(name.clone());
+                                }
+                                if index % 2 == 1 {
+                                    ui.end_row();
+                                }
+                            }
+                        });
+
+                    if let Some(name) = layout_clicked {
+                        actions.push(UiAction::LoadLayout(name));
+                    }
+
+                    ui.add_space(20.0);
+
+                    // Shuffle desk
+                    ui.separator();
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut ui_state.shuffle_seed_input)
+                                .hint_text(lang.tr(Key::ShuffleSeed))
+                                .desired_width(ui.available_width() - 80.0),
+                        );
+                        let seed_text = ui_state.shuffle_seed_input.trim();
+                        let seed = seed_text.parse::<u64>().ok();
+                        let valid = seed_text.is_empty() || seed.is_some();
+                        let response = ui
+                            .add_enabled(valid, egui::Button::new(format!("🎲 {}", lang.tr(Key::Shuffle))))
+                            .on_hover_text(lang.tr(Key::ShuffleHint));
+                        if response.clicked() {
+                            actions.push(UiAction::Shuffle(seed));
+                        }
+                    });
+                    if ui
+                        .button(format!("🧹 {}", lang.tr(Key::Arrange)))
+                        .on_hover_text(lang.tr(Key::ArrangeHint))
+                        .clicked()
+                    {
+                        actions.push(UiAction::Arrange);
+                    }
+
+                    ui.add_space(20.0);
+
                     // Clear all button
                     ui.separator();
                     ui.add_space(10.0);
 
                     let clear_button = egui::Button::new(
-                        RichText::new("🗑️ Clear All Objects")
+                        RichText::new(format!("🗑️ {}", lang.tr(Key::ClearAll)))
                             .size(14.0)
-                            .color(Color32::from_rgb(239, 68, 68)),
+                            .color(palette.danger),
                     )
-                    .fill(Color32::from_rgba_unmultiplied(239, 68, 68, 51))
+                    .fill(palette.danger_fill)
                     .min_size(Vec2::new(ui.available_width() - 20.0, 40.0));
 
                     if ui.add(clear_button).clicked() {
-                        actions.push(UiAction::ClearAll);
+                        actions.push(UiAction::ClearAll {
+                            keep_locked: ui_state.keep_locked_on_clear,
+                        });
                     }
+                    ui.checkbox(&mut ui_state.keep_locked_on_clear, lang.tr(Key::KeepLocked));
 
                     ui.add_space(20.0);
 
                     // Instructions
                     ui.separator();
                     ui.add_space(10.0);
-                    ui.label(RichText::new("Controls:").size(12.0).color(Color32::from_gray(150)));
-                    ui.label(RichText::new("• Click+Drag to move").size(11.0).color(Color32::from_gray(120)));
-                    ui.label(RichText::new("• Scroll to rotate").size(11.0).color(Color32::from_gray(120)));
-                    ui.label(RichText::new("• Shift+Scroll to scale").size(11.0).color(Color32::from_gray(120)));
-                    ui.label(RichText::new("• Right-click to customize").size(11.0).color(Color32::from_gray(120)));
-                    ui.label(RichText::new("• Delete to remove").size(11.0).color(Color32::from_gray(120)));
+                    ui.label(RichText::new(lang.tr(Key::Controls)).size(12.0).color(palette.muted));
+                    ui.label(RichText::new(lang.tr(Key::ControlMove)).size(11.0).color(palette.faint));
+                    ui.label(RichText::new(lang.tr(Key::ControlRotate)).size(11.0).color(palette.faint));
+                    ui.label(RichText::new(lang.tr(Key::ControlScale)).size(11.0).color(palette.faint));
+                    ui.label(RichText::new(lang.tr(Key::ControlPan)).size(11.0).color(palette.faint));
+                    ui.label(RichText::new(lang.tr(Key::ControlCustomize)).size(11.0).color(palette.faint));
+                    ui.label(RichText::new(lang.tr(Key::ControlDelete)).size(11.0).color(palette.faint));
                 });
             });
     }
@@ -386,6 +1714,8 @@ pub fn render_right_sidebar(ctx: &egui::Context, ui_state: &mut UiState, object_
     }
 
     let object_id = ui_state.selected_object_id.unwrap();
+    let palette = ui_state.theme.palette();
+    let lang = ui_state.lang;
 
     egui::SidePanel::right("customization_panel")
         .resizable(false)
@@ -396,8 +1726,8 @@ pub fn render_right_sidebar(ctx: &egui::Context, ui_state: &mut UiState, object_
             // Header with close button
             ui.horizontal(|ui| {
                 ui.add_space(10.0);
-                let title = object_name.unwrap_or("Object");
-                ui.label(RichText::new(format!("Customize {}", title)).size(16.0).strong().color(Color32::WHITE));
+                let title = object_name.unwrap_or(lang.tr(Key::Object));
+                ui.label(RichText::new(lang.format(Key::Customize, &[&title])).size(16.0).strong().color(palette.heading));
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button(RichText::new("✕").size(16.0)).clicked() {
@@ -405,37 +1735,110 @@ pub fn render_right_sidebar(ctx: &egui::Context, ui_state: &mut UiState, object_
                     }
                 });
             });
+            if ui_state.selected_ids.len() > 1 {
+                ui.horizontal(|ui| {
+                    ui.add_space(10.0);
+                    let editing = lang.format(Key::EditingObjects, &[&ui_state.selected_ids.len()]);
+                    ui.label(RichText::new(editing).size(11.0).color(palette.accent));
+                });
+            }
 
             ui.add_space(10.0);
             ui.separator();
             ui.add_space(15.0);
 
+            // Name section
+            ui.label(RichText::new(lang.tr(Key::Name)).size(11.0).color(palette.muted));
+            ui.add_space(8.0);
+            let name_field = ui.add(
+                egui::TextEdit::singleline(&mut ui_state.name_input)
+                    .hint_text(object_name.unwrap_or(lang.tr(Key::Object)))
+                    .desired_width(f32::INFINITY),
+            );
+            if name_field.lost_focus() {
+                let name = ui_state.name_input.trim();
+                actions.push(UiAction::RenameObject(object_id, (!name.is_empty()).then(|| name.to_string())));
+            }
+            ui.add_space(15.0);
+
+            // Tags section
+            ui.label(RichText::new(lang.tr(Key::Tags)).size(11.0).color(palette.muted));
+            ui.add_space(8.0);
+            let tags_field = ui.add(
+                egui::TextEdit::singleline(&mut ui_state.tags_input)
+                    .hint_text(lang.tr(Key::TagsHint))
+                    .desired_width(f32::INFINITY),
+            );
+            if tags_field.lost_focus() {
+                let tags = parse_tags(&ui_state.tags_input);
+                ui_state.tags_input = tags.join(", ");
+                actions.push(UiAction::SetTags(object_id, tags));
+            }
+            ui.add_space(15.0);
+
+            // Transform inspector (read-only while locked)
+            ui.label(RichText::new(lang.tr(Key::Transform)).size(11.0).color(palette.muted));
+            ui.add_space(8.0);
+            let locked = ui_state.current_locked;
+            let fields = &mut ui_state.transform;
+            let (mut changed, mut finished) = (false, false);
+            let mut track = |response: egui::Response| {
+                changed |= response.changed();
+                // A drag or typed value is finished once let go of
+                finished |= response.drag_stopped()
+                    || response.lost_focus()
+                    || (response.changed() && !response.dragged() && !response.has_focus());
+            };
+            ui.add_enabled_ui(!locked, |ui| {
+                egui::Grid::new("transform_fields").spacing(Vec2::new(6.0, 6.0)).show(ui, |ui| {
+                    ui.label(lang.tr(Key::Position));
+                    for value in fields.position.as_mut() {
+                        track(ui.add(egui::DragValue::new(value).speed(0.01).max_decimals(3)));
+                    }
+                    ui.end_row();
+
+                    ui.label(lang.tr(Key::Rotation));
+                    for value in fields.rotation.as_mut() {
+                        let field = egui::DragValue::new(value).speed(1.0).max_decimals(1).suffix("°");
+                        track(ui.add(field));
+                    }
+                    ui.end_row();
+
+                    ui.label(lang.tr(Key::Scale));
+                    let field = egui::DragValue::new(&mut fields.scale)
+                        .speed(0.01)
+                        .range(MIN_SCALE..=MAX_SCALE)
+                        .max_decimals(2);
+                    track(ui.add(field));
+                    ui.end_row();
+                });
+            });
+            fields.editing |= changed;
+            if fields.editing && (changed || finished) {
+                fields.editing = !finished;
+                fields.rotation_source = fields.rotation_quat();
+                actions.push(UiAction::SetTransform {
+                    id: object_id,
+                    position: fields.position,
+                    rotation: fields.rotation_source,
+                    scale: fields.scale,
+                    finished,
+                });
+            }
+            ui.add_space(15.0);
+
             // Main color section
-            ui.label(RichText::new("MAIN COLOR").size(11.0).color(Color32::from_gray(150)));
+            ui.label(RichText::new(color_heading(lang, Key::MainColor, ui_state.main_color_mixed)).size(11.0).color(palette.muted));
             ui.add_space(8.0);
 
             egui::Grid::new("main_colors")
                 .spacing(Vec2::new(8.0, 8.0))
                 .show(ui, |ui| {
-                    for (i, (color, _name)) in COLOR_PRESETS.iter().enumerate() {
-                        let r = ((color >> 16) & 0xFF) as u8;
-                        let g = ((color >> 8) & 0xFF) as u8;
-                        let b = (color & 0xFF) as u8;
-
-                        let is_selected = *color == ui_state.current_main_color;
-                        let button_size = if is_selected { 36.0 } else { 32.0 };
-
-                        let button = egui::Button::new("")
-                            .fill(Color32::from_rgb(r, g, b))
-                            .min_size(Vec2::new(button_size, button_size))
-                            .stroke(if is_selected {
-                                egui::Stroke::new(2.0, Color32::WHITE)
-                            } else {
-                                egui::Stroke::NONE
-                            });
-
-                        if ui.add(button).clicked() {
+                    for (i, (color, name)) in COLOR_PRESETS.iter().enumerate() {
+                        let is_selected = *color == ui_state.current_main_color && !ui_state.main_color_mixed;
+                        if color_swatch(ui, &palette, *color, is_selected).on_hover_text(lang.tr(*name)).clicked() {
                             ui_state.current_main_color = *color;
+                            ui_state.main_color_input = format_hex_color(*color);
                             actions.push(UiAction::ChangeMainColor(object_id, *color));
                         }
 
@@ -444,38 +1847,45 @@ pub fn render_right_sidebar(ctx: &egui::Context, ui_state: &mut UiState, object_
                         }
                     }
                 });
+            let recent = recent_colors_row(
+                ui,
+                &palette,
+                lang,
+                "recent_main_colors",
+                &ui_state.recent_colors,
+                ui_state.current_main_color,
+            );
+            if let Some(color) = recent {
+                ui_state.current_main_color = color;
+                ui_state.main_color_input = format_hex_color(color);
+                actions.push(UiAction::ChangeMainColor(object_id, color));
+            }
+            ui.add_space(8.0);
+            let custom = custom_color_row(
+                ui,
+                lang,
+                "main_color",
+                &mut ui_state.current_main_color,
+                &mut ui_state.main_color_input,
+            );
+            if let Some(color) = custom {
+                actions.push(UiAction::ChangeMainColor(object_id, color));
+            }
 
             ui.add_space(20.0);
 
             // Accent color section
-            ui.label(RichText::new("ACCENT COLOR").size(11.0).color(Color32::from_gray(150)));
+            ui.label(RichText::new(color_heading(lang, Key::AccentColor, ui_state.accent_color_mixed)).size(11.0).color(palette.muted));
             ui.add_space(8.0);
 
             egui::Grid::new("accent_colors")
                 .spacing(Vec2::new(8.0, 8.0))
                 .show(ui, |ui| {
-                    for (i, (color, _name)) in ACCENT_COLOR_PRESETS.iter().enumerate() {
-                        let r = ((color >> 16) & 0xFF) as u8;
-                        let g = ((color >> 8) & 0xFF) as u8;
-                        let b = (color & 0xFF) as u8;
-
-                        let is_selected = *color == ui_state.current_accent_color;
-                        let button_size = if is_selected { 36.0 } else { 32.0 };
-
-                        let mut stroke = egui::Stroke::NONE;
-                        if is_selected {
-                            stroke = egui::Stroke::new(2.0, Color32::WHITE);
-                        } else if *color == 0x000000 {
-                            stroke = egui::Stroke::new(1.0, Color32::from_gray(100));
-                        }
-
-                        let button = egui::Button::new("")
-                            .fill(Color32::from_rgb(r, g, b))
-                            .min_size(Vec2::new(button_size, button_size))
-                            .stroke(stroke);
-
-                        if ui.add(button).clicked() {
+                    for (i, (color, name)) in ACCENT_COLOR_PRESETS.iter().enumerate() {
+                        let is_selected = *color == ui_state.current_accent_color && !ui_state.accent_color_mixed;
+                        if color_swatch(ui, &palette, *color, is_selected).on_hover_text(lang.tr(*name)).clicked() {
                             ui_state.current_accent_color = *color;
+                            ui_state.accent_color_input = format_hex_color(*color);
                             actions.push(UiAction::ChangeAccentColor(object_id, *color));
                         }
 
@@ -484,26 +1894,549 @@ pub fn render_right_sidebar(ctx: &egui::Context, ui_state: &mut UiState, object_
                         }
                     }
                 });
+            let recent = recent_colors_row(
+                ui,
+                &palette,
+                lang,
+                "recent_accent_colors",
+                &ui_state.recent_colors,
+                ui_state.current_accent_color,
+            );
+            if let Some(color) = recent {
+                ui_state.current_accent_color = color;
+                ui_state.accent_color_input = format_hex_color(color);
+                actions.push(UiAction::ChangeAccentColor(object_id, color));
+            }
+            ui.add_space(8.0);
+            let custom = custom_color_row(
+                ui,
+                lang,
+                "accent_color",
+                &mut ui_state.current_accent_color,
+                &mut ui_state.accent_color_input,
+            );
+            if let Some(color) = custom {
+                actions.push(UiAction::ChangeAccentColor(object_id, color));
+            }
+
+            // Books stack size
+            if ui_state.selected_object_type == Some(ObjectType::Books) {
+                ui.add_space(20.0);
+                ui.label(RichText::new(lang.tr(Key::BooksInStack)).size(11.0).color(palette.muted));
+                ui.add_space(8.0);
+
+                let slider = egui::Slider::new(&mut ui_state.current_book_count, BOOK_COUNT_RANGE);
+                if ui.add(slider).changed() {
+                    actions.push(UiAction::ChangeBookCount(object_id, ui_state.current_book_count));
+                }
+            }
+
+            // Lamp light reach
+            if ui_state.selected_object_type == Some(ObjectType::Lamp) {
+                ui.add_space(20.0);
+                ui.label(RichText::new(lang.tr(Key::LightRadius)).size(11.0).color(palette.muted));
+                ui.add_space(8.0);
+
+                let slider = egui::Slider::new(&mut ui_state.current_light_radius, LIGHT_RADIUS_RANGE).step_by(0.1);
+                if ui.add(slider).on_hover_text(lang.tr(Key::LightRadiusHint)).changed() {
+                    actions.push(UiAction::SetLightRadius(object_id, ui_state.current_light_radius));
+                }
+            }
+
+            // Idle motion speed (decals lie still)
+            if ui_state.selected_object_type.is_some_and(|object_type| !object_type.is_decal()) {
+                ui.add_space(20.0);
+                ui.label(RichText::new(lang.tr(Key::AnimSpeed)).size(11.0).color(palette.muted));
+                ui.add_space(8.0);
+
+                let slider = egui::Slider::new(&mut ui_state.current_anim_speed, ANIM_SPEED_RANGE)
+                    .step_by(0.05)
+                    .suffix("×");
+                if ui.add(slider).on_hover_text(lang.tr(Key::AnimSpeedHint)).changed() {
+                    actions.push(UiAction::ChangeAnimSpeed(object_id, ui_state.current_anim_speed));
+                }
+            }
+
+            // Magazine cover, photo frame photo or laptop screen image
+            if let Some(object_type) = ui_state.selected_object_type.filter(ObjectType::shows_image) {
+                let (heading, clear_label) = match object_type {
+                    ObjectType::Magazine => (Key::CoverImage, Key::UseDefault),
+                    ObjectType::PhotoFrame => (Key::Photo, Key::Remove),
+                    _ => (Key::ScreenImage, Key::Remove),
+                };
+                ui.add_space(20.0);
+                ui.label(RichText::new(lang.tr(heading)).size(11.0).color(palette.muted));
+                ui.add_space(8.0);
+
+                ui.add(
+                    egui::TextEdit::singleline(&mut ui_state.cover_path_input)
+                        .hint_text(lang.tr(Key::ImagePathHint)),
+                )
+                .on_hover_text(lang.tr(Key::DropImageHint));
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr(Key::SetImage)).clicked() && !ui_state.cover_path_input.trim().is_empty() {
+                        let path = ui_state.cover_path_input.trim().to_string();
+                        ui_state.current_builtin_image = None;
+                        actions.push(UiAction::SetCoverImage(object_id, Some(ImageSource::Path(path))));
+                    }
+                    if ui.button(lang.tr(clear_label)).clicked() {
+                        ui_state.cover_path_input.clear();
+                        ui_state.current_builtin_image = None;
+                        actions.push(UiAction::SetCoverImage(object_id, None));
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.label(RichText::new(lang.tr(Key::BuiltinImages)).size(11.0).color(palette.faint));
+                ui.add_space(4.0);
+                egui::Grid::new("builtin_images").spacing(Vec2::new(6.0, 6.0)).show(ui, |ui| {
+                    for (index, builtin) in BUILTIN_IMAGES.iter().enumerate() {
+                        let Some(texture) = ui_state.builtin_image_texture(ctx, index) else { continue };
+                        let selected = ui_state.current_builtin_image == Some(index);
+                        let image = egui::Image::new(&texture).fit_to_exact_size(BUILTIN_IMAGE_DISPLAY_SIZE);
+                        let response = ui
+                            .add(egui::ImageButton::new(image).selected(selected))
+                            .on_hover_text(lang.tr(builtin.name));
+                        if response.clicked() && !selected {
+                            ui_state.cover_path_input.clear();
+                            ui_state.current_builtin_image = Some(index);
+                            actions.push(UiAction::SetCoverImage(object_id, Some(ImageSource::Builtin { builtin: index })));
+                        }
+                        if (index + 1) % 4 == 0 {
+                            ui.end_row();
+                        }
+                    }
+                });
+            }
+
+            ui.add_space(20.0);
+            if ui
+                .checkbox(&mut ui_state.current_locked, format!("🔒 {}", lang.tr(Key::Locked)))
+                .on_hover_text(lang.tr(Key::LockedHint))
+                .changed()
+            {
+                actions.push(UiAction::SetLocked(object_id, ui_state.current_locked));
+            }
+            if ui
+                .button(format!("⭐ {}", lang.tr(Key::SaveTemplate)))
+                .on_hover_text(lang.tr(Key::SaveTemplateHint))
+                .clicked()
+            {
+                actions.push(UiAction::SaveTemplate(object_id));
+            }
 
             ui.add_space(30.0);
 
             // Delete button
             let delete_button = egui::Button::new(
-                RichText::new("Delete Object")
+                RichText::new(lang.tr(Key::DeleteObject))
                     .size(14.0)
-                    .color(Color32::from_rgb(239, 68, 68)),
+                    .color(palette.danger),
             )
-            .fill(Color32::from_rgba_unmultiplied(239, 68, 68, 51))
+            .fill(palette.danger_fill)
             .min_size(Vec2::new(ui.available_width() - 20.0, 40.0));
 
-            if ui.add(delete_button).clicked() {
+            if ui.add_enabled(!ui_state.current_locked, delete_button).clicked() {
                 actions.push(UiAction::DeleteObject(object_id));
             }
+
+            // With several objects selected, delete them all at once
+            let count = ui_state.selected_ids.len();
+            if count > 1 {
+                ui.add_space(8.0);
+                let label = if ui_state.delete_selected_armed {
+                    lang.format(Key::ConfirmDeleteSelected, &[&count])
+                } else {
+                    lang.format(Key::DeleteSelected, &[&count])
+                };
+                let delete_selected_button =
+                    egui::Button::new(RichText::new(format!("🗑 {}", label)).size(14.0).color(palette.danger))
+                        .fill(palette.danger_fill)
+                        .min_size(Vec2::new(ui.available_width() - 20.0, 40.0));
+                if ui
+                    .add(delete_selected_button)
+                    .on_hover_text(lang.tr(Key::DeleteSelectedHint))
+                    .clicked()
+                    && ui_state.confirm_delete_selected()
+                {
+                    actions.push(UiAction::DeleteSelected);
+                }
+            }
+        });
+
+    actions
+}
+
+/// Render the right-click context menu for an object
+pub fn render_context_menu(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<UiAction> {
+    let mut actions = Vec::new();
+
+    let Some((object_id, position)) = ui_state.context_menu else {
+        return actions;
+    };
+    let lang = ui_state.lang;
+
+    let response = egui::Area::new(egui::Id::new("object_context_menu"))
+        .fixed_pos(position)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                ui.set_min_width(150.0);
+                if ui.button(format!("🎨 {}", lang.tr(Key::MenuCustomize))).clicked() {
+                    actions.push(UiAction::Customize(object_id));
+                }
+                if ui.button(format!("📋 {}", lang.tr(Key::Duplicate))).clicked() {
+                    actions.push(UiAction::Duplicate(object_id));
+                }
+                if ui.button(format!("🔦 {}", lang.tr(Key::Focus))).clicked() {
+                    actions.push(UiAction::Focus(object_id));
+                }
+                if let Some(running) = ui_state.context_menu_timer {
+                    let timer_label = if running {
+                        format!("⏹ {}", lang.tr(Key::StopTimer))
+                    } else {
+                        format!("⏳ {}", lang.tr(Key::StartTimer))
+                    };
+                    if ui.button(timer_label).clicked() {
+                        actions.push(UiAction::ToggleTimer(object_id));
+                    }
+                }
+                if ui_state.context_menu_interact && ui.button(format!("👆 {}", lang.tr(Key::Use))).clicked() {
+                    actions.push(UiAction::Interact(object_id));
+                }
+                ui.separator();
+                if ui.button(format!("⬆ {}", lang.tr(Key::BringToFront))).clicked() {
+                    actions.push(UiAction::BringToFront(object_id));
+                }
+                if ui.button(format!("⬇ {}", lang.tr(Key::SendToBack))).clicked() {
+                    actions.push(UiAction::SendToBack(object_id));
+                }
+                ui.separator();
+                let locked = ui_state.context_menu_locked;
+                if ui.add_enabled(!locked, egui::Button::new(format!("⇔ {}", lang.tr(Key::MirrorLeftRight)))).clicked() {
+                    actions.push(UiAction::Mirror(object_id, MirrorAxis::X));
+                }
+                if ui.add_enabled(!locked, egui::Button::new(format!("⇕ {}", lang.tr(Key::MirrorFrontBack)))).clicked() {
+                    actions.push(UiAction::Mirror(object_id, MirrorAxis::Z));
+                }
+                ui.separator();
+                let lock_label = if locked {
+                    format!("🔓 {}", lang.tr(Key::Unlock))
+                } else {
+                    format!("🔒 {}", lang.tr(Key::Lock))
+                };
+                if ui.button(lock_label).clicked() {
+                    actions.push(UiAction::SetLocked(object_id, !locked));
+                }
+                let delete = egui::Button::new(
+                    RichText::new(format!("🗑 {}", lang.tr(Key::Delete))).color(ui_state.theme.palette().danger),
+                );
+                if ui.add_enabled(!locked, delete).clicked() {
+                    actions.push(UiAction::DeleteObject(object_id));
+                }
+            });
+        })
+        .response;
+
+    // Close after choosing an item or left-clicking anywhere else (the right-click
+    // that opened the menu is still being released, so only primary clicks count)
+    let clicked_outside =
+        ctx.input(|i| i.pointer.primary_clicked()) && !response.contains_pointer();
+    if !actions.is_empty() || clicked_outside {
+        ui_state.close_context_menu();
+    }
+
+    actions
+}
+
+/// Draw a lock badge above each locked object (positions in points)
+pub fn render_lock_badges(ctx: &egui::Context, palette: &ThemePalette, positions: &[egui::Pos2]) {
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("lock_badges"),
+    ));
+    for &position in positions {
+        painter.circle_filled(position, 10.0, palette.badge);
+        painter.text(
+            position,
+            egui::Align2::CENTER_CENTER,
+            "🔒",
+            egui::FontId::proportional(12.0),
+            palette.badge_icon,
+        );
+    }
+}
+
+/// Draw the grid rulers' world-unit labels (positions in points)
+pub fn render_grid_rulers(ctx: &egui::Context, palette: &ThemePalette, labels: &[(egui::Pos2, String)]) {
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("grid_rulers"),
+    ));
+    for (position, text) in labels {
+        let galley = painter.layout_no_wrap(text.clone(), egui::FontId::monospace(11.0), palette.heading);
+        let rect = egui::Align2::CENTER_CENTER.anchor_size(*position, galley.size());
+        painter.rect_filled(rect.expand(2.0), 3.0, palette.badge);
+        painter.galley(rect.min, galley, palette.heading);
+    }
+}
+
+/// Render active toast notifications, dropping expired ones
+pub fn render_toasts(ctx: &egui::Context, ui_state: &mut UiState) {
+    let now = Instant::now();
+    let palette = ui_state.theme.palette();
+    ui_state.toasts.retain(|toast| toast.expires_at > now);
+
+    if ui_state.toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("toast_area"))
+        .anchor(egui::Align2::CENTER_BOTTOM, Vec2::new(0.0, -30.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            for toast in &ui_state.toasts {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(RichText::new(&toast.message).size(14.0).color(palette.heading));
+                });
+            }
+        });
+}
+
+/// Render the debug console along the bottom of the window while it's open
+pub fn render_console(ctx: &egui::Context, ui_state: &mut UiState) -> Vec<UiAction> {
+    let mut actions = Vec::new();
+    if !ui_state.console_open {
+        return actions;
+    }
+    let palette = ui_state.theme.palette();
+
+    // The console was closed unless it was drawn in the previous pass
+    let shown_id = egui::Id::new("console_shown_pass");
+    let pass = ctx.cumulative_pass_nr();
+    let opened = ctx.data_mut(|d| {
+        let last_shown = d.get_temp::<u64>(shown_id);
+        d.insert_temp(shown_id, pass);
+        last_shown != Some(pass.wrapping_sub(1))
+    });
+
+    egui::TopBottomPanel::bottom("console_panel")
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .stick_to_bottom(true)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for line in &ui_state.console_log {
+                        ui.label(RichText::new(line).monospace().color(palette.heading));
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(">").monospace());
+                let input = ui.add(
+                    egui::TextEdit::singleline(&mut ui_state.console_input)
+                        .font(egui::TextStyle::Monospace)
+                        .hint_text("help")
+                        .desired_width(f32::INFINITY),
+                );
+                if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let line = std::mem::take(&mut ui_state.console_input);
+                    if !line.trim().is_empty() {
+                        actions.push(UiAction::RunCommand(line));
+                    }
+                }
+                // Focus the field when the console opens; clicking elsewhere
+                // afterwards gives the keyboard back to the scene
+                if opened {
+                    input.request_focus();
+                }
+            });
         });
 
     actions
 }
 
+/// Render the always-visible focus widget at the top center (clear of both
+/// sidebars): today's focus time, the current streak and any running timer
+pub fn render_focus_widget(ctx: &egui::Context, ui_state: &UiState, summary: FocusSummary) {
+    let palette = ui_state.theme.palette();
+    let lang = ui_state.lang;
+    egui::Area::new(egui::Id::new("focus_widget"))
+        .anchor(egui::Align2::CENTER_TOP, Vec2::new(0.0, 12.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("🎯 {}", format_focus_time(summary.today)))
+                            .color(palette.heading),
+                    )
+                    .on_hover_text(lang.tr(Key::FocusToday));
+                    if summary.streak > 0 {
+                        ui.label(
+                            RichText::new(format!("🔥 {}", lang.days(summary.streak)))
+                                .color(palette.badge_icon),
+                        );
+                    }
+                    if let Some(remaining) = summary.timer_remaining {
+                        let secs = remaining.as_secs_f32().ceil() as u64;
+                        ui.label(
+                            RichText::new(format!("⏳ {}:{:02}", secs / 60, secs % 60))
+                                .monospace()
+                                .color(palette.text),
+                        );
+                    }
+                });
+            });
+        });
+}
+
+/// Render the FPS/debug overlay in the top-right corner
+pub fn render_debug_overlay(ctx: &egui::Context, ui_state: &UiState, stats: DebugStats) {
+    if !ui_state.show_debug_overlay {
+        return;
+    }
+    let lang = ui_state.lang;
+
+    egui::Area::new(egui::Id::new("debug_overlay"))
+        .anchor(egui::Align2::RIGHT_TOP, Vec2::new(-20.0, 20.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let fps = format!("{:.0}", stats.fps);
+                ui.label(RichText::new(lang.format(Key::DebugFps, &[&fps])).monospace());
+                ui.label(
+                    RichText::new(lang.format(Key::DebugObjects, &[&stats.object_count, &stats.max_objects]))
+                        .monospace(),
+                );
+            });
+        });
+}
+
+/// A clickable color swatch; the selected one is larger and outlined
+fn color_swatch(ui: &mut egui::Ui, palette: &ThemePalette, color: u32, selected: bool) -> egui::Response {
+    let size = if selected { 36.0 } else { 32.0 };
+    let stroke = if selected {
+        egui::Stroke::new(2.0, palette.selection)
+    } else {
+        egui::Stroke::new(1.0, palette.swatch_border)
+    };
+    ui.add(
+        egui::Button::new("")
+            .fill(hex_to_color32(color))
+            .min_size(Vec2::new(size, size))
+            .stroke(stroke),
+    )
+}
+
+/// Swatches of the recently used colors under a "Recently used" label,
+/// returning the one clicked. Shows nothing until a color has been used.
+fn recent_colors_row(
+    ui: &mut egui::Ui,
+    palette: &ThemePalette,
+    lang: Lang,
+    id: &str,
+    recent: &[u32],
+    current: u32,
+) -> Option<u32> {
+    if recent.is_empty() {
+        return None;
+    }
+    let mut clicked = None;
+    ui.add_space(8.0);
+    ui.label(RichText::new(lang.tr(Key::RecentlyUsed)).size(11.0).color(palette.faint));
+    ui.add_space(4.0);
+    egui::Grid::new(id).spacing(Vec2::new(8.0, 8.0)).show(ui, |ui| {
+        for (i, &color) in recent.iter().enumerate() {
+            if color_swatch(ui, palette, color, color == current)
+                .on_hover_text(format_hex_color(color))
+                .clicked()
+            {
+                clicked = Some(color);
+            }
+            if (i + 1) % 5 == 0 {
+                ui.end_row();
+            }
+        }
+    });
+    clicked
+}
+
+/// Name of a workspace's tab, numbering it if it has no name
+pub fn workspace_label(lang: Lang, name: &str, index: usize) -> String {
+    if name.is_empty() {
+        lang.format(Key::WorkspaceNumber, &[&(index + 1)])
+    } else {
+        name.to_string()
+    }
+}
+
+/// Heading of a color section, noting when the selected objects differ in it
+fn color_heading(lang: Lang, heading: Key, mixed: bool) -> String {
+    if mixed {
+        format!("{} · {}", lang.tr(heading), lang.tr(Key::MixedColors))
+    } else {
+        lang.tr(heading).to_string()
+    }
+}
+
+/// Color picker and hex field for choosing any color, returning the new
+/// color when it changes. A color picked in the picker is applied when the
+/// picker closes, so dragging through it is one change. Typed hex is applied
+/// on Enter or when the field loses focus; invalid text reverts to the
+/// current color.
+fn custom_color_row(ui: &mut egui::Ui, lang: Lang, id: &str, current: &mut u32, hex_input: &mut String) -> Option<u32> {
+    let mut changed = None;
+    ui.horizontal(|ui| {
+        let [r, g, b, _] = hex_to_color32(*current).to_array();
+        let mut rgb = [r, g, b];
+        // The picker's popup, named the way `color_edit_button_srgb` names it
+        let popup_id = ui.auto_id_with("popup");
+        let was_open = ui.memory(|mem| mem.is_popup_open(popup_id));
+        let picker = ui.color_edit_button_srgb(&mut rgb);
+        if picker.on_hover_text(lang.tr(Key::PickAnyColor)).changed() {
+            // Only the swatch and hex field follow the picker while it's open
+            *current = (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32;
+            *hex_input = format_hex_color(*current);
+        }
+        let open = ui.memory(|mem| mem.is_popup_open(popup_id));
+        if open && !was_open {
+            ui.data_mut(|data| data.insert_temp(popup_id, *current));
+        } else if was_open && !open {
+            let opened_with = ui.data_mut(|data| data.remove_temp::<u32>(popup_id));
+            if opened_with != Some(*current) {
+                changed = Some(*current);
+            }
+        }
+
+        let field = ui.add(
+            egui::TextEdit::singleline(hex_input)
+                .id_salt(id)
+                .hint_text("#RRGGBB")
+                .desired_width(80.0),
+        );
+        if field.lost_focus() {
+            match parse_hex_color(hex_input) {
+                Some(color) if color != *current => changed = Some(color),
+                _ => *hex_input = format_hex_color(*current),
+            }
+        }
+    });
+
+    if let Some(color) = changed {
+        *current = color;
+        *hex_input = format_hex_color(color);
+    }
+    changed
+}
+
+/// Hex text for a color, e.g. "#3B82F6"
+fn format_hex_color(color: u32) -> String {
+    format!("#{:06X}", color & 0xFF_FFFF)
+}
+
 /// Helper function to convert hex color to egui Color32
 pub fn hex_to_color32(hex: u32) -> Color32 {
     let r = ((hex >> 16) & 0xFF) as u8;
@@ -511,3 +2444,187 @@ pub fn hex_to_color32(hex: u32) -> Color32 {
     let b = (hex & 0xFF) as u8;
     Color32::from_rgb(r, g, b)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_presets_are_distinct() {
+        let presets: Vec<&(u32, Key)> = COLOR_PRESETS.iter().chain(ACCENT_COLOR_PRESETS).collect();
+        for (i, (color, name)) in presets.iter().enumerate() {
+            for (other_color, other_name) in &presets[i + 1..] {
+                assert_ne!(color, other_color, "{:?} and {:?} share a color", name, other_name);
+                for lang in Lang::ALL {
+                    assert_ne!(lang.tr(*name), lang.tr(*other_name));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn palette_filters_combine_chips_and_search() {
+        let mut ui_state = UiState::new();
+        assert!(ui_state.category_shown(Key::Clocks));
+        ui_state.toggle_category_filter(Key::Lighting);
+        assert!(!ui_state.category_shown(Key::Clocks));
+        assert!(ui_state.category_shown(Key::Lighting));
+        ui_state.toggle_category_filter(Key::Lighting);
+        assert!(ui_state.category_shown(Key::Clocks));
+
+        let clocks = &ui_state.categories[0];
+        let search = |query: &str, lang: Lang| -> Vec<ObjectType> {
+            clocks
+                .variants
+                .iter()
+                .filter(|variant| variant.matches_search(query, lang))
+                .map(|variant| variant.object_type)
+                .collect()
+        };
+        assert_eq!(search(" HOUR", Lang::English), [ObjectType::Hourglass]);
+        // Russian names match, and so do English ones in the Russian UI
+        assert_eq!(search("песочные", Lang::Russian), [ObjectType::Hourglass]);
+        assert_eq!(search("hourglass", Lang::Russian), [ObjectType::Hourglass]);
+    }
+
+    #[test]
+    fn palette_results_follow_search_and_chips_in_palette_order() {
+        let mut ui_state = UiState::new();
+        ui_state.palette_search = "lamp".into();
+        ui_state.update_palette_results(Lang::English);
+        assert_eq!(ui_state.palette_results.first(), Some(&ObjectType::Lamp));
+
+        ui_state.palette_search = "clock".into();
+        ui_state.update_palette_results(Lang::English);
+        let first_clock = ui_state.categories[0].variants[0].object_type;
+        assert_eq!(ui_state.palette_results.first(), Some(&first_clock));
+        ui_state.toggle_category_filter(Key::Lighting);
+        ui_state.update_palette_results(Lang::English);
+        assert!(!ui_state.palette_results.contains(&first_clock));
+    }
+
+    #[test]
+    fn hex_field_round_trips() {
+        assert_eq!(format_hex_color(0x3B82F6), "#3B82F6");
+        assert_eq!(parse_hex_color(&format_hex_color(0x00000A)), Some(0x00000A));
+    }
+
+    #[test]
+    fn object_list_search_matches_names_and_tags() {
+        let mut lamp = DeskObject::new(1, ObjectType::Lamp, glam::Vec3::ZERO);
+        lamp.tags = vec!["Morning".into(), "work".into()];
+        let mut plant = DeskObject::new(2, ObjectType::Plant, glam::Vec3::ZERO);
+        plant.name = Some("Fern".into());
+        plant.tags = vec!["decor".into()];
+        let objects = [lamp, plant];
+        let ids = |query: &str, lang: Lang| -> Vec<u64> {
+            objects
+                .iter()
+                .filter(|obj| object_matches_search(obj, query, lang))
+                .map(|obj| obj.id)
+                .collect()
+        };
+        assert_eq!(ids("", Lang::English), [1, 2]);
+        assert_eq!(ids(" MORN", Lang::English), [1]);
+        assert_eq!(ids("fern", Lang::English), [2]);
+        assert_eq!(ids("decor", Lang::Russian), [2]);
+        assert_eq!(ids("lamp", Lang::Russian), [1]);
+        assert!(ids("garden", Lang::English).is_empty());
+    }
+
+    #[test]
+    fn inspector_angles_survive_their_own_round_trip() {
+        let mut obj = DeskObject::new(1, ObjectType::Lamp, Vec3::ZERO);
+        obj.rotation = Quat::from_euler(EulerRot::YXZ, 0.5, 0.2, -0.1);
+        let mut fields = TransformFields::default();
+        fields.sync(&obj);
+        assert!(fields.rotation_quat().dot(obj.rotation).abs() > 1.0 - 1e-6);
+        assert!((fields.rotation.y - 0.5f32.to_degrees()).abs() < 1e-3);
+
+        // Typed angles stay as typed once written back, even at gimbal lock
+        fields.rotation = Vec3::new(90.0, 200.0, 30.0);
+        fields.rotation_source = fields.rotation_quat();
+        obj.rotation = fields.rotation_source;
+        fields.sync(&obj);
+        assert_eq!(fields.rotation, Vec3::new(90.0, 200.0, 30.0));
+
+        // Turning the object some other way updates them
+        obj.rotation = Quat::from_rotation_y(1.0);
+        fields.sync(&obj);
+        assert!((fields.rotation - Vec3::new(0.0, 1f32.to_degrees(), 0.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn selecting_several_objects_flags_mixed_colors() {
+        let lamp = DeskObject::new(1, ObjectType::Lamp, Vec3::ZERO);
+        let mut clock = DeskObject::new(2, ObjectType::Clock, Vec3::ZERO);
+        clock.color = lamp.color;
+        let mut ui = UiState::new();
+        ui.select_many(&[&lamp, &clock]);
+        assert_eq!(ui.selected_object_id, Some(1));
+        assert_eq!(ui.selected_ids, [1, 2]);
+        assert!(!ui.main_color_mixed);
+        assert_eq!(ui.accent_color_mixed, lamp.accent_color != clock.accent_color);
+
+        clock.color ^= 0xFF;
+        ui.select_many(&[&clock, &lamp]);
+        assert_eq!(ui.selected_object_id, Some(2));
+        assert!(ui.main_color_mixed && ui.main_color_input.is_empty());
+
+        ui.select_many(&[]);
+        assert_eq!(ui.selected_object_id, None);
+        assert!(ui.selected_ids.is_empty());
+    }
+
+    #[test]
+    fn large_selections_take_a_second_click_to_delete() {
+        let objects: Vec<DeskObject> = (1..=CONFIG.objects.delete_confirm_count as u64)
+            .map(|id| DeskObject::new(id, ObjectType::Lamp, Vec3::ZERO))
+            .collect();
+        let mut ui = UiState::new();
+        ui.select_many(&objects.iter().take(2).collect::<Vec<_>>());
+        assert!(ui.confirm_delete_selected());
+
+        ui.select_many(&objects.iter().collect::<Vec<_>>());
+        assert!(!ui.confirm_delete_selected());
+        assert!(ui.delete_selected_armed);
+        assert!(ui.confirm_delete_selected());
+        assert!(!ui.delete_selected_armed);
+
+        // Changing the selection disarms it
+        assert!(!ui.confirm_delete_selected());
+        ui.select_many(&objects.iter().collect::<Vec<_>>());
+        assert!(!ui.delete_selected_armed);
+    }
+
+    #[test]
+    fn workspaces_take_a_second_click_to_delete() {
+        let mut ui = UiState::new();
+        assert!(!ui.confirm_delete_workspace(1));
+        // Clicking another workspace's Delete arms that one instead
+        assert!(!ui.confirm_delete_workspace(2));
+        assert!(!ui.confirm_delete_workspace(1));
+        assert!(ui.confirm_delete_workspace(1));
+        assert_eq!(ui.delete_workspace_armed, None);
+    }
+
+    #[test]
+    fn clean_view_restores_the_open_panels() {
+        let mut ui = UiState::new();
+        ui.left_sidebar_open = true;
+        ui.console_open = true;
+        ui.open_customization(&DeskObject::new(1, ObjectType::Lamp, glam::Vec3::ZERO));
+
+        ui.toggle_clean_view();
+        assert!(ui.ui_hidden);
+        assert!(!ui.left_sidebar_open && !ui.right_sidebar_open && !ui.console_open);
+
+        // Deselecting while hidden keeps the customization sidebar closed
+        ui.close_customization();
+        ui.toggle_clean_view();
+        assert!(!ui.ui_hidden);
+        assert!(ui.left_sidebar_open && ui.console_open);
+        assert!(!ui.right_sidebar_open && !ui.show_debug_overlay);
+        assert!(!ui.leave_clean_view());
+    }
+}
@@ -0,0 +1,27 @@
+// Focus Desktop Simulator - Upscale Shader
+// Stretches a low-resolution frame over the target with nearest filtering
+
+@group(0) @binding(0)
+var frame_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var nearest_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// A single triangle covering the whole target
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_upscale(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(frame_texture, nearest_sampler, in.uv);
+}
@@ -0,0 +1,246 @@
+//! Save/load round-trips and object bookkeeping of `AppState`
+
+use focus_desktop_simulator::{AppState, DeskObject, ObjectType, CONFIG};
+use glam::Vec3;
+use std::fs;
+use std::path::PathBuf;
+
+/// An empty directory of its own under the system temp dir, removed on drop
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("focus-desktop-sim-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn join(&self, file: &str) -> PathBuf {
+        self.0.join(file)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A state holding a customized lamp and a plain clock
+fn sample_state() -> AppState {
+    let mut state = AppState::new();
+    let mut lamp = DeskObject::new(state.next_id(), ObjectType::Lamp, Vec3::new(-1.5, 0.8, 0.5));
+    lamp.color = 0x3b82f6;
+    lamp.scale = 1.25;
+    lamp.locked = true;
+    state.add_object(lamp);
+    let clock = DeskObject::new(state.next_id(), ObjectType::Clock, Vec3::new(2.0, 0.8, -1.0));
+    state.add_object(clock);
+    state.idle_motion = 0.5;
+    state.show_grid = true;
+    state
+}
+
+#[test]
+fn saved_state_loads_back_unchanged() {
+    let dir = TempDir::new("round-trip");
+    let path = dir.join("desk-state.json");
+    let state = sample_state();
+
+    state.save_to(&path).unwrap();
+    let loaded = AppState::load_from(&path);
+
+    // Compared as saved, since drag and animation fields aren't persisted
+    let saved = |state: &AppState| serde_json::to_value(&state.objects).unwrap();
+    assert_eq!(saved(&loaded), saved(&state));
+    assert_eq!(loaded.next_object_id, state.next_object_id);
+    assert_eq!(loaded.idle_motion, state.idle_motion);
+    assert!(loaded.show_grid);
+    // Nothing is left behind from the atomic write
+    assert_eq!(fs::read_dir(&dir.0).unwrap().count(), 1);
+}
+
+#[test]
+fn saving_over_an_existing_file_replaces_it() {
+    let dir = TempDir::new("overwrite");
+    let path = dir.join("desk-state.json");
+    let mut state = sample_state();
+    state.save_to(&path).unwrap();
+
+    state.clear_objects();
+    state.save_to(&path).unwrap();
+
+    assert!(AppState::load_from(&path).objects.is_empty());
+}
+
+#[test]
+fn missing_file_loads_default_state() {
+    let dir = TempDir::new("missing");
+    let loaded = AppState::load_from(&dir.join("desk-state.json"));
+
+    assert!(loaded.objects.is_empty());
+    assert_eq!(loaded.next_object_id, AppState::default().next_object_id);
+}
+
+#[test]
+fn corrupted_file_is_backed_up_and_default_state_used() {
+    let dir = TempDir::new("corrupted");
+    let path = dir.join("desk-state.json");
+    let garbage = r#"{"version": 1, "objects": [{"id": "#;
+    fs::write(&path, garbage).unwrap();
+
+    let loaded = AppState::load_from(&path);
+
+    assert!(loaded.objects.is_empty());
+    let backup = fs::read_to_string(dir.join("desk-state.json.backup")).unwrap();
+    assert_eq!(backup, garbage);
+}
+
+#[test]
+fn newer_file_loads_what_it_can_and_is_never_saved_over() {
+    let dir = TempDir::new("newer");
+    let path = dir.join("desk-state.json");
+    let mut saved = serde_json::to_value(sample_state()).unwrap();
+    saved["version"] = 99.into();
+    // A field this build doesn't know, one whose type changed, and an object
+    // of a type it doesn't have
+    saved["hologram_mode"] = true.into();
+    saved["ui_scale"] = "large".into();
+    saved["objects"].as_array_mut().unwrap().push(serde_json::json!({"id": 3, "object_type": "hologram"}));
+    let content = serde_json::to_string_pretty(&saved).unwrap();
+    fs::write(&path, &content).unwrap();
+
+    let loaded = AppState::load_from(&path);
+
+    assert!(loaded.read_only);
+    assert_eq!(loaded.objects.len(), 2);
+    assert_eq!(loaded.objects[0].color, 0x3b82f6);
+    assert!(loaded.show_grid);
+    assert_eq!(loaded.ui_scale, AppState::default().ui_scale);
+    assert!(loaded.save_to(&path).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    assert!(!dir.join("desk-state.json.backup").exists());
+}
+
+#[test]
+fn objects_of_unregistered_types_survive_a_load_and_save() {
+    let dir = TempDir::new("unregistered");
+    let path = dir.join("desk-state.json");
+    let mut saved = serde_json::to_value(sample_state()).unwrap();
+    let coaster = serde_json::json!({"id": 3, "object_type": {"custom": "unregistered-coaster"}});
+    saved["objects"].as_array_mut().unwrap().push(coaster.clone());
+    saved["templates"] = serde_json::json!([coaster]);
+    let content = serde_json::to_string_pretty(&saved).unwrap();
+    fs::write(&path, &content).unwrap();
+
+    let loaded = AppState::load_from(&path);
+
+    assert!(loaded.read_only);
+    assert_eq!(loaded.objects.len(), 2);
+    assert!(loaded.templates.is_empty());
+    assert!(loaded.save_to(&path).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    assert!(AppState::load_from(&path).read_only);
+}
+
+#[test]
+fn objects_can_be_added_found_removed_and_cleared() {
+    let mut state = sample_state();
+    let lamp_id = state.objects[0].id;
+    let clock_id = state.objects[1].id;
+
+    assert_eq!(state.get_object(lamp_id).map(|obj| obj.object_type), Some(ObjectType::Lamp));
+    state.get_object_mut(clock_id).unwrap().scale = 2.0;
+    assert_eq!(state.get_object(clock_id).unwrap().scale, 2.0);
+
+    let removed = state.remove_object(lamp_id).unwrap();
+    assert_eq!(removed.id, lamp_id);
+    assert!(state.get_object(lamp_id).is_none());
+    assert!(state.remove_object(lamp_id).is_none());
+    assert_eq!(state.objects.len(), 1);
+
+    state.clear_objects();
+    assert!(state.objects.is_empty());
+    assert!(state.get_object(clock_id).is_none());
+}
+
+#[test]
+fn draw_order_changes_are_saved() {
+    let dir = TempDir::new("draw-order");
+    let path = dir.join("desk-state.json");
+    let mut state = sample_state();
+    let paper = DeskObject::new(state.next_id(), ObjectType::Paper, Vec3::new(0.0, 0.75, 0.0));
+    let paper_id = paper.id;
+    state.add_object(paper);
+    let lamp_id = state.objects[0].id;
+
+    state.send_to_back(paper_id);
+    state.bring_to_front(lamp_id);
+    state.save_to(&path).unwrap();
+
+    let ids = |state: &AppState| state.objects.iter().map(|obj| obj.id).collect::<Vec<_>>();
+    let loaded = AppState::load_from(&path);
+    assert_eq!(ids(&loaded), ids(&state));
+    assert_eq!(loaded.objects.first().map(|obj| obj.id), Some(paper_id));
+    assert_eq!(loaded.objects.last().map(|obj| obj.id), Some(lamp_id));
+}
+
+#[test]
+fn ids_keep_increasing_across_removals_and_reloads() {
+    let dir = TempDir::new("ids");
+    let path = dir.join("desk-state.json");
+    let mut state = sample_state();
+    let mut last_id = state.objects.iter().map(|obj| obj.id).max().unwrap();
+
+    // Removing objects never frees their ids for reuse
+    state.clear_objects();
+    let id = state.next_id();
+    assert!(id > last_id);
+    last_id = id;
+
+    state.save_to(&path).unwrap();
+    let mut loaded = AppState::load_from(&path);
+    for _ in 0..10 {
+        let id = loaded.next_id();
+        assert!(id > last_id);
+        last_id = id;
+    }
+}
+
+#[test]
+fn stale_id_counter_is_raised_past_the_loaded_objects() {
+    let dir = TempDir::new("stale-counter");
+    let path = dir.join("desk-state.json");
+    let mut saved = serde_json::to_value(sample_state()).unwrap();
+    saved["objects"][1]["id"] = 40.into();
+    saved["next_object_id"] = 2.into();
+    fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+    let mut loaded = AppState::load_from(&path);
+    assert_eq!(loaded.next_id(), 41);
+    let mut named = AppState::load_file(&path).unwrap();
+    assert_eq!(named.next_id(), 41);
+}
+
+#[test]
+fn objects_from_a_desk_without_legs_are_raised_onto_the_desk_top() {
+    let dir = TempDir::new("desk-top");
+    let path = dir.join("desk-state.json");
+    let mut saved = serde_json::to_value(sample_state()).unwrap();
+    // An older save: no desk top height, one object on the desk and one on the floor
+    saved.as_object_mut().unwrap().remove("desk_top");
+    saved["objects"][0]["position"] = serde_json::json!([-1.5, CONFIG.desk.height, 0.5]);
+    saved["objects"][1]["position"] = serde_json::json!([CONFIG.desk.width, 0.0, 0.0]);
+    fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+    let loaded = AppState::load_from(&path);
+    assert!((loaded.objects[0].position.y - CONFIG.desk.top_y()).abs() < 1e-5);
+    assert_eq!(loaded.objects[1].position.y, 0.0);
+    assert_eq!(loaded.desk_top, CONFIG.desk.top_y());
+
+    // Saved again, the positions are kept as they are
+    loaded.save_to(&path).unwrap();
+    let reloaded = AppState::load_file(&path).unwrap();
+    assert_eq!(reloaded.objects[0].position, loaded.objects[0].position);
+}